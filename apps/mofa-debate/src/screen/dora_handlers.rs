@@ -104,13 +104,33 @@ impl MoFaDebateScreen {
         // =====================================================
         // Poll SharedDoraState for all data
         // =====================================================
+        // Barge-in pause / user hold - buffered audio is kept while paused
+        let pause_signal = self
+            .dora_integration
+            .as_ref()
+            .and_then(|dora| dora.shared_dora_state().audio.take_pause_signal());
+        if let (Some(paused), Some(ref player)) = (pause_signal, &self.audio_player) {
+            if paused {
+                player.pause();
+                ::log::info!("⏸️ Audio paused");
+            } else {
+                player.resume();
+                ::log::info!("▶️ Audio resumed");
+            }
+        }
+
         // Collect data first, then update UI (avoids borrow checker issues)
         let (chat_messages, audio_chunks, log_entries, status) =
             if let Some(ref dora) = self.dora_integration {
                 let shared_state = dora.shared_dora_state();
                 (
                     shared_state.chat.read_if_dirty(),
-                    shared_state.audio.drain(),
+                    // While paused/held, leave chunks queued in shared state
+                    if shared_state.audio.is_paused() {
+                        Vec::new()
+                    } else {
+                        shared_state.audio.drain()
+                    },
                     shared_state.logs.read_if_dirty(),
                     shared_state.status.read_if_dirty(),
                 )
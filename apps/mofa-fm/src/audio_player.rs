@@ -572,6 +572,9 @@ fn run_audio_thread(
         .play()
         .map_err(|e| format!("Failed to start audio stream: {}", e))?;
 
+    // Explicit pause - incoming audio is buffered but must not restart playback
+    let mut paused = false;
+
     loop {
         match command_rx.try_recv() {
             Ok(AudioCommand::Write(samples, participant_id, question_id)) => {
@@ -579,11 +582,12 @@ fn run_audio_thread(
                 buf.write_with_participant(&samples, participant_id, question_id);
 
                 // Start playing if we have enough audio
-                if buf.available() > sample_rate as usize / 10 {
+                if !paused && buf.available() > sample_rate as usize / 10 {
                     is_playing.store(true, Ordering::Relaxed);
                 }
             }
             Ok(AudioCommand::Reset) => {
+                paused = false;
                 is_playing.store(false, Ordering::Relaxed);
                 buffer.lock().reset();
                 // Clear force_mute after buffer is reset - playback can resume when new audio arrives
@@ -595,10 +599,13 @@ fn run_audio_thread(
                 log::info!("Audio buffer smart reset for question_id={}", question_id);
             }
             Ok(AudioCommand::Pause) => {
+                paused = true;
                 is_playing.store(false, Ordering::Relaxed);
             }
             Ok(AudioCommand::Resume) => {
-                is_playing.store(true, Ordering::Relaxed);
+                paused = false;
+                // Nothing buffered - the next Write starts playback as usual
+                is_playing.store(buffer.lock().available() > 0, Ordering::Relaxed);
             }
             Ok(AudioCommand::Stop) => {
                 log::info!("Audio thread stopping");
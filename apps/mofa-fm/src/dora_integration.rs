@@ -5,7 +5,8 @@
 
 use crossbeam_channel::{bounded, Receiver, Sender};
 use mofa_dora_bridge::{
-    controller::DataflowController, dispatcher::DynamicNodeDispatcher, SharedDoraState,
+    controller::DataflowController, dispatcher::DynamicNodeDispatcher, MicInputMode,
+    ProcessingStage, SharedDoraState,
 };
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -28,6 +29,8 @@ pub enum DoraCommand {
     ForceStopDataflow,
     /// Send a prompt to LLM
     SendPrompt { message: String },
+    /// Attach an image file to the participants' next message
+    SendImage { path: String },
     /// Send a control command
     SendControl { command: String },
     /// Update buffer status
@@ -38,6 +41,14 @@ pub enum DoraCommand {
     StopRecording,
     /// Enable/disable AEC (echo cancellation)
     SetAecEnabled { enabled: bool },
+    /// Switch mic turn mode (VAD / push-to-talk / tap-to-end)
+    SetMicInputMode { mode: MicInputMode },
+    /// Push-to-talk pressed/released
+    PushToTalk { pressed: bool },
+    /// End the human turn explicitly (sends question_ended)
+    EndTurn,
+    /// Enable or bypass a mic processing stage (high-pass, noise suppression, AGC)
+    SetMicProcessing { stage: ProcessingStage, enabled: bool },
 }
 
 /// Events sent from dora integration to UI
@@ -162,6 +173,11 @@ impl DoraIntegration {
         })
     }
 
+    /// Attach an image file to the participants' next message
+    pub fn send_image(&self, path: impl Into<String>) -> bool {
+        self.send_command(DoraCommand::SendImage { path: path.into() })
+    }
+
     /// Send a control command (e.g., "reset", "cancel")
     pub fn send_control(&self, command: impl Into<String>) -> bool {
         self.send_command(DoraCommand::SendControl {
@@ -184,6 +200,26 @@ impl DoraIntegration {
         self.send_command(DoraCommand::SetAecEnabled { enabled })
     }
 
+    /// Switch mic turn mode
+    pub fn set_mic_input_mode(&self, mode: MicInputMode) -> bool {
+        self.send_command(DoraCommand::SetMicInputMode { mode })
+    }
+
+    /// Push-to-talk pressed (true) or released (false)
+    pub fn push_to_talk(&self, pressed: bool) -> bool {
+        self.send_command(DoraCommand::PushToTalk { pressed })
+    }
+
+    /// End the human turn explicitly
+    pub fn end_turn(&self) -> bool {
+        self.send_command(DoraCommand::EndTurn)
+    }
+
+    /// Enable or bypass a mic processing stage
+    pub fn set_mic_processing(&self, stage: ProcessingStage, enabled: bool) -> bool {
+        self.send_command(DoraCommand::SetMicProcessing { stage, enabled })
+    }
+
     /// Poll for events (non-blocking)
     pub fn poll_events(&self) -> Vec<DoraEvent> {
         let mut events = Vec::new();
@@ -327,6 +363,22 @@ impl DoraIntegration {
                         }
                     }
 
+                    DoraCommand::SendImage { path } => {
+                        if let Some(ref disp) = dispatcher {
+                            if let Some(bridge) = disp.get_bridge("mofa-prompt-input") {
+                                log::info!("Sending image via bridge: {}", path);
+                                if let Err(e) = bridge.send(
+                                    "image",
+                                    mofa_dora_bridge::DoraData::Text(path.clone()),
+                                ) {
+                                    log::error!("Failed to send image: {}", e);
+                                }
+                            } else {
+                                log::warn!("mofa-prompt-input bridge not found for image");
+                            }
+                        }
+                    }
+
                     DoraCommand::SendControl { command } => {
                         if let Some(ref disp) = dispatcher {
                             if let Some(bridge) = disp.get_bridge("mofa-prompt-input") {
@@ -406,6 +458,31 @@ impl DoraIntegration {
                             }
                         }
                     }
+
+                    DoraCommand::SetMicInputMode { mode } => {
+                        Self::send_mic_control(
+                            dispatcher.as_ref(),
+                            serde_json::json!({"action": "set_input_mode", "mode": mode.as_str()}),
+                        );
+                    }
+
+                    DoraCommand::PushToTalk { pressed } => {
+                        Self::send_mic_control(
+                            dispatcher.as_ref(),
+                            serde_json::json!({"action": "push_to_talk", "pressed": pressed}),
+                        );
+                    }
+
+                    DoraCommand::EndTurn => {
+                        Self::send_mic_control(dispatcher.as_ref(), serde_json::json!({"action": "end_turn"}));
+                    }
+
+                    DoraCommand::SetMicProcessing { stage, enabled } => {
+                        Self::send_mic_control(
+                            dispatcher.as_ref(),
+                            serde_json::json!({"action": "set_processing", "stage": stage.as_str(), "enabled": enabled}),
+                        );
+                    }
                 }
             }
 
@@ -459,6 +536,20 @@ impl DoraIntegration {
 
         log::info!("Dora integration worker stopped");
     }
+
+    /// Send a JSON control action to the mic input bridge
+    fn send_mic_control(dispatcher: Option<&DynamicNodeDispatcher>, action: serde_json::Value) {
+        if let Some(disp) = dispatcher {
+            if let Some(bridge) = disp.get_bridge("mofa-mic-input") {
+                log::info!("Sending mic control: {}", action);
+                if let Err(e) = bridge.send("control", mofa_dora_bridge::DoraData::Json(action)) {
+                    log::error!("Failed to send mic control: {}", e);
+                }
+            } else {
+                log::warn!("mofa-mic-input bridge not found");
+            }
+        }
+    }
 }
 
 impl Drop for DoraIntegration {
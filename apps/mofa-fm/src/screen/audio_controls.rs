@@ -4,7 +4,8 @@
 
 use makepad_widgets::*;
 use mofa_settings::data::Preferences;
-use mofa_ui::LedMeterWidgetExt;
+use mofa_dora_bridge::MicInputMode;
+use mofa_ui::{LedMeterWidgetExt, MicButtonWidgetExt, MofaHeroWidgetExt};
 
 use super::MoFaFMScreen;
 
@@ -100,6 +101,15 @@ impl MoFaFMScreen {
         // AEC enabled by default (blink animation is shader-driven, no timer needed)
         self.aec_enabled = true;
 
+        // Restore mic turn mode
+        self.mic_input_mode = prefs
+            .mic_input_mode
+            .as_deref()
+            .and_then(MicInputMode::parse)
+            .unwrap_or_default();
+        self.view.mic_button(ids!(running_tab_content.audio_container.audio_controls_row.mic_container.mic_group.mic_mute_btn))
+            .set_input_mode(cx, self.mic_input_mode);
+
         // Initialize demo log entries
         self.init_demo_logs(cx);
 
@@ -149,6 +159,47 @@ impl MoFaFMScreen {
             .set_level(cx, display_level);
     }
 
+    /// Update VAD readout (detector name and noise floor) in the mic panel
+    pub(super) fn update_vad_status(&mut self, cx: &mut Cx, status: &mofa_dora_bridge::VadStatus) {
+        let text = match status.noise_floor_db {
+            Some(floor) => format!("VAD {} · floor {:.0} dB", status.detector, floor),
+            None if status.detector.is_empty() => String::new(),
+            None => format!("VAD {}", status.detector),
+        };
+        self.view.label(ids!(running_tab_content.audio_container.audio_controls_row.mic_container.mic_group.vad_label))
+            .set_text(cx, &text);
+    }
+
+    /// Update capture processing readout (input → output level, AGC gain) in the mic panel
+    pub(super) fn update_processing_status(&mut self, cx: &mut Cx, status: &mofa_dora_bridge::ProcessingStatus) {
+        let text = if !status.high_pass && !status.noise_suppression && !status.agc {
+            String::new()
+        } else {
+            let mut text = format!("in {:.0} → out {:.0} dB", status.input_level_db, status.output_level_db);
+            if status.noise_suppression {
+                text.push_str(" · NS");
+            }
+            if status.agc {
+                text.push_str(&format!(" · AGC {:+.0} dB", status.agc_gain_db));
+            }
+            text
+        };
+        self.view.label(ids!(running_tab_content.audio_container.audio_controls_row.mic_container.mic_group.processing_label))
+            .set_text(cx, &text);
+    }
+
+    /// Hold or release AI audio playback
+    ///
+    /// Held audio stays queued in shared state and the player reports a full
+    /// buffer upstream, so generation backs off instead of dropping speech.
+    pub(super) fn set_audio_hold(&mut self, cx: &mut Cx, held: bool) {
+        if let Some(ref dora) = self.dora_integration {
+            dora.shared_dora_state().audio.set_hold(held);
+        }
+        self.view.mofa_hero(ids!(left_column.mofa_hero)).set_held(cx, held);
+        self.add_log(cx, if held { "[INFO] [App] AI audio held" } else { "[INFO] [App] AI audio resumed" });
+    }
+
     /// Update buffer level LEDs based on audio buffer fill percentage
     pub(super) fn update_buffer_level(&mut self, cx: &mut Cx, level: f64) {
         // Use the LedMeter widget from mofa-ui with blue colors
@@ -171,6 +222,24 @@ impl MoFaFMScreen {
         self.view.label(ids!(running_tab_content.audio_container.audio_controls_row.buffer_container.buffer_group.buffer_pct)).set_text(cx, &pct_text);
     }
 
+    /// Switch mic turn mode, forward it to the AEC bridge and persist it
+    pub(super) fn set_mic_input_mode(&mut self, mode: MicInputMode) {
+        ::log::info!("Mic input mode: {}", mode.as_str());
+        self.mic_input_mode = mode;
+        self.ptt_key_held = false;
+
+        if let Some(ref dora) = self.dora_integration {
+            dora.set_mic_input_mode(mode);
+        }
+
+        // Save preference
+        let mut prefs = Preferences::load();
+        prefs.mic_input_mode = Some(mode.as_str().to_string());
+        if let Err(e) = prefs.save() {
+            eprintln!("Failed to save mic input mode preference: {}", e);
+        }
+    }
+
     /// Select input device for mic monitoring
     pub(super) fn select_input_device(&mut self, cx: &mut Cx, device_name: &str) {
         ::log::info!("select_input_device: {}", device_name);
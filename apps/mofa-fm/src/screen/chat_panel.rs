@@ -46,6 +46,36 @@ impl MoFaFMScreen {
         self.view.redraw(cx);
     }
 
+    /// Pick an image (slide, diagram) to send with the participants' next message
+    pub(super) fn attach_image(&mut self, cx: &mut Cx) {
+        let dialog = rfd::FileDialog::new()
+            .add_filter("Images", &["png", "jpg", "jpeg", "gif", "webp"])
+            .set_title("Attach Image");
+        let Some(path) = dialog.pick_file() else {
+            return;
+        };
+
+        self.init_dora(cx);
+
+        let file_name = path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("image")
+            .to_string();
+
+        if let Some(ref dora) = self.dora_integration {
+            if dora.is_running() {
+                dora.send_image(path.to_string_lossy());
+                self.chat_messages.push(ChatMessageEntry::new("You", format!("🖼 {}", file_name)));
+                self.update_chat_display(cx);
+                self.add_log(cx, &format!("[INFO] [App] Attached image: {}", file_name));
+            } else {
+                self.add_log(cx, "[WARN] [App] Dataflow not running - image not sent to LLM");
+            }
+        }
+
+        self.view.redraw(cx);
+    }
+
     /// Reset conversation - sends reset to conference controller
     pub(super) fn reset_conversation(&mut self, cx: &mut Cx) {
         ::log::info!("Reset clicked");
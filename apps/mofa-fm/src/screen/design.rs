@@ -76,7 +76,9 @@ live_design! {
     MicButton = {{MicButton}} {
         width: Fit
         height: Fit
-        flow: Overlay
+        flow: Down
+        align: {x: 0.5}
+        spacing: 1
         cursor: Hand
         padding: 4
 
@@ -141,6 +143,26 @@ live_design! {
                 icon_walk: {width: 20, height: 20}
             }
         }
+
+        // Turn mode selector (tap to cycle: VAD -> PTT -> TAP)
+        mode_badge = <View> {
+            width: Fit, height: Fit
+            visible: true
+            mode_label = <Label> {
+                draw_text: {
+                    instance dark_mode: 0.0
+                    text_style: { font_size: 7.0 }
+                    fn get_color(self) -> vec4 {
+                        return mix(
+                            vec4(0.392, 0.455, 0.545, 1.0),  // SLATE_500
+                            vec4(0.796, 0.835, 0.882, 1.0),  // SLATE_300
+                            self.dark_mode
+                        );
+                    }
+                }
+                text: "VAD"
+            }
+        }
     }
 
     // AEC toggle button with animated speaking indicator
@@ -532,6 +554,30 @@ live_design! {
                         mic_mute_btn = <MicButton> {}
 
                         mic_level_meter = <LedMeter> {}
+
+                        // Active VAD detector and noise floor (from AEC bridge)
+                        vad_label = <Label> {
+                            draw_text: {
+                                instance dark_mode: 0.0
+                                text_style: <FONT_REGULAR>{ font_size: 10.0 }
+                                fn get_color(self) -> vec4 {
+                                    return mix((GRAY_500), (TEXT_SECONDARY_DARK), self.dark_mode);
+                                }
+                            }
+                            text: ""
+                        }
+
+                        // Pre/post processing levels and AGC gain (from AEC bridge)
+                        processing_label = <Label> {
+                            draw_text: {
+                                instance dark_mode: 0.0
+                                text_style: <FONT_REGULAR>{ font_size: 10.0 }
+                                fn get_color(self) -> vec4 {
+                                    return mix((GRAY_500), (TEXT_SECONDARY_DARK), self.dark_mode);
+                                }
+                            }
+                            text: ""
+                        }
                     }
                 }
 
@@ -890,6 +936,61 @@ live_design! {
                             flow: Right
                             spacing: 8
 
+                            attach_image_btn = <Button> {
+                                width: Fit, height: 35
+                                padding: {left: 16, right: 16}
+                                text: "Image"
+
+                                animator: {
+                                    hover = {
+                                        default: off,
+                                        off = {
+                                            from: {all: Forward {duration: 0.15}}
+                                            apply: { draw_bg: {hover: 0.0} }
+                                        }
+                                        on = {
+                                            from: {all: Forward {duration: 0.15}}
+                                            apply: { draw_bg: {hover: 1.0} }
+                                        }
+                                    }
+                                    pressed = {
+                                        default: off,
+                                        off = {
+                                            from: {all: Forward {duration: 0.1}}
+                                            apply: { draw_bg: {pressed: 0.0} }
+                                        }
+                                        on = {
+                                            from: {all: Forward {duration: 0.1}}
+                                            apply: { draw_bg: {pressed: 1.0} }
+                                        }
+                                    }
+                                }
+
+                                draw_text: {
+                                    instance dark_mode: 0.0
+                                    text_style: <FONT_MEDIUM>{ font_size: 11.0 }
+                                    fn get_color(self) -> vec4 {
+                                        return mix((GRAY_700), (SLATE_300), self.dark_mode);
+                                    }
+                                }
+                                draw_bg: {
+                                    instance hover: 0.0
+                                    instance pressed: 0.0
+                                    instance dark_mode: 0.0
+                                    border_radius: 4.0
+                                    fn pixel(self) -> vec4 {
+                                        let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                                        sdf.box(0., 0., self.rect_size.x, self.rect_size.y, self.border_radius);
+                                        let base = mix((HOVER_BG), (SLATE_600), self.dark_mode);
+                                        let hover_color = mix((SLATE_200), (SLATE_500), self.dark_mode);
+                                        let pressed_color = mix((SLATE_300), (SLATE_400), self.dark_mode);
+                                        let color = mix(mix(base, hover_color, self.hover), pressed_color, self.pressed);
+                                        sdf.fill(color);
+                                        return sdf.result;
+                                    }
+                                }
+                            }
+
                             send_prompt_btn = <Button> {
                                 width: Fit, height: 35
                                 padding: {left: 16, right: 16}
@@ -122,12 +122,30 @@ impl MoFaFMScreen {
             }
         }
 
+        // Barge-in pause / user hold - buffered audio is kept while paused
+        let pause_signal = self.dora_integration.as_ref()
+            .and_then(|dora| dora.shared_dora_state().audio.take_pause_signal());
+        if let (Some(paused), Some(ref player)) = (pause_signal, &self.audio_player) {
+            if paused {
+                player.pause();
+                ::log::info!("⏸️ Audio paused");
+            } else {
+                player.resume();
+                ::log::info!("▶️ Audio resumed");
+            }
+        }
+
         // Collect data first, then update UI (avoids borrow checker issues)
         let (chat_messages, audio_chunks, log_entries, status) = if let Some(ref dora) = self.dora_integration {
             let shared_state = dora.shared_dora_state();
             (
                 shared_state.chat.read_if_dirty(),
-                shared_state.audio.drain(),
+                // While paused/held, leave chunks queued in shared state
+                if shared_state.audio.is_paused() {
+                    Vec::new()
+                } else {
+                    shared_state.audio.drain()
+                },
                 shared_state.logs.read_if_dirty(),
                 shared_state.status.read_if_dirty(),
             )
@@ -215,15 +233,17 @@ impl MoFaFMScreen {
         // Poll mic state from AEC input bridge
         // =====================================================
         // Read all mic state first to avoid borrow checker issues
-        let (mic_level, aec_enabled_state, is_speaking) = if let Some(ref dora) = self.dora_integration {
+        let (mic_level, aec_enabled_state, is_speaking, vad_status, processing) = if let Some(ref dora) = self.dora_integration {
             let shared_state = dora.shared_dora_state();
             (
                 shared_state.mic.read_level_if_dirty(),
                 shared_state.mic.read_aec_enabled_if_dirty(),
                 shared_state.mic.read_speaking_if_dirty(),
+                shared_state.mic.read_vad_status_if_dirty(),
+                shared_state.mic.read_processing_if_dirty(),
             )
         } else {
-            (None, None, None)
+            (None, None, None, None, None)
         };
 
         // Update mic level LEDs (from AEC bridge)
@@ -240,6 +260,16 @@ impl MoFaFMScreen {
             }
         }
 
+        // Update VAD readout next to the mic meter
+        if let Some(status) = vad_status {
+            self.update_vad_status(cx, &status);
+        }
+
+        // Update processing readout (pre/post levels, AGC gain)
+        if let Some(status) = processing {
+            self.update_processing_status(cx, &status);
+        }
+
         // Update AEC button VAD indicator (red when speaking, green when silent)
         if let Some(speaking) = is_speaking {
             self.view.aec_button(ids!(running_tab_content.audio_container.audio_controls_row.aec_container.aec_group.aec_toggle_btn))
@@ -277,6 +307,7 @@ impl MoFaFMScreen {
                     if let Some(ref dora) = self.dora_integration {
                         dora.set_aec_enabled(true);
                         dora.start_recording();
+                        dora.set_mic_input_mode(self.mic_input_mode);
                     }
                 }
                 DoraEvent::DataflowStopped => {
@@ -284,6 +315,9 @@ impl MoFaFMScreen {
                     self.add_log(cx, "[INFO] [App] Dataflow stopped");
                     self.view.mofa_hero(ids!(left_column.mofa_hero)).set_running(cx, false);
                     self.view.mofa_hero(ids!(left_column.mofa_hero)).set_connection_status(cx, ConnectionStatus::Stopped);
+                    if let Some(ref dora) = self.dora_integration {
+                        dora.shared_dora_state().audio.set_hold(false);
+                    }
                     // Clear tracking state on stop
                     self.connected_bridges.clear();
                     self.processed_dora_log_count = 0;
@@ -298,6 +332,8 @@ impl MoFaFMScreen {
 
                     // Reset mic level LEDs to zero
                     self.update_mic_level_from_dora(cx, 0.0);
+                    self.update_vad_status(cx, &Default::default());
+                    self.update_processing_status(cx, &Default::default());
 
                     // Reset buffer level
                     self.update_buffer_level(cx, 0.0);
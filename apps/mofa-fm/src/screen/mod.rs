@@ -23,6 +23,7 @@ use crate::dora_integration::{DoraIntegration, DoraCommand};
 use mofa_widgets::participant_panel::ParticipantPanelWidgetExt;
 use mofa_widgets::{StateChangeListener, TimerControl};
 use mofa_ui::{LedMeterWidgetExt, MicButtonWidgetExt, AecButtonWidgetExt};
+use mofa_dora_bridge::MicInputMode;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
@@ -36,6 +37,9 @@ struct PreloadedData {
     loading_complete: bool,
 }
 
+/// Key held for push-to-talk (when the mic is in push-to-talk mode)
+const PUSH_TO_TALK_KEY: KeyCode = KeyCode::Backtick;
+
 /// Register live design for this module
 pub fn live_design(cx: &mut Cx) {
     design::live_design(cx);
@@ -108,6 +112,12 @@ pub struct MoFaFMScreen {
     #[rust]
     mic_muted: bool,
 
+    // Mic turn mode (VAD / push-to-talk / tap-to-end), persisted in preferences
+    #[rust]
+    mic_input_mode: MicInputMode,
+    #[rust]
+    ptt_key_held: bool,
+
     // Dora integration
     #[rust]
     dora_integration: Option<DoraIntegration>,
@@ -466,6 +476,39 @@ impl Widget for MoFaFMScreen {
             }
         }
 
+        // Handle mic turn mode selection (badge on the mic button)
+        if let Some(mode) = mic_btn.mode_changed(&actions) {
+            self.set_mic_input_mode(mode);
+        }
+
+        // Push-to-talk via the mic button (hold) or keyboard
+        let mut ptt_event = mic_btn.push_to_talk(&actions);
+        if self.mic_input_mode == MicInputMode::PushToTalk {
+            match event {
+                Event::KeyDown(ke) if ke.key_code == PUSH_TO_TALK_KEY && !ke.is_repeat && !self.ptt_key_held => {
+                    self.ptt_key_held = true;
+                    ptt_event = Some(true);
+                }
+                Event::KeyUp(ke) if ke.key_code == PUSH_TO_TALK_KEY && self.ptt_key_held => {
+                    self.ptt_key_held = false;
+                    ptt_event = Some(false);
+                }
+                _ => {}
+            }
+        }
+        if let Some(pressed) = ptt_event {
+            if let Some(ref dora) = self.dora_integration {
+                dora.push_to_talk(pressed);
+            }
+        }
+
+        // Tap-to-end: explicit end of the human turn
+        if mic_btn.end_turn_requested(&actions) {
+            if let Some(ref dora) = self.dora_integration {
+                dora.end_turn();
+            }
+        }
+
         // Handle AEC toggle button click
         // AEC toggle switches between:
         // - ON: macOS VoiceProcessingIO with hardware echo cancellation
@@ -560,6 +603,12 @@ impl Widget for MoFaFMScreen {
                     ::log::info!("Screen received StopClicked action");
                     self.handle_mofa_stop(cx);
                 }
+                MofaHeroAction::HoldClicked => {
+                    self.set_audio_hold(cx, true);
+                }
+                MofaHeroAction::ResumeClicked => {
+                    self.set_audio_hold(cx, false);
+                }
                 MofaHeroAction::None => {}
             }
         }
@@ -640,6 +689,11 @@ impl Widget for MoFaFMScreen {
             self.update_log_display(cx);
         }
 
+        // Handle Image button click
+        if self.view.button(ids!(left_column.prompt_container.prompt_section.prompt_row.button_group.attach_image_btn)).clicked(&actions) {
+            self.attach_image(cx);
+        }
+
         // Handle Send button click
         if self.view.button(ids!(left_column.prompt_container.prompt_section.prompt_row.button_group.send_prompt_btn)).clicked(&actions) {
             self.send_prompt(cx);
@@ -1508,6 +1562,10 @@ impl StateChangeListener for MoFaFMScreenRef {
                 draw_bg: { dark_mode: (dark_mode) }
             });
             // NOTE: TextInput apply_over causes "target class not found" errors
+            inner.view.button(ids!(left_column.running_tab_content.prompt_container.prompt_section.prompt_row.button_group.attach_image_btn)).apply_over(cx, live!{
+                draw_bg: { dark_mode: (dark_mode) }
+                draw_text: { dark_mode: (dark_mode) }
+            });
             inner.view.button(ids!(left_column.running_tab_content.prompt_container.prompt_section.prompt_row.button_group.reset_btn)).apply_over(cx, live!{
                 draw_bg: { dark_mode: (dark_mode) }
                 draw_text: { dark_mode: (dark_mode) }
@@ -1057,7 +1057,12 @@ impl Widget for FriendScreen {
             if let Some(dora) = &self.dora {
                 if dora.is_running() {
                     let shared = dora.shared_dora_state();
-                    let chunks = shared.audio.drain();
+                    // While paused/held, leave chunks queued in shared state
+                    let chunks = if shared.audio.is_paused() {
+                        Vec::new()
+                    } else {
+                        shared.audio.drain()
+                    };
                     if !chunks.is_empty() {
                         for audio in chunks {
                             self.stored_audio_samples.extend(&audio.samples);
@@ -469,6 +469,7 @@ impl AddProviderModalRef {
                 models: vec![],
                 is_custom: true,
                 connection_status: ProviderConnectionStatus::Disconnected,
+                latency: None,
             })
         })
     }
@@ -0,0 +1,300 @@
+//! Provider connectivity checks and model discovery
+//!
+//! A check lists the provider's models with `GET {url}/models`, the
+//! OpenAI-compatible endpoint served by OpenAI, DashScope (compatible mode),
+//! DeepSeek, NVIDIA and Ollama. A successful answer both validates the API key
+//! and gives the live model list. Checks run on background threads;
+//! [`ConnectivityChecker`] collects their results for the UI to poll.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use serde_json::Value;
+
+use super::providers::{Provider, ProviderConnectionStatus, ProviderId};
+
+/// Time allowed for one check
+pub const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Result of checking one provider
+#[derive(Clone, Debug)]
+pub struct ConnectionCheck {
+    pub provider_id: ProviderId,
+    pub status: ProviderConnectionStatus,
+    /// Models listed by the provider, empty unless connected
+    pub models: Vec<String>,
+    /// Round trip of the models request
+    pub latency: Option<Duration>,
+}
+
+impl ConnectionCheck {
+    /// Store the result on `provider`. An empty model list keeps the old one.
+    pub fn apply(&self, provider: &mut Provider) {
+        provider.connection_status = self.status.clone();
+        provider.latency = self.latency;
+        if !self.models.is_empty() {
+            provider.models = self.models.clone();
+        }
+    }
+}
+
+/// Models endpoint of a provider's API base URL
+pub fn models_url(base_url: &str) -> String {
+    format!("{}/models", base_url.trim().trim_end_matches('/'))
+}
+
+/// Check `provider` now, blocking for up to `timeout`
+pub fn check_provider(provider: &Provider, timeout: Duration) -> ConnectionCheck {
+    let agent = ureq::AgentBuilder::new().timeout(timeout).build();
+    let mut request = agent.get(&models_url(&provider.url));
+    if let Some(api_key) = provider.api_key.as_deref().filter(|key| !key.is_empty()) {
+        request = request.set("Authorization", &format!("Bearer {}", api_key));
+    }
+
+    let started = Instant::now();
+    let response = request.call();
+    let latency = started.elapsed();
+
+    let error = |message: String| ConnectionCheck {
+        provider_id: provider.id.clone(),
+        status: ProviderConnectionStatus::Error(message),
+        models: Vec::new(),
+        latency: None,
+    };
+    match response {
+        Ok(response) => match response.into_json::<Value>() {
+            Ok(body) => ConnectionCheck {
+                provider_id: provider.id.clone(),
+                status: ProviderConnectionStatus::Connected,
+                models: parse_models(&body),
+                latency: Some(latency),
+            },
+            Err(_) => error("Invalid models response".to_string()),
+        },
+        Err(ureq::Error::Status(code, _)) => error(status_message(code)),
+        Err(ureq::Error::Transport(transport)) => error(transport_message(&transport)),
+    }
+}
+
+/// Model IDs from an OpenAI-style `data` list or an Ollama-style `models` list
+fn parse_models(body: &Value) -> Vec<String> {
+    let mut models: Vec<String> = body
+        .get("data")
+        .or_else(|| body.get("models"))
+        .and_then(Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.get("id").or_else(|| item.get("name")))
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    models.sort();
+    models.dedup();
+    models
+}
+
+fn status_message(code: u16) -> String {
+    match code {
+        401 | 403 => "Invalid API key".to_string(),
+        404 => "Models endpoint not found".to_string(),
+        429 => "Rate limited".to_string(),
+        code => format!("HTTP {}", code),
+    }
+}
+
+fn transport_message(transport: &ureq::Transport) -> String {
+    match transport.kind() {
+        ureq::ErrorKind::Dns => "Unknown host".to_string(),
+        ureq::ErrorKind::ConnectionFailed => "Cannot connect".to_string(),
+        ureq::ErrorKind::InvalidUrl | ureq::ErrorKind::UnknownScheme => "Invalid API host".to_string(),
+        ureq::ErrorKind::Io => "Network error or timeout".to_string(),
+        _ => format!("Network error: {}", transport),
+    }
+}
+
+/// Runs checks on background threads and collects their results
+pub struct ConnectivityChecker {
+    sender: Sender<ConnectionCheck>,
+    receiver: Receiver<ConnectionCheck>,
+    pending: Vec<ProviderId>,
+    timeout: Duration,
+}
+
+impl Default for ConnectivityChecker {
+    fn default() -> Self {
+        Self::new(CHECK_TIMEOUT)
+    }
+}
+
+impl ConnectivityChecker {
+    pub fn new(timeout: Duration) -> Self {
+        let (sender, receiver) = unbounded();
+        Self {
+            sender,
+            receiver,
+            pending: Vec::new(),
+            timeout,
+        }
+    }
+
+    /// Start checking `provider`; false if a check of it is already running
+    pub fn check(&mut self, provider: &Provider) -> bool {
+        if self.is_checking(&provider.id) {
+            return false;
+        }
+        self.pending.push(provider.id.clone());
+        let provider = provider.clone();
+        let sender = self.sender.clone();
+        let timeout = self.timeout;
+        thread::spawn(move || {
+            let _ = sender.send(check_provider(&provider, timeout));
+        });
+        true
+    }
+
+    /// Next finished check, if any
+    pub fn try_recv(&mut self) -> Option<ConnectionCheck> {
+        let check = self.receiver.try_recv().ok()?;
+        self.pending.retain(|id| *id != check.provider_id);
+        Some(check)
+    }
+
+    pub fn is_checking(&self, provider_id: &str) -> bool {
+        self.pending.iter().any(|id| id == provider_id)
+    }
+
+    /// Whether no check is running
+    pub fn is_idle(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    /// Serve one HTTP response on a local port; returns the API base URL and
+    /// a handle yielding the request head
+    fn stub_server(status: &str, body: &str) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/v1", listener.local_addr().unwrap());
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut head = String::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                head.push_str(&line);
+            }
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+            head
+        });
+        (url, handle)
+    }
+
+    fn provider(url: &str, api_key: Option<&str>) -> Provider {
+        Provider {
+            id: "stub".to_string(),
+            url: url.to_string(),
+            api_key: api_key.map(str::to_string),
+            enabled: true,
+            models: vec!["saved-model".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_models_url() {
+        assert_eq!(models_url("https://api.openai.com/v1"), "https://api.openai.com/v1/models");
+        assert_eq!(models_url("https://api.deepseek.com/ "), "https://api.deepseek.com/models");
+    }
+
+    #[test]
+    fn test_parse_models() {
+        let openai = serde_json::json!({"object": "list", "data": [{"id": "gpt-4o"}, {"id": "gpt-4o-mini"}]});
+        assert_eq!(parse_models(&openai), vec!["gpt-4o", "gpt-4o-mini"]);
+        let ollama = serde_json::json!({"models": [{"name": "qwen2.5:7b"}, {"name": "gemma3:4b"}]});
+        assert_eq!(parse_models(&ollama), vec!["gemma3:4b", "qwen2.5:7b"]);
+        assert!(parse_models(&serde_json::json!({"error": "nope"})).is_empty());
+    }
+
+    #[test]
+    fn test_check_lists_models() {
+        let (url, server) = stub_server("200 OK", r#"{"data": [{"id": "qwen-plus"}, {"id": "qwen-max"}]}"#);
+        let mut provider = provider(&url, Some("sk-stub"));
+
+        let check = check_provider(&provider, CHECK_TIMEOUT);
+        let head = server.join().unwrap();
+        assert!(head.starts_with("GET /v1/models "));
+        assert!(head.to_lowercase().contains("authorization: bearer sk-stub"));
+        assert_eq!(check.status, ProviderConnectionStatus::Connected);
+        assert!(check.latency.is_some());
+
+        check.apply(&mut provider);
+        assert_eq!(provider.models, vec!["qwen-max", "qwen-plus"]);
+        assert!(provider.status_text().starts_with("Connected ("));
+    }
+
+    #[test]
+    fn test_check_rejected_key() {
+        let (url, server) = stub_server("401 Unauthorized", r#"{"error": {"message": "bad key"}}"#);
+        let mut provider = provider(&url, Some("sk-wrong"));
+
+        let check = check_provider(&provider, CHECK_TIMEOUT);
+        server.join().unwrap();
+        assert_eq!(check.status, ProviderConnectionStatus::Error("Invalid API key".to_string()));
+
+        // A failed check keeps the saved models
+        check.apply(&mut provider);
+        assert_eq!(provider.models, vec!["saved-model"]);
+        assert_eq!(provider.latency, None);
+    }
+
+    #[test]
+    fn test_check_unreachable_host() {
+        // A port that was just freed refuses connections
+        let url = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}/v1", listener.local_addr().unwrap())
+        };
+        let check = check_provider(&provider(&url, None), CHECK_TIMEOUT);
+        assert!(matches!(check.status, ProviderConnectionStatus::Error(_)));
+    }
+
+    #[test]
+    fn test_checker_runs_in_background() {
+        let (url, server) = stub_server("200 OK", r#"{"data": [{"id": "deepseek-chat"}]}"#);
+        let provider = provider(&url, Some("sk-stub"));
+        let mut checker = ConnectivityChecker::default();
+
+        assert!(checker.check(&provider));
+        assert!(!checker.check(&provider));
+        assert!(checker.is_checking("stub"));
+
+        server.join().unwrap();
+        let check = loop {
+            if let Some(check) = checker.try_recv() {
+                break check;
+            }
+            thread::sleep(Duration::from_millis(10));
+        };
+        assert_eq!(check.models, vec!["deepseek-chat"]);
+        assert!(checker.is_idle());
+    }
+}
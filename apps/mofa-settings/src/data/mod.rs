@@ -1,7 +1,11 @@
 //! Data models for settings
 
+pub mod connectivity;
 pub mod preferences;
 pub mod providers;
+pub mod secrets;
 
+pub use connectivity::{ConnectionCheck, ConnectivityChecker};
 pub use preferences::*;
 pub use providers::*;
+pub use secrets::{mask_secret, secret_store, SecretStore};
@@ -2,9 +2,10 @@
 
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use super::providers::{get_supported_providers, Provider, ProviderId};
+use super::secrets::{secret_store, SecretStore};
 
 /// User preferences for the dashboard
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
@@ -20,6 +21,13 @@ pub struct Preferences {
     /// Dark mode preference (true = dark, false = light)
     #[serde(default)]
     pub dark_mode: bool,
+    /// Mic turn mode: "vad", "push_to_talk" or "tap_to_end" (None = vad)
+    #[serde(default)]
+    pub mic_input_mode: Option<String>,
+    /// Whether provider API keys were read from the secret store; without
+    /// them, a missing key does not mean it was removed
+    #[serde(skip)]
+    secrets_loaded: bool,
 }
 
 impl Preferences {
@@ -33,15 +41,19 @@ impl Preferences {
 
     /// Load preferences from disk, or create defaults if not found
     pub fn load() -> Self {
-        let path = Self::get_preferences_path();
+        Self::load_from(&Self::get_preferences_path(), secret_store())
+    }
 
+    /// Load preferences from `path`, with API keys from `secrets`
+    pub fn load_from(path: &Path, secrets: &dyn SecretStore) -> Self {
         if path.exists() {
-            match fs::read_to_string(&path) {
+            match fs::read_to_string(path) {
                 Ok(content) => {
                     match serde_json::from_str::<Preferences>(&content) {
                         Ok(mut prefs) => {
                             // Merge with supported providers to ensure all are present
                             prefs.merge_with_supported_providers();
+                            prefs.load_secrets(path, secrets);
                             return prefs;
                         }
                         Err(e) => {
@@ -56,14 +68,29 @@ impl Preferences {
         }
 
         // Return defaults with supported providers
-        let mut prefs = Self::default();
-        prefs.providers = get_supported_providers();
+        let mut prefs = Self {
+            providers: get_supported_providers(),
+            ..Default::default()
+        };
+        prefs.load_secrets(path, secrets);
         prefs
     }
 
     /// Save preferences to disk
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let path = Self::get_preferences_path();
+        self.save_to(&Self::get_preferences_path(), secret_store())
+    }
+
+    /// Save preferences to `path` and API keys to `secrets`
+    pub fn save_to(&self, path: &Path, secrets: &dyn SecretStore) -> Result<(), Box<dyn std::error::Error>> {
+        // Keys first: the file below no longer holds them
+        for provider in &self.providers {
+            match provider.api_key.as_deref() {
+                Some(key) if !key.is_empty() => secrets.set(&provider.id, key)?,
+                _ if self.secrets_loaded => secrets.delete(&provider.id)?,
+                _ => {}
+            }
+        }
 
         // Ensure directory exists
         if let Some(parent) = path.parent() {
@@ -71,11 +98,47 @@ impl Preferences {
         }
 
         let content = serde_json::to_string_pretty(self)?;
-        fs::write(&path, content)?;
+        fs::write(path, content)?;
 
         Ok(())
     }
 
+    /// Fill in API keys from the secret store, moving plaintext keys of
+    /// older preference files into it
+    fn load_secrets(&mut self, path: &Path, secrets: &dyn SecretStore) {
+        let mut migrated = false;
+        let mut loaded = true;
+        for provider in &mut self.providers {
+            match provider.api_key.as_deref() {
+                Some(key) if !key.is_empty() => match secrets.set(&provider.id, key) {
+                    Ok(()) => migrated = true,
+                    Err(e) => {
+                        eprintln!("Failed to move the {} API key to the {}: {}", provider.id, secrets.backend_name(), e);
+                        loaded = false;
+                    }
+                },
+                _ => match secrets.get(&provider.id) {
+                    Ok(key) => provider.api_key = key,
+                    Err(e) => {
+                        if loaded {
+                            eprintln!("Failed to read API keys from the {}: {}", secrets.backend_name(), e);
+                        }
+                        loaded = false;
+                    }
+                },
+            }
+        }
+        self.secrets_loaded = loaded;
+
+        // Rewrite the file without the plaintext keys
+        if migrated && loaded {
+            match self.save_to(path, secrets) {
+                Ok(()) => ::log::info!("Moved API keys from {} to the {}", path.display(), secrets.backend_name()),
+                Err(e) => eprintln!("Failed to remove API keys from {}: {}", path.display(), e),
+            }
+        }
+    }
+
     /// Merge loaded preferences with supported providers
     fn merge_with_supported_providers(&mut self) {
         let supported = get_supported_providers();
@@ -130,6 +193,7 @@ impl Preferences {
 mod tests {
     use super::*;
     use crate::data::providers::ProviderType;
+    use crate::data::secrets::{EncryptedFileStore, KeySource};
 
     fn create_test_provider(id: &str, is_custom: bool, enabled: bool) -> Provider {
         Provider {
@@ -156,6 +220,7 @@ mod tests {
         assert!(!prefs.dark_mode);
         assert!(prefs.audio_input_device.is_none());
         assert!(prefs.audio_output_device.is_none());
+        assert!(prefs.mic_input_mode.is_none());
     }
 
     #[test]
@@ -287,6 +352,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn test_merge_does_not_duplicate() {
         let mut prefs = Preferences::default();
         // Pre-populate with supported providers
@@ -339,4 +405,39 @@ mod tests {
         assert!(prefs.audio_input_device.is_none());
         assert!(prefs.audio_output_device.is_none());
     }
+
+    #[test]
+    fn test_plaintext_keys_migrate_to_secret_store() {
+        let dir = std::env::temp_dir().join(format!("mofa-preferences-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("preferences.json");
+        let secrets = EncryptedFileStore::new(dir.join("secrets.json"), KeySource::Machine(dir.join("secrets.key")));
+
+        // A file written before the secret store
+        let mut old = serde_json::to_value(Preferences {
+            providers: get_supported_providers(),
+            ..Default::default()
+        })
+        .unwrap();
+        old["providers"][0]["api_key"] = "sk-plaintext-openai".into();
+        fs::write(&path, old.to_string()).unwrap();
+
+        let mut prefs = Preferences::load_from(&path, &secrets);
+        assert_eq!(prefs.get_provider("openai").unwrap().api_key.as_deref(), Some("sk-plaintext-openai"));
+        assert!(!fs::read_to_string(&path).unwrap().contains("sk-plaintext"));
+        assert_eq!(secrets.get("openai").unwrap().as_deref(), Some("sk-plaintext-openai"));
+
+        // Keys are set and removed through save
+        prefs.get_provider_mut("deepseek").unwrap().api_key = Some("sk-deepseek".to_string());
+        prefs.get_provider_mut("openai").unwrap().api_key = None;
+        prefs.save_to(&path, &secrets).unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("sk-deepseek"));
+
+        let prefs = Preferences::load_from(&path, &secrets);
+        assert!(prefs.get_provider("openai").unwrap().api_key.is_none());
+        assert_eq!(prefs.get_provider("deepseek").unwrap().api_key.as_deref(), Some("sk-deepseek"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
 }
@@ -1,7 +1,11 @@
 //! Provider data models
 
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
+use super::secrets::mask_secret;
+
 /// Unique identifier for a provider
 pub type ProviderId = String;
 
@@ -13,6 +17,7 @@ pub enum ProviderType {
     DeepSeek,
     AlibabaCloud,
     Nvidia,
+    Ollama,
     Custom,
 }
 
@@ -23,9 +28,15 @@ impl ProviderType {
             ProviderType::DeepSeek => "DeepSeek",
             ProviderType::AlibabaCloud => "Alibaba Cloud",
             ProviderType::Nvidia => "NVIDIA",
+            ProviderType::Ollama => "Ollama",
             ProviderType::Custom => "Custom",
         }
     }
+
+    /// Whether the provider runs locally and needs no API key
+    pub fn is_local(&self) -> bool {
+        matches!(self, ProviderType::Ollama)
+    }
 }
 
 /// Connection status of a provider
@@ -51,14 +62,27 @@ impl ProviderConnectionStatus {
     pub fn is_connected(&self) -> bool {
         matches!(self, ProviderConnectionStatus::Connected)
     }
+
+    /// Badge color of this status
+    pub fn color(&self) -> &'static str {
+        match self {
+            ProviderConnectionStatus::Connected => "#22c55e", // Green
+            ProviderConnectionStatus::Connecting => "#f59e0b", // Yellow
+            ProviderConnectionStatus::Disconnected => "#6b7280", // Gray
+            ProviderConnectionStatus::Error(_) => "#ef4444",  // Red
+        }
+    }
 }
 
 /// A configured AI provider
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Provider {
     pub id: ProviderId,
     pub name: String,
     pub url: String,
+    /// Kept in the secret store, never written to `preferences.json`; only
+    /// read from it to migrate older files
+    #[serde(default, skip_serializing)]
     pub api_key: Option<String>,
     pub provider_type: ProviderType,
     pub enabled: bool,
@@ -66,6 +90,26 @@ pub struct Provider {
     pub is_custom: bool,
     #[serde(skip)]
     pub connection_status: ProviderConnectionStatus,
+    /// Round trip of the last successful connection check
+    #[serde(skip)]
+    pub latency: Option<Duration>,
+}
+
+impl std::fmt::Debug for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Provider")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .field("url", &self.url)
+            .field("api_key", &self.api_key.as_deref().map(mask_secret))
+            .field("provider_type", &self.provider_type)
+            .field("enabled", &self.enabled)
+            .field("models", &self.models)
+            .field("is_custom", &self.is_custom)
+            .field("connection_status", &self.connection_status)
+            .field("latency", &self.latency)
+            .finish()
+    }
 }
 
 impl Default for Provider {
@@ -80,6 +124,7 @@ impl Default for Provider {
             models: Vec::new(),
             is_custom: false,
             connection_status: ProviderConnectionStatus::Disconnected,
+            latency: None,
         }
     }
 }
@@ -97,6 +142,7 @@ impl Provider {
             models: Vec::new(),
             is_custom: true,
             connection_status: ProviderConnectionStatus::Disconnected,
+            latency: None,
         }
     }
 
@@ -107,16 +153,21 @@ impl Provider {
             .collect()
     }
 
+    /// Status for display, with the latency of the last check when connected
+    pub fn status_text(&self) -> String {
+        match (&self.connection_status, self.latency) {
+            (ProviderConnectionStatus::Connected, Some(latency)) => {
+                format!("Connected ({} ms)", latency.as_millis())
+            }
+            (status, _) => status.display_text().to_string(),
+        }
+    }
+
     pub fn status_color(&self) -> &'static str {
         if !self.enabled {
             "#9ca3af" // Gray - disabled
         } else {
-            match &self.connection_status {
-                ProviderConnectionStatus::Connected => "#22c55e", // Green
-                ProviderConnectionStatus::Connecting => "#f59e0b", // Yellow
-                ProviderConnectionStatus::Disconnected => "#6b7280", // Gray
-                ProviderConnectionStatus::Error(_) => "#ef4444",  // Red
-            }
+            self.connection_status.color()
         }
     }
 }
@@ -138,6 +189,7 @@ pub fn get_supported_providers() -> Vec<Provider> {
             ],
             is_custom: false,
             connection_status: ProviderConnectionStatus::Disconnected,
+            latency: None,
         },
         Provider {
             id: "deepseek".to_string(),
@@ -149,6 +201,7 @@ pub fn get_supported_providers() -> Vec<Provider> {
             models: vec!["deepseek-chat".to_string(), "deepseek-reasoner".to_string()],
             is_custom: false,
             connection_status: ProviderConnectionStatus::Disconnected,
+            latency: None,
         },
         Provider {
             id: "alibaba_cloud".to_string(),
@@ -164,6 +217,7 @@ pub fn get_supported_providers() -> Vec<Provider> {
             ],
             is_custom: false,
             connection_status: ProviderConnectionStatus::Disconnected,
+            latency: None,
         },
         Provider {
             id: "nvidia".to_string(),
@@ -181,6 +235,23 @@ pub fn get_supported_providers() -> Vec<Provider> {
             ],
             is_custom: false,
             connection_status: ProviderConnectionStatus::Disconnected,
+            latency: None,
+        },
+        Provider {
+            id: "ollama".to_string(),
+            name: "Ollama (Local)".to_string(),
+            url: "http://localhost:11434/v1".to_string(),
+            api_key: None,
+            provider_type: ProviderType::Ollama,
+            enabled: false,
+            models: vec![
+                "qwen2.5:7b".to_string(),
+                "llama3.2:3b".to_string(),
+                "gemma3:4b".to_string(),
+            ],
+            is_custom: false,
+            connection_status: ProviderConnectionStatus::Disconnected,
+            latency: None,
         },
     ]
 }
@@ -195,6 +266,7 @@ mod tests {
         assert_eq!(ProviderType::DeepSeek.display_name(), "DeepSeek");
         assert_eq!(ProviderType::AlibabaCloud.display_name(), "Alibaba Cloud");
         assert_eq!(ProviderType::Nvidia.display_name(), "NVIDIA");
+        assert_eq!(ProviderType::Ollama.display_name(), "Ollama");
         assert_eq!(ProviderType::Custom.display_name(), "Custom");
     }
 
@@ -241,6 +313,21 @@ mod tests {
         assert_eq!(Provider::generate_id("Test123"), "test123");
     }
 
+    #[test]
+    fn test_status_text_with_latency() {
+        let mut provider = Provider {
+            connection_status: ProviderConnectionStatus::Connected,
+            ..Default::default()
+        };
+        assert_eq!(provider.status_text(), "Connected");
+
+        provider.latency = Some(Duration::from_millis(142));
+        assert_eq!(provider.status_text(), "Connected (142 ms)");
+
+        provider.connection_status = ProviderConnectionStatus::Error("Invalid API key".to_string());
+        assert_eq!(provider.status_text(), "Invalid API key");
+    }
+
     #[test]
     fn test_status_color_disabled() {
         let provider = Provider {
@@ -293,7 +380,7 @@ mod tests {
     fn test_get_supported_providers() {
         let providers = get_supported_providers();
 
-        assert_eq!(providers.len(), 4);
+        assert_eq!(providers.len(), 5);
 
         // Check OpenAI
         let openai = providers.iter().find(|p| p.id == "openai").unwrap();
@@ -318,6 +405,31 @@ mod tests {
         assert_eq!(nvidia.provider_type, ProviderType::Nvidia);
         assert_eq!(nvidia.url, "https://integrate.api.nvidia.com/v1");
         assert!(nvidia.models.contains(&"deepseek-ai/deepseek-r1".to_string()));
+
+        // Check Ollama (local, no API key)
+        let ollama = providers.iter().find(|p| p.id == "ollama").unwrap();
+        assert_eq!(ollama.provider_type, ProviderType::Ollama);
+        assert!(ollama.provider_type.is_local());
+        assert_eq!(ollama.url, "http://localhost:11434/v1");
+        assert!(ollama.api_key.is_none());
+    }
+
+    #[test]
+    fn test_api_key_not_serialized_or_logged() {
+        let provider = Provider {
+            id: "openai".to_string(),
+            api_key: Some("sk-live-1234567890".to_string()),
+            ..Default::default()
+        };
+
+        let json = serde_json::to_string(&provider).unwrap();
+        assert!(!json.contains("sk-live"));
+        assert!(!format!("{:?}", provider).contains("sk-live"));
+
+        // Older files with a plaintext key still load, for migration
+        let old = r#"{"id":"openai","name":"OpenAI","url":"","api_key":"sk-old","provider_type":"OpenAi","enabled":true,"models":[],"is_custom":false}"#;
+        let provider: Provider = serde_json::from_str(old).unwrap();
+        assert_eq!(provider.api_key.as_deref(), Some("sk-old"));
     }
 
     #[test]
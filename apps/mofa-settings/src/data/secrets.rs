@@ -0,0 +1,493 @@
+//! Secret storage for provider API keys
+//!
+//! API keys are kept out of `preferences.json` in a [`SecretStore`]:
+//!
+//! - **Encrypted file** (default): `~/.dora/dashboard/secrets.json`, encrypted
+//!   with XChaCha20-Poly1305. The key comes from `MOFA_SECRETS_PASSPHRASE`
+//!   (through Argon2id) when set, otherwise from a random machine key in
+//!   `~/.dora/dashboard/secrets.key`, readable only by the user. The machine
+//!   key keeps keys out of shared or backed-up preference files; use a
+//!   passphrase to also protect them from other software running as the user.
+//! - **OS keyring** (`keyring` feature, `MOFA_SECRET_STORE=keyring`): macOS
+//!   Keychain, Windows Credential Manager or the Secret Service on Linux.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use parking_lot::Mutex;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+/// Passphrase for the encrypted file store
+pub const PASSPHRASE_ENV: &str = "MOFA_SECRETS_PASSPHRASE";
+/// Backend selection: `file` (default) or `keyring`
+pub const BACKEND_ENV: &str = "MOFA_SECRET_STORE";
+/// Service name of keyring entries
+pub const KEYRING_SERVICE: &str = "mofa-studio";
+
+const FILE_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+pub type SecretResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+/// Storage for secrets such as API keys, by account name (the provider ID)
+pub trait SecretStore: Send + Sync {
+    /// Backend name for logs
+    fn backend_name(&self) -> &'static str;
+
+    /// Read a secret; `None` if it was never stored
+    fn get(&self, account: &str) -> SecretResult<Option<String>>;
+
+    /// Store a secret, replacing an older one
+    fn set(&self, account: &str, secret: &str) -> SecretResult<()>;
+
+    /// Remove a secret; removing a missing one is not an error
+    fn delete(&self, account: &str) -> SecretResult<()>;
+}
+
+/// The secret store of this process, chosen from `MOFA_SECRET_STORE`
+pub fn secret_store() -> &'static dyn SecretStore {
+    static STORE: OnceLock<Box<dyn SecretStore>> = OnceLock::new();
+    STORE.get_or_init(open_secret_store).as_ref()
+}
+
+fn open_secret_store() -> Box<dyn SecretStore> {
+    let backend = std::env::var(BACKEND_ENV).unwrap_or_default();
+    match backend.trim() {
+        #[cfg(feature = "keyring")]
+        "keyring" => return Box::new(KeyringStore::new(KEYRING_SERVICE)),
+        #[cfg(not(feature = "keyring"))]
+        "keyring" => {
+            eprintln!("{}=keyring needs the `keyring` feature, using the encrypted file", BACKEND_ENV);
+        }
+        "" | "file" => {}
+        other => eprintln!("Unknown {} '{}', using the encrypted file", BACKEND_ENV, other),
+    }
+    Box::new(EncryptedFileStore::new(
+        EncryptedFileStore::default_path(),
+        KeySource::from_env(),
+    ))
+}
+
+/// Mask a secret for log output, keeping only its length
+pub fn mask_secret(secret: &str) -> String {
+    if secret.is_empty() {
+        "(empty)".to_string()
+    } else {
+        format!("***({} chars)", secret.chars().count())
+    }
+}
+
+/// Where the encryption key of an [`EncryptedFileStore`] comes from
+#[derive(Clone)]
+pub enum KeySource {
+    /// Random key stored in a file next to the secrets
+    Machine(PathBuf),
+    /// Key derived from a passphrase with Argon2id
+    Passphrase(String),
+}
+
+impl KeySource {
+    /// `MOFA_SECRETS_PASSPHRASE` if set, otherwise the machine key
+    pub fn from_env() -> Self {
+        match std::env::var(PASSPHRASE_ENV) {
+            Ok(passphrase) if !passphrase.is_empty() => KeySource::Passphrase(passphrase),
+            _ => KeySource::Machine(EncryptedFileStore::default_key_path()),
+        }
+    }
+
+    fn kdf(&self) -> Kdf {
+        match self {
+            KeySource::Machine(_) => Kdf::Machine,
+            KeySource::Passphrase(_) => Kdf::Argon2id,
+        }
+    }
+}
+
+impl std::fmt::Debug for KeySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeySource::Machine(path) => f.debug_tuple("Machine").field(path).finish(),
+            KeySource::Passphrase(passphrase) => {
+                f.debug_tuple("Passphrase").field(&mask_secret(passphrase)).finish()
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Kdf {
+    Machine,
+    Argon2id,
+}
+
+/// On-disk format of the encrypted file
+#[derive(Serialize, Deserialize)]
+struct SecretsFile {
+    version: u32,
+    kdf: Kdf,
+    /// Hex; empty for the machine key
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Decrypted contents and the key used for the next write
+struct Unlocked {
+    kdf: Kdf,
+    key: [u8; 32],
+    salt: Vec<u8>,
+    secrets: BTreeMap<String, String>,
+}
+
+/// Secrets in one encrypted JSON file
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    key_source: KeySource,
+    unlocked: Mutex<Option<Unlocked>>,
+}
+
+impl EncryptedFileStore {
+    pub fn new(path: PathBuf, key_source: KeySource) -> Self {
+        Self {
+            path,
+            key_source,
+            unlocked: Mutex::new(None),
+        }
+    }
+
+    /// `~/.dora/dashboard/secrets.json`
+    pub fn default_path() -> PathBuf {
+        dashboard_dir().join("secrets.json")
+    }
+
+    /// `~/.dora/dashboard/secrets.key`
+    pub fn default_key_path() -> PathBuf {
+        dashboard_dir().join("secrets.key")
+    }
+
+    /// Run `f` on the decrypted secrets, reading the file the first time
+    fn with_secrets<R>(&self, f: impl FnOnce(&mut Unlocked) -> SecretResult<R>) -> SecretResult<R> {
+        let mut unlocked = self.unlocked.lock();
+        if unlocked.is_none() {
+            *unlocked = Some(self.unlock()?);
+        }
+        f(unlocked.as_mut().expect("secrets unlocked above"))
+    }
+
+    fn unlock(&self) -> SecretResult<Unlocked> {
+        let kdf = self.key_source.kdf();
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let salt = new_salt(kdf);
+                let key = self.derive_key(kdf, &salt, true)?;
+                return Ok(Unlocked { kdf, key, salt, secrets: BTreeMap::new() });
+            }
+            Err(e) => return Err(format!("Failed to read {}: {}", self.path.display(), e).into()),
+        };
+
+        let file: SecretsFile = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid secrets file {}: {}", self.path.display(), e))?;
+        if file.version > FILE_VERSION {
+            return Err(format!(
+                "Secrets file {} has version {}, this build reads up to {}",
+                self.path.display(),
+                file.version,
+                FILE_VERSION
+            )
+            .into());
+        }
+        let salt = from_hex(&file.salt)?;
+        let file_key = self.derive_key(file.kdf, &salt, false)?;
+        let cipher = XChaCha20Poly1305::new(Key::from_slice(&file_key));
+        let nonce = from_hex(&file.nonce)?;
+        if nonce.len() != NONCE_LEN {
+            return Err(format!("Invalid nonce in {}", self.path.display()).into());
+        }
+        let plaintext = cipher
+            .decrypt(XNonce::from_slice(&nonce), from_hex(&file.ciphertext)?.as_slice())
+            .map_err(|_| match file.kdf {
+                Kdf::Argon2id => format!("Wrong {} for {}", PASSPHRASE_ENV, self.path.display()),
+                Kdf::Machine => format!("Secrets in {} do not match the machine key", self.path.display()),
+            })?;
+        let secrets = serde_json::from_slice(&plaintext)?;
+
+        // A newly set (or removed) passphrase applies from the next write on
+        if file.kdf == kdf {
+            Ok(Unlocked { kdf, key: file_key, salt, secrets })
+        } else {
+            let salt = new_salt(kdf);
+            let key = self.derive_key(kdf, &salt, true)?;
+            Ok(Unlocked { kdf, key, salt, secrets })
+        }
+    }
+
+    fn derive_key(&self, kdf: Kdf, salt: &[u8], create: bool) -> SecretResult<[u8; 32]> {
+        let mut key = [0u8; 32];
+        match (kdf, &self.key_source) {
+            (Kdf::Argon2id, KeySource::Passphrase(passphrase)) => {
+                Argon2::default()
+                    .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+                    .map_err(|e| format!("Failed to derive the secrets key: {}", e))?;
+            }
+            (Kdf::Argon2id, KeySource::Machine(_)) => {
+                return Err(format!(
+                    "{} is protected by a passphrase, set {}",
+                    self.path.display(),
+                    PASSPHRASE_ENV
+                )
+                .into());
+            }
+            (Kdf::Machine, _) => key = self.machine_key(create)?,
+        }
+        Ok(key)
+    }
+
+    /// Read the machine key, creating it if `create` is set
+    fn machine_key(&self, create: bool) -> SecretResult<[u8; 32]> {
+        let path = match &self.key_source {
+            KeySource::Machine(path) => path.clone(),
+            KeySource::Passphrase(_) => Self::default_key_path(),
+        };
+        match fs::read_to_string(&path) {
+            Ok(hex) => from_hex(hex.trim())?
+                .try_into()
+                .map_err(|_| format!("Invalid machine key {}", path.display()).into()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && create => {
+                let mut key = [0u8; 32];
+                rand::rngs::OsRng.fill_bytes(&mut key);
+                write_private(&path, to_hex(&key).as_bytes())?;
+                Ok(key)
+            }
+            Err(e) => Err(format!("Failed to read machine key {}: {}", path.display(), e).into()),
+        }
+    }
+
+    fn write(&self, unlocked: &Unlocked) -> SecretResult<()> {
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+        let cipher = XChaCha20Poly1305::new(Key::from_slice(&unlocked.key));
+        let plaintext = serde_json::to_vec(&unlocked.secrets)?;
+        let ciphertext = cipher
+            .encrypt(XNonce::from_slice(&nonce), plaintext.as_slice())
+            .map_err(|_| "Failed to encrypt secrets")?;
+        let file = SecretsFile {
+            version: FILE_VERSION,
+            kdf: unlocked.kdf,
+            salt: to_hex(&unlocked.salt),
+            nonce: to_hex(&nonce),
+            ciphertext: to_hex(&ciphertext),
+        };
+        write_private(&self.path, serde_json::to_string_pretty(&file)?.as_bytes())
+    }
+}
+
+impl SecretStore for EncryptedFileStore {
+    fn backend_name(&self) -> &'static str {
+        "encrypted file"
+    }
+
+    fn get(&self, account: &str) -> SecretResult<Option<String>> {
+        self.with_secrets(|unlocked| Ok(unlocked.secrets.get(account).cloned()))
+    }
+
+    fn set(&self, account: &str, secret: &str) -> SecretResult<()> {
+        self.with_secrets(|unlocked| {
+            if unlocked.secrets.get(account).map(String::as_str) == Some(secret) {
+                return Ok(());
+            }
+            unlocked.secrets.insert(account.to_string(), secret.to_string());
+            self.write(unlocked)
+        })
+    }
+
+    fn delete(&self, account: &str) -> SecretResult<()> {
+        self.with_secrets(|unlocked| {
+            if unlocked.secrets.remove(account).is_none() {
+                return Ok(());
+            }
+            self.write(unlocked)
+        })
+    }
+}
+
+/// Secrets in the OS keyring
+#[cfg(feature = "keyring")]
+pub struct KeyringStore {
+    service: String,
+}
+
+#[cfg(feature = "keyring")]
+impl KeyringStore {
+    pub fn new(service: &str) -> Self {
+        Self { service: service.to_string() }
+    }
+
+    fn entry(&self, account: &str) -> SecretResult<keyring::Entry> {
+        Ok(keyring::Entry::new(&self.service, account)?)
+    }
+}
+
+#[cfg(feature = "keyring")]
+impl SecretStore for KeyringStore {
+    fn backend_name(&self) -> &'static str {
+        "OS keyring"
+    }
+
+    fn get(&self, account: &str) -> SecretResult<Option<String>> {
+        match self.entry(account)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set(&self, account: &str, secret: &str) -> SecretResult<()> {
+        Ok(self.entry(account)?.set_password(secret)?)
+    }
+
+    fn delete(&self, account: &str) -> SecretResult<()> {
+        match self.entry(account)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+fn dashboard_dir() -> PathBuf {
+    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    home.join(".dora").join("dashboard")
+}
+
+fn new_salt(kdf: Kdf) -> Vec<u8> {
+    match kdf {
+        Kdf::Machine => Vec::new(),
+        Kdf::Argon2id => {
+            let mut salt = vec![0u8; SALT_LEN];
+            rand::rngs::OsRng.fill_bytes(&mut salt);
+            salt
+        }
+    }
+}
+
+/// Write a file only the user can read, through a temporary file
+fn write_private(path: &Path, content: &[u8]) -> SecretResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("tmp");
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    {
+        use std::io::Write;
+        let mut file = options.open(&tmp)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> SecretResult<Vec<u8>> {
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .filter(|pair| pair.len() == 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| "Invalid hex in secrets file".into())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("mofa-secrets-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_mask_secret() {
+        assert_eq!(mask_secret(""), "(empty)");
+        assert_eq!(mask_secret("sk-1234567890abcdef"), "***(19 chars)");
+        let debug = format!("{:?}", KeySource::Passphrase("hunter22".to_string()));
+        assert!(!debug.contains("hunter22"));
+    }
+
+    #[test]
+    fn test_hex_roundtrip() {
+        let bytes = [0u8, 1, 0xab, 0xff];
+        assert_eq!(to_hex(&bytes), "0001abff");
+        assert_eq!(from_hex("0001abff").unwrap(), bytes);
+        assert!(from_hex("abc").is_err());
+        assert!(from_hex("zz").is_err());
+    }
+
+    #[test]
+    fn test_machine_key_store() {
+        let dir = temp_dir("machine");
+        let path = dir.join("secrets.json");
+        let source = KeySource::Machine(dir.join("secrets.key"));
+
+        let store = EncryptedFileStore::new(path.clone(), source.clone());
+        assert_eq!(store.get("openai").unwrap(), None);
+        store.set("openai", "sk-test-openai").unwrap();
+        store.set("deepseek", "sk-test-deepseek").unwrap();
+        store.delete("deepseek").unwrap();
+        store.delete("missing").unwrap();
+
+        // Keys never appear in the file
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("sk-test"));
+
+        // A new store (e.g. another app) reads them back
+        let store = EncryptedFileStore::new(path, source);
+        assert_eq!(store.get("openai").unwrap().as_deref(), Some("sk-test-openai"));
+        assert_eq!(store.get("deepseek").unwrap(), None);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_passphrase_store() {
+        let dir = temp_dir("passphrase");
+        let path = dir.join("secrets.json");
+
+        let store = EncryptedFileStore::new(path.clone(), KeySource::Passphrase("correct horse".to_string()));
+        store.set("nvidia", "nvapi-secret").unwrap();
+
+        let wrong = EncryptedFileStore::new(path.clone(), KeySource::Passphrase("battery staple".to_string()));
+        assert!(wrong.get("nvidia").is_err());
+
+        let no_passphrase = EncryptedFileStore::new(path.clone(), KeySource::Machine(dir.join("secrets.key")));
+        let error = no_passphrase.get("nvidia").unwrap_err().to_string();
+        assert!(error.contains(PASSPHRASE_ENV));
+
+        let store = EncryptedFileStore::new(path, KeySource::Passphrase("correct horse".to_string()));
+        assert_eq!(store.get("nvidia").unwrap().as_deref(), Some("nvapi-secret"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -630,8 +630,9 @@ impl ProviderViewRef {
                 provider.api_key.as_deref().unwrap_or("")
             );
 
-            // Update sync button state based on API key
-            let has_api_key = provider.api_key.as_ref().map(|k| !k.is_empty()).unwrap_or(false);
+            // Update sync button state based on API key (local providers need none)
+            let has_api_key = provider.provider_type.is_local()
+                || provider.api_key.as_ref().map(|k| !k.is_empty()).unwrap_or(false);
             inner.view.button(ids!(sync_button)).apply_over(cx, live!{
                 draw_bg: { disabled: (if has_api_key { 0.0 } else { 1.0 }) }
             });
@@ -662,6 +663,18 @@ impl ProviderViewRef {
         }
     }
 
+    /// Show the result of a connection check in the header
+    pub fn show_connection(&self, cx: &mut Cx, provider: &Provider) {
+        if let Some(inner) = self.borrow_mut() {
+            let status_text = match &provider.connection_status {
+                ProviderConnectionStatus::Disconnected => String::new(),
+                _ => provider.status_text(),
+            };
+            inner.view.label(ids!(status_label)).set_text(cx, &status_text);
+            inner.view.redraw(cx);
+        }
+    }
+
     /// Internal helper to display models
     fn display_models_internal(inner: &mut std::cell::RefMut<ProviderView>, cx: &mut Cx, models: &[String]) {
         // Show/hide no models label based on whether we have models
@@ -1,7 +1,7 @@
 //! Providers Panel - List of AI providers with collapsible custom providers section
 
 use makepad_widgets::*;
-use crate::data::{Provider, ProviderId, Preferences};
+use crate::data::{Provider, ProviderConnectionStatus, ProviderId, Preferences};
 
 live_design! {
     use link::theme::*;
@@ -53,6 +53,37 @@ live_design! {
         align: {x: 0.0, y: 0.5}
     }
 
+    // Connection status badge - dot plus latency, shown once a provider was checked
+    StatusBadge = <View> {
+        width: Fit, height: Fit
+        flow: Right
+        align: {x: 0.0, y: 0.5}
+        spacing: 4
+        visible: false
+
+        status_dot = <View> {
+            width: 8, height: 8
+            show_bg: true
+            draw_bg: {
+                color: #6b7280
+                fn pixel(self) -> vec4 {
+                    let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                    sdf.circle(4.0, 4.0, 4.0);
+                    sdf.fill(self.color);
+                    return sdf.result;
+                }
+            }
+        }
+
+        status_text = <Label> {
+            text: ""
+            draw_text: {
+                color: (SLATE_500)
+                text_style: <FONT_REGULAR>{ font_size: 10.0 }
+            }
+        }
+    }
+
     // Custom provider item - View with hover/selected effects
     // Uses custom shader with instance variables (proven to work like SectionHeader)
     CustomProviderItem = <View> {
@@ -109,10 +140,13 @@ live_design! {
             }
             text: "Custom Provider"
         }
+
+        status_badge = <StatusBadge> {}
     }
 
     // Provider label
     ProviderLabel = <Label> {
+        width: Fill
         draw_text: {
             color: (GRAY_700)
             text_style: <FONT_REGULAR>{ font_size: 12.0 }
@@ -292,6 +326,7 @@ live_design! {
                     openai_label = <ProviderLabel> {
                         text: "OpenAI"
                     }
+                    status_badge = <StatusBadge> {}
                 }
 
                 deepseek_item = <ProviderItem> {
@@ -305,6 +340,7 @@ live_design! {
                     deepseek_label = <ProviderLabel> {
                         text: "DeepSeek"
                     }
+                    status_badge = <StatusBadge> {}
                 }
 
                 alibaba_item = <ProviderItem> {
@@ -318,6 +354,7 @@ live_design! {
                     alibaba_label = <ProviderLabel> {
                         text: "Alibaba Cloud (Qwen)"
                     }
+                    status_badge = <StatusBadge> {}
                 }
 
                 nvidia_item = <ProviderItem> {
@@ -331,6 +368,21 @@ live_design! {
                     nvidia_label = <ProviderLabel> {
                         text: "NVIDIA"
                     }
+                    status_badge = <StatusBadge> {}
+                }
+
+                ollama_item = <ProviderItem> {
+                    <Icon> {
+                        draw_icon: {
+                            svg_file: (ICO_DEEPSEEK)
+                            fn get_color(self) -> vec4 { return (SLATE_500); }
+                        }
+                        icon_walk: {width: 20, height: 20, margin: {right: 10}}
+                    }
+                    ollama_label = <ProviderLabel> {
+                        text: "Ollama (Local)"
+                    }
+                    status_badge = <StatusBadge> {}
                 }
             }
 
@@ -422,6 +474,7 @@ impl Widget for ProvidersPanel {
             ids!(scroll_view.list_container.deepseek_item),
             ids!(scroll_view.list_container.alibaba_item),
             ids!(scroll_view.list_container.nvidia_item),
+            ids!(scroll_view.list_container.ollama_item),
         ];
 
         // Handle hover effects for built-in providers
@@ -568,6 +621,9 @@ impl Widget for ProvidersPanel {
         if self.view.view(ids!(scroll_view.list_container.nvidia_item)).finger_up(actions).is_some() {
             new_selection = Some(ProviderId::from("nvidia"));
         }
+        if self.view.view(ids!(scroll_view.list_container.ollama_item)).finger_up(actions).is_some() {
+            new_selection = Some(ProviderId::from("ollama"));
+        }
 
         if let Some(id) = new_selection {
             if self.selected_provider_id.as_ref() != Some(&id) {
@@ -620,6 +676,7 @@ impl ProvidersPanel {
             Some("deepseek") => item_id == ids!(scroll_view.list_container.deepseek_item),
             Some("alibaba_cloud") => item_id == ids!(scroll_view.list_container.alibaba_item),
             Some("nvidia") => item_id == ids!(scroll_view.list_container.nvidia_item),
+            Some("ollama") => item_id == ids!(scroll_view.list_container.ollama_item),
             _ => false,
         }
     }
@@ -639,6 +696,7 @@ impl ProvidersPanel {
             ids!(scroll_view.list_container.deepseek_item),
             ids!(scroll_view.list_container.alibaba_item),
             ids!(scroll_view.list_container.nvidia_item),
+            ids!(scroll_view.list_container.ollama_item),
         ];
 
         let normal_color = if self.dark_mode {
@@ -702,6 +760,11 @@ impl ProvidersPanel {
                     draw_bg: { color: (selected_color) }
                 });
             }
+            "ollama" => {
+                self.view.view(ids!(scroll_view.list_container.ollama_item)).apply_over(cx, live!{
+                    draw_bg: { color: (selected_color) }
+                });
+            }
             _ => {
                 // Check if it's a custom provider - use instance variable for selected
                 for (i, provider) in self.custom_providers.iter().enumerate() {
@@ -740,6 +803,7 @@ impl ProvidersPanelRef {
             "deepseek_item" => Some(ProviderId::from("deepseek")),
             "alibaba_item" => Some(ProviderId::from("alibaba_cloud")),
             "nvidia_item" => Some(ProviderId::from("nvidia")),
+            "ollama_item" => Some(ProviderId::from("ollama")),
             _ => None,
         }
     }
@@ -781,6 +845,7 @@ impl ProvidersPanelRef {
                 ("deepseek", ids!(scroll_view.list_container.deepseek_item), ids!(scroll_view.list_container.deepseek_item.deepseek_label)),
                 ("alibaba_cloud", ids!(scroll_view.list_container.alibaba_item), ids!(scroll_view.list_container.alibaba_item.alibaba_label)),
                 ("nvidia", ids!(scroll_view.list_container.nvidia_item), ids!(scroll_view.list_container.nvidia_item.nvidia_label)),
+                ("ollama", ids!(scroll_view.list_container.ollama_item), ids!(scroll_view.list_container.ollama_item.ollama_label)),
             ];
 
             for (provider_name, item_path, label_path) in items {
@@ -921,6 +986,56 @@ impl ProvidersPanelRef {
         }
     }
 
+    /// Show a provider's connection status and latency on its badge
+    pub fn set_provider_status(&self, cx: &mut Cx, provider: &Provider) {
+        if let Some(mut inner) = self.borrow_mut() {
+            let custom_badges = [
+                ids!(scroll_view.custom_section.custom_provider_1.status_badge),
+                ids!(scroll_view.custom_section.custom_provider_2.status_badge),
+                ids!(scroll_view.custom_section.custom_provider_3.status_badge),
+                ids!(scroll_view.custom_section.custom_provider_4.status_badge),
+                ids!(scroll_view.custom_section.custom_provider_5.status_badge),
+                ids!(scroll_view.custom_section.custom_provider_6.status_badge),
+                ids!(scroll_view.custom_section.custom_provider_7.status_badge),
+                ids!(scroll_view.custom_section.custom_provider_8.status_badge),
+                ids!(scroll_view.custom_section.custom_provider_9.status_badge),
+                ids!(scroll_view.custom_section.custom_provider_10.status_badge),
+            ];
+
+            let badge_path = match provider.id.as_str() {
+                "openai" => ids!(scroll_view.list_container.openai_item.status_badge),
+                "deepseek" => ids!(scroll_view.list_container.deepseek_item.status_badge),
+                "alibaba_cloud" => ids!(scroll_view.list_container.alibaba_item.status_badge),
+                "nvidia" => ids!(scroll_view.list_container.nvidia_item.status_badge),
+                "ollama" => ids!(scroll_view.list_container.ollama_item.status_badge),
+                _ => match inner.custom_providers.iter().position(|p| p.id == provider.id) {
+                    Some(i) if i < custom_badges.len() => custom_badges[i],
+                    _ => return,
+                },
+            };
+
+            // Latency when connected, nothing before the first check
+            let text = match &provider.connection_status {
+                ProviderConnectionStatus::Disconnected => None,
+                ProviderConnectionStatus::Connecting => Some("...".to_string()),
+                ProviderConnectionStatus::Connected => Some(
+                    provider.latency.map(|latency| format!("{} ms", latency.as_millis())).unwrap_or_default()
+                ),
+                ProviderConnectionStatus::Error(_) => Some("Error".to_string()),
+            };
+            let color = hex_color(provider.connection_status.color());
+
+            inner.view.view(badge_path).set_visible(cx, text.is_some());
+            let badge = inner.view.widget(badge_path);
+            badge.view(ids!(status_dot)).apply_over(cx, live!{
+                draw_bg: { color: (color) }
+            });
+            badge.label(ids!(status_text)).set_text(cx, text.as_deref().unwrap_or(""));
+
+            inner.view.redraw(cx);
+        }
+    }
+
     /// Refresh the provider list (reload from preferences)
     pub fn refresh(&self, cx: &mut Cx) {
         self.load_providers(cx);
@@ -933,3 +1048,14 @@ impl ProvidersPanelRef {
         }
     }
 }
+
+/// `#rrggbb` as a color
+fn hex_color(hex: &str) -> Vec4 {
+    let rgb = u32::from_str_radix(hex.trim_start_matches('#'), 16).unwrap_or(0x6b7280);
+    vec4(
+        ((rgb >> 16) & 0xff) as f32 / 255.0,
+        ((rgb >> 8) & 0xff) as f32 / 255.0,
+        (rgb & 0xff) as f32 / 255.0,
+        1.0,
+    )
+}
@@ -1,7 +1,7 @@
 //! Settings Screen - Main settings interface
 
 use makepad_widgets::*;
-use crate::data::{Provider, ProviderId, Preferences};
+use crate::data::{ConnectivityChecker, Provider, ProviderConnectionStatus, ProviderId, Preferences};
 use crate::providers_panel::{ProvidersPanelAction, ProvidersPanelWidgetExt};
 use crate::provider_view::ProviderViewWidgetExt;
 use crate::add_provider_modal::{AddProviderModalAction, AddProviderModalWidgetExt};
@@ -71,6 +71,14 @@ pub struct SettingsScreen {
 
     #[rust]
     selected_provider_id: Option<ProviderId>,
+
+    /// Background connection checks and model discovery
+    #[rust]
+    checker: ConnectivityChecker,
+
+    /// Polls `checker` while checks are running
+    #[rust]
+    check_timer: Timer,
 }
 
 impl Widget for SettingsScreen {
@@ -85,6 +93,13 @@ impl Widget for SettingsScreen {
             let default_id = ProviderId::from("openai");
             self.selected_provider_id = Some(default_id.clone());
             self.load_provider_to_view(cx, &default_id);
+
+            // Validate saved keys in the background
+            self.check_configured_providers(cx);
+        }
+
+        if self.check_timer.is_event(event).is_some() {
+            self.poll_connection_checks(cx);
         }
 
         // Extract actions for button clicks
@@ -169,7 +184,8 @@ impl SettingsScreen {
                         provider.url.clone(),
                         provider.api_key.as_deref().unwrap_or("").to_string(),
                         provider.models.clone(),
-                        provider.api_key.as_ref().map(|k| !k.is_empty()).unwrap_or(false),
+                        provider.provider_type.is_local()
+                            || provider.api_key.as_ref().map(|k| !k.is_empty()).unwrap_or(false),
                         provider.is_custom,
                     )
                 } else {
@@ -179,6 +195,7 @@ impl SettingsScreen {
                         "deepseek" => "DeepSeek",
                         "alibaba_cloud" => "Alibaba Cloud (Qwen)",
                         "nvidia" => "NVIDIA",
+                        "ollama" => "Ollama (Local)",
                         _ => provider_id.as_str(),
                     };
                     let url = match provider_id.as_str() {
@@ -186,6 +203,7 @@ impl SettingsScreen {
                         "deepseek" => "https://api.deepseek.com",
                         "alibaba_cloud" => "https://dashscope.aliyuncs.com/compatible-mode/v1",
                         "nvidia" => "https://integrate.api.nvidia.com/v1",
+                        "ollama" => "http://localhost:11434/v1",
                         _ => "",
                     };
                     (name.to_string(), url.to_string(), "".to_string(), Vec::new(), false, false)
@@ -197,6 +215,7 @@ impl SettingsScreen {
                     "deepseek" => "DeepSeek",
                     "alibaba_cloud" => "Alibaba Cloud (Qwen)",
                     "nvidia" => "NVIDIA",
+                    "ollama" => "Ollama (Local)",
                     _ => provider_id.as_str(),
                 };
                 let url = match provider_id.as_str() {
@@ -204,6 +223,7 @@ impl SettingsScreen {
                     "deepseek" => "https://api.deepseek.com",
                     "alibaba_cloud" => "https://dashscope.aliyuncs.com/compatible-mode/v1",
                     "nvidia" => "https://integrate.api.nvidia.com/v1",
+                    "ollama" => "http://localhost:11434/v1",
                     _ => "",
                 };
                 (name.to_string(), url.to_string(), "".to_string(), Vec::new(), false, false)
@@ -215,7 +235,16 @@ impl SettingsScreen {
         self.view.text_input(ids!(content.provider_view.api_host_input)).set_text(cx, &provider_url);
         self.view.text_input(ids!(content.provider_view.api_key_input)).set_text(cx, &api_key);
 
-        // Models are now handled by ProviderView's PortalList - just update status labels
+        // Connection status of the last check, if any
+        let provider_view = self.view.provider_view(ids!(content.provider_view));
+        match self.preferences.as_ref().and_then(|prefs| prefs.get_provider(provider_id)) {
+            Some(provider) => provider_view.show_connection(cx, provider),
+            None => provider_view.show_connection(cx, &Provider::default()),
+        }
+
+        // Models are rendered by ProviderView's PortalList
+        provider_view.display_models(cx, saved_models.clone());
+
         // Update no models label and sync status
         self.view.label(ids!(content.provider_view.no_models_label)).set_visible(cx, saved_models.is_empty());
         if !saved_models.is_empty() {
@@ -245,8 +274,8 @@ impl SettingsScreen {
         self.view.redraw(cx);
     }
 
-    fn save_current_provider(&mut self, _cx: &mut Cx) {
-        if let Some(provider_id) = &self.selected_provider_id {
+    fn save_current_provider(&mut self, cx: &mut Cx) {
+        if let Some(provider_id) = self.selected_provider_id.clone() {
             let api_host = self.view.text_input(ids!(content.provider_view.api_host_input)).text();
             let api_key = {
                 let key = self.view.text_input(ids!(content.provider_view.api_key_input)).text();
@@ -258,15 +287,21 @@ impl SettingsScreen {
                 self.preferences = Some(Preferences::load());
             }
             if let Some(prefs) = &mut self.preferences {
-                if let Some(provider) = prefs.providers.iter_mut().find(|p| &p.id == provider_id) {
+                if let Some(provider) = prefs.providers.iter_mut().find(|p| p.id == provider_id) {
                     provider.url = api_host;
                     provider.api_key = api_key;
+                    let provider = provider.clone();
 
                     if let Err(e) = prefs.save() {
                         eprintln!("Failed to save preferences: {}", e);
                     } else {
                         ::log::info!("Saved provider settings for {}", provider_id.as_str());
                     }
+
+                    // The host or key may have changed: check again
+                    if provider.provider_type.is_local() || provider.api_key.is_some() {
+                        self.start_check(cx, &provider);
+                    }
                 }
             }
         }
@@ -289,6 +324,7 @@ impl SettingsScreen {
 
                     // Refresh the providers panel to remove the deleted provider
                     self.view.providers_panel(ids!(content.providers_panel)).refresh(cx);
+                    self.refresh_status_badges(cx);
 
                     // Clear the view and selection
                     self.selected_provider_id = None;
@@ -316,6 +352,7 @@ impl SettingsScreen {
 
             // Refresh the providers panel to show the new custom provider
             self.view.providers_panel(ids!(content.providers_panel)).refresh(cx);
+            self.refresh_status_badges(cx);
 
             // Select the new provider
             self.selected_provider_id = Some(id.clone());
@@ -324,88 +361,135 @@ impl SettingsScreen {
         }
     }
 
+    /// Check the selected provider with the host and key in the form,
+    /// fetching its live model list
     fn sync_models(&mut self, cx: &mut Cx) {
-        // Check if API key is provided
+        let Some(provider_id) = self.selected_provider_id.clone() else {
+            return;
+        };
+        if self.preferences.is_none() {
+            self.preferences = Some(Preferences::load());
+        }
+        let Some(mut provider) = self.preferences.as_ref().and_then(|prefs| prefs.get_provider(&provider_id)).cloned() else {
+            return;
+        };
+
+        // Check what is in the form, even before it is saved
         let api_key = self.view.text_input(ids!(content.provider_view.api_key_input)).text();
-        if api_key.is_empty() {
+        if api_key.is_empty() && !provider.provider_type.is_local() {
             self.view.label(ids!(content.provider_view.sync_status)).set_text(cx, "Enter API key to sync models");
             self.view.redraw(cx);
             return;
         }
+        provider.url = self.view.text_input(ids!(content.provider_view.api_host_input)).text();
+        provider.api_key = if api_key.is_empty() { None } else { Some(api_key) };
 
         // Set syncing state
         self.view.label(ids!(content.provider_view.sync_status)).set_text(cx, "Syncing models...");
         self.view.button(ids!(content.provider_view.sync_button)).apply_over(cx, live!{
             draw_bg: { disabled: 1.0 }
         });
+        self.start_check(cx, &provider);
         self.view.redraw(cx);
+    }
 
-        // For now, simulate fetching models based on provider
-        let models = if let Some(provider_id) = &self.selected_provider_id {
-            match provider_id.as_str() {
-                "openai" => vec![
-                    "gpt-4o".to_string(),
-                    "gpt-4o-mini".to_string(),
-                    "gpt-4-turbo".to_string(),
-                    "gpt-4".to_string(),
-                    "gpt-3.5-turbo".to_string(),
-                ],
-                "deepseek" => vec![
-                    "deepseek-chat".to_string(),
-                    "deepseek-coder".to_string(),
-                ],
-                "alibaba_cloud" => vec![
-                    "qwen-turbo".to_string(),
-                    "qwen-plus".to_string(),
-                    "qwen-max".to_string(),
-                ],
-                "nvidia" => vec![
-                    "deepseek-ai/deepseek-r1".to_string(),
-                    "deepseek-ai/deepseek-v3.2".to_string(),
-                    "moonshotai/kimi-k2-thinking".to_string(),
-                    "minimaxai/minimax-m2".to_string(),
-                    "meta/llama-3.3-70b-instruct".to_string(),
-                ],
-                _ => vec!["custom-model".to_string()],
-            }
-        } else {
-            vec![]
-        };
+    /// Check every provider that has an API key
+    fn check_configured_providers(&mut self, cx: &mut Cx) {
+        if self.preferences.is_none() {
+            self.preferences = Some(Preferences::load());
+        }
+        let providers: Vec<Provider> = self.preferences.as_ref()
+            .map(|prefs| prefs.providers.iter()
+                .filter(|p| p.api_key.as_ref().map(|k| !k.is_empty()).unwrap_or(false))
+                .cloned()
+                .collect())
+            .unwrap_or_default();
+        for provider in &providers {
+            self.start_check(cx, provider);
+        }
+    }
 
-        // Persist models to provider in preferences
-        if let Some(provider_id) = &self.selected_provider_id {
-            // Load preferences if needed
-            if self.preferences.is_none() {
-                self.preferences = Some(Preferences::load());
+    /// Start a background check of `provider` and show it as connecting
+    fn start_check(&mut self, cx: &mut Cx, provider: &Provider) {
+        let was_idle = self.checker.is_idle();
+        if !self.checker.check(provider) {
+            return;
+        }
+        if was_idle {
+            self.check_timer = cx.start_interval(0.1);
+        }
+
+        let mut connecting = provider.clone();
+        connecting.connection_status = ProviderConnectionStatus::Connecting;
+        if let Some(stored) = self.preferences.as_mut().and_then(|prefs| prefs.get_provider_mut(&provider.id)) {
+            stored.connection_status = ProviderConnectionStatus::Connecting;
+        }
+        self.view.providers_panel(ids!(content.providers_panel)).set_provider_status(cx, &connecting);
+        if self.selected_provider_id.as_ref() == Some(&provider.id) {
+            self.view.provider_view(ids!(content.provider_view)).show_connection(cx, &connecting);
+        }
+    }
+
+    /// Apply finished checks: status badge, latency and the live model list
+    fn poll_connection_checks(&mut self, cx: &mut Cx) {
+        while let Some(check) = self.checker.try_recv() {
+            let Some(prefs) = self.preferences.as_mut() else {
+                continue;
+            };
+            let Some(provider) = prefs.get_provider_mut(&check.provider_id) else {
+                continue;
+            };
+            check.apply(provider);
+            let provider = provider.clone();
+
+            if !check.models.is_empty() {
+                if let Err(e) = prefs.save() {
+                    eprintln!("Failed to save models: {}", e);
+                }
             }
-            if let Some(prefs) = &mut self.preferences {
-                if let Some(provider) = prefs.providers.iter_mut().find(|p| &p.id == provider_id) {
-                    provider.models = models.clone();
-                    if let Err(e) = prefs.save() {
-                        eprintln!("Failed to save models: {}", e);
-                    }
+            match &check.status {
+                ProviderConnectionStatus::Error(message) => {
+                    ::log::warn!("Connection check of {} failed: {}", provider.id, message);
                 }
+                _ => ::log::info!("{}: {}, {} models", provider.id, provider.status_text(), check.models.len()),
             }
-        }
 
-        // Update the no_models_label visibility and sync status
-        self.view.label(ids!(content.provider_view.no_models_label)).set_visible(cx, models.is_empty());
+            self.view.providers_panel(ids!(content.providers_panel)).set_provider_status(cx, &provider);
+            if self.selected_provider_id.as_ref() != Some(&provider.id) {
+                continue;
+            }
 
-        // Update sync status
-        if models.is_empty() {
-            self.view.label(ids!(content.provider_view.sync_status)).set_text(cx, "No models found");
-        } else {
-            self.view.label(ids!(content.provider_view.sync_status)).set_text(cx, &format!("Found {} models", models.len()));
+            let provider_view = self.view.provider_view(ids!(content.provider_view));
+            provider_view.show_connection(cx, &provider);
+            let sync_status = match &check.status {
+                ProviderConnectionStatus::Error(message) => message.clone(),
+                _ if check.models.is_empty() => format!("{}, no models listed", provider.status_text()),
+                _ => {
+                    provider_view.display_models(cx, provider.models.clone());
+                    let latency = provider.latency.unwrap_or_default().as_millis();
+                    format!("Found {} models ({} ms)", check.models.len(), latency)
+                }
+            };
+            self.view.label(ids!(content.provider_view.sync_status)).set_text(cx, &sync_status);
+
+            // Re-enable sync button
+            self.view.button(ids!(content.provider_view.sync_button)).apply_over(cx, live!{
+                draw_bg: { disabled: 0.0 }
+            });
+            self.view.redraw(cx);
         }
 
-        // Re-enable sync button
-        self.view.button(ids!(content.provider_view.sync_button)).apply_over(cx, live!{
-            draw_bg: { disabled: 0.0 }
-        });
+        if self.checker.is_idle() {
+            cx.stop_timer(self.check_timer);
+        }
+    }
 
-        // Reload the provider to view to refresh the model list
-        if let Some(provider_id) = self.selected_provider_id.clone() {
-            self.load_provider_to_view(cx, &provider_id);
+    /// Re-apply known connection statuses after the provider list was rebuilt
+    fn refresh_status_badges(&mut self, cx: &mut Cx) {
+        let providers = self.preferences.as_ref().map(|prefs| prefs.providers.clone()).unwrap_or_default();
+        let panel = self.view.providers_panel(ids!(content.providers_panel));
+        for provider in &providers {
+            panel.set_provider_status(cx, provider);
         }
     }
 }
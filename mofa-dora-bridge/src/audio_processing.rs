@@ -0,0 +1,601 @@
+//! Capture-side audio processing for the mic input bridge
+//!
+//! Optional chain applied to microphone audio before VAD and before
+//! `audio_segment` is sent to ASR:
+//!
+//! ```text
+//! mic ──▶ HighPassFilter ──▶ NoiseSuppressor ──▶ AutomaticGainControl ──▶ VAD / ASR
+//!         (rumble, DC)       (fan, hiss)         (quiet laptop mics)
+//! ```
+//!
+//! Each stage can be bypassed at runtime. Settings come from the `env:`
+//! section of the `mofa-mic-input` node, like [`crate::vad::VadConfig`]
+//! (including `${VAR}` placeholders). All stages are off by default:
+//!
+//! ```yaml
+//! env:
+//!   MIC_HIGH_PASS: "true"
+//!   MIC_NOISE_SUPPRESSION: "true"
+//!   MIC_AGC: "true"
+//!   MIC_AGC_TARGET_DB: "-20"
+//! ```
+
+use crate::vad::{env_parse, frame_level_db};
+use std::collections::{HashMap, VecDeque};
+use std::f32::consts::PI;
+
+/// Processing stage identifiers (for bypass toggles)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessingStage {
+    HighPass,
+    NoiseSuppression,
+    Agc,
+}
+
+impl ProcessingStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProcessingStage::HighPass => "high_pass",
+            ProcessingStage::NoiseSuppression => "noise_suppression",
+            ProcessingStage::Agc => "agc",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "high_pass" | "hpf" => Some(ProcessingStage::HighPass),
+            "noise_suppression" | "ns" => Some(ProcessingStage::NoiseSuppression),
+            "agc" => Some(ProcessingStage::Agc),
+            _ => None,
+        }
+    }
+}
+
+/// Capture processing configuration
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessingConfig {
+    /// Capture sample rate (Hz)
+    pub sample_rate: u32,
+    /// Enable the high-pass filter
+    pub high_pass: bool,
+    /// High-pass cutoff (Hz)
+    pub high_pass_cutoff_hz: f32,
+    /// Enable spectral noise suppression
+    pub noise_suppression: bool,
+    /// Maximum attenuation applied to noise-only bins (dB)
+    pub noise_max_attenuation_db: f32,
+    /// Enable automatic gain control
+    pub agc: bool,
+    /// AGC target speech level (dBFS RMS)
+    pub agc_target_db: f32,
+    /// AGC maximum boost (dB)
+    pub agc_max_gain_db: f32,
+    /// Frames quieter than this never move the AGC gain (dBFS)
+    pub agc_gate_db: f32,
+}
+
+impl Default for ProcessingConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 16000,
+            high_pass: false,
+            high_pass_cutoff_hz: 80.0,
+            noise_suppression: false,
+            noise_max_attenuation_db: 15.0,
+            agc: false,
+            agc_target_db: -20.0,
+            agc_max_gain_db: 24.0,
+            agc_gate_db: -50.0,
+        }
+    }
+}
+
+impl ProcessingConfig {
+    /// Read configuration from process environment variables
+    pub fn from_env() -> Self {
+        Self::from_env_map(&HashMap::new())
+    }
+
+    /// Read configuration from a dataflow node `env:` map, falling back to
+    /// process environment variables, then defaults. `${VAR}` placeholders
+    /// are resolved as in [`crate::vad::VadConfig::from_env_map`].
+    pub fn from_env_map(env: &HashMap<String, String>) -> Self {
+        let defaults = Self::default();
+        Self {
+            sample_rate: defaults.sample_rate,
+            high_pass: env_parse(env, "MIC_HIGH_PASS").unwrap_or(defaults.high_pass),
+            high_pass_cutoff_hz: env_parse(env, "MIC_HIGH_PASS_HZ").unwrap_or(defaults.high_pass_cutoff_hz),
+            noise_suppression: env_parse(env, "MIC_NOISE_SUPPRESSION").unwrap_or(defaults.noise_suppression),
+            noise_max_attenuation_db: env_parse(env, "MIC_NOISE_MAX_ATTENUATION_DB")
+                .unwrap_or(defaults.noise_max_attenuation_db),
+            agc: env_parse(env, "MIC_AGC").unwrap_or(defaults.agc),
+            agc_target_db: env_parse(env, "MIC_AGC_TARGET_DB").unwrap_or(defaults.agc_target_db),
+            agc_max_gain_db: env_parse(env, "MIC_AGC_MAX_GAIN_DB").unwrap_or(defaults.agc_max_gain_db),
+            agc_gate_db: env_parse(env, "MIC_AGC_GATE_DB").unwrap_or(defaults.agc_gate_db),
+        }
+    }
+}
+
+/// Live processing state for UI display
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProcessingStatus {
+    pub high_pass: bool,
+    pub noise_suppression: bool,
+    pub agc: bool,
+    /// Current AGC gain (dB)
+    pub agc_gain_db: f32,
+    /// Level before processing (dBFS)
+    pub input_level_db: f32,
+    /// Level after processing (dBFS)
+    pub output_level_db: f32,
+}
+
+/// Second-order Butterworth high-pass filter (RBJ biquad)
+pub struct HighPassFilter {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl HighPassFilter {
+    pub fn new(sample_rate: u32, cutoff_hz: f32) -> Self {
+        let w0 = 2.0 * PI * cutoff_hz / sample_rate as f32;
+        let (sin_w0, cos_w0) = w0.sin_cos();
+        // Butterworth Q = 1/sqrt(2)
+        let alpha = sin_w0 / (2.0 * std::f32::consts::FRAC_1_SQRT_2);
+        let a0 = 1.0 + alpha;
+        Self {
+            b0: (1.0 + cos_w0) / 2.0 / a0,
+            b1: -(1.0 + cos_w0) / a0,
+            b2: (1.0 + cos_w0) / 2.0 / a0,
+            a1: -2.0 * cos_w0 / a0,
+            a2: (1.0 - alpha) / a0,
+            x1: 0.0,
+            x2: 0.0,
+            y1: 0.0,
+            y2: 0.0,
+        }
+    }
+
+    pub fn process(&mut self, samples: &mut [f32]) {
+        for s in samples.iter_mut() {
+            let x = *s;
+            let y = self.b0 * x + self.b1 * self.x1 + self.b2 * self.x2
+                - self.a1 * self.y1
+                - self.a2 * self.y2;
+            self.x2 = self.x1;
+            self.x1 = x;
+            self.y2 = self.y1;
+            self.y1 = y;
+            *s = y;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.x1 = 0.0;
+        self.x2 = 0.0;
+        self.y1 = 0.0;
+        self.y2 = 0.0;
+    }
+}
+
+/// Streaming spectral-subtraction noise suppressor.
+///
+/// Works on 256-sample frames with 50% overlap (sqrt-Hann analysis and
+/// synthesis windows). The per-bin noise estimate falls quickly toward quieter
+/// spectra and rises slowly, so stationary noise (fans, hiss) is tracked while
+/// speech is not. Output length always equals input length; the suppressor
+/// adds one hop (8ms at 16kHz) of latency.
+pub struct NoiseSuppressor {
+    window: Vec<f32>,
+    noise: Vec<f32>,
+    min_gain: f32,
+    input: Vec<f32>,
+    overlap: Vec<f32>,
+    output: VecDeque<f32>,
+    frames_seen: usize,
+}
+
+impl NoiseSuppressor {
+    const FRAME: usize = 256;
+    const HOP: usize = Self::FRAME / 2;
+    /// Over-subtraction factor
+    const ALPHA: f32 = 2.0;
+    /// Noise estimate fall rate per frame
+    const NOISE_FALL: f32 = 0.03;
+    /// Noise estimate rise rate per frame
+    const NOISE_RISE: f32 = 0.003;
+
+    pub fn new(max_attenuation_db: f32) -> Self {
+        let window = (0..Self::FRAME)
+            .map(|i| (PI * (i as f32 + 0.5) / Self::FRAME as f32).sin())
+            .collect();
+        let mut suppressor = Self {
+            window,
+            noise: vec![0.0; Self::FRAME / 2 + 1],
+            min_gain: 10f32.powf(-max_attenuation_db.abs() / 20.0),
+            input: Vec::with_capacity(Self::FRAME * 2),
+            overlap: vec![0.0; Self::HOP],
+            output: VecDeque::with_capacity(Self::FRAME * 2),
+            frames_seen: 0,
+        };
+        suppressor.reset();
+        suppressor
+    }
+
+    pub fn reset(&mut self) {
+        self.noise.iter_mut().for_each(|n| *n = 0.0);
+        self.input.clear();
+        self.input.resize(Self::HOP, 0.0);
+        self.overlap.iter_mut().for_each(|o| *o = 0.0);
+        self.output.clear();
+        self.output.resize(Self::HOP, 0.0);
+        self.frames_seen = 0;
+    }
+
+    pub fn process(&mut self, samples: &mut [f32]) {
+        self.input.extend_from_slice(samples);
+        while self.input.len() >= Self::FRAME {
+            self.process_frame();
+            self.input.drain(..Self::HOP);
+        }
+        for s in samples.iter_mut() {
+            *s = self.output.pop_front().unwrap_or(0.0);
+        }
+    }
+
+    fn process_frame(&mut self) {
+        let mut re: Vec<f32> = self.input[..Self::FRAME]
+            .iter()
+            .zip(&self.window)
+            .map(|(x, w)| x * w)
+            .collect();
+        let mut im = vec![0.0; Self::FRAME];
+        fft(&mut re, &mut im, false);
+
+        let bins = Self::FRAME / 2 + 1;
+        for k in 0..bins {
+            let mag = (re[k] * re[k] + im[k] * im[k]).sqrt();
+            let noise = &mut self.noise[k];
+            if self.frames_seen == 0 {
+                *noise = mag;
+            } else if mag < *noise {
+                *noise += (mag - *noise) * Self::NOISE_FALL;
+            } else {
+                *noise += (mag - *noise) * Self::NOISE_RISE;
+            }
+
+            let gain = if mag > 0.0 {
+                (1.0 - Self::ALPHA * *noise / mag).max(self.min_gain)
+            } else {
+                self.min_gain
+            };
+            re[k] *= gain;
+            im[k] *= gain;
+            // Mirror bins keep the spectrum conjugate-symmetric
+            if k > 0 && k < Self::FRAME / 2 {
+                re[Self::FRAME - k] *= gain;
+                im[Self::FRAME - k] *= gain;
+            }
+        }
+        self.frames_seen += 1;
+
+        fft(&mut re, &mut im, true);
+
+        for i in 0..Self::HOP {
+            self.output.push_back(self.overlap[i] + re[i] * self.window[i]);
+            self.overlap[i] = re[i + Self::HOP] * self.window[i + Self::HOP];
+        }
+    }
+}
+
+/// Automatic gain control toward a target speech level with a hard limiter
+pub struct AutomaticGainControl {
+    target_db: f32,
+    max_gain_db: f32,
+    gate_db: f32,
+    gain_db: f32,
+}
+
+impl AutomaticGainControl {
+    /// Gain change per 10ms block when boosting (slow, avoids pumping)
+    const RELEASE_DB: f32 = 0.5;
+    /// Gain change per 10ms block when cutting (fast, avoids clipping)
+    const ATTACK_DB: f32 = 3.0;
+    const BLOCK: usize = 160;
+
+    pub fn new(target_db: f32, max_gain_db: f32, gate_db: f32) -> Self {
+        Self {
+            target_db,
+            max_gain_db,
+            gate_db,
+            gain_db: 0.0,
+        }
+    }
+
+    pub fn gain_db(&self) -> f32 {
+        self.gain_db
+    }
+
+    pub fn reset(&mut self) {
+        self.gain_db = 0.0;
+    }
+
+    pub fn process(&mut self, samples: &mut [f32]) {
+        for block in samples.chunks_mut(Self::BLOCK) {
+            let level_db = frame_level_db(block);
+            if level_db > self.gate_db {
+                let wanted = (self.target_db - level_db).clamp(-self.max_gain_db, self.max_gain_db);
+                let step = if wanted < self.gain_db { Self::ATTACK_DB } else { Self::RELEASE_DB };
+                self.gain_db += (wanted - self.gain_db).clamp(-step, step);
+            }
+            let gain = 10f32.powf(self.gain_db / 20.0);
+            for s in block.iter_mut() {
+                *s = (*s * gain).clamp(-1.0, 1.0);
+            }
+        }
+    }
+}
+
+/// The full capture chain with per-stage bypass
+pub struct AudioProcessor {
+    config: ProcessingConfig,
+    high_pass: HighPassFilter,
+    noise_suppressor: NoiseSuppressor,
+    agc: AutomaticGainControl,
+    status: ProcessingStatus,
+}
+
+impl AudioProcessor {
+    pub fn new(config: ProcessingConfig) -> Self {
+        Self {
+            high_pass: HighPassFilter::new(config.sample_rate, config.high_pass_cutoff_hz),
+            noise_suppressor: NoiseSuppressor::new(config.noise_max_attenuation_db),
+            agc: AutomaticGainControl::new(
+                config.agc_target_db,
+                config.agc_max_gain_db,
+                config.agc_gate_db,
+            ),
+            status: ProcessingStatus {
+                high_pass: config.high_pass,
+                noise_suppression: config.noise_suppression,
+                agc: config.agc,
+                ..Default::default()
+            },
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &ProcessingConfig {
+        &self.config
+    }
+
+    /// Enable or bypass one stage
+    pub fn set_enabled(&mut self, stage: ProcessingStage, enabled: bool) {
+        match stage {
+            ProcessingStage::HighPass => {
+                self.config.high_pass = enabled;
+                self.high_pass.reset();
+            }
+            ProcessingStage::NoiseSuppression => {
+                self.config.noise_suppression = enabled;
+                self.noise_suppressor.reset();
+            }
+            ProcessingStage::Agc => {
+                self.config.agc = enabled;
+                self.agc.reset();
+            }
+        }
+    }
+
+    /// Reset filter and adaptive state (e.g. after switching capture source)
+    pub fn reset(&mut self) {
+        self.high_pass.reset();
+        self.noise_suppressor.reset();
+        self.agc.reset();
+    }
+
+    /// Process samples in place
+    pub fn process(&mut self, samples: &mut [f32]) {
+        self.status.input_level_db = frame_level_db(samples);
+
+        if self.config.high_pass {
+            self.high_pass.process(samples);
+        }
+        if self.config.noise_suppression {
+            self.noise_suppressor.process(samples);
+        }
+        if self.config.agc {
+            self.agc.process(samples);
+        }
+
+        self.status.high_pass = self.config.high_pass;
+        self.status.noise_suppression = self.config.noise_suppression;
+        self.status.agc = self.config.agc;
+        self.status.agc_gain_db = if self.config.agc { self.agc.gain_db() } else { 0.0 };
+        self.status.output_level_db = frame_level_db(samples);
+    }
+
+    pub fn status(&self) -> ProcessingStatus {
+        self.status.clone()
+    }
+}
+
+/// In-place iterative radix-2 FFT (length must be a power of two)
+fn fft(re: &mut [f32], im: &mut [f32], inverse: bool) {
+    let n = re.len();
+    debug_assert!(n.is_power_of_two() && im.len() == n);
+
+    // Bit-reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let angle = sign * 2.0 * PI / len as f32;
+        let (w_im, w_re) = angle.sin_cos();
+        for start in (0..n).step_by(len) {
+            let (mut cur_re, mut cur_im) = (1.0f32, 0.0f32);
+            for k in 0..len / 2 {
+                let a = start + k;
+                let b = a + len / 2;
+                let t_re = re[b] * cur_re - im[b] * cur_im;
+                let t_im = re[b] * cur_im + im[b] * cur_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+                let next_re = cur_re * w_re - cur_im * w_im;
+                cur_im = cur_re * w_im + cur_im * w_re;
+                cur_re = next_re;
+            }
+        }
+        len <<= 1;
+    }
+
+    if inverse {
+        let scale = 1.0 / n as f32;
+        re.iter_mut().for_each(|x| *x *= scale);
+        im.iter_mut().for_each(|x| *x *= scale);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, amplitude: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (2.0 * PI * freq * i as f32 / 16000.0).sin())
+            .collect()
+    }
+
+    /// Deterministic white-ish noise
+    fn noise(amplitude: f32, len: usize) -> Vec<f32> {
+        let mut state = 0x1234_5678u32;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                amplitude * ((state as f32 / u32::MAX as f32) * 2.0 - 1.0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_high_pass_removes_rumble() {
+        let mut filter = HighPassFilter::new(16000, 80.0);
+        let mut rumble = sine(20.0, 0.5, 16000);
+        let mut voice = sine(500.0, 0.5, 16000);
+        filter.process(&mut rumble);
+        filter.reset();
+        filter.process(&mut voice);
+
+        // Skip the filter settling time
+        assert!(frame_level_db(&rumble[8000..]) < frame_level_db(&voice[8000..]) - 15.0);
+        assert!(frame_level_db(&voice[8000..]) > -10.0);
+    }
+
+    #[test]
+    fn test_noise_suppressor_preserves_length_and_attenuates_noise() {
+        let mut suppressor = NoiseSuppressor::new(15.0);
+        let mut total = 0;
+        let mut last = Vec::new();
+        for chunk in [100usize, 333, 160, 1000, 16000] {
+            let mut samples = noise(0.05, chunk);
+            suppressor.process(&mut samples);
+            assert_eq!(samples.len(), chunk);
+            total += chunk;
+            last = samples;
+        }
+        assert!(total > 16000);
+        assert!(frame_level_db(&last[8000..]) < frame_level_db(&noise(0.05, 8000)) - 6.0);
+    }
+
+    #[test]
+    fn test_noise_suppressor_keeps_tone_over_noise() {
+        let mut suppressor = NoiseSuppressor::new(15.0);
+        // Let the noise estimate settle, then add a loud tone
+        let mut settle = noise(0.02, 16000);
+        suppressor.process(&mut settle);
+
+        let tone = sine(500.0, 0.3, 4800);
+        let mut mixed: Vec<f32> = tone.iter().zip(noise(0.02, 4800)).map(|(t, n)| t + n).collect();
+        suppressor.process(&mut mixed);
+        assert!((frame_level_db(&mixed[1600..]) - frame_level_db(&tone)).abs() < 3.0);
+    }
+
+    #[test]
+    fn test_fft_roundtrip() {
+        let original = sine(440.0, 0.7, 256);
+        let mut re = original.clone();
+        let mut im = vec![0.0; 256];
+        fft(&mut re, &mut im, false);
+        fft(&mut re, &mut im, true);
+        for (a, b) in original.iter().zip(&re) {
+            assert!((a - b).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_agc_boosts_quiet_speech_toward_target() {
+        let mut agc = AutomaticGainControl::new(-20.0, 24.0, -50.0);
+        let mut quiet = sine(300.0, 0.02, 16000 * 3);
+        agc.process(&mut quiet);
+        let out_db = frame_level_db(&quiet[16000 * 2..]);
+        assert!((out_db - -20.0).abs() < 2.0, "out_db = {}", out_db);
+
+        // Silence below the gate does not move the gain
+        let gain = agc.gain_db();
+        let mut silence = vec![0.0001; 16000];
+        agc.process(&mut silence);
+        assert_eq!(agc.gain_db(), gain);
+    }
+
+    #[test]
+    fn test_config_from_env_map() {
+        let mut env = HashMap::new();
+        env.insert("MIC_HIGH_PASS".to_string(), "${MOFA_TEST_UNSET_VAR:-true}".to_string());
+        env.insert("MIC_AGC_TARGET_DB".to_string(), "-18".to_string());
+
+        let config = ProcessingConfig::from_env_map(&env);
+        assert!(config.high_pass);
+        assert_eq!(config.agc_target_db, -18.0);
+        assert!(!config.noise_suppression);
+
+        assert!(!ProcessingConfig::from_env_map(&HashMap::new()).high_pass);
+    }
+
+    #[test]
+    fn test_processor_bypass() {
+        let mut processor = AudioProcessor::new(ProcessingConfig::default());
+        let original = sine(300.0, 0.1, 1600);
+        let mut samples = original.clone();
+        processor.process(&mut samples);
+        assert_eq!(samples, original);
+
+        processor.set_enabled(ProcessingStage::Agc, true);
+        processor.process(&mut samples);
+        assert!(processor.status().agc);
+        assert!(processor.status().output_level_db > processor.status().input_level_db);
+    }
+}
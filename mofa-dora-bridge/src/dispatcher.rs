@@ -4,11 +4,13 @@
 //! dora dynamic nodes. Each widget type has its own bridge that
 //! connects as a separate dynamic node.
 
+use crate::audio_processing::ProcessingConfig;
 use crate::bridge::{BridgeState, DoraBridge};
 use crate::controller::DataflowController;
 use crate::error::{BridgeError, BridgeResult};
 use crate::parser::MofaNodeSpec;
 use crate::shared_state::SharedDoraState;
+use crate::vad::VadConfig;
 use crate::widgets::{AecInputBridge, AudioPlayerBridge, PromptInputBridge, SystemLogBridge};
 use crate::MofaNodeType;
 use parking_lot::RwLock;
@@ -104,9 +106,14 @@ impl DynamicNodeDispatcher {
                     &node_spec.id,
                     shared_state.clone(),
                 )),
-                MofaNodeType::MicInput => {
-                    Box::new(AecInputBridge::with_shared_state(&node_spec.id, shared_state.clone()))
-                }
+                MofaNodeType::MicInput => Box::new(
+                    AecInputBridge::with_vad_config(
+                        &node_spec.id,
+                        shared_state.clone(),
+                        VadConfig::from_env_map(&node_spec.env),
+                    )
+                    .with_processing_config(ProcessingConfig::from_env_map(&node_spec.env)),
+                ),
                 MofaNodeType::ChatViewer => {
                     // TODO: Implement ChatViewerBridge
                     continue;
@@ -48,6 +48,19 @@
 //! - [`LogEntry`] - Log entry with level, node_id, timestamp
 //! - [`ControlCommand`] - Dataflow control commands (start, stop, reset)
 //!
+//! ### Voice Activity Detection ([`vad`] module)
+//!
+//! - [`Vad`] trait - Pluggable frame-based speech detector used by the mic bridge
+//! - [`EnergyVad`] - Adaptive energy detector with noise floor, hangover, min speech
+//! - [`ModelVad`] - Model-based detector loaded from a native library
+//! - [`VadConfig`] - Per-dataflow parameters (from the `mofa-mic-input` node env)
+//!
+//! ### Capture Processing ([`audio_processing`] module)
+//!
+//! - [`AudioProcessor`] - High-pass → noise suppression → AGC, applied before VAD
+//! - [`ProcessingConfig`] - Per-stage enable flags and parameters (`MIC_*` node env)
+//! - [`ProcessingStatus`] - Pre/post levels and AGC gain for the mic panel
+//!
 //! ### Bridge Infrastructure
 //!
 //! - [`DoraBridge`] trait - Interface for widget bridges
@@ -104,6 +117,7 @@
 //! 4. **Lock-Free Reads** - AtomicBool for dirty flags, RwLock for data
 //! 5. **Bounded Collections** - All collections have max sizes to prevent memory growth
 
+pub mod audio_processing;
 pub mod bridge;
 pub mod controller;
 pub mod data;
@@ -111,19 +125,22 @@ pub mod dispatcher;
 pub mod error;
 pub mod parser;
 pub mod shared_state;
+pub mod vad;
 
 // Widget-specific bridges
 pub mod widgets;
 
 // Re-exports
+pub use audio_processing::{AudioProcessor, ProcessingConfig, ProcessingStage, ProcessingStatus};
 pub use bridge::{BridgeState, DoraBridge};
 pub use controller::{DataflowController, DataflowState};
 pub use data::{AudioData, ChatMessage, ControlCommand, DoraData, LogEntry};
 pub use dispatcher::{DynamicNodeDispatcher, WidgetBinding};
 pub use error::{BridgeError, BridgeResult};
 pub use shared_state::{SharedDoraState, DoraStatus, ChatState, AudioState, DirtyVec, DirtyValue, MicState};
-pub use widgets::AecControlCommand;
+pub use widgets::{AecControlCommand, MicInputMode};
 pub use parser::{DataflowParser, EnvRequirement, LogSource, ParsedDataflow, ParsedNode};
+pub use vad::{EnergyVad, ModelVad, Vad, VadConfig, VadMode, VadStatus};
 
 /// Prefix for MoFA built-in dynamic nodes in dataflow YAML
 pub const MOFA_NODE_PREFIX: &str = "mofa-";
@@ -40,6 +40,8 @@ pub struct MofaNodeSpec {
     pub inputs: Vec<InputDef>,
     /// Expected outputs
     pub outputs: Vec<String>,
+    /// Environment variables from the node's `env:` section
+    pub env: HashMap<String, String>,
 }
 
 /// Parsed node from dataflow
@@ -145,6 +147,7 @@ impl DataflowParser {
                             node_type: mofa_type,
                             inputs: parsed.inputs.clone(),
                             outputs: parsed.outputs.clone(),
+                            env: parsed.env.clone(),
                         });
                     }
 
@@ -56,7 +56,9 @@ use std::collections::VecDeque;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
+use crate::audio_processing::ProcessingStatus;
 use crate::data::{AudioData, ChatMessage, LogEntry};
+use crate::vad::VadStatus;
 
 /// Thread-safe vector with dirty tracking and maximum size enforcement.
 ///
@@ -361,6 +363,13 @@ impl ChatState {
 /// register its AudioPlayer's force_mute flag with [`AudioState::register_force_mute`].
 /// When the bridge receives a reset signal, calling [`AudioState::signal_clear`]
 /// will immediately set the force_mute flag, bypassing any polling latency.
+///
+/// # Hold
+///
+/// [`AudioState::set_hold`] (user) and [`AudioState::signal_pause`] (barge-in)
+/// stop playback without dropping anything: the UI pauses its player, stops
+/// draining chunks, and the bridge reports a full buffer upstream so TTS stops
+/// generating. Releasing both resumes exactly where playback stopped.
 pub struct AudioState {
     chunks: RwLock<VecDeque<AudioData>>,
     max_chunks: usize,
@@ -370,6 +379,14 @@ pub struct AudioState {
     /// Registered force_mute flag from AudioPlayer for instant silencing
     /// Set by the bridge to immediately mute audio output
     force_mute_flag: RwLock<Option<Arc<AtomicBool>>>,
+    /// Requested pause state (barge-in) - buffered audio is kept
+    paused: AtomicBool,
+    /// User hold (hero controls) - buffered audio is kept
+    held: AtomicBool,
+    /// Set when `paused` or `held` changed and the UI has not applied it yet
+    pause_changed: AtomicBool,
+    /// UI player buffer was non-empty at its last `buffer_status` report
+    playing: AtomicBool,
 }
 
 impl AudioState {
@@ -379,6 +396,10 @@ impl AudioState {
             max_chunks,
             should_clear: std::sync::atomic::AtomicBool::new(false),
             force_mute_flag: RwLock::new(None),
+            paused: AtomicBool::new(false),
+            held: AtomicBool::new(false),
+            pause_changed: AtomicBool::new(false),
+            playing: AtomicBool::new(false),
         }
     }
 
@@ -402,8 +423,10 @@ impl AudioState {
     pub fn push(&self, chunk: AudioData) {
         let mut chunks = self.chunks.write();
         chunks.push_back(chunk);
-        // Bound to prevent memory growth
-        while chunks.len() > self.max_chunks {
+        // Bound to prevent memory growth. While paused nothing is drained, so allow
+        // extra room for in-flight audio (backpressure stops new generation)
+        let max_chunks = if self.is_paused() { self.max_chunks * 4 } else { self.max_chunks };
+        while chunks.len() > max_chunks {
             chunks.pop_front();
         }
     }
@@ -453,6 +476,10 @@ impl AudioState {
         // Also set should_clear for backwards compatibility with UI polling
         self.should_clear.store(true, std::sync::atomic::Ordering::Release);
         self.clear();
+        // A reset ends a barge-in pause - new audio should play (a user hold stays)
+        if self.paused.swap(false, Ordering::AcqRel) {
+            self.pause_changed.store(true, Ordering::Release);
+        }
     }
 
     /// Check and reset the clear signal (UI calls this)
@@ -460,6 +487,48 @@ impl AudioState {
     pub fn take_clear_signal(&self) -> bool {
         self.should_clear.swap(false, std::sync::atomic::Ordering::AcqRel)
     }
+
+    /// Pause or resume playback without dropping buffered audio (barge-in)
+    pub fn signal_pause(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Release);
+        self.pause_changed.store(true, Ordering::Release);
+    }
+
+    /// Hold or release playback without dropping buffered audio (user control)
+    pub fn set_hold(&self, held: bool) {
+        self.held.store(held, Ordering::Release);
+        self.pause_changed.store(true, Ordering::Release);
+    }
+
+    /// Check and reset the pause signal (UI calls this)
+    /// Returns whether playback should be paused if that changed since the last call
+    pub fn take_pause_signal(&self) -> Option<bool> {
+        if self.pause_changed.swap(false, Ordering::AcqRel) {
+            Some(self.is_paused())
+        } else {
+            None
+        }
+    }
+
+    /// Whether playback is paused (barge-in pause or user hold)
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Acquire) || self.held.load(Ordering::Acquire)
+    }
+
+    /// Whether the user has put playback on hold
+    pub fn is_held(&self) -> bool {
+        self.held.load(Ordering::Acquire)
+    }
+
+    /// Record the UI player's buffer fill (audio player bridge calls this)
+    pub fn set_playback_fill(&self, fill_percentage: f64) {
+        self.playing.store(fill_percentage > 0.0, Ordering::Release);
+    }
+
+    /// Whether AI audio is pending or buffered in the UI player
+    pub fn is_playing(&self) -> bool {
+        self.playing.load(Ordering::Acquire) || self.has_audio()
+    }
 }
 
 /// Dora connection status
@@ -490,23 +559,32 @@ pub struct DoraStatus {
 /// }
 /// ```
 pub struct MicState {
-    /// Microphone input level (0.0 - 1.0, RMS normalized)
+    /// Microphone input level after processing (0.0 - 1.0, RMS normalized)
     level: DirtyValue<f32>,
+    /// Microphone input level before processing (0.0 - 1.0, RMS normalized)
+    raw_level: DirtyValue<f32>,
     /// Whether VAD detects speech
     is_speaking: DirtyValue<bool>,
     /// Whether recording is active
     is_recording: DirtyValue<bool>,
     /// Whether AEC is enabled
     aec_enabled: DirtyValue<bool>,
+    /// Active VAD detector and its live parameters
+    vad_status: DirtyValue<VadStatus>,
+    /// Capture processing chain (high-pass, noise suppression, AGC)
+    processing: DirtyValue<ProcessingStatus>,
 }
 
 impl MicState {
     pub fn new() -> Self {
         Self {
             level: DirtyValue::new(0.0),
+            raw_level: DirtyValue::new(0.0),
             is_speaking: DirtyValue::new(false),
             is_recording: DirtyValue::new(false),
             aec_enabled: DirtyValue::new(true),
+            vad_status: DirtyValue::new(VadStatus::default()),
+            processing: DirtyValue::new(ProcessingStatus::default()),
         }
     }
 
@@ -517,6 +595,11 @@ impl MicState {
         self.level.set(level);
     }
 
+    /// Set pre-processing mic level (0.0 - 1.0)
+    pub fn set_raw_level(&self, level: f32) {
+        self.raw_level.set(level);
+    }
+
     /// Set speaking state (from VAD)
     pub fn set_speaking(&self, speaking: bool) {
         self.is_speaking.set(speaking);
@@ -532,6 +615,16 @@ impl MicState {
         self.aec_enabled.set(enabled);
     }
 
+    /// Set VAD detector status
+    pub fn set_vad_status(&self, status: VadStatus) {
+        self.vad_status.set(status);
+    }
+
+    /// Set capture processing status
+    pub fn set_processing(&self, status: ProcessingStatus) {
+        self.processing.set(status);
+    }
+
     // Getters (for UI thread)
 
     /// Read mic level if changed
@@ -539,6 +632,11 @@ impl MicState {
         self.level.read_if_dirty()
     }
 
+    /// Read pre-processing mic level if changed
+    pub fn read_raw_level_if_dirty(&self) -> Option<f32> {
+        self.raw_level.read_if_dirty()
+    }
+
     /// Read speaking state if changed
     pub fn read_speaking_if_dirty(&self) -> Option<bool> {
         self.is_speaking.read_if_dirty()
@@ -554,11 +652,26 @@ impl MicState {
         self.aec_enabled.read_if_dirty()
     }
 
+    /// Read VAD status if changed
+    pub fn read_vad_status_if_dirty(&self) -> Option<VadStatus> {
+        self.vad_status.read_if_dirty()
+    }
+
+    /// Read capture processing status if changed
+    pub fn read_processing_if_dirty(&self) -> Option<ProcessingStatus> {
+        self.processing.read_if_dirty()
+    }
+
     /// Read mic level unconditionally
     pub fn level(&self) -> f32 {
         self.level.read()
     }
 
+    /// Read pre-processing mic level unconditionally
+    pub fn raw_level(&self) -> f32 {
+        self.raw_level.read()
+    }
+
     /// Read speaking state unconditionally
     pub fn is_speaking(&self) -> bool {
         self.is_speaking.read()
@@ -574,12 +687,25 @@ impl MicState {
         self.aec_enabled.read()
     }
 
+    /// Read VAD status unconditionally
+    pub fn vad_status(&self) -> VadStatus {
+        self.vad_status.read()
+    }
+
+    /// Read capture processing status unconditionally
+    pub fn processing(&self) -> ProcessingStatus {
+        self.processing.read()
+    }
+
     /// Clear all state
     pub fn clear(&self) {
         self.level.set(0.0);
+        self.raw_level.set(0.0);
         self.is_speaking.set(false);
         self.is_recording.set(false);
         self.aec_enabled.set(true);
+        self.vad_status.set(VadStatus::default());
+        self.processing.set(ProcessingStatus::default());
     }
 }
 
@@ -907,4 +1033,56 @@ mod tests {
         assert_eq!(chunks.len(), 2);
         assert_eq!(audio.len(), 0);
     }
+
+    #[test]
+    fn test_audio_pause_keeps_chunks() {
+        let audio = AudioState::new(10);
+        audio.push(AudioData {
+            samples: vec![0.1],
+            sample_rate: 32000,
+            channels: 1,
+            participant_id: None,
+            question_id: None,
+        });
+
+        audio.signal_pause(true);
+        assert_eq!(audio.take_pause_signal(), Some(true));
+        assert_eq!(audio.take_pause_signal(), None);
+        assert_eq!(audio.len(), 1); // Pause never drops audio
+
+        // Reset while paused clears audio and releases the pause
+        audio.signal_clear();
+        assert!(audio.take_clear_signal());
+        assert_eq!(audio.take_pause_signal(), Some(false));
+        assert!(!audio.is_paused());
+        assert_eq!(audio.len(), 0);
+    }
+
+    #[test]
+    fn test_audio_hold_survives_reset() {
+        let audio = AudioState::new(2);
+        audio.set_hold(true);
+        assert_eq!(audio.take_pause_signal(), Some(true));
+
+        // Nothing is drained while held - extra room before dropping
+        for i in 0..5 {
+            audio.push(AudioData {
+                samples: vec![i as f32],
+                sample_rate: 32000,
+                channels: 1,
+                participant_id: None,
+                question_id: None,
+            });
+        }
+        assert_eq!(audio.len(), 5);
+
+        // Barge-in pause ends on reset, user hold does not
+        audio.signal_pause(true);
+        audio.signal_clear();
+        assert!(audio.is_held());
+        assert!(audio.is_paused());
+
+        audio.set_hold(false);
+        assert_eq!(audio.take_pause_signal(), Some(false));
+    }
 }
@@ -0,0 +1,626 @@
+//! Voice Activity Detection for the mic input bridge
+//!
+//! Provides a pluggable [`Vad`] trait with two implementations:
+//! - [`EnergyVad`]: adaptive energy/spectral detector with noise floor tracking
+//! - [`ModelVad`]: model-based detector loaded from a native library
+//!
+//! Both feed their raw per-frame decision through a [`SpeechSmoother`], which
+//! applies the minimum speech duration (rejects keyboard clicks) and the
+//! hangover (keeps quiet word endings attached to the segment).
+//!
+//! Parameters come from [`VadConfig`], which is read from the `env:` section
+//! of the `mofa-mic-input` node in the dataflow YAML:
+//!
+//! ```yaml
+//! - id: mofa-mic-input
+//!   path: dynamic
+//!   env:
+//!     VAD_MODE: energy          # auto | energy | model
+//!     VAD_THRESHOLD_DB: "9"     # dB above the tracked noise floor
+//!     VAD_HANGOVER_MS: "200"
+//!     VAD_MIN_SPEECH_MS: "90"
+//! ```
+
+use libloading::{Library, Symbol};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::path::{Path, PathBuf};
+
+/// Lowest level reported by the detectors (digital silence)
+const SILENCE_DB: f32 = -100.0;
+
+/// Which detector the mic bridge uses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VadMode {
+    /// Native AEC library flag when AEC is active, energy detector otherwise
+    #[default]
+    Auto,
+    /// Adaptive energy/spectral detector for every capture source
+    Energy,
+    /// Model-based detector loaded from `VAD_MODEL_PATH`
+    Model,
+}
+
+impl VadMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VadMode::Auto => "auto",
+            VadMode::Energy => "energy",
+            VadMode::Model => "model",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "auto" | "native" => Some(VadMode::Auto),
+            "energy" | "spectral" => Some(VadMode::Energy),
+            "model" | "silero" => Some(VadMode::Model),
+            _ => None,
+        }
+    }
+}
+
+/// VAD and silence-timing configuration for the mic bridge
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VadConfig {
+    /// Detector selection
+    pub mode: VadMode,
+    /// Capture sample rate (Hz)
+    pub sample_rate: u32,
+    /// Analysis frame length (ms)
+    pub frame_ms: u32,
+    /// Speech must exceed the noise floor by this many dB
+    pub threshold_db: f32,
+    /// Absolute level below which a frame is never speech (dBFS)
+    pub min_energy_db: f32,
+    /// Noise floor adaptation rate while silent (0.0 - 1.0 per frame)
+    pub noise_adapt_rate: f32,
+    /// Keep reporting speech this long after the last voiced frame (ms)
+    pub hangover_ms: u32,
+    /// Voiced frames must last at least this long to count as speech (ms)
+    pub min_speech_ms: u32,
+    /// Native model library for [`VadMode::Model`]
+    pub model_path: Option<PathBuf>,
+    /// Speech probability threshold for [`VadMode::Model`]
+    pub model_threshold: f32,
+    /// Silent frames before `speech_ended` (~10ms per frame)
+    pub speech_end_frames: usize,
+    /// Silence after `speech_ended` before `question_ended` (ms)
+    pub question_end_silence_ms: f64,
+    /// Speech must last this long before `speech_started` is sent (barge-in
+    /// gate, ms); shorter utterances are dropped entirely. 0 disables the gate
+    pub barge_in_min_speech_ms: u32,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            mode: VadMode::Auto,
+            sample_rate: 16000,
+            frame_ms: 10,
+            threshold_db: 9.0,
+            min_energy_db: -55.0,
+            noise_adapt_rate: 0.05,
+            hangover_ms: 200,
+            min_speech_ms: 90,
+            model_path: None,
+            model_threshold: 0.5,
+            speech_end_frames: 10,
+            question_end_silence_ms: 1000.0,
+            barge_in_min_speech_ms: 0,
+        }
+    }
+}
+
+impl VadConfig {
+    /// Read configuration from process environment variables
+    pub fn from_env() -> Self {
+        Self::from_env_map(&HashMap::new())
+    }
+
+    /// Read configuration from a dataflow node `env:` map.
+    ///
+    /// Keys missing from the map fall back to process environment variables,
+    /// then to defaults. Values using `${VAR}` placeholders are resolved the
+    /// same way.
+    pub fn from_env_map(env: &HashMap<String, String>) -> Self {
+        let defaults = Self::default();
+        Self {
+            mode: env_lookup(env, "VAD_MODE")
+                .and_then(|v| VadMode::parse(&v))
+                .unwrap_or(defaults.mode),
+            sample_rate: defaults.sample_rate,
+            frame_ms: env_parse(env, "VAD_FRAME_MS").unwrap_or(defaults.frame_ms),
+            threshold_db: env_parse(env, "VAD_THRESHOLD_DB").unwrap_or(defaults.threshold_db),
+            min_energy_db: env_parse(env, "VAD_MIN_ENERGY_DB").unwrap_or(defaults.min_energy_db),
+            noise_adapt_rate: env_parse(env, "VAD_NOISE_ADAPT_RATE").unwrap_or(defaults.noise_adapt_rate),
+            hangover_ms: env_parse(env, "VAD_HANGOVER_MS").unwrap_or(defaults.hangover_ms),
+            min_speech_ms: env_parse(env, "VAD_MIN_SPEECH_MS").unwrap_or(defaults.min_speech_ms),
+            model_path: env_lookup(env, "VAD_MODEL_PATH").map(PathBuf::from),
+            model_threshold: env_parse(env, "VAD_MODEL_THRESHOLD").unwrap_or(defaults.model_threshold),
+            speech_end_frames: env_parse(env, "SPEECH_END_FRAMES").unwrap_or(defaults.speech_end_frames),
+            question_end_silence_ms: env_parse(env, "QUESTION_END_SILENCE_MS")
+                .unwrap_or(defaults.question_end_silence_ms),
+            barge_in_min_speech_ms: env_parse(env, "BARGE_IN_MIN_SPEECH_MS")
+                .unwrap_or(defaults.barge_in_min_speech_ms),
+        }
+    }
+
+    /// Samples per analysis frame
+    pub fn frame_len(&self) -> usize {
+        ((self.sample_rate as usize * self.frame_ms.max(1) as usize) / 1000).max(1)
+    }
+
+    fn ms_to_frames(&self, ms: u32) -> usize {
+        (ms / self.frame_ms.max(1)) as usize
+    }
+}
+
+/// Look up `key` in a node `env:` map, falling back to the process env.
+/// `${VAR}` placeholders are resolved and blank values count as unset.
+pub(crate) fn env_lookup(env: &HashMap<String, String>, key: &str) -> Option<String> {
+    let value = match env.get(key) {
+        Some(v) => resolve_placeholder(v),
+        None => std::env::var(key).ok(),
+    };
+    value.filter(|v| !v.trim().is_empty())
+}
+
+/// [`env_lookup`] parsed into `T`; unparsable values count as unset
+pub(crate) fn env_parse<T: std::str::FromStr>(env: &HashMap<String, String>, key: &str) -> Option<T> {
+    env_lookup(env, key).and_then(|v| v.trim().parse().ok())
+}
+
+/// Resolve `${VAR}`, `${VAR:-default}` and `$VAR` against the process env
+fn resolve_placeholder(value: &str) -> Option<String> {
+    if let Some(inner) = value.strip_prefix("${").and_then(|v| v.strip_suffix('}')) {
+        match inner.split_once(":-") {
+            Some((var, default)) => Some(std::env::var(var).unwrap_or_else(|_| default.to_string())),
+            None => std::env::var(inner).ok(),
+        }
+    } else if let Some(var) = value.strip_prefix('$') {
+        std::env::var(var).ok()
+    } else {
+        Some(value.to_string())
+    }
+}
+
+/// Live detector state for UI display
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VadStatus {
+    /// Active detector name ("energy", "model", "native")
+    pub detector: String,
+    /// Tracked noise floor (dBFS), if the detector estimates one
+    pub noise_floor_db: Option<f32>,
+    /// Effective speech threshold (dBFS or probability)
+    pub threshold: f32,
+    /// Level of the last analysed frame (dBFS)
+    pub frame_level_db: f32,
+}
+
+/// Frame-based voice activity detector
+pub trait Vad: Send {
+    /// Short detector name for logs and UI
+    fn name(&self) -> &'static str;
+
+    /// Classify one frame of `frame_len` samples (after smoothing)
+    fn process_frame(&mut self, frame: &[f32]) -> bool;
+
+    /// Forget all adaptive state (e.g. after switching capture source)
+    fn reset(&mut self);
+
+    /// Current detector state for display
+    fn status(&self) -> VadStatus;
+}
+
+/// Create the software detector selected by `config`.
+///
+/// [`VadMode::Auto`] and [`VadMode::Energy`] use [`EnergyVad`] (in auto mode
+/// the bridge prefers the native AEC flag while AEC capture is active). A model
+/// that fails to load falls back to [`EnergyVad`]; the error is returned so
+/// the caller can report it.
+pub fn create_vad(config: &VadConfig) -> (Box<dyn Vad>, Option<String>) {
+    match config.mode {
+        VadMode::Auto | VadMode::Energy => (Box::new(EnergyVad::new(config.clone())), None),
+        VadMode::Model => {
+            let result = match config.model_path {
+                Some(ref path) => ModelVad::load(path, config.clone()),
+                None => Err("VAD_MODE=model requires VAD_MODEL_PATH".to_string()),
+            };
+            match result {
+                Ok(vad) => (Box::new(vad), None),
+                Err(e) => (Box::new(EnergyVad::new(config.clone())), Some(e)),
+            }
+        }
+    }
+}
+
+/// Applies minimum speech duration and hangover to raw frame decisions
+#[derive(Debug, Clone)]
+pub struct SpeechSmoother {
+    min_speech_frames: usize,
+    hangover_frames: usize,
+    voiced_run: usize,
+    hangover_left: usize,
+    in_speech: bool,
+}
+
+impl SpeechSmoother {
+    pub fn new(min_speech_frames: usize, hangover_frames: usize) -> Self {
+        Self {
+            min_speech_frames: min_speech_frames.max(1),
+            hangover_frames,
+            voiced_run: 0,
+            hangover_left: 0,
+            in_speech: false,
+        }
+    }
+
+    /// Feed one raw decision, returns the smoothed decision
+    pub fn update(&mut self, voiced: bool) -> bool {
+        if voiced {
+            self.voiced_run += 1;
+            if self.in_speech || self.voiced_run >= self.min_speech_frames {
+                self.in_speech = true;
+                self.hangover_left = self.hangover_frames;
+            }
+        } else {
+            self.voiced_run = 0;
+            if self.in_speech {
+                if self.hangover_left > 0 {
+                    self.hangover_left -= 1;
+                } else {
+                    self.in_speech = false;
+                }
+            }
+        }
+        self.in_speech
+    }
+
+    pub fn reset(&mut self) {
+        self.voiced_run = 0;
+        self.hangover_left = 0;
+        self.in_speech = false;
+    }
+}
+
+/// Adaptive energy/spectral VAD.
+///
+/// A frame is voiced when its level exceeds both the tracked noise floor by
+/// `threshold_db` and the absolute `min_energy_db`, and its zero-crossing rate
+/// is in the range typical for speech (broadband hiss and fan noise cross far
+/// more often). The noise floor follows quiet frames quickly and rises slowly
+/// while no speech is present, so a steady fan raises the bar instead of
+/// being detected as a talker.
+pub struct EnergyVad {
+    config: VadConfig,
+    smoother: SpeechSmoother,
+    noise_floor_db: Option<f32>,
+    last_level_db: f32,
+}
+
+impl EnergyVad {
+    /// Zero-crossing rate above which a frame is treated as noise
+    const MAX_SPEECH_ZCR: f32 = 0.45;
+
+    pub fn new(config: VadConfig) -> Self {
+        let smoother = SpeechSmoother::new(
+            config.ms_to_frames(config.min_speech_ms),
+            config.ms_to_frames(config.hangover_ms),
+        );
+        Self {
+            config,
+            smoother,
+            noise_floor_db: None,
+            last_level_db: SILENCE_DB,
+        }
+    }
+
+    fn threshold_db(&self) -> f32 {
+        let floor = self.noise_floor_db.unwrap_or(self.config.min_energy_db);
+        (floor + self.config.threshold_db).max(self.config.min_energy_db)
+    }
+
+    fn track_noise_floor(&mut self, level_db: f32) {
+        self.noise_floor_db = Some(match self.noise_floor_db {
+            None => level_db,
+            // Drop quickly to quieter frames
+            Some(floor) if level_db < floor => floor + (level_db - floor) * 0.5,
+            // Rise slowly while silent
+            Some(floor) => floor + (level_db - floor) * self.config.noise_adapt_rate,
+        });
+    }
+}
+
+impl Vad for EnergyVad {
+    fn name(&self) -> &'static str {
+        "energy"
+    }
+
+    fn process_frame(&mut self, frame: &[f32]) -> bool {
+        let level_db = frame_level_db(frame);
+        self.last_level_db = level_db;
+
+        // Seed the floor from the first frame so calibration starts immediately
+        if self.noise_floor_db.is_none() {
+            self.noise_floor_db = Some(level_db);
+        }
+
+        let voiced = level_db > self.threshold_db() && zero_crossing_rate(frame) < Self::MAX_SPEECH_ZCR;
+        let speaking = self.smoother.update(voiced);
+
+        if !speaking {
+            self.track_noise_floor(level_db);
+        }
+        speaking
+    }
+
+    fn reset(&mut self) {
+        self.smoother.reset();
+        self.noise_floor_db = None;
+        self.last_level_db = SILENCE_DB;
+    }
+
+    fn status(&self) -> VadStatus {
+        VadStatus {
+            detector: self.name().to_string(),
+            noise_floor_db: self.noise_floor_db,
+            threshold: self.threshold_db(),
+            frame_level_db: self.last_level_db,
+        }
+    }
+}
+
+/// Model-based VAD loaded from a native library (e.g. a Silero wrapper).
+///
+/// The library must export:
+/// - `vad_create(sample_rate: i32) -> *mut c_void`
+/// - `vad_process(handle: *mut c_void, samples: *const f32, len: i32) -> f32`
+///   returning a speech probability in 0.0 - 1.0
+/// - `vad_destroy(handle: *mut c_void)`
+pub struct ModelVad {
+    _library: Library,
+    process: Symbol<'static, unsafe extern "C" fn(*mut c_void, *const f32, i32) -> f32>,
+    destroy: Symbol<'static, unsafe extern "C" fn(*mut c_void)>,
+    handle: *mut c_void,
+    config: VadConfig,
+    smoother: SpeechSmoother,
+    last_probability: f32,
+    last_level_db: f32,
+}
+
+// The model handle is only ever used from the owning bridge thread
+unsafe impl Send for ModelVad {}
+
+impl ModelVad {
+    /// Load the model library
+    pub fn load(library_path: &Path, config: VadConfig) -> Result<Self, String> {
+        if !library_path.exists() {
+            return Err(format!("VAD model library not found: {:?}", library_path));
+        }
+
+        unsafe {
+            let library = Library::new(library_path)
+                .map_err(|e| format!("Failed to load VAD model library: {}", e))?;
+
+            // Symbols are transmuted to 'static - the library is kept alive in the struct
+            let create: Symbol<unsafe extern "C" fn(i32) -> *mut c_void> = library
+                .get(b"vad_create")
+                .map_err(|e| format!("Failed to get vad_create: {}", e))?;
+            let process: Symbol<unsafe extern "C" fn(*mut c_void, *const f32, i32) -> f32> = library
+                .get(b"vad_process")
+                .map_err(|e| format!("Failed to get vad_process: {}", e))?;
+            let process: Symbol<'static, unsafe extern "C" fn(*mut c_void, *const f32, i32) -> f32> =
+                std::mem::transmute(process);
+            let destroy: Symbol<unsafe extern "C" fn(*mut c_void)> = library
+                .get(b"vad_destroy")
+                .map_err(|e| format!("Failed to get vad_destroy: {}", e))?;
+            let destroy: Symbol<'static, unsafe extern "C" fn(*mut c_void)> =
+                std::mem::transmute(destroy);
+
+            let handle = create(config.sample_rate as i32);
+            if handle.is_null() {
+                return Err("vad_create returned null".to_string());
+            }
+
+            let smoother = SpeechSmoother::new(
+                config.ms_to_frames(config.min_speech_ms),
+                config.ms_to_frames(config.hangover_ms),
+            );
+
+            Ok(Self {
+                _library: library,
+                process,
+                destroy,
+                handle,
+                config,
+                smoother,
+                last_probability: 0.0,
+                last_level_db: SILENCE_DB,
+            })
+        }
+    }
+}
+
+impl Vad for ModelVad {
+    fn name(&self) -> &'static str {
+        "model"
+    }
+
+    fn process_frame(&mut self, frame: &[f32]) -> bool {
+        self.last_level_db = frame_level_db(frame);
+        self.last_probability =
+            unsafe { (self.process)(self.handle, frame.as_ptr(), frame.len() as i32) };
+        self.smoother
+            .update(self.last_probability >= self.config.model_threshold)
+    }
+
+    fn reset(&mut self) {
+        self.smoother.reset();
+        self.last_probability = 0.0;
+    }
+
+    fn status(&self) -> VadStatus {
+        VadStatus {
+            detector: self.name().to_string(),
+            noise_floor_db: None,
+            threshold: self.config.model_threshold,
+            frame_level_db: self.last_level_db,
+        }
+    }
+}
+
+impl Drop for ModelVad {
+    fn drop(&mut self) {
+        unsafe { (self.destroy)(self.handle) };
+    }
+}
+
+/// Splits arbitrary-sized capture chunks into fixed VAD frames
+pub struct VadProcessor {
+    vad: Box<dyn Vad>,
+    frame_len: usize,
+    pending: Vec<f32>,
+}
+
+impl VadProcessor {
+    pub fn new(vad: Box<dyn Vad>, config: &VadConfig) -> Self {
+        Self {
+            vad,
+            frame_len: config.frame_len(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Run the detector over `samples`, returns one decision per complete frame.
+    /// Leftover samples are kept for the next call.
+    pub fn process(&mut self, samples: &[f32]) -> Vec<bool> {
+        self.pending.extend_from_slice(samples);
+        let frames = self.pending.len() / self.frame_len;
+        let decisions = self
+            .pending
+            .chunks_exact(self.frame_len)
+            .map(|frame| self.vad.process_frame(frame))
+            .collect();
+        self.pending.drain(..frames * self.frame_len);
+        decisions
+    }
+
+    pub fn reset(&mut self) {
+        self.vad.reset();
+        self.pending.clear();
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.vad.name()
+    }
+
+    pub fn status(&self) -> VadStatus {
+        self.vad.status()
+    }
+}
+
+/// RMS level of a frame in dBFS
+pub fn frame_level_db(frame: &[f32]) -> f32 {
+    if frame.is_empty() {
+        return SILENCE_DB;
+    }
+    let mean_square = frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32;
+    if mean_square <= 0.0 {
+        SILENCE_DB
+    } else {
+        (10.0 * mean_square.log10()).max(SILENCE_DB)
+    }
+}
+
+/// Fraction of adjacent sample pairs that change sign
+fn zero_crossing_rate(frame: &[f32]) -> f32 {
+    if frame.len() < 2 {
+        return 0.0;
+    }
+    let crossings = frame
+        .windows(2)
+        .filter(|w| (w[0] >= 0.0) != (w[1] >= 0.0))
+        .count();
+    crossings as f32 / (frame.len() - 1) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(len: usize, amplitude: f32) -> Vec<f32> {
+        // 200 Hz at 16 kHz - voiced-speech-like zero-crossing rate
+        (0..len)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * 200.0 * i as f32 / 16000.0).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_smoother_min_speech_and_hangover() {
+        let mut smoother = SpeechSmoother::new(3, 2);
+
+        // A 2-frame click never becomes speech
+        assert!(!smoother.update(true));
+        assert!(!smoother.update(true));
+        assert!(!smoother.update(false));
+
+        // 3 voiced frames start speech
+        assert!(!smoother.update(true));
+        assert!(!smoother.update(true));
+        assert!(smoother.update(true));
+
+        // Hangover holds speech for 2 silent frames
+        assert!(smoother.update(false));
+        assert!(smoother.update(false));
+        assert!(!smoother.update(false));
+    }
+
+    #[test]
+    fn test_energy_vad_adapts_to_noise_floor() {
+        let config = VadConfig::default();
+        let frame_len = config.frame_len();
+        let mut processor = VadProcessor::new(Box::new(EnergyVad::new(config)), &VadConfig::default());
+
+        // Steady background hum never triggers once the floor has adapted
+        let hum = tone(frame_len * 100, 0.01);
+        let decisions = processor.process(&hum);
+        assert_eq!(decisions.len(), 100);
+        assert!(decisions.iter().all(|&d| !d));
+
+        // Speech well above the floor is detected after min duration
+        let speech = tone(frame_len * 30, 0.3);
+        let decisions = processor.process(&speech);
+        assert!(decisions.iter().any(|&d| d));
+        assert!(!decisions[0]);
+    }
+
+    #[test]
+    fn test_processor_keeps_partial_frames() {
+        let config = VadConfig::default();
+        let frame_len = config.frame_len();
+        let mut processor = VadProcessor::new(Box::new(EnergyVad::new(config.clone())), &config);
+
+        assert!(processor.process(&vec![0.0; frame_len / 2]).is_empty());
+        assert_eq!(processor.process(&vec![0.0; frame_len / 2]).len(), 1);
+    }
+
+    #[test]
+    fn test_config_from_env_map() {
+        let mut env = HashMap::new();
+        env.insert("VAD_MODE".to_string(), "energy".to_string());
+        env.insert("VAD_THRESHOLD_DB".to_string(), "12.5".to_string());
+        env.insert("VAD_HANGOVER_MS".to_string(), "${MOFA_TEST_UNSET_VAR:-300}".to_string());
+        env.insert("SPEECH_END_FRAMES".to_string(), "20".to_string());
+
+        let config = VadConfig::from_env_map(&env);
+        assert_eq!(config.mode, VadMode::Energy);
+        assert_eq!(config.threshold_db, 12.5);
+        assert_eq!(config.hangover_ms, 300);
+        assert_eq!(config.speech_end_frames, 20);
+        assert_eq!(config.frame_len(), 160);
+    }
+}
@@ -3,15 +3,20 @@
 //! Connects to dora as `mofa-aec-input` dynamic node.
 //! Captures microphone audio with macOS AEC via native library.
 //! Provides:
-//! - VAD-based speech segmentation
-//! - Mic level for UI visualization
+//! - VAD-based speech segmentation (see [`crate::vad`])
+//! - Capture processing (high-pass, noise suppression, AGC) ahead of VAD and ASR
+//!   (see [`crate::audio_processing`])
+//! - Mic level for UI visualization (before and after processing)
 //! - Speech detection state
 //! - Audio segments for ASR
+//! - Turn modes: VAD (automatic), push-to-talk, and tap-to-end-turn
 
+use crate::audio_processing::{AudioProcessor, ProcessingConfig, ProcessingStage};
 use crate::bridge::{BridgeState, DoraBridge};
 use crate::data::DoraData;
 use crate::error::{BridgeError, BridgeResult};
 use crate::shared_state::SharedDoraState;
+use crate::vad::{create_vad, VadConfig, VadMode, VadProcessor, VadStatus};
 use crossbeam_channel::{bounded, Receiver, Sender};
 use dora_node_api::{
     dora_core::config::{DataId, NodeId},
@@ -27,12 +32,62 @@ use std::thread;
 use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
 
+/// How the mic bridge decides when the human's turn starts and ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MicInputMode {
+    /// VAD segments speech and silence timers emit `question_ended`
+    #[default]
+    Vad,
+    /// Speech is captured only while the push-to-talk key/button is held;
+    /// releasing it ends the turn
+    PushToTalk,
+    /// VAD segments speech, but `question_ended` is only sent on
+    /// [`AecControlCommand::EndTurn`]
+    TapToEnd,
+}
+
+impl MicInputMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MicInputMode::Vad => "vad",
+            MicInputMode::PushToTalk => "push_to_talk",
+            MicInputMode::TapToEnd => "tap_to_end",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "vad" | "auto" => Some(MicInputMode::Vad),
+            "push_to_talk" | "ptt" => Some(MicInputMode::PushToTalk),
+            "tap_to_end" | "manual" => Some(MicInputMode::TapToEnd),
+            _ => None,
+        }
+    }
+
+    /// Next mode in UI cycling order
+    pub fn next(&self) -> Self {
+        match self {
+            MicInputMode::Vad => MicInputMode::PushToTalk,
+            MicInputMode::PushToTalk => MicInputMode::TapToEnd,
+            MicInputMode::TapToEnd => MicInputMode::Vad,
+        }
+    }
+}
+
 /// Control commands for AEC input
 #[derive(Debug, Clone)]
 pub enum AecControlCommand {
     StartRecording,
     StopRecording,
     SetAecEnabled(bool),
+    /// Switch turn mode
+    SetInputMode(MicInputMode),
+    /// Push-to-talk key/button pressed (true) or released (false)
+    PushToTalk(bool),
+    /// End the human turn now: flush speech and send `question_ended`
+    EndTurn,
+    /// Enable or bypass one capture processing stage
+    SetProcessingEnabled(ProcessingStage, bool),
 }
 
 /// VAD segmentation state
@@ -49,38 +104,89 @@ struct VadState {
     last_speech_end_time: Option<Instant>,
     question_end_sent: bool,
     current_question_id: u32,
+    /// Samples of speech required before `speech_started` is announced
+    barge_in_min_samples: usize,
+    /// Whether `speech_started` was sent for the current utterance
+    speech_announced: bool,
 }
 
-impl Default for VadState {
-    fn default() -> Self {
-        // Read from environment variables (matching Python behavior)
-        let speech_end_threshold = std::env::var("SPEECH_END_FRAMES")
-            .ok()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(10); // Default 10 frames (~100ms)
-
-        let question_end_silence_ms = std::env::var("QUESTION_END_SILENCE_MS")
-            .ok()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(1000.0); // Default 1000ms
-
+impl VadState {
+    fn new(config: &VadConfig) -> Self {
         Self {
             is_speaking: false,
             speech_buffer: Vec::new(),
             audio_segment_buffer: Vec::new(),
             silence_count: 0,
             speech_start_threshold: 3,      // Frames of speech to start
-            speech_end_threshold,           // From env or default 10 frames (~100ms)
+            speech_end_threshold: config.speech_end_frames, // Default 10 frames (~100ms)
             min_segment_size: 4800,         // 0.3s at 16kHz
             max_segment_size: 160000,       // 10s at 16kHz
-            question_end_silence_ms,        // From env or default 1000ms
+            question_end_silence_ms: config.question_end_silence_ms, // Default 1000ms
             last_speech_end_time: None,
             question_end_sent: false,
             current_question_id: rand::random::<u32>() % 900000 + 100000,
+            barge_in_min_samples: config.barge_in_min_speech_ms as usize * config.sample_rate as usize / 1000,
+            speech_announced: false,
+        }
+    }
+
+    /// A turn is open while speech is active, or after speech ended until
+    /// `question_ended` is sent for it
+    fn has_open_turn(&self) -> bool {
+        self.is_speaking || (self.last_speech_end_time.is_some() && !self.question_end_sent)
+    }
+
+    /// Whether the current utterance should be announced with `speech_started`.
+    /// The `BARGE_IN_MIN_SPEECH_MS` gate only holds speech back while AI audio
+    /// is playing; otherwise any speech is a normal turn.
+    fn should_announce(&self, ai_speaking: bool) -> bool {
+        self.is_speaking
+            && !self.speech_announced
+            && (!ai_speaking || self.audio_segment_buffer.len() >= self.barge_in_min_samples)
+    }
+
+    /// Close the open turn and rotate the question_id. The caller sends what
+    /// the returned [`TurnEnd`] lists.
+    fn end_turn(&mut self) -> TurnEnd {
+        let mut end = TurnEnd {
+            question_id: self.current_question_id,
+            segment: None,
+            speech_ended: false,
+            question_ended: self.has_open_turn(),
+        };
+
+        if self.is_speaking {
+            let segment = std::mem::take(&mut self.audio_segment_buffer);
+            if segment.len() >= self.min_segment_size {
+                end.segment = Some(segment);
+            }
+            self.is_speaking = false;
+            self.silence_count = 0;
+            self.speech_buffer.clear();
+            end.speech_ended = true;
         }
+
+        if end.question_ended {
+            self.question_end_sent = true;
+            self.last_speech_end_time = None;
+            self.current_question_id = rand::random::<u32>() % 900000 + 100000;
+        }
+        end
     }
 }
 
+/// Outputs for a turn closed by [`VadState::end_turn`]
+#[derive(Debug)]
+struct TurnEnd {
+    question_id: u32,
+    /// In-progress speech, when long enough for ASR
+    segment: Option<Vec<f32>>,
+    /// Speech was active: send `speech_ended`
+    speech_ended: bool,
+    /// Send `question_ended` for `question_id`
+    question_ended: bool,
+}
+
 /// Native audio capture wrapper using libloading
 struct NativeAudioCapture {
     _library: Library,
@@ -240,13 +346,13 @@ impl Drop for NativeAudioCapture {
 }
 
 /// Regular CPAL-based mic capture (no AEC)
-/// Used when AEC is disabled - falls back to standard mic input
+/// Used when AEC is disabled - falls back to standard mic input.
+/// Has no built-in VAD - speech detection is done by the bridge's [`crate::vad::Vad`].
 struct CpalMicCapture {
     stream: Option<cpal::Stream>,
     audio_buffer: Arc<parking_lot::Mutex<Vec<i16>>>,
     is_recording: bool,
     sample_rate: u32,
-    vad_threshold: f32, // Energy threshold for simple VAD
 }
 
 impl CpalMicCapture {
@@ -256,7 +362,6 @@ impl CpalMicCapture {
             audio_buffer: Arc::new(parking_lot::Mutex::new(Vec::new())),
             is_recording: false,
             sample_rate: 16000,
-            vad_threshold: 0.01, // Simple energy-based VAD threshold
         })
     }
 
@@ -317,9 +422,8 @@ impl CpalMicCapture {
         }
     }
 
-    /// Get audio data with simple energy-based VAD
-    /// Returns (audio_samples_i16, vad_active)
-    fn get_audio(&self) -> Option<(Vec<i16>, bool)> {
+    /// Get all buffered audio samples (i16)
+    fn get_audio(&self) -> Option<Vec<i16>> {
         if !self.is_recording {
             return None;
         }
@@ -329,20 +433,7 @@ impl CpalMicCapture {
             return None;
         }
 
-        // Take all available samples
-        let samples: Vec<i16> = buffer.drain(..).collect();
-
-        // Simple energy-based VAD: calculate RMS and compare to threshold
-        let rms: f32 = if samples.is_empty() {
-            0.0
-        } else {
-            let sum_squares: f64 = samples.iter().map(|&s| (s as f64).powi(2)).sum();
-            ((sum_squares / samples.len() as f64).sqrt() / 32768.0) as f32
-        };
-
-        let vad_active = rms > self.vad_threshold;
-
-        Some((samples, vad_active))
+        Some(buffer.drain(..).collect())
     }
 }
 
@@ -363,6 +454,8 @@ pub struct AecInputBridge {
     worker_handle: Option<thread::JoinHandle<()>>,
     is_recording: Arc<AtomicBool>,
     aec_enabled: Arc<AtomicBool>,
+    vad_config: VadConfig,
+    processing_config: ProcessingConfig,
 }
 
 impl AecInputBridge {
@@ -370,7 +463,17 @@ impl AecInputBridge {
         Self::with_shared_state(node_id, None)
     }
 
+    /// Create with VAD settings from process environment variables
     pub fn with_shared_state(node_id: &str, shared_state: Option<Arc<SharedDoraState>>) -> Self {
+        Self::with_vad_config(node_id, shared_state, VadConfig::from_env())
+    }
+
+    /// Create with explicit VAD settings (e.g. from the dataflow node `env:`)
+    pub fn with_vad_config(
+        node_id: &str,
+        shared_state: Option<Arc<SharedDoraState>>,
+        vad_config: VadConfig,
+    ) -> Self {
         let (control_tx, control_rx) = bounded(10);
 
         Self {
@@ -383,9 +486,27 @@ impl AecInputBridge {
             worker_handle: None,
             is_recording: Arc::new(AtomicBool::new(false)),
             aec_enabled: Arc::new(AtomicBool::new(false)), // Default to CPAL (safer startup)
+            vad_config,
+            processing_config: ProcessingConfig::from_env(),
         }
     }
 
+    /// Use explicit capture processing settings (e.g. from the dataflow node `env:`)
+    pub fn with_processing_config(mut self, processing_config: ProcessingConfig) -> Self {
+        self.processing_config = processing_config;
+        self
+    }
+
+    /// VAD settings used by this bridge
+    pub fn vad_config(&self) -> &VadConfig {
+        &self.vad_config
+    }
+
+    /// Capture processing settings used by this bridge
+    pub fn processing_config(&self) -> &ProcessingConfig {
+        &self.processing_config
+    }
+
     /// Send control command (from UI)
     pub fn send_control(&self, cmd: AecControlCommand) -> BridgeResult<()> {
         self.control_sender
@@ -443,6 +564,8 @@ impl AecInputBridge {
         stop_receiver: Receiver<()>,
         is_recording: Arc<AtomicBool>,
         aec_enabled: Arc<AtomicBool>,
+        vad_config: VadConfig,
+        processing_config: ProcessingConfig,
     ) {
         eprintln!("[AecInput] Starting event loop for {}", node_id);
 
@@ -509,9 +632,19 @@ impl AecInputBridge {
         }
 
         // VAD state
-        let mut vad_state = VadState::default();
+        let mut vad_state = VadState::new(&vad_config);
         let mut recording_active = false;
         let mut using_aec = aec_enabled.load(Ordering::Acquire) && aec_available;
+        let mut input_mode = MicInputMode::default();
+        let mut ptt_pressed = false;
+
+        // Software VAD (always used for CPAL, and for AEC unless mode is auto)
+        let (vad, vad_error) = create_vad(&vad_config);
+        let mut vad_processor = VadProcessor::new(vad, &vad_config);
+        if let Some(e) = vad_error {
+            warn!("VAD model unavailable: {} - using energy VAD", e);
+            let _ = Self::send_log(&mut node, &node_id, "WARNING", &format!("VAD model unavailable: {} - using energy VAD", e));
+        }
 
         // Log config on startup (matching Python behavior)
         let _ = Self::send_log(
@@ -519,10 +652,47 @@ impl AecInputBridge {
             &node_id,
             "INFO",
             &format!(
-                "🔧 CONFIG: SPEECH_END_FRAMES={}, QUESTION_END_SILENCE_MS={}ms, AEC_AVAILABLE={}",
-                vad_state.speech_end_threshold, vad_state.question_end_silence_ms, aec_available
+                "🔧 CONFIG: SPEECH_END_FRAMES={}, QUESTION_END_SILENCE_MS={}ms, BARGE_IN_MIN_SPEECH_MS={}ms, AEC_AVAILABLE={}",
+                vad_state.speech_end_threshold,
+                vad_state.question_end_silence_ms,
+                vad_config.barge_in_min_speech_ms,
+                aec_available
+            ),
+        );
+        let _ = Self::send_log(
+            &mut node,
+            &node_id,
+            "INFO",
+            &format!(
+                "🔧 VAD: mode={}, detector={}, threshold={}dB, min_energy={}dBFS, hangover={}ms, min_speech={}ms",
+                vad_config.mode.as_str(),
+                vad_processor.name(),
+                vad_config.threshold_db,
+                vad_config.min_energy_db,
+                vad_config.hangover_ms,
+                vad_config.min_speech_ms
+            ),
+        );
+        // Capture processing runs before VAD, the audio stream and audio_segment
+        let mut processor = AudioProcessor::new(processing_config);
+        let _ = Self::send_log(
+            &mut node,
+            &node_id,
+            "INFO",
+            &format!(
+                "🔧 PROCESSING: high_pass={} ({}Hz), noise_suppression={} (max {}dB), agc={} (target {}dBFS, max +{}dB)",
+                processor.config().high_pass,
+                processor.config().high_pass_cutoff_hz,
+                processor.config().noise_suppression,
+                processor.config().noise_max_attenuation_db,
+                processor.config().agc,
+                processor.config().agc_target_db,
+                processor.config().agc_max_gain_db
             ),
         );
+        if let Some(ref ss) = shared_state {
+            ss.mic.set_processing(processor.status());
+        }
         let speech_end_ms = vad_state.speech_end_threshold * 10; // ~10ms per frame
         let total_silence_ms = speech_end_ms as f64 + vad_state.question_end_silence_ms;
         let _ = Self::send_log(
@@ -639,8 +809,10 @@ impl AecInputBridge {
                                 }
                             }
 
-                            // Switch capture method
+                            // Switch capture method - new source, new noise floor
                             using_aec = new_using_aec;
+                            vad_processor.reset();
+                            processor.reset();
 
                             // Start new capture if was recording
                             if recording_active {
@@ -664,6 +836,70 @@ impl AecInputBridge {
                         }
                         info!("AEC enabled: {} (using_aec: {})", enabled, using_aec);
                     }
+                    AecControlCommand::SetInputMode(mode) => {
+                        if mode != input_mode {
+                            // Close any turn in progress, including a question still
+                            // waiting for tap-to-end, so nothing is left half-open
+                            Self::end_turn(&mut node, &node_id, &mut vad_state, shared_state.as_deref());
+                            input_mode = mode;
+                            ptt_pressed = false;
+                            vad_state.last_speech_end_time = None;
+                            vad_state.speech_buffer.clear();
+                            vad_processor.reset();
+                            let _ = Self::send_log(
+                                &mut node,
+                                &node_id,
+                                "INFO",
+                                &format!("🎚️ Mic input mode: {}", mode.as_str()),
+                            );
+                        }
+                    }
+                    AecControlCommand::PushToTalk(pressed) => {
+                        if input_mode != MicInputMode::PushToTalk || pressed == ptt_pressed {
+                            continue;
+                        }
+                        ptt_pressed = pressed;
+                        if pressed {
+                            // The key press is deliberate - no barge-in gate
+                            vad_state.is_speaking = true;
+                            vad_state.speech_announced = true;
+                            vad_state.question_end_sent = false;
+                            vad_state.audio_segment_buffer.clear();
+                            if let Some(ref ss) = shared_state {
+                                ss.mic.set_speaking(true);
+                            }
+                            if let Err(e) = Self::send_speech_started(&mut node, vad_config.barge_in_min_speech_ms) {
+                                warn!("Failed to send speech_started: {}", e);
+                            }
+                            let _ = Self::send_is_speaking(&mut node, true);
+                            let _ = Self::send_log(
+                                &mut node,
+                                &node_id,
+                                "INFO",
+                                &format!("🎤 PUSH-TO-TALK pressed - question_id={}", vad_state.current_question_id),
+                            );
+                        } else {
+                            Self::end_turn(&mut node, &node_id, &mut vad_state, shared_state.as_deref());
+                        }
+                    }
+                    AecControlCommand::EndTurn => {
+                        ptt_pressed = false;
+                        Self::end_turn(&mut node, &node_id, &mut vad_state, shared_state.as_deref());
+                    }
+                    AecControlCommand::SetProcessingEnabled(stage, enabled) => {
+                        processor.set_enabled(stage, enabled);
+                        // Levels change with the chain - let the VAD re-learn its floor
+                        vad_processor.reset();
+                        if let Some(ref ss) = shared_state {
+                            ss.mic.set_processing(processor.status());
+                        }
+                        let _ = Self::send_log(
+                            &mut node,
+                            &node_id,
+                            "INFO",
+                            &format!("🎛️ {}: {}", stage.as_str(), if enabled { "ON" } else { "BYPASSED" }),
+                        );
+                    }
                 }
             }
 
@@ -681,10 +917,11 @@ impl AecInputBridge {
 
                 for _ in 0..100 {
                     // Get audio from the appropriate capture source
+                    // (CPAL has no native VAD flag - the software VAD decides below)
                     let audio_result = if using_aec {
                         aec_capture.as_ref().and_then(|aec| aec.get_audio())
                     } else {
-                        cpal_capture.get_audio()
+                        cpal_capture.get_audio().map(|samples| (samples, false))
                     };
 
                     match audio_result {
@@ -710,8 +947,10 @@ impl AecInputBridge {
                 }
 
                 // Check question_ended timer (runs even without audio)
+                // (in push-to-talk and tap-to-end modes the UI ends the turn)
                 let mut question_ended = false;
-                if !vad_state.is_speaking
+                if input_mode == MicInputMode::Vad
+                    && !vad_state.is_speaking
                     && vad_state.last_speech_end_time.is_some()
                     && !vad_state.question_end_sent
                 {
@@ -754,10 +993,16 @@ impl AecInputBridge {
                     continue;
                 }
 
-                // Calculate mic level and update shared state
+                // Run the processing chain, tracking levels on both sides of it
+                let raw_rms = Self::calculate_rms(&all_audio);
+                processor.process(&mut all_audio);
                 let rms = Self::calculate_rms(&all_audio);
                 if let Some(ref ss) = shared_state {
+                    ss.mic.set_raw_level(raw_rms);
                     ss.mic.set_level(rms);
+                    if debug_count % 10 == 0 {
+                        ss.mic.set_processing(processor.status());
+                    }
                 }
 
                 // Send continuous audio stream (matching Python behavior)
@@ -765,10 +1010,45 @@ impl AecInputBridge {
                     warn!("Failed to send audio: {}", e);
                 }
 
-                // VAD processing
+                // Push-to-talk: the key defines the segment, VAD is bypassed
+                if input_mode == MicInputMode::PushToTalk {
+                    if ptt_pressed {
+                        vad_state.audio_segment_buffer.extend(&all_audio);
+                        if vad_state.audio_segment_buffer.len() >= vad_state.max_segment_size {
+                            let segment = std::mem::take(&mut vad_state.audio_segment_buffer);
+                            if let Err(e) = Self::send_audio_segment(&mut node, &segment, vad_state.current_question_id) {
+                                warn!("Failed to send audio_segment: {}", e);
+                            }
+                        }
+                    }
+                    continue;
+                }
+
+                // VAD processing - native AEC flag in auto mode, software VAD otherwise
+                let use_native_vad = using_aec && vad_config.mode == VadMode::Auto;
+                if !use_native_vad {
+                    vad_results = vad_processor.process(&all_audio);
+                }
                 let vad_result = vad_results.iter().any(|&v| v);
                 let num_chunks = vad_results.len();
 
+                // Publish detector state for the mic panel (~10 updates/s)
+                if debug_count % 10 == 0 {
+                    if let Some(ref ss) = shared_state {
+                        let status = if use_native_vad {
+                            VadStatus {
+                                detector: "native".to_string(),
+                                noise_floor_db: None,
+                                threshold: 0.0,
+                                frame_level_db: crate::vad::frame_level_db(&all_audio),
+                            }
+                        } else {
+                            vad_processor.status()
+                        };
+                        ss.mic.set_vad_status(status);
+                    }
+                }
+
                 let mut speech_started = false;
                 let mut speech_ended = false;
                 let mut audio_segment: Option<Vec<f32>> = None;
@@ -780,9 +1060,9 @@ impl AecInputBridge {
                         vad_state.speech_buffer.push(all_audio.clone());
 
                         if vad_state.speech_buffer.len() >= vad_state.speech_start_threshold {
+                            // speech_started is announced below once the barge-in gate passes
                             vad_state.is_speaking = true;
-                            speech_started = true;
-                            vad_state.question_end_sent = false;
+                            vad_state.speech_announced = false;
 
                             // Start segment buffer
                             vad_state.audio_segment_buffer.clear();
@@ -814,7 +1094,14 @@ impl AecInputBridge {
                         vad_state.audio_segment_buffer.extend(&all_audio);
                         vad_state.silence_count += num_chunks;
 
-                        if vad_state.silence_count >= vad_state.speech_end_threshold {
+                        if vad_state.silence_count >= vad_state.speech_end_threshold && !vad_state.speech_announced {
+                            // Too short to count as barge-in (cough, "mm") - drop it silently
+                            vad_state.audio_segment_buffer.clear();
+                            vad_state.is_speaking = false;
+                            vad_state.silence_count = 0;
+                            vad_state.speech_buffer.clear();
+                            debug!("Dropped short utterance below BARGE_IN_MIN_SPEECH_MS");
+                        } else if vad_state.silence_count >= vad_state.speech_end_threshold {
                             // Speech ended
                             if vad_state.audio_segment_buffer.len() >= vad_state.min_segment_size {
                                 audio_segment = Some(vad_state.audio_segment_buffer.clone());
@@ -838,6 +1125,17 @@ impl AecInputBridge {
                     }
                 }
 
+                // Barge-in gate: while the AI talks, announce speech only once it
+                // has lasted long enough
+                let ai_speaking = shared_state
+                    .as_ref()
+                    .is_some_and(|ss| ss.audio.is_playing());
+                if vad_state.should_announce(ai_speaking) {
+                    vad_state.speech_announced = true;
+                    vad_state.question_end_sent = false;
+                    speech_started = true;
+                }
+
                 // Update shared state with speaking status
                 if let Some(ref ss) = shared_state {
                     if speech_started || speech_ended {
@@ -847,7 +1145,7 @@ impl AecInputBridge {
 
                 // Send dora outputs
                 if speech_started {
-                    if let Err(e) = Self::send_speech_started(&mut node) {
+                    if let Err(e) = Self::send_speech_started(&mut node, vad_config.barge_in_min_speech_ms) {
                         warn!("Failed to send speech_started: {}", e);
                     }
                     if let Err(e) = Self::send_is_speaking(&mut node, true) {
@@ -939,14 +1237,67 @@ impl AecInputBridge {
         info!("AEC input bridge event loop ended");
     }
 
-    fn send_speech_started(node: &mut DoraNode) -> BridgeResult<()> {
+    /// End the human turn explicitly (push-to-talk release, tap-to-end or a
+    /// mode switch).
+    ///
+    /// Flushes the in-progress segment to ASR, sends `speech_ended` if speech
+    /// was active, then `question_ended` and rotates the question_id.
+    fn end_turn(
+        node: &mut DoraNode,
+        node_id: &str,
+        vad_state: &mut VadState,
+        shared_state: Option<&SharedDoraState>,
+    ) {
+        let end = vad_state.end_turn();
+
+        if let Some(segment) = &end.segment {
+            if let Err(e) = Self::send_audio_segment(node, segment, end.question_id) {
+                warn!("Failed to send audio_segment: {}", e);
+            }
+        }
+        if end.speech_ended {
+            if let Some(ss) = shared_state {
+                ss.mic.set_speaking(false);
+            }
+            if let Err(e) = Self::send_speech_ended(node) {
+                warn!("Failed to send speech_ended: {}", e);
+            }
+            let _ = Self::send_is_speaking(node, false);
+        }
+
+        if !end.question_ended {
+            // Nothing said since the last turn ended
+            return;
+        }
+
+        let _ = Self::send_log(
+            node,
+            node_id,
+            "INFO",
+            &format!("📤 TURN ENDED by user - sending question_ended with question_id={}", end.question_id),
+        );
+        if let Err(e) = Self::send_question_ended(node, end.question_id) {
+            warn!("Failed to send question_ended: {}", e);
+        }
+    }
+
+    /// `barge_in_min_speech_ms` metadata tells the controller which gate this
+    /// mic applies, so the setting lives in one place
+    fn send_speech_started(node: &mut DoraNode, barge_in_min_speech_ms: u32) -> BridgeResult<()> {
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .map(|d| d.as_secs_f64())
             .unwrap_or(0.0);
         let data = vec![now].into_arrow();
         let output_id: DataId = "speech_started".to_string().into();
-        node.send_output(output_id, BTreeMap::new(), data)
+
+        let mut params: BTreeMap<String, Parameter> = BTreeMap::new();
+        params.insert(
+            "barge_in_min_speech_ms".to_string(),
+            Parameter::Integer(barge_in_min_speech_ms as i64),
+        );
+
+        node.send_output(output_id, params, data)
             .map_err(|e| BridgeError::SendFailed(e.to_string()))
     }
 
@@ -1079,6 +1430,8 @@ impl DoraBridge for AecInputBridge {
         let control_receiver = self.control_receiver.clone();
         let is_recording = Arc::clone(&self.is_recording);
         let aec_enabled = Arc::clone(&self.aec_enabled);
+        let vad_config = self.vad_config.clone();
+        let processing_config = self.processing_config.clone();
 
         let handle = thread::spawn(move || {
             Self::run_event_loop(
@@ -1089,6 +1442,8 @@ impl DoraBridge for AecInputBridge {
                 stop_rx,
                 is_recording,
                 aec_enabled,
+                vad_config,
+                processing_config,
             );
         });
 
@@ -1160,6 +1515,29 @@ impl DoraBridge for AecInputBridge {
                                     .unwrap_or(true);
                                 Some(AecControlCommand::SetAecEnabled(enabled))
                             }
+                            "set_input_mode" => val
+                                .get("mode")
+                                .and_then(|v| v.as_str())
+                                .and_then(MicInputMode::parse)
+                                .map(AecControlCommand::SetInputMode),
+                            "push_to_talk" => {
+                                let pressed = val
+                                    .get("pressed")
+                                    .and_then(|v| v.as_bool())
+                                    .unwrap_or(false);
+                                Some(AecControlCommand::PushToTalk(pressed))
+                            }
+                            "end_turn" => Some(AecControlCommand::EndTurn),
+                            "set_processing" => {
+                                let enabled = val
+                                    .get("enabled")
+                                    .and_then(|v| v.as_bool())
+                                    .unwrap_or(true);
+                                val.get("stage")
+                                    .and_then(|v| v.as_str())
+                                    .and_then(ProcessingStage::parse)
+                                    .map(|stage| AecControlCommand::SetProcessingEnabled(stage, enabled))
+                            }
                             _ => None,
                         };
                         if let Some(cmd) = cmd {
@@ -1199,3 +1577,60 @@ impl Drop for AecInputBridge {
         let _ = self.disconnect();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mode_switch_flushes_pending_question() {
+        // Tap-to-end: speech ended and the question waits for the user
+        let mut state = VadState::new(&VadConfig::default());
+        state.last_speech_end_time = Some(Instant::now());
+        state.question_end_sent = false;
+        let question_id = state.current_question_id;
+        assert!(state.has_open_turn());
+
+        let end = state.end_turn();
+        assert!(end.question_ended);
+        assert_eq!(end.question_id, question_id);
+        assert!(!end.speech_ended);
+        assert!(!state.has_open_turn());
+
+        // Nothing said since: a second switch sends nothing
+        assert!(!state.end_turn().question_ended);
+    }
+
+    #[test]
+    fn test_end_turn_flushes_active_speech() {
+        let mut state = VadState::new(&VadConfig::default());
+        state.is_speaking = true;
+        state.audio_segment_buffer = vec![0.1; state.min_segment_size];
+
+        let end = state.end_turn();
+        assert!(end.speech_ended);
+        assert!(end.question_ended);
+        assert_eq!(end.segment.map(|segment| segment.len()), Some(state.min_segment_size));
+        assert!(!state.is_speaking);
+    }
+
+    #[test]
+    fn test_min_speech_gate_only_while_ai_speaks() {
+        let config = VadConfig {
+            barge_in_min_speech_ms: 400,
+            ..VadConfig::default()
+        };
+        let mut state = VadState::new(&config);
+        state.is_speaking = true;
+        state.audio_segment_buffer = vec![0.1; state.barge_in_min_samples / 2];
+
+        assert!(!state.should_announce(true));
+        assert!(state.should_announce(false));
+
+        state.audio_segment_buffer = vec![0.1; state.barge_in_min_samples];
+        assert!(state.should_announce(true));
+
+        state.speech_announced = true;
+        assert!(!state.should_announce(false));
+    }
+}
@@ -71,6 +71,18 @@
 //! | Full Reset | None        | Clear buffer, no filtering |
 //! | Smart Reset| Present     | Clear buffer + filter by question_id |
 //!
+//! ## 3. Barge-in Pause
+//!
+//! With the controller's `pause_resume` barge-in policy, human speech does not
+//! reset the player. The controller sends `pause` on the `control` input instead:
+//! playback stops, but buffered audio is kept and incoming audio is still accepted
+//! (no filtering). `resume` continues where playback stopped; if the human turn
+//! turns out to be meaningful, the usual smart reset follows and clears everything.
+//!
+//! The same mechanism backs the user "hold" control ([`crate::AudioState::set_hold`]).
+//! While paused or held, `buffer_status` is reported as 100% so the text
+//! segmenter's backpressure stops TTS generation.
+//!
 //! # Comparison with Python Implementation
 //!
 //! This implementation matches the Python `audio_player.py` from the conference example:
@@ -249,6 +261,15 @@ impl AudioPlayerBridge {
             // The actual buffer fill percentage comes from CircularAudioBuffer::fill_percentage()
             // in the UI layer, sent here via channel every 50ms
             while let Ok(status) = buffer_status_receiver.try_recv() {
+                // While paused/held, report a full buffer so the text segmenter
+                // stops feeding TTS (backpressure) until playback resumes
+                if let Some(ref ss) = shared_state {
+                    ss.audio.set_playback_fill(status);
+                }
+                let status = match shared_state {
+                    Some(ref ss) if ss.audio.is_paused() => 100.0,
+                    _ => status,
+                };
                 if let Err(e) = Self::send_buffer_status_to_dora(&mut node, status) {
                     warn!("Failed to send buffer status: {}", e);
                 } else {
@@ -313,22 +334,31 @@ impl AudioPlayerBridge {
                     event_meta.values.insert(key.clone(), string_value);
                 }
 
+                // Handle barge-in pause/resume - stop playback but keep buffered audio
+                if input_id == "control" {
+                    let command = Self::extract_command(&data, &event_meta);
+                    match command.as_str() {
+                        "pause" => {
+                            info!("⏸️ Audio player PAUSE (barge-in): keeping buffered audio");
+                            if let Some(ss) = shared_state {
+                                ss.audio.signal_pause(true);
+                            }
+                        }
+                        "resume" => {
+                            info!("▶️ Audio player RESUME (barge-in)");
+                            if let Some(ss) = shared_state {
+                                ss.audio.signal_pause(false);
+                            }
+                        }
+                        _ => warn!("Unknown audio player control command: {}", command),
+                    }
+                    return;
+                }
+
                 // Handle reset input - immediately clear audio buffer (human speaking interrupt)
                 // Smart reset: if question_id is provided, filter incoming audio until matching question_id arrives
                 if input_id == "reset" {
-                    // Extract command from data or metadata
-                    let command = if let Some(cmd) = event_meta.get("command") {
-                        cmd.to_string()
-                    } else {
-                        // Try to read from data (StringArray)
-                        use arrow::array::AsArray;
-                        data.as_string::<i32>()
-                            .iter()
-                            .filter_map(|s| s)
-                            .next()
-                            .map(|s| s.to_string())
-                            .unwrap_or_default()
-                    };
+                    let command = Self::extract_command(&data, &event_meta);
 
                     if command == "cancel" || command == "reset" {
                         // Extract question_id for smart reset
@@ -515,6 +545,21 @@ impl AudioPlayerBridge {
         }
     }
 
+    /// Extract a control command from metadata (`command`) or the string payload
+    fn extract_command(data: &dora_node_api::ArrowData, event_meta: &EventMetadata) -> String {
+        if let Some(cmd) = event_meta.get("command") {
+            return cmd.to_string();
+        }
+        // Try to read from data (StringArray)
+        use arrow::array::AsArray;
+        data.as_string::<i32>()
+            .iter()
+            .filter_map(|s| s)
+            .next()
+            .map(|s| s.to_string())
+            .unwrap_or_default()
+    }
+
     // NOTE: Audio level and band calculation removed - now done in screen.rs from output waveform
     // This is more accurate since it reflects what's actually being played,
     // not what's being received (which may be buffered ahead of playback)
@@ -14,7 +14,7 @@ mod audio_player;
 mod prompt_input;
 mod system_log;
 
-pub use aec_input::{AecControlCommand, AecInputBridge};
+pub use aec_input::{AecControlCommand, AecInputBridge, MicInputMode};
 pub use audio_player::AudioPlayerBridge;
 pub use prompt_input::PromptInputBridge;
 pub use system_log::SystemLogBridge;
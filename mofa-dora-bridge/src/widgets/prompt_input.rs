@@ -1,7 +1,7 @@
 //! Prompt input bridge
 //!
 //! Connects to dora as `mofa-prompt-input` dynamic node.
-//! Sends user prompts to LLM nodes and receives:
+//! Sends user prompts (and image file paths) to LLM nodes and receives:
 //! - Text responses (streaming)
 //! - Status updates
 
@@ -39,6 +39,10 @@ pub struct PromptInputBridge {
     control_sender: Sender<ControlCommand>,
     /// Control command receiver for dora
     control_receiver: Receiver<ControlCommand>,
+    /// Image path sender from widget
+    image_sender: Sender<String>,
+    /// Image path receiver for dora
+    image_receiver: Receiver<String>,
     /// Stop signal
     stop_sender: Option<Sender<()>>,
     /// Worker thread handle
@@ -55,6 +59,7 @@ impl PromptInputBridge {
     pub fn with_shared_state(node_id: &str, shared_state: Option<Arc<SharedDoraState>>) -> Self {
         let (prompt_tx, prompt_rx) = bounded(10);
         let (control_tx, control_rx) = bounded(10);
+        let (image_tx, image_rx) = bounded(10);
 
         Self {
             node_id: node_id.to_string(),
@@ -64,6 +69,8 @@ impl PromptInputBridge {
             prompt_receiver: prompt_rx,
             control_sender: control_tx,
             control_receiver: control_rx,
+            image_sender: image_tx,
+            image_receiver: image_rx,
             stop_sender: None,
             worker_handle: None,
         }
@@ -83,6 +90,13 @@ impl PromptInputBridge {
             .map_err(|_| BridgeError::ChannelSendError)
     }
 
+    /// Send an image file path to the LLM nodes (widget calls this)
+    pub fn send_image(&self, path: impl Into<String>) -> BridgeResult<()> {
+        self.image_sender
+            .send(path.into())
+            .map_err(|_| BridgeError::ChannelSendError)
+    }
+
     /// Run the dora event loop in background thread
     fn run_event_loop(
         node_id: String,
@@ -90,6 +104,7 @@ impl PromptInputBridge {
         shared_state: Option<Arc<SharedDoraState>>,
         prompt_receiver: Receiver<String>,
         control_receiver: Receiver<ControlCommand>,
+        image_receiver: Receiver<String>,
         stop_receiver: Receiver<()>,
     ) {
         info!("Starting prompt input bridge event loop for {}", node_id);
@@ -137,6 +152,13 @@ impl PromptInputBridge {
                 }
             }
 
+            // Check for images to send
+            while let Ok(path) = image_receiver.try_recv() {
+                if let Err(e) = Self::send_image_to_dora(&mut node, &path) {
+                    warn!("Failed to send image: {}", e);
+                }
+            }
+
             // Receive dora events with timeout
             match events.recv_timeout(std::time::Duration::from_millis(100)) {
                 Some(event) => {
@@ -278,6 +300,17 @@ impl PromptInputBridge {
             .map_err(|e| BridgeError::SendFailed(e.to_string()))
     }
 
+    /// Send an image file path to dora via image output
+    /// The maas-client attaches it to the participant's next user message
+    fn send_image_to_dora(node: &mut DoraNode, path: &str) -> BridgeResult<()> {
+        info!("Sending image to dora: {}", path);
+
+        let data = path.to_string().into_arrow();
+        let output_id: DataId = "image".to_string().into();
+        node.send_output(output_id, Default::default(), data)
+            .map_err(|e| BridgeError::SendFailed(e.to_string()))
+    }
+
     /// Send control command to dora
     fn send_control_to_dora(node: &mut DoraNode, cmd: &ControlCommand) -> BridgeResult<()> {
         let payload =
@@ -314,6 +347,7 @@ impl DoraBridge for PromptInputBridge {
         let shared_state = self.shared_state.clone();
         let prompt_receiver = self.prompt_receiver.clone();
         let control_receiver = self.control_receiver.clone();
+        let image_receiver = self.image_receiver.clone();
 
         let handle = thread::spawn(move || {
             Self::run_event_loop(
@@ -322,6 +356,7 @@ impl DoraBridge for PromptInputBridge {
                 shared_state,
                 prompt_receiver,
                 control_receiver,
+                image_receiver,
                 stop_rx,
             );
         });
@@ -365,6 +400,12 @@ impl DoraBridge for PromptInputBridge {
                     .send(cmd)
                     .map_err(|_| BridgeError::ChannelSendError)?;
             }
+            ("image", DoraData::Text(path)) => {
+                info!("Queuing image for sending: {}", path);
+                self.image_sender
+                    .send(path)
+                    .map_err(|_| BridgeError::ChannelSendError)?;
+            }
             _ => {
                 warn!("Unknown output: {}", output_id);
             }
@@ -383,7 +424,8 @@ impl DoraBridge for PromptInputBridge {
     }
 
     fn expected_outputs(&self) -> Vec<String> {
-        vec!["control".to_string()] // Prompts are sent via control output as JSON
+        // Prompts are sent via control output as JSON
+        vec!["control".to_string(), "image".to_string()]
     }
 }
 
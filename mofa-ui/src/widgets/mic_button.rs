@@ -1,6 +1,7 @@
 //! Microphone Toggle Button Widget
 //!
-//! A toggle button showing mic on/off icons with muted state visualization.
+//! A toggle button showing mic on/off icons with muted state visualization,
+//! plus an optional turn mode badge (VAD / push-to-talk / tap-to-end).
 //!
 //! ## Usage
 //!
@@ -21,8 +22,16 @@
 //!     // Toggle mic state
 //! }
 //! ```
+//!
+//! ## Turn Modes
+//!
+//! Tapping the `mode_badge` cycles [`MicInputMode`]. The icon then behaves per mode:
+//! - `Vad`: tap toggles mute ([`MicButtonAction::Clicked`])
+//! - `PushToTalk`: press/release emit [`MicButtonAction::PushToTalk`]
+//! - `TapToEnd`: tap emits [`MicButtonAction::EndTurn`]
 
 use makepad_widgets::*;
+use mofa_dora_bridge::MicInputMode;
 
 live_design! {
     use link::theme::*;
@@ -33,7 +42,9 @@ live_design! {
     pub MicButton = {{MicButton}} {
         width: Fit
         height: Fit
-        flow: Overlay
+        flow: Down
+        align: {x: 0.5}
+        spacing: 1
         cursor: Hand
         padding: 4
 
@@ -98,6 +109,26 @@ live_design! {
                 icon_walk: {width: 20, height: 20}
             }
         }
+
+        // Turn mode selector (tap to cycle: VAD -> PTT -> TAP)
+        mode_badge = <View> {
+            width: Fit, height: Fit
+            visible: false
+            mode_label = <Label> {
+                draw_text: {
+                    instance dark_mode: 0.0
+                    text_style: { font_size: 7.0 }
+                    fn get_color(self) -> vec4 {
+                        return mix(
+                            vec4(0.392, 0.455, 0.545, 1.0),  // SLATE_500
+                            vec4(0.796, 0.835, 0.882, 1.0),  // SLATE_300
+                            self.dark_mode
+                        );
+                    }
+                }
+                text: "VAD"
+            }
+        }
     }
 }
 
@@ -109,6 +140,12 @@ pub enum MicButtonAction {
     Clicked,
     /// Mic state changed (contains new muted state)
     StateChanged(bool),
+    /// Turn mode changed via the mode badge
+    ModeChanged(MicInputMode),
+    /// Push-to-talk pressed (true) or released (false)
+    PushToTalk(bool),
+    /// User tapped to end their turn (tap-to-end mode)
+    EndTurn,
 }
 
 #[derive(Live, LiveHook, Widget)]
@@ -127,20 +164,57 @@ pub struct MicButton {
     /// Current dark mode value
     #[rust]
     dark_mode: f64,
+
+    /// Turn mode (decides what a tap on the icon means)
+    #[rust]
+    input_mode: MicInputMode,
+
+    /// Whether push-to-talk is currently held on the button
+    #[rust]
+    ptt_held: bool,
 }
 
 impl Widget for MicButton {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
         self.view.handle_event(cx, event, scope);
 
-        // Handle click
-        match event.hits(cx, self.view.area()) {
-            Hit::FingerUp(fe) if fe.is_over && fe.was_tap() => {
-                cx.widget_action(
-                    self.widget_uid(),
-                    &scope.path,
-                    MicButtonAction::Clicked,
-                );
+        // Mode badge tap cycles the turn mode (checked first so it captures the finger)
+        let badge = self.view.view(ids!(mode_badge));
+        if badge.is_visible() {
+            match event.hits(cx, badge.area()) {
+                Hit::FingerUp(fe) => {
+                    if fe.is_over && fe.was_tap() {
+                        let mode = self.input_mode.next();
+                        self.set_input_mode(cx, mode);
+                        cx.widget_action(
+                            self.widget_uid(),
+                            &scope.path,
+                            MicButtonAction::ModeChanged(mode),
+                        );
+                    }
+                    return;
+                }
+                Hit::Nothing => {}
+                _ => return,
+            }
+        }
+
+        // Handle click / hold depending on turn mode
+        let uid = self.widget_uid();
+        match (self.input_mode, event.hits(cx, self.view.area())) {
+            (MicInputMode::PushToTalk, Hit::FingerDown(_)) => {
+                self.ptt_held = true;
+                cx.widget_action(uid, &scope.path, MicButtonAction::PushToTalk(true));
+            }
+            (MicInputMode::PushToTalk, Hit::FingerUp(_)) if self.ptt_held => {
+                self.ptt_held = false;
+                cx.widget_action(uid, &scope.path, MicButtonAction::PushToTalk(false));
+            }
+            (MicInputMode::TapToEnd, Hit::FingerUp(fe)) if fe.is_over && fe.was_tap() => {
+                cx.widget_action(uid, &scope.path, MicButtonAction::EndTurn);
+            }
+            (MicInputMode::Vad, Hit::FingerUp(fe)) if fe.is_over && fe.was_tap() => {
+                cx.widget_action(uid, &scope.path, MicButtonAction::Clicked);
             }
             _ => {}
         }
@@ -175,12 +249,33 @@ impl MicButton {
         self.update_shader(cx);
     }
 
+    /// Get turn mode
+    pub fn input_mode(&self) -> MicInputMode {
+        self.input_mode
+    }
+
+    /// Set turn mode (updates the badge text)
+    pub fn set_input_mode(&mut self, cx: &mut Cx, mode: MicInputMode) {
+        self.input_mode = mode;
+        self.ptt_held = false;
+        let text = match mode {
+            MicInputMode::Vad => "VAD",
+            MicInputMode::PushToTalk => "PTT",
+            MicInputMode::TapToEnd => "TAP",
+        };
+        self.view.label(ids!(mode_badge.mode_label)).set_text(cx, text);
+        self.view.redraw(cx);
+    }
+
     /// Apply dark mode
     pub fn apply_dark_mode(&mut self, cx: &mut Cx, dark_mode: f64) {
         self.dark_mode = dark_mode;
         self.view.view(ids!(mic_icon_on)).icon(ids!(icon)).apply_over(cx, live! {
             draw_icon: { dark_mode: (dark_mode) }
         });
+        self.view.label(ids!(mode_badge.mode_label)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
         self.view.redraw(cx);
     }
 
@@ -229,6 +324,18 @@ impl MicButtonRef {
         }
     }
 
+    /// Get turn mode
+    pub fn input_mode(&self) -> MicInputMode {
+        self.borrow().map(|inner| inner.input_mode()).unwrap_or_default()
+    }
+
+    /// Set turn mode
+    pub fn set_input_mode(&self, cx: &mut Cx, mode: MicInputMode) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_input_mode(cx, mode);
+        }
+    }
+
     /// Apply dark mode
     pub fn apply_dark_mode(&self, cx: &mut Cx, dark_mode: f64) {
         if let Some(mut inner) = self.borrow_mut() {
@@ -244,4 +351,34 @@ impl MicButtonRef {
             .filter_map(|a| a.as_widget_action())
             .any(|a| matches!(a.cast(), MicButtonAction::Clicked))
     }
+
+    /// Check if the turn mode was changed in actions
+    pub fn mode_changed(&self, actions: &Actions) -> Option<MicInputMode> {
+        actions
+            .iter()
+            .filter_map(|a| a.as_widget_action())
+            .find_map(|a| match a.cast() {
+                MicButtonAction::ModeChanged(mode) => Some(mode),
+                _ => None,
+            })
+    }
+
+    /// Check if push-to-talk was pressed/released in actions
+    pub fn push_to_talk(&self, actions: &Actions) -> Option<bool> {
+        actions
+            .iter()
+            .filter_map(|a| a.as_widget_action())
+            .find_map(|a| match a.cast() {
+                MicButtonAction::PushToTalk(pressed) => Some(pressed),
+                _ => None,
+            })
+    }
+
+    /// Check if end of turn was requested in actions
+    pub fn end_turn_requested(&self, actions: &Actions) -> bool {
+        actions
+            .iter()
+            .filter_map(|a| a.as_widget_action())
+            .any(|a| matches!(a.cast(), MicButtonAction::EndTurn))
+    }
 }
@@ -1,7 +1,7 @@
 //! MofaHero Widget - System status bar with Dataflow, CPU, Memory, GPU, and VRAM panels
 //!
 //! A shared hero widget that displays system monitoring information and
-//! provides dataflow start/stop controls.
+//! provides dataflow start/stop controls and an audio hold toggle.
 
 use crate::system_monitor;
 use makepad_widgets::*;
@@ -23,6 +23,7 @@ live_design! {
     // Icons
     ICO_START = dep("crate://self/resources/icons/start.svg")
     ICO_STOP = dep("crate://self/resources/icons/stop.svg")
+    ICO_PAUSE = dep("crate://self/resources/icons/pause.svg")
 
     // Dataflow status button (Ready/Connected/Failed with color change) with hover animation
     DataflowButton = <Button> {
@@ -351,6 +352,82 @@ live_design! {
             }
         }
 
+        // Audio hold toggle (only while running) - pauses AI audio without dropping it
+        hold_section = <RoundedView> {
+            visible: false
+            width: Fill, height: Fill
+            padding: { left: 12, right: 12, top: 8, bottom: 8 }
+            draw_bg: {
+                instance dark_mode: 0.0
+                border_radius: (HERO_RADIUS)
+                fn pixel(self) -> vec4 {
+                    let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                    let r = self.border_radius;
+                    let bg = mix((PANEL_BG), (PANEL_BG_DARK), self.dark_mode);
+                    sdf.box(0., 0., self.rect_size.x, self.rect_size.y, r);
+                    sdf.fill(bg);
+                    return sdf.result;
+                }
+            }
+            flow: Down
+            align: {x: 0.5, y: 0.5}
+
+            // Playing - click to hold
+            hold_view = <View> {
+                width: Fill, height: Fill
+                flow: Down
+                spacing: 4
+                align: {x: 0.5, y: 0.5}
+                cursor: Hand
+
+                hold_label = <StatusLabel> {
+                    text: "Hold Audio"
+                }
+
+                <View> {
+                    width: 24, height: 20
+                    align: {x: 0.5, y: 0.5}
+                    <Icon> {
+                        draw_icon: {
+                            svg_file: (ICO_PAUSE)
+                            fn get_color(self) -> vec4 {
+                                return vec4(0.918, 0.702, 0.031, 1.0);  // Amber #eab308
+                            }
+                        }
+                        icon_walk: {width: 20, height: 20}
+                    }
+                }
+            }
+
+            // Held - click to resume
+            resume_view = <View> {
+                visible: false
+                width: Fill, height: Fill
+                flow: Down
+                spacing: 4
+                align: {x: 0.5, y: 0.5}
+                cursor: Hand
+
+                resume_label = <StatusLabel> {
+                    text: "Resume Audio"
+                }
+
+                <View> {
+                    width: 24, height: 20
+                    align: {x: 0.5, y: 0.5}
+                    <Icon> {
+                        draw_icon: {
+                            svg_file: (ICO_START)
+                            fn get_color(self) -> vec4 {
+                                return vec4(0.918, 0.702, 0.031, 1.0);  // Amber #eab308
+                            }
+                        }
+                        icon_walk: {width: 20, height: 20}
+                    }
+                }
+            }
+        }
+
         // Dataflow status section - matches conference-dashboard pattern
         connection_section = <StatusSection> {
             <View> {
@@ -468,6 +545,10 @@ pub enum MofaHeroAction {
     None,
     StartClicked,
     StopClicked,
+    /// Hold AI audio (keep buffered audio, stop playback)
+    HoldClicked,
+    /// Release the hold and continue playback
+    ResumeClicked,
 }
 
 #[derive(Live, LiveHook, Widget)]
@@ -478,6 +559,9 @@ pub struct MofaHero {
     #[rust]
     is_running: bool,
 
+    #[rust]
+    is_held: bool,
+
     #[rust]
     cpu_usage: f64,
 
@@ -548,6 +632,24 @@ impl Widget for MofaHero {
             }
             _ => {}
         }
+
+        // Handle hold/resume clicks
+        let hold_view = self.view.view(ids!(hold_section.hold_view));
+        let resume_view = self.view.view(ids!(hold_section.resume_view));
+
+        match event.hits(cx, hold_view.area()) {
+            Hit::FingerUp(_) => {
+                cx.widget_action(self.widget_uid(), &scope.path, MofaHeroAction::HoldClicked);
+            }
+            _ => {}
+        }
+
+        match event.hits(cx, resume_view.area()) {
+            Hit::FingerUp(_) => {
+                cx.widget_action(self.widget_uid(), &scope.path, MofaHeroAction::ResumeClicked);
+            }
+            _ => {}
+        }
     }
 
     fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
@@ -599,6 +701,24 @@ impl MofaHero {
         self.view
             .view(ids!(action_section.stop_view))
             .set_visible(cx, running);
+        self.view
+            .view(ids!(hold_section))
+            .set_visible(cx, running);
+        if !running {
+            self.set_held(cx, false);
+        }
+        self.view.redraw(cx);
+    }
+
+    /// Set the audio hold state (shows hold or resume view)
+    pub fn set_held(&mut self, cx: &mut Cx, held: bool) {
+        self.is_held = held;
+        self.view
+            .view(ids!(hold_section.hold_view))
+            .set_visible(cx, !held);
+        self.view
+            .view(ids!(hold_section.resume_view))
+            .set_visible(cx, held);
         self.view.redraw(cx);
     }
 
@@ -810,6 +930,11 @@ impl MofaHero {
     pub fn is_running(&self) -> bool {
         self.is_running
     }
+
+    /// Get the current audio hold state
+    pub fn is_held(&self) -> bool {
+        self.is_held
+    }
 }
 
 impl MofaHeroRef {
@@ -819,6 +944,12 @@ impl MofaHeroRef {
         }
     }
 
+    pub fn set_held(&self, cx: &mut Cx, held: bool) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_held(cx, held);
+        }
+    }
+
     pub fn set_cpu_usage(&self, cx: &mut Cx, usage: f64) {
         if let Some(mut inner) = self.borrow_mut() {
             inner.set_cpu_usage(cx, usage);
@@ -866,6 +997,32 @@ impl MofaHeroRef {
                     },
                 );
 
+            // Hold section
+            inner.view.view(ids!(hold_section)).apply_over(
+                cx,
+                live! {
+                    draw_bg: { dark_mode: (dark_mode) }
+                },
+            );
+            inner
+                .view
+                .label(ids!(hold_section.hold_view.hold_label))
+                .apply_over(
+                    cx,
+                    live! {
+                        draw_text: { dark_mode: (dark_mode) }
+                    },
+                );
+            inner
+                .view
+                .label(ids!(hold_section.resume_view.resume_label))
+                .apply_over(
+                    cx,
+                    live! {
+                        draw_text: { dark_mode: (dark_mode) }
+                    },
+                );
+
             // Connection section
             inner.view.view(ids!(connection_section)).apply_over(
                 cx,
@@ -1,11 +1,18 @@
 use dora_node_api::{self, DoraNode, Event, Parameter};
 use dora_node_api::arrow::array::{StringArray, AsArray};
 use dora_node_api::arrow;
-use dora_conference_controller::policies::{Policy, UnifiedRatioPolicy};
+use dora_conference_controller::policies::{
+    BargeInConfig, BargeInPolicy, Policy, SpeechAction, TranscriptAction, UnifiedRatioPolicy,
+};
 use dora_core::config::DataId;
 use eyre::Result;
 use std::collections::HashMap;
 use std::env;
+use std::time::Instant;
+
+// Minimum `confidence` for a participant's `next_speaker` hint to be followed
+// (override with NEXT_SPEAKER_MIN_CONFIDENCE); hints without a confidence apply
+const DEFAULT_NEXT_SPEAKER_MIN_CONFIDENCE: f64 = 0.5;
 
 // Enhanced Question ID (16-bit: 8-4-4 layout)
 // Bits 15-8: Round number (0-255)
@@ -133,6 +140,11 @@ struct ConferenceController {
 
     // Human interrupt control
     system_paused: bool,  // True when human is speaking or processing human input
+    barge_in: BargeInPolicy,  // Decides whether human speech interrupts, pauses or is ignored
+    ai_audio_active: bool,    // AI audio buffered/playing (from audio player buffer_status)
+
+    // Structured reply hints (next_speaker / confidence / ends_round metadata)
+    next_speaker_min_confidence: f64,  // Hints below this confidence are ignored
 }
 
 impl ConferenceController {
@@ -191,6 +203,18 @@ impl ConferenceController {
             &format!("🏷️ Starting with enhanced question_id: {} ({})",
                 initial_enhanced_id, enhanced_id_debug_string(initial_enhanced_id)));
 
+        let barge_in_config = BargeInConfig::from_env();
+        send_log(node, LogLevel::Info, log_level,
+            &format!("🗣️ Barge-in: mode={}, keywords={:?}, min_words={}, resume_timeout={}ms",
+                barge_in_config.mode.as_str(),
+                barge_in_config.keywords,
+                barge_in_config.min_words,
+                barge_in_config.resume_timeout.as_millis()));
+
+        let next_speaker_min_confidence = env::var("NEXT_SPEAKER_MIN_CONFIDENCE").ok()
+            .and_then(|s| s.parse::<f64>().ok())
+            .unwrap_or(DEFAULT_NEXT_SPEAKER_MIN_CONFIDENCE);
+
         // Log the ready message after all initialization is complete
         send_log(node, LogLevel::Info, log_level, "🚀 all nodes are ready, starting dataflow");
 
@@ -208,6 +232,9 @@ impl ConferenceController {
             waiting_for_session_start: None,  // Cold start - no waiting initially
             pending_next_speaker: false,
             system_paused: false,  // Initialize as not paused
+            barge_in: BargeInPolicy::new(barge_in_config),
+            ai_audio_active: false,
+            next_speaker_min_confidence,
         })
     }
 
@@ -279,8 +306,21 @@ impl ConferenceController {
     ) -> Result<()> {
         // NEW: Special handling for human input (non-streaming)
         // Human input always arrives with session_status="ended" (single shot from ASR)
+        // The barge-in policy decides whether it is a new turn or a backchannel
         if participant_id == "human" {
-            return self.handle_human_input(participant_id, text, metadata, node);
+            return match self.barge_in.on_transcript(&text) {
+                TranscriptAction::Interrupt => self.handle_human_input(participant_id, text, metadata, node),
+                TranscriptAction::Resume => {
+                    send_log(node, LogLevel::Info, self.log_level,
+                        &format!("▶️ Backchannel '{}' - resuming AI audio", text.chars().take(50).collect::<String>()));
+                    self.send_audio_control(node, "resume")
+                }
+                TranscriptAction::Drop => {
+                    send_log(node, LogLevel::Info, self.log_level,
+                        &format!("🙉 No interrupt keyword in '{}' - AI keeps speaking", text.chars().take(50).collect::<String>()));
+                    Ok(())
+                }
+            };
         }
 
         // Check session_status to understand the input type
@@ -346,6 +386,8 @@ impl ConferenceController {
             send_log(node, LogLevel::Info, self.log_level,
                 &format!("📥 {} completed ({} words)", participant_id, word_count));
 
+            self.apply_reply_hints(participant_id, metadata, node);
+
             // Process next speaker (will wait for session_start if needed)
             self.process_next_speaker(node)?;
         }
@@ -353,6 +395,51 @@ impl ConferenceController {
         Ok(())
     }
 
+    /// Apply the turn-taking fields of a structured participant reply
+    /// (`next_speaker`, `confidence`, `ends_round` on the `ended` marker)
+    fn apply_reply_hints(&mut self, participant_id: &str, metadata: &dora_node_api::Metadata, node: &mut DoraNode) {
+        let params = &metadata.parameters;
+
+        if let Some(Parameter::String(name)) = params.get("next_speaker") {
+            let confidence = match params.get("confidence") {
+                Some(Parameter::Float(f)) => Some(*f),
+                Some(Parameter::Integer(i)) => Some(*i as f64),
+                Some(Parameter::String(s)) => s.parse::<f64>().ok(),
+                _ => None,
+            };
+            // Role names ("tutor", "student1") map to the pattern's participant IDs
+            let speaker = self.participant_name_map.get(name.trim()).cloned();
+            match (speaker, confidence) {
+                (None, _) if name.trim().is_empty() => {}
+                (None, _) => {
+                    send_log(node, LogLevel::Debug, self.log_level,
+                        &format!("🤷 {} suggested unknown next speaker '{}' - ignored", participant_id, name));
+                }
+                (Some(speaker), Some(confidence)) if confidence < self.next_speaker_min_confidence => {
+                    send_log(node, LogLevel::Debug, self.log_level,
+                        &format!("🤔 {} suggested {} with low confidence {:.2} - ignored", participant_id, speaker, confidence));
+                }
+                (Some(speaker), confidence) => {
+                    send_log(node, LogLevel::Info, self.log_level,
+                        &format!("👉 {} suggests {} speaks next (confidence: {})", participant_id, speaker,
+                            confidence.map(|c| format!("{:.2}", c)).unwrap_or_else(|| "n/a".to_string())));
+                    self.policy.set_next_speaker_hint(Some(speaker));
+                }
+            }
+        }
+
+        let ends_round = match params.get("ends_round") {
+            Some(Parameter::Bool(b)) => *b,
+            Some(Parameter::String(s)) => s == "true",
+            _ => false,
+        };
+        if ends_round {
+            send_log(node, LogLevel::Info, self.log_level,
+                &format!("🏁 {} ended the round", participant_id));
+            self.policy.reset_round_tracking();
+        }
+    }
+
     /// Generate new question_id for next conversation round
     /// Handle session_start signals from audio player
     fn handle_session_start(&mut self, question_id: u16, node: &mut DoraNode, log_level: LogLevel) -> Result<()> {
@@ -521,6 +608,79 @@ impl ConferenceController {
         Ok(())
     }
 
+    /// Handle speech_started from the mic according to the barge-in policy
+    fn handle_human_speaking(&mut self, node: &mut DoraNode) -> Result<()> {
+        match self.barge_in.on_speech_started(self.ai_audio_active, Instant::now()) {
+            SpeechAction::Interrupt => {
+                // IMMEDIATE interrupt - don't wait for ASR transcription, cancel everything NOW
+                send_log(node, LogLevel::Info, self.log_level, "🎤 Human speaking detected - IMMEDIATE INTERRUPT");
+
+                // Send cancel to all LLMs immediately
+                node.send_output(
+                    DataId::from("llm_control".to_string()),
+                    Default::default(),
+                    StringArray::from(vec!["cancel"]),
+                )?;
+                node.send_output(
+                    DataId::from("judge_prompt".to_string()),
+                    Default::default(),
+                    StringArray::from(vec!["cancel"]),
+                )?;
+
+                // Send cancel to text segmenter to clear pending text
+                // Create metadata with cancel command and new question_id
+                let mut cancel_metadata = std::collections::BTreeMap::new();
+                cancel_metadata.insert(
+                    "command".to_string(),
+                    Parameter::String("cancel".to_string())
+                );
+                // Use a high question_id to ensure all old segments are cleared
+                let interrupt_qid = self.current_question_id.saturating_add(256);
+                cancel_metadata.insert(
+                    "question_id".to_string(),
+                    Parameter::String(interrupt_qid.to_string())
+                );
+                node.send_output(
+                    DataId::from("segmenter_control".to_string()),
+                    cancel_metadata,
+                    StringArray::from(vec!["cancel"]),
+                )?;
+
+                send_log(node, LogLevel::Info, self.log_level, "🔇 Sent immediate cancel to all LLMs and text segmenter");
+            }
+            SpeechAction::Pause => {
+                // LLMs and TTS keep generating into the (held) audio buffer
+                send_log(node, LogLevel::Info, self.log_level, "⏸️ Human speaking detected - pausing AI audio until transcript");
+                self.send_audio_control(node, "pause")?;
+            }
+            SpeechAction::Ignore => {
+                send_log(node, LogLevel::Info, self.log_level, "🎤 Human speaking detected - waiting for interrupt keyword");
+            }
+        }
+        Ok(())
+    }
+
+    /// Resume held AI audio if the transcript never arrived
+    fn check_barge_in_timeout(&mut self, node: &mut DoraNode) -> Result<()> {
+        if self.barge_in.check_timeout(Instant::now()) {
+            send_log(node, LogLevel::Info, self.log_level, "▶️ No transcript after pause - resuming AI audio");
+            self.send_audio_control(node, "resume")?;
+        }
+        Ok(())
+    }
+
+    /// Send pause/resume to the audio player (keeps buffered audio, unlike reset)
+    fn send_audio_control(&self, node: &mut DoraNode, command: &str) -> Result<()> {
+        let mut metadata = std::collections::BTreeMap::new();
+        metadata.insert("command".to_string(), Parameter::String(command.to_string()));
+        node.send_output(
+            DataId::from("audio_control".to_string()),
+            metadata,
+            StringArray::from(vec![command]),
+        )?;
+        Ok(())
+    }
+
     /// Get control output name for a participant
     fn get_control_output(&self, participant: &str) -> &str {
         if self.participant_name_map.contains_key("judge") &&
@@ -717,6 +877,7 @@ impl ConferenceController {
         self.streaming_accumulators.clear();
         self.waiting_for_session_start = None;
         self.pending_next_speaker = false;
+        self.barge_in.reset();
         self.policy.reset_counts();
         self.policy.reset_round_tracking();
         self.state = ControllerState::Waiting;
@@ -776,6 +937,9 @@ fn main() -> Result<()> {
                 // Debug: Log all incoming event IDs
                 send_log(&mut node, LogLevel::Debug, log_level, &format!("📨 Received event from input: '{}'", id.as_str()));
 
+                // buffer_status arrives every ~50ms, so this doubles as the pause timer
+                controller.check_barge_in_timeout(&mut node)?;
+
                 if id.as_str() == "control" {
                     // Extract text from control input
                     let control_array = data.as_string::<i32>();
@@ -915,47 +1079,25 @@ fn main() -> Result<()> {
                         send_log(&mut node, LogLevel::Error, log_level, &format!("❌ Error handling session start: {}", e));
                     }
                 } else if id.as_str() == "buffer_status" {
-                    // Buffer status from audio player - we don't use this anymore
-                    // Just consume it to avoid crashes
-                    let _buffer_data = data.as_primitive::<arrow::datatypes::Float64Type>();
-                    send_log(&mut node, LogLevel::Debug, log_level, "📊 Received buffer_status (ignored)");
+                    // Buffer status from audio player - only used to know whether AI audio
+                    // is still pending/playing (barge-in gating)
+                    let buffer_data = data.as_primitive::<arrow::datatypes::Float64Type>();
+                    if let Some(fill) = buffer_data.iter().flatten().last() {
+                        controller.ai_audio_active = fill > 0.0;
+                    }
                 } else if id.as_str() == "human_speaking" {
-                    // IMMEDIATE interrupt when human starts speaking
-                    // Don't wait for ASR transcription - cancel everything NOW
-                    send_log(&mut node, LogLevel::Info, log_level, "🎤 Human speaking detected - IMMEDIATE INTERRUPT");
-
-                    // Send cancel to all LLMs immediately
-                    node.send_output(
-                        DataId::from("llm_control".to_string()),
-                        Default::default(),
-                        StringArray::from(vec!["cancel"]),
-                    )?;
-                    node.send_output(
-                        DataId::from("judge_prompt".to_string()),
-                        Default::default(),
-                        StringArray::from(vec!["cancel"]),
-                    )?;
-
-                    // Send cancel to text segmenter to clear pending text
-                    // Create metadata with cancel command and new question_id
-                    let mut cancel_metadata = std::collections::BTreeMap::new();
-                    cancel_metadata.insert(
-                        "command".to_string(),
-                        Parameter::String("cancel".to_string())
-                    );
-                    // Use a high question_id to ensure all old segments are cleared
-                    let interrupt_qid = controller.current_question_id.saturating_add(256);
-                    cancel_metadata.insert(
-                        "question_id".to_string(),
-                        Parameter::String(interrupt_qid.to_string())
-                    );
-                    node.send_output(
-                        DataId::from("segmenter_control".to_string()),
-                        cancel_metadata,
-                        StringArray::from(vec!["cancel"]),
-                    )?;
-
-                    send_log(&mut node, LogLevel::Info, log_level, "🔇 Sent immediate cancel to all LLMs and text segmenter");
+                    // The mic reports the min-speech gate it applies
+                    let mic_gate = metadata.parameters.get("barge_in_min_speech_ms")
+                        .and_then(|p| match p {
+                            Parameter::Integer(i) => u64::try_from(*i).ok(),
+                            Parameter::String(s) => s.parse().ok(),
+                            _ => None
+                        });
+                    if let Some(warning) = controller.barge_in.check_mic_gate(mic_gate) {
+                        send_log(&mut node, LogLevel::Warn, log_level, &format!("⚠️ {}", warning));
+                    }
+                    // Interrupt, pause or ignore depending on the barge-in policy
+                    controller.handle_human_speaking(&mut node)?;
                 } else if id.as_str() == "question_ended" {
                     // Question ended signal - prolonged silence after speech
                     // This means the user has finished their question/statement
@@ -979,6 +1121,13 @@ fn main() -> Result<()> {
                     // 1. Confirm the cancel commands had time to propagate
                     // 2. Clear any lingering state from the interrupt
 
+                    // A dismissed or pending barge-in must not wipe the AI's audio
+                    if controller.barge_in.suppresses_turn_end() {
+                        send_log(&mut node, LogLevel::Info, log_level,
+                            "🗣️ Barge-in pending/dismissed - keeping AI audio");
+                        continue;
+                    }
+
                     // Send a final clear to audio player to ensure buffer is empty
                     let mut reset_metadata = std::collections::BTreeMap::new();
                     reset_metadata.insert(
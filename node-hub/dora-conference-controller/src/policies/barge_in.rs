@@ -0,0 +1,302 @@
+use std::time::{Duration, Instant};
+
+/// How human speech interrupts AI participants
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BargeInMode {
+    /// Any detected speech cancels the AI immediately (original behavior)
+    Immediate,
+    /// Same as immediate, but the mic bridge only reports speech that lasted
+    /// at least `BARGE_IN_MIN_SPEECH_MS` while the AI is speaking (coughs and
+    /// clicks never arrive). The mic reports its gate with `speech_started`.
+    MinDuration,
+    /// While the AI is speaking, only transcripts containing a keyword interrupt
+    Keyword,
+    /// AI audio is paused on speech and resumed if the transcript turns out
+    /// to be a backchannel ("mm-hm", "ok") or never arrives
+    PauseResume,
+}
+
+impl BargeInMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "immediate" | "hard" => Some(BargeInMode::Immediate),
+            "min_duration" | "duration" => Some(BargeInMode::MinDuration),
+            "keyword" | "keywords" => Some(BargeInMode::Keyword),
+            "pause_resume" | "pause" => Some(BargeInMode::PauseResume),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BargeInMode::Immediate => "immediate",
+            BargeInMode::MinDuration => "min_duration",
+            BargeInMode::Keyword => "keyword",
+            BargeInMode::PauseResume => "pause_resume",
+        }
+    }
+}
+
+/// Barge-in configuration
+#[derive(Debug, Clone)]
+pub struct BargeInConfig {
+    pub mode: BargeInMode,
+    /// Lowercased keywords that always interrupt (e.g. "stop", "wait", "等一下")
+    pub keywords: Vec<String>,
+    /// Lowercased backchannel words that never count as meaningful speech
+    pub fillers: Vec<String>,
+    /// Minimum meaningful tokens for a transcript to interrupt in pause/resume mode
+    pub min_words: usize,
+    /// Resume paused audio if no transcript arrives within this time
+    pub resume_timeout: Duration,
+}
+
+impl Default for BargeInConfig {
+    fn default() -> Self {
+        Self {
+            mode: BargeInMode::Immediate,
+            keywords: ["stop", "wait", "hold on", "停", "等一下"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            fillers: [
+                "mm", "mhm", "mm-hm", "uh-huh", "uh", "um", "hmm", "ok", "okay", "yeah", "yes",
+                "嗯", "啊", "哦", "呃", "对", "好",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+            min_words: 2,
+            resume_timeout: Duration::from_millis(3000),
+        }
+    }
+}
+
+impl BargeInConfig {
+    /// Load from environment variables:
+    /// `BARGE_IN_MODE`, `BARGE_IN_KEYWORDS`, `BARGE_IN_FILLERS` (comma-separated),
+    /// `BARGE_IN_MIN_WORDS`, `BARGE_IN_RESUME_TIMEOUT_MS`
+    pub fn from_env() -> Self {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = Self::default();
+        let list = |value: String| -> Vec<String> {
+            value
+                .split(',')
+                .map(|s| s.trim().to_lowercase())
+                .filter(|s| !s.is_empty())
+                .collect()
+        };
+
+        Self {
+            mode: lookup("BARGE_IN_MODE")
+                .and_then(|v| BargeInMode::parse(&v))
+                .unwrap_or(defaults.mode),
+            keywords: lookup("BARGE_IN_KEYWORDS").map(list).unwrap_or(defaults.keywords),
+            fillers: lookup("BARGE_IN_FILLERS").map(list).unwrap_or(defaults.fillers),
+            min_words: lookup("BARGE_IN_MIN_WORDS")
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(defaults.min_words),
+            resume_timeout: lookup("BARGE_IN_RESUME_TIMEOUT_MS")
+                .and_then(|v| v.trim().parse().ok())
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.resume_timeout),
+        }
+    }
+}
+
+/// What to do when the mic reports human speech
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpeechAction {
+    /// Cancel LLMs and clear the audio pipeline now
+    Interrupt,
+    /// Pause AI audio, keep everything buffered, wait for the transcript
+    Pause,
+    /// Let the AI keep talking, decide on the transcript
+    Ignore,
+}
+
+/// What to do with a human transcript
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptAction {
+    /// Treat as a new human turn (full interrupt and reset)
+    Interrupt,
+    /// Not meaningful - resume paused AI audio and discard the transcript
+    Resume,
+    /// Not meaningful - discard the transcript, AI was never paused
+    Drop,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    Idle,
+    /// AI audio paused, waiting for a transcript
+    Holding { since: Instant },
+    /// AI still speaking, waiting for a transcript
+    Gated,
+    /// Last utterance was dismissed; its question_ended must not reset audio
+    Dismissed,
+}
+
+/// Decides how human speech interrupts the AI
+///
+/// The controller feeds it mic events and transcripts; the policy returns what
+/// to do. Timestamps are passed in so decisions are deterministic in tests.
+pub struct BargeInPolicy {
+    config: BargeInConfig,
+    state: State,
+    /// Last gate reported by the mic (`None` until the first `speech_started`)
+    mic_gate: Option<Option<u64>>,
+}
+
+impl BargeInPolicy {
+    pub fn new(config: BargeInConfig) -> Self {
+        Self {
+            config,
+            state: State::Idle,
+            mic_gate: None,
+        }
+    }
+
+    pub fn config(&self) -> &BargeInConfig {
+        &self.config
+    }
+
+    /// Human speech detected by the mic bridge
+    pub fn on_speech_started(&mut self, ai_speaking: bool, now: Instant) -> SpeechAction {
+        if let State::Holding { .. } = self.state {
+            // Still holding from a previous utterance - keep the original deadline
+            return SpeechAction::Pause;
+        }
+
+        let action = match self.config.mode {
+            BargeInMode::Immediate | BargeInMode::MinDuration => SpeechAction::Interrupt,
+            BargeInMode::Keyword if ai_speaking => SpeechAction::Ignore,
+            BargeInMode::PauseResume if ai_speaking => SpeechAction::Pause,
+            BargeInMode::Keyword | BargeInMode::PauseResume => SpeechAction::Interrupt,
+        };
+
+        self.state = match action {
+            SpeechAction::Interrupt => State::Idle,
+            SpeechAction::Pause => State::Holding { since: now },
+            SpeechAction::Ignore => State::Gated,
+        };
+        action
+    }
+
+    /// Check the `barge_in_min_speech_ms` the mic sends with `speech_started`.
+    /// Returns a warning when the gate changes to one that makes `min_duration`
+    /// behave like `immediate`.
+    pub fn check_mic_gate(&mut self, min_speech_ms: Option<u64>) -> Option<String> {
+        if self.config.mode != BargeInMode::MinDuration || self.mic_gate == Some(min_speech_ms) {
+            return None;
+        }
+        self.mic_gate = Some(min_speech_ms);
+        match min_speech_ms {
+            Some(ms) if ms > 0 => None,
+            Some(_) => Some(
+                "BARGE_IN_MODE=min_duration, but the mic's BARGE_IN_MIN_SPEECH_MS is 0 - \
+                 any sound interrupts as in immediate mode"
+                    .to_string(),
+            ),
+            None => Some(
+                "BARGE_IN_MODE=min_duration, but the mic does not report BARGE_IN_MIN_SPEECH_MS - \
+                 any sound may interrupt as in immediate mode"
+                    .to_string(),
+            ),
+        }
+    }
+
+    /// Human transcript arrived from ASR
+    pub fn on_transcript(&mut self, text: &str) -> TranscriptAction {
+        let action = match self.state {
+            State::Holding { .. } if !self.is_meaningful(text) => TranscriptAction::Resume,
+            State::Gated if !self.contains_keyword(text) => TranscriptAction::Drop,
+            _ => TranscriptAction::Interrupt,
+        };
+
+        self.state = match action {
+            TranscriptAction::Interrupt => State::Idle,
+            TranscriptAction::Resume | TranscriptAction::Drop => State::Dismissed,
+        };
+        action
+    }
+
+    /// Returns true when paused audio should resume because no transcript came
+    pub fn check_timeout(&mut self, now: Instant) -> bool {
+        match self.state {
+            State::Holding { since } if now.duration_since(since) >= self.config.resume_timeout => {
+                self.state = State::Dismissed;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the end of the current utterance must leave AI audio alone
+    pub fn suppresses_turn_end(&self) -> bool {
+        !matches!(self.state, State::Idle)
+    }
+
+    /// Whether AI audio is currently paused by this policy
+    pub fn is_holding(&self) -> bool {
+        matches!(self.state, State::Holding { .. })
+    }
+
+    /// Forget any pending decision (controller reset)
+    pub fn reset(&mut self) {
+        self.state = State::Idle;
+    }
+
+    /// Keywords match whole words ("stop" matches "Stop!" but not "nonstop");
+    /// multi-word keywords must appear as consecutive words. CJK keywords have
+    /// no word boundaries and match as substrings.
+    fn contains_keyword(&self, text: &str) -> bool {
+        let lower = text.to_lowercase();
+        let words = split_words(&lower);
+        self.config.keywords.iter().any(|keyword| {
+            if keyword.chars().any(is_cjk) {
+                return lower.contains(keyword.as_str());
+            }
+            let needle = split_words(keyword);
+            !needle.is_empty() && words.windows(needle.len()).any(|w| w == needle.as_slice())
+        })
+    }
+
+    /// A transcript is meaningful if it contains a keyword, or has at least
+    /// `min_words` tokens that are not backchannel fillers. CJK characters
+    /// count as one token each.
+    fn is_meaningful(&self, text: &str) -> bool {
+        if self.contains_keyword(text) {
+            return true;
+        }
+
+        let is_filler = |word: &str| self.config.fillers.iter().any(|f| f == word);
+        let lower = text.to_lowercase();
+        let mut tokens = 0;
+        for word in lower.split(|c: char| !(c.is_alphanumeric() || c == '-' || c == '\'')) {
+            if word.is_empty() || is_filler(word) {
+                continue;
+            }
+            if word.chars().any(is_cjk) {
+                let mut buf = [0u8; 4];
+                tokens += word.chars().filter(|c| !is_filler(c.encode_utf8(&mut buf))).count();
+            } else {
+                tokens += 1;
+            }
+        }
+        tokens >= self.config.min_words
+    }
+}
+
+fn split_words(text: &str) -> Vec<&str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32, 0x4E00..=0x9FFF | 0x3400..=0x4DBF | 0x3040..=0x30FF | 0xAC00..=0xD7AF)
+}
@@ -1,8 +1,10 @@
 // Policy module for conference controller
 // This module defines traits and implementations for customizable control policies
 
+pub mod barge_in;
 pub mod unified_ratio;
 
+pub use barge_in::{BargeInConfig, BargeInMode, BargeInPolicy, SpeechAction, TranscriptAction};
 pub use unified_ratio::{PatternParser, PolicyPattern, UnifiedRatioPolicy, Weight};
 
 /// Core trait that defines the interface for all control policies
@@ -45,5 +47,6 @@ pub trait Policy: Send + Sync {
 
 #[cfg(test)]
 mod tests {
+    mod barge_in_test;
     mod unified_ratio_test;
 }
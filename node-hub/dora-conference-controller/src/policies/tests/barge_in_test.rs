@@ -0,0 +1,134 @@
+use crate::policies::{BargeInConfig, BargeInMode, BargeInPolicy, SpeechAction, TranscriptAction};
+use std::time::{Duration, Instant};
+
+fn policy(mode: BargeInMode) -> BargeInPolicy {
+    BargeInPolicy::new(BargeInConfig {
+        mode,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn test_parse_mode() {
+    assert_eq!(BargeInMode::parse("pause_resume"), Some(BargeInMode::PauseResume));
+    assert_eq!(BargeInMode::parse(" Keyword "), Some(BargeInMode::Keyword));
+    assert_eq!(BargeInMode::parse("min_duration"), Some(BargeInMode::MinDuration));
+    assert_eq!(BargeInMode::parse("bogus"), None);
+}
+
+#[test]
+fn test_config_from_lookup() {
+    let config = BargeInConfig::from_lookup(|key| match key {
+        "BARGE_IN_MODE" => Some("keyword".to_string()),
+        "BARGE_IN_KEYWORDS" => Some("Stop, Enough ,".to_string()),
+        "BARGE_IN_RESUME_TIMEOUT_MS" => Some("1500".to_string()),
+        _ => None,
+    });
+
+    assert_eq!(config.mode, BargeInMode::Keyword);
+    assert_eq!(config.keywords, vec!["stop", "enough"]);
+    assert_eq!(config.resume_timeout, Duration::from_millis(1500));
+    assert_eq!(config.min_words, 2);
+}
+
+#[test]
+fn test_min_duration_warns_about_ungated_mic() {
+    let mut min_duration = policy(BargeInMode::MinDuration);
+    assert!(min_duration.check_mic_gate(Some(0)).is_some());
+    // Reported once per gate value
+    assert!(min_duration.check_mic_gate(Some(0)).is_none());
+    assert!(min_duration.check_mic_gate(Some(400)).is_none());
+    assert!(min_duration.check_mic_gate(None).is_some());
+
+    // Other modes don't depend on the mic gate
+    let mut immediate = policy(BargeInMode::Immediate);
+    assert!(immediate.check_mic_gate(Some(0)).is_none());
+}
+
+#[test]
+fn test_immediate_always_interrupts() {
+    let mut policy = policy(BargeInMode::Immediate);
+    assert_eq!(policy.on_speech_started(true, Instant::now()), SpeechAction::Interrupt);
+    assert_eq!(policy.on_transcript("mm-hm"), TranscriptAction::Interrupt);
+    assert!(!policy.suppresses_turn_end());
+}
+
+#[test]
+fn test_keyword_gates_only_while_ai_speaks() {
+    let mut policy = policy(BargeInMode::Keyword);
+
+    assert_eq!(policy.on_speech_started(true, Instant::now()), SpeechAction::Ignore);
+    assert!(policy.suppresses_turn_end());
+    assert_eq!(policy.on_transcript("that's interesting"), TranscriptAction::Drop);
+
+    assert_eq!(policy.on_speech_started(true, Instant::now()), SpeechAction::Ignore);
+    assert_eq!(policy.on_transcript("Wait, go back"), TranscriptAction::Interrupt);
+
+    assert_eq!(policy.on_speech_started(true, Instant::now()), SpeechAction::Ignore);
+    assert_eq!(policy.on_transcript("hold   on a second"), TranscriptAction::Interrupt);
+
+    // AI silent - any speech is a normal turn
+    assert_eq!(policy.on_speech_started(false, Instant::now()), SpeechAction::Interrupt);
+    assert_eq!(policy.on_transcript("what is a monad"), TranscriptAction::Interrupt);
+}
+
+#[test]
+fn test_pause_resume_backchannel_resumes() {
+    let mut policy = policy(BargeInMode::PauseResume);
+
+    assert_eq!(policy.on_speech_started(true, Instant::now()), SpeechAction::Pause);
+    assert!(policy.is_holding());
+    assert_eq!(policy.on_transcript("Mm-hm."), TranscriptAction::Resume);
+    assert!(!policy.is_holding());
+    // question_ended for the dismissed utterance must not clear AI audio
+    assert!(policy.suppresses_turn_end());
+
+    assert_eq!(policy.on_speech_started(true, Instant::now()), SpeechAction::Pause);
+    assert_eq!(policy.on_transcript("嗯嗯"), TranscriptAction::Resume);
+}
+
+#[test]
+fn test_pause_resume_meaningful_interrupts() {
+    let mut policy = policy(BargeInMode::PauseResume);
+
+    policy.on_speech_started(true, Instant::now());
+    assert_eq!(policy.on_transcript("can you explain that again"), TranscriptAction::Interrupt);
+    assert!(!policy.suppresses_turn_end());
+
+    policy.on_speech_started(true, Instant::now());
+    assert_eq!(policy.on_transcript("为什么"), TranscriptAction::Interrupt);
+
+    // Single keyword is enough even below min_words
+    policy.on_speech_started(true, Instant::now());
+    assert_eq!(policy.on_transcript("stop"), TranscriptAction::Interrupt);
+}
+
+#[test]
+fn test_pause_resume_timeout() {
+    let mut policy = policy(BargeInMode::PauseResume);
+    let start = Instant::now();
+
+    assert_eq!(policy.on_speech_started(true, start), SpeechAction::Pause);
+    assert!(!policy.check_timeout(start + Duration::from_millis(1000)));
+
+    // Speech again while holding keeps the original deadline
+    assert_eq!(policy.on_speech_started(true, start + Duration::from_millis(2000)), SpeechAction::Pause);
+    assert!(policy.check_timeout(start + Duration::from_millis(3000)));
+    assert!(!policy.is_holding());
+    assert!(!policy.check_timeout(start + Duration::from_millis(4000)));
+}
+
+#[test]
+fn test_keyword_matches_whole_words() {
+    let mut policy = policy(BargeInMode::Keyword);
+
+    for text in ["it stopped raining", "a nonstop flight", "the waiter came", "the awaited result", "hold onto it"] {
+        assert_eq!(policy.on_speech_started(true, Instant::now()), SpeechAction::Ignore);
+        assert_eq!(policy.on_transcript(text), TranscriptAction::Drop, "{text}");
+    }
+
+    for text in ["please STOP!", "wait...", "等一下,我有问题"] {
+        assert_eq!(policy.on_speech_started(true, Instant::now()), SpeechAction::Ignore);
+        assert_eq!(policy.on_transcript(text), TranscriptAction::Interrupt, "{text}");
+    }
+}
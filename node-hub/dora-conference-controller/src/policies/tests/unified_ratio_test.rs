@@ -279,3 +279,23 @@ fn test_stats_generation() {
     assert_eq!(stats["word_counts"]["defense"], 200);
     assert_eq!(stats["word_counts"]["prosecution"], 150);
 }
+
+#[test]
+fn test_next_speaker_hint() {
+    let mut policy = UnifiedRatioPolicy::new();
+    policy.configure("[student1 → student2 → tutor]").unwrap();
+
+    policy.update_word_count("student1", 20);
+    policy.set_next_speaker_hint(Some("tutor".to_string()));
+    assert_eq!(policy.get_stats()["next_speaker_hint"], "tutor");
+    assert_eq!(policy.determine_next_speaker(), Some("tutor".to_string()));
+
+    // The hint is used once; the sequence continues after the hinted speaker
+    assert_eq!(policy.determine_next_speaker(), Some("student1".to_string()));
+
+    // Unknown names and the speaker who just spoke are ignored
+    policy.set_next_speaker_hint(Some("moderator".to_string()));
+    assert_eq!(policy.determine_next_speaker(), Some("student2".to_string()));
+    policy.set_next_speaker_hint(Some("student2".to_string()));
+    assert_eq!(policy.determine_next_speaker(), Some("tutor".to_string()));
+}
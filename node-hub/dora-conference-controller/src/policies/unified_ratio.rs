@@ -167,6 +167,7 @@ pub struct UnifiedRatioPolicy {
     sequential_cycle: usize,
     ratio_priority_cycle: usize,  // Track cycles for ratio/priority mode
     round_speakers: Vec<String>,  // Track speakers in current round
+    next_speaker_hint: Option<String>,  // Suggested by the last speaker, used once
 }
 
 impl UnifiedRatioPolicy {
@@ -182,6 +183,7 @@ impl UnifiedRatioPolicy {
             sequential_cycle: 0,
             ratio_priority_cycle: 0,
             round_speakers: Vec::new(),
+            next_speaker_hint: None,
         }
     }
 
@@ -194,6 +196,7 @@ impl UnifiedRatioPolicy {
         self.last_speaker = None;
         self.sequential_cycle = 0;
         self.ratio_priority_cycle = 0;
+        self.next_speaker_hint = None;
         match &pattern {
             PolicyPattern::RatioPriority { participants, .. } | PolicyPattern::Sequential { participants, .. } => {
                 for participant in participants {
@@ -219,6 +222,7 @@ impl UnifiedRatioPolicy {
         self.last_speaker = None;
         self.sequential_cycle = 0;
         self.ratio_priority_cycle = 0;
+        self.next_speaker_hint = None;
     }
 
     /// Set last speaker (used after human input to avoid cold start)
@@ -228,6 +232,13 @@ impl UnifiedRatioPolicy {
         self.last_speaker = speaker;
     }
 
+    /// Suggest who speaks next (e.g. from a participant's structured reply).
+    /// The next `determine_next_speaker` call follows the hint if it names a
+    /// participant other than the last speaker, then the pattern takes over again.
+    pub fn set_next_speaker_hint(&mut self, speaker: Option<String>) {
+        self.next_speaker_hint = speaker;
+    }
+
     /// Get statistics
     pub fn get_stats(&self) -> serde_json::Value {
         let mut stats = serde_json::Map::new();
@@ -260,6 +271,7 @@ impl UnifiedRatioPolicy {
         }
         stats.insert("cycle".to_string(), serde_json::Value::Number(self.get_current_cycle().into()));
         if let Some(last) = &self.last_speaker { stats.insert("current_speaker".to_string(), serde_json::Value::String(last.clone())); }
+        if let Some(hint) = &self.next_speaker_hint { stats.insert("next_speaker_hint".to_string(), serde_json::Value::String(hint.clone())); }
         serde_json::Value::Object(stats)
     }
 }
@@ -277,6 +289,19 @@ impl Policy for UnifiedRatioPolicy {
     }
 
     fn determine_next_speaker(&mut self) -> Option<String> {
+        if let Some(hint) = self.next_speaker_hint.take() {
+            let participants = self.get_participants();
+            if let Some(index) = participants.iter().position(|p| *p == hint) {
+                if self.last_speaker.as_deref() != Some(hint.as_str()) {
+                    // Sequential patterns continue after the hinted speaker
+                    if let PolicyPattern::Sequential { .. } = self.pattern {
+                        self.position = (index + 1) % participants.len();
+                    }
+                    self.last_speaker = Some(hint.clone());
+                    return Some(hint);
+                }
+            }
+        }
         match &self.pattern {
             PolicyPattern::RatioPriority { participants, weights } => {
                 let p = participants.clone();
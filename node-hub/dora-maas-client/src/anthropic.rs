@@ -0,0 +1,581 @@
+//! Translation between OpenAI chat completion JSON and Anthropic's Messages API.
+//!
+//! `AnthropicClient` serializes the OpenAI-shaped request, converts it with
+//! [`to_anthropic_request`], and converts replies back with
+//! [`from_anthropic_response`]. Streaming events are mapped by
+//! [`AnthropicStreamTranslator`] into the same [`StreamChunk`] / [`Delta`]
+//! values the OpenAI SSE parser produces, so tool call accumulation and
+//! segmentation are shared.
+//!
+//! Mapping:
+//! - `system` / `developer` messages -> top-level `system`
+//! - `image_url` parts -> `image` blocks (base64 for `data:` URLs)
+//! - assistant `tool_calls` -> `tool_use` content blocks
+//! - `tool` messages -> `tool_result` blocks in a `user` message
+//! - `tools` -> `{name, description, input_schema}`, `tool_choice` -> `{type}`
+
+use std::collections::HashSet;
+
+use eyre::{Result, eyre};
+use serde_json::{Map, Value, json};
+
+use crate::openai_compat::{ReplyAccumulator, content_text, now_secs};
+use crate::streaming::{Delta, DeltaFunctionCall, DeltaToolCall, StreamChoice, StreamChunk};
+use crate::usage::TokenUsage;
+use crate::vision;
+
+/// Messages API version sent in the `anthropic-version` header
+pub const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Convert an OpenAI chat completion request (as JSON) into a Messages API request.
+///
+/// `default_max_tokens` is used when the request sets no limit (Anthropic requires one).
+pub fn to_anthropic_request(request: &Value, default_max_tokens: u32) -> Result<Value> {
+    let model = request
+        .get("model")
+        .and_then(Value::as_str)
+        .ok_or_else(|| eyre!("Request has no model"))?;
+    let messages = request
+        .get("messages")
+        .and_then(Value::as_array)
+        .ok_or_else(|| eyre!("Request has no messages"))?;
+
+    let mut system: Vec<String> = Vec::new();
+    let mut converted: Vec<Value> = Vec::new();
+
+    for message in messages {
+        let role = message.get("role").and_then(Value::as_str).unwrap_or("user");
+        match role {
+            "system" | "developer" => {
+                let text = content_text(message.get("content"));
+                if !text.is_empty() {
+                    system.push(text);
+                }
+            }
+            "assistant" => {
+                let mut blocks = Vec::new();
+                let text = content_text(message.get("content"));
+                if !text.is_empty() {
+                    blocks.push(json!({ "type": "text", "text": text }));
+                }
+                if let Some(calls) = message.get("tool_calls").and_then(Value::as_array) {
+                    for call in calls {
+                        let input = call
+                            .pointer("/function/arguments")
+                            .and_then(Value::as_str)
+                            .and_then(|a| serde_json::from_str::<Value>(a).ok())
+                            .filter(Value::is_object)
+                            .unwrap_or_else(|| json!({}));
+                        blocks.push(json!({
+                            "type": "tool_use",
+                            "id": call.get("id").cloned().unwrap_or(Value::Null),
+                            "name": call.pointer("/function/name").cloned().unwrap_or(Value::Null),
+                            "input": input,
+                        }));
+                    }
+                }
+                push_message(&mut converted, "assistant", blocks);
+            }
+            "tool" | "function" => {
+                let block = json!({
+                    "type": "tool_result",
+                    "tool_use_id": message.get("tool_call_id").cloned().unwrap_or(Value::Null),
+                    "content": content_text(message.get("content")),
+                });
+                push_message(&mut converted, "user", vec![block]);
+            }
+            _ => {
+                let blocks = user_blocks(message.get("content"));
+                if !blocks.is_empty() {
+                    push_message(&mut converted, "user", blocks);
+                }
+            }
+        }
+    }
+
+    let max_tokens = request
+        .get("max_completion_tokens")
+        .or_else(|| request.get("max_tokens"))
+        .and_then(Value::as_u64)
+        .unwrap_or(default_max_tokens as u64);
+
+    let mut body = Map::new();
+    body.insert("model".into(), json!(model));
+    body.insert("max_tokens".into(), json!(max_tokens));
+    body.insert("messages".into(), Value::Array(converted));
+    if !system.is_empty() {
+        body.insert("system".into(), json!(system.join("\n\n")));
+    }
+    for key in ["temperature", "top_p"] {
+        if let Some(value) = request.get(key).filter(|v| !v.is_null()) {
+            body.insert(key.into(), value.clone());
+        }
+    }
+    match request.get("stop") {
+        Some(Value::String(stop)) => {
+            body.insert("stop_sequences".into(), json!([stop]));
+        }
+        Some(Value::Array(stops)) if !stops.is_empty() => {
+            body.insert("stop_sequences".into(), Value::Array(stops.clone()));
+        }
+        _ => {}
+    }
+
+    if let Some(tools) = request.get("tools").and_then(Value::as_array) {
+        let tools: Vec<Value> = tools
+            .iter()
+            .filter_map(|tool| tool.get("function"))
+            .map(|function| {
+                json!({
+                    "name": function.get("name").cloned().unwrap_or(Value::Null),
+                    "description": function.get("description").cloned().unwrap_or(json!("")),
+                    "input_schema": function
+                        .get("parameters")
+                        .cloned()
+                        .unwrap_or_else(|| json!({ "type": "object", "properties": {} })),
+                })
+            })
+            .collect();
+        if !tools.is_empty() {
+            body.insert("tools".into(), Value::Array(tools));
+        }
+    }
+
+    if let Some(choice) = request.get("tool_choice") {
+        let choice = match choice {
+            Value::String(mode) => match mode.as_str() {
+                "none" => Some(json!({ "type": "none" })),
+                "required" => Some(json!({ "type": "any" })),
+                "auto" => Some(json!({ "type": "auto" })),
+                _ => None,
+            },
+            Value::Object(_) => choice
+                .pointer("/function/name")
+                .map(|name| json!({ "type": "tool", "name": name })),
+            _ => None,
+        };
+        if let Some(choice) = choice {
+            body.insert("tool_choice".into(), choice);
+        }
+    }
+
+    if request.get("stream").and_then(Value::as_bool).unwrap_or(false) {
+        body.insert("stream".into(), json!(true));
+    }
+
+    Ok(Value::Object(body))
+}
+
+/// Convert a Messages API response into an OpenAI chat completion response.
+pub fn from_anthropic_response(response: &Value) -> Result<Value> {
+    if response.get("type").and_then(Value::as_str) == Some("error") {
+        return Err(api_error(response));
+    }
+
+    let id = response.get("id").and_then(Value::as_str).unwrap_or_default();
+    let mut reply = ReplyAccumulator::new(id);
+    if let Some(blocks) = response.get("content").and_then(Value::as_array) {
+        for block in blocks {
+            match block.get("type").and_then(Value::as_str) {
+                Some("text") => {
+                    reply.push_text(block.get("text").and_then(Value::as_str).unwrap_or_default());
+                }
+                Some("tool_use") => {
+                    reply.push_tool_call(
+                        block.get("id").and_then(Value::as_str),
+                        block.get("name").and_then(Value::as_str).unwrap_or_default(),
+                        block.get("input").cloned().unwrap_or_else(|| json!({})).to_string(),
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+    if let Some(usage) = response.get("usage") {
+        let count = |key: &str| usage.get(key).and_then(Value::as_u64).unwrap_or(0);
+        reply.set_usage(
+            count("input_tokens"),
+            count("output_tokens"),
+            count("input_tokens") + count("output_tokens"),
+        );
+    }
+
+    let finish_reason = finish_reason(response.get("stop_reason").and_then(Value::as_str));
+    let model = response.get("model").and_then(Value::as_str).unwrap_or_default();
+    Ok(reply.into_response(model, finish_reason))
+}
+
+/// Maps Messages API stream events onto OpenAI-style [`StreamChunk`]s.
+///
+/// Text deltas become `delta.content`; a `tool_use` block start becomes a
+/// tool call delta with id and name, and its `input_json_delta`s become
+/// argument fragments, keyed by content block index.
+#[derive(Default)]
+pub struct AnthropicStreamTranslator {
+    id: String,
+    model: String,
+    /// Tool blocks that have received at least one argument fragment
+    tool_blocks_with_input: HashSet<i32>,
+    /// Open tool blocks (by content block index)
+    tool_blocks: HashSet<i32>,
+    /// Input tokens from `message_start`, output tokens from `message_delta`
+    usage: Option<TokenUsage>,
+    done: bool,
+}
+
+impl AnthropicStreamTranslator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `message_stop` was received
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Token usage reported so far
+    pub fn usage(&self) -> Option<TokenUsage> {
+        self.usage
+    }
+
+    /// Translate one SSE event (`event:` name and `data:` payload).
+    ///
+    /// Returns `None` for events with no content (ping, block stop, ...).
+    pub fn translate(&mut self, event: &str, data: &str) -> Result<Option<StreamChunk>> {
+        let payload: Value = match serde_json::from_str(data) {
+            Ok(payload) => payload,
+            // Silently skip unparseable events
+            Err(_) => return Ok(None),
+        };
+        // The payload repeats the event name in `type`; prefer it when present
+        let kind = payload.get("type").and_then(Value::as_str).unwrap_or(event);
+        let index = payload.get("index").and_then(Value::as_i64).unwrap_or(0) as i32;
+
+        let delta = match kind {
+            "message_start" => {
+                let message = payload.get("message").cloned().unwrap_or(Value::Null);
+                self.id = message.get("id").and_then(Value::as_str).unwrap_or_default().to_string();
+                self.model = message.get("model").and_then(Value::as_str).unwrap_or_default().to_string();
+                if let Some(input) = message.pointer("/usage/input_tokens").and_then(Value::as_u64) {
+                    self.usage = Some(TokenUsage::new(input, 0));
+                }
+                return Ok(None);
+            }
+            "content_block_start" => {
+                let block = payload.get("content_block").cloned().unwrap_or(Value::Null);
+                if block.get("type").and_then(Value::as_str) != Some("tool_use") {
+                    return Ok(None);
+                }
+                self.tool_blocks.insert(index);
+                // Input is normally streamed; use it if it arrives inline
+                let arguments = block
+                    .get("input")
+                    .filter(|input| input.as_object().is_some_and(|obj| !obj.is_empty()))
+                    .map(|input| {
+                        self.tool_blocks_with_input.insert(index);
+                        input.to_string()
+                    });
+                tool_delta(
+                    index,
+                    block.get("id").and_then(Value::as_str).map(String::from),
+                    block.get("name").and_then(Value::as_str).map(String::from),
+                    arguments,
+                )
+            }
+            "content_block_delta" => {
+                let delta = payload.get("delta").cloned().unwrap_or(Value::Null);
+                match delta.get("type").and_then(Value::as_str) {
+                    Some("text_delta") => Delta {
+                        role: None,
+                        content: delta.get("text").and_then(Value::as_str).map(String::from),
+                        tool_calls: None,
+                    },
+                    Some("input_json_delta") => {
+                        let partial = delta
+                            .get("partial_json")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default();
+                        if partial.is_empty() {
+                            return Ok(None);
+                        }
+                        self.tool_blocks_with_input.insert(index);
+                        tool_delta(index, None, None, Some(partial.to_string()))
+                    }
+                    _ => return Ok(None),
+                }
+            }
+            "content_block_stop" => {
+                // A tool call without arguments still needs valid JSON
+                let missing_input = self.tool_blocks.remove(&index)
+                    && !self.tool_blocks_with_input.contains(&index);
+                if !missing_input {
+                    return Ok(None);
+                }
+                tool_delta(index, None, None, Some("{}".to_string()))
+            }
+            "message_delta" => {
+                let reason = payload.pointer("/delta/stop_reason").and_then(Value::as_str);
+                if let Some(output) = payload.pointer("/usage/output_tokens").and_then(Value::as_u64) {
+                    let input = self.usage.map(|u| u.prompt_tokens).unwrap_or(0);
+                    self.usage = Some(TokenUsage::new(input, output));
+                }
+                return Ok(Some(self.chunk(
+                    Delta { role: None, content: None, tool_calls: None },
+                    reason.map(|r| finish_reason(Some(r)).to_string()),
+                )));
+            }
+            "message_stop" => {
+                self.done = true;
+                return Ok(None);
+            }
+            "error" => return Err(api_error(&payload)),
+            _ => return Ok(None),
+        };
+
+        Ok(Some(self.chunk(delta, None)))
+    }
+
+    fn chunk(&self, delta: Delta, finish_reason: Option<String>) -> StreamChunk {
+        StreamChunk {
+            id: self.id.clone(),
+            object: "chat.completion.chunk".to_string(),
+            created: now_secs() as i64,
+            model: self.model.clone(),
+            choices: vec![StreamChoice {
+                index: 0,
+                delta,
+                finish_reason,
+            }],
+            usage: None,
+        }
+    }
+}
+
+fn tool_delta(
+    index: i32,
+    id: Option<String>,
+    name: Option<String>,
+    arguments: Option<String>,
+) -> Delta {
+    let r#type = id.as_ref().map(|_| "function".to_string());
+    Delta {
+        role: None,
+        content: None,
+        tool_calls: Some(vec![DeltaToolCall {
+            index,
+            id,
+            r#type,
+            function: Some(DeltaFunctionCall { name, arguments }),
+        }]),
+    }
+}
+
+fn finish_reason(stop_reason: Option<&str>) -> &'static str {
+    match stop_reason {
+        Some("max_tokens") => "length",
+        Some("tool_use") => "tool_calls",
+        Some("refusal") => "content_filter",
+        _ => "stop",
+    }
+}
+
+fn api_error(payload: &Value) -> eyre::Report {
+    let message = payload
+        .pointer("/error/message")
+        .and_then(Value::as_str)
+        .unwrap_or("unknown error");
+    let kind = payload
+        .pointer("/error/type")
+        .and_then(Value::as_str)
+        .unwrap_or("error");
+    eyre!("Anthropic API error ({}): {}", kind, message)
+}
+
+/// Append content blocks as a message, merging with the previous message of the
+/// same role (the Messages API requires alternating user/assistant turns)
+fn push_message(messages: &mut Vec<Value>, role: &str, blocks: Vec<Value>) {
+    if blocks.is_empty() {
+        return;
+    }
+    if let Some(last) = messages.last_mut() {
+        if last.get("role").and_then(Value::as_str) == Some(role) {
+            if let Some(existing) = last.get_mut("content").and_then(Value::as_array_mut) {
+                existing.extend(blocks);
+                return;
+            }
+        }
+    }
+    messages.push(json!({ "role": role, "content": blocks }));
+}
+
+/// Content blocks of a user message: text, and images as base64 or URL sources
+fn user_blocks(content: Option<&Value>) -> Vec<Value> {
+    let Some(Value::Array(parts)) = content else {
+        let text = content_text(content);
+        return if text.is_empty() {
+            Vec::new()
+        } else {
+            vec![json!({ "type": "text", "text": text })]
+        };
+    };
+    parts
+        .iter()
+        .filter_map(|part| match vision::image_url(part) {
+            Some(url) => {
+                let source = match vision::parse_data_url(url) {
+                    Some((media_type, data)) => {
+                        json!({ "type": "base64", "media_type": media_type, "data": data })
+                    }
+                    None => json!({ "type": "url", "url": url }),
+                };
+                Some(json!({ "type": "image", "source": source }))
+            }
+            None => part
+                .get("text")
+                .and_then(Value::as_str)
+                .filter(|text| !text.is_empty())
+                .map(|text| json!({ "type": "text", "text": text })),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_translation() {
+        let request = json!({
+            "model": "claude-sonnet-4-5",
+            "messages": [
+                { "role": "system", "content": "Be brief." },
+                { "role": "user", "content": "Weather in Paris?" },
+                { "role": "assistant", "content": "Checking.", "tool_calls": [{
+                    "id": "toolu_1", "type": "function",
+                    "function": { "name": "get_weather", "arguments": "{\"city\":\"Paris\"}" }
+                }]},
+                { "role": "tool", "tool_call_id": "toolu_1", "content": "sunny" },
+                { "role": "user", "content": "Thanks" },
+            ],
+            "tools": [{ "type": "function", "function": {
+                "name": "get_weather", "description": "Get weather",
+                "parameters": { "type": "object", "properties": { "city": { "type": "string" } } }
+            }}],
+            "tool_choice": "auto",
+            "stream": true,
+        });
+
+        let body = to_anthropic_request(&request, 1024).unwrap();
+        assert_eq!(body["system"], "Be brief.");
+        assert_eq!(body["max_tokens"], 1024);
+        assert_eq!(body["stream"], true);
+
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1]["content"][1]["type"], "tool_use");
+        assert_eq!(messages[1]["content"][1]["input"]["city"], "Paris");
+        // Tool result and the next user text share one user turn
+        assert_eq!(messages[2]["role"], "user");
+        assert_eq!(messages[2]["content"][0]["type"], "tool_result");
+        assert_eq!(messages[2]["content"][0]["tool_use_id"], "toolu_1");
+        assert_eq!(messages[2]["content"][1]["text"], "Thanks");
+
+        assert_eq!(body["tools"][0]["input_schema"]["properties"]["city"]["type"], "string");
+        assert_eq!(body["tool_choice"]["type"], "auto");
+    }
+
+    #[test]
+    fn test_request_image_blocks() {
+        let request = json!({
+            "model": "claude-sonnet-4-5",
+            "messages": [{ "role": "user", "content": [
+                { "type": "text", "text": "What is on this slide?" },
+                { "type": "image_url", "image_url": { "url": "data:image/jpeg;base64,/9j/4AAQ" } },
+                { "type": "image_url", "image_url": { "url": "https://example.com/slide.png" } },
+            ]}],
+        });
+
+        let body = to_anthropic_request(&request, 1024).unwrap();
+        let blocks = &body["messages"][0]["content"];
+        assert_eq!(blocks[0]["text"], "What is on this slide?");
+        assert_eq!(blocks[1]["type"], "image");
+        assert_eq!(blocks[1]["source"]["type"], "base64");
+        assert_eq!(blocks[1]["source"]["media_type"], "image/jpeg");
+        assert_eq!(blocks[1]["source"]["data"], "/9j/4AAQ");
+        assert_eq!(blocks[2]["source"]["url"], "https://example.com/slide.png");
+    }
+
+    #[test]
+    fn test_response_translation() {
+        let response = json!({
+            "id": "msg_1", "type": "message", "role": "assistant", "model": "claude-sonnet-4-5",
+            "content": [
+                { "type": "text", "text": "Let me check." },
+                { "type": "tool_use", "id": "toolu_1", "name": "get_weather", "input": { "city": "Paris" } },
+            ],
+            "stop_reason": "tool_use",
+            "usage": { "input_tokens": 12, "output_tokens": 8 },
+        });
+
+        let openai = from_anthropic_response(&response).unwrap();
+        let choice = &openai["choices"][0];
+        assert_eq!(choice["message"]["content"], "Let me check.");
+        assert_eq!(choice["finish_reason"], "tool_calls");
+        assert_eq!(choice["message"]["tool_calls"][0]["id"], "toolu_1");
+        assert_eq!(choice["message"]["tool_calls"][0]["function"]["arguments"], "{\"city\":\"Paris\"}");
+        assert_eq!(openai["usage"]["total_tokens"], 20);
+
+        let error = json!({ "type": "error", "error": { "type": "overloaded_error", "message": "Overloaded" } });
+        assert!(from_anthropic_response(&error).is_err());
+    }
+
+    #[test]
+    fn test_stream_translation() {
+        let mut translator = AnthropicStreamTranslator::new();
+        let events = [
+            ("message_start", r#"{"type":"message_start","message":{"id":"msg_1","model":"claude","usage":{"input_tokens":12,"output_tokens":1}}}"#),
+            ("content_block_start", r#"{"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#),
+            ("ping", r#"{"type":"ping"}"#),
+            ("content_block_delta", r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hi"}}"#),
+            ("content_block_stop", r#"{"type":"content_block_stop","index":0}"#),
+            ("content_block_start", r#"{"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"toolu_1","name":"get_weather","input":{}}}"#),
+            ("content_block_delta", r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"city\":"}}"#),
+            ("content_block_delta", r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"\"Paris\"}"}}"#),
+            ("content_block_stop", r#"{"type":"content_block_stop","index":1}"#),
+            ("content_block_start", r#"{"type":"content_block_start","index":2,"content_block":{"type":"tool_use","id":"toolu_2","name":"get_time","input":{}}}"#),
+            ("content_block_stop", r#"{"type":"content_block_stop","index":2}"#),
+            ("message_delta", r#"{"type":"message_delta","delta":{"stop_reason":"tool_use"},"usage":{"output_tokens":8}}"#),
+            ("message_stop", r#"{"type":"message_stop"}"#),
+        ];
+
+        let chunks: Vec<StreamChunk> = events
+            .iter()
+            .filter_map(|(event, data)| translator.translate(event, data).unwrap())
+            .collect();
+        assert!(translator.is_done());
+        assert_eq!(translator.usage(), Some(TokenUsage::new(12, 8)));
+
+        assert_eq!(chunks[0].choices[0].delta.content.as_deref(), Some("Hi"));
+        assert_eq!(chunks[0].id, "msg_1");
+
+        let tool_start = &chunks[1].choices[0].delta.tool_calls.as_ref().unwrap()[0];
+        assert_eq!(tool_start.index, 1);
+        assert_eq!(tool_start.id.as_deref(), Some("toolu_1"));
+
+        let arguments: String = chunks[2..4]
+            .iter()
+            .map(|c| c.choices[0].delta.tool_calls.as_ref().unwrap()[0].function.as_ref().unwrap().arguments.clone().unwrap())
+            .collect();
+        assert_eq!(arguments, "{\"city\":\"Paris\"}");
+
+        // Tool without streamed input gets "{}" on block stop
+        let empty = &chunks[5].choices[0].delta.tool_calls.as_ref().unwrap()[0];
+        assert_eq!(empty.index, 2);
+        assert_eq!(empty.function.as_ref().unwrap().arguments.as_deref(), Some("{}"));
+
+        assert_eq!(chunks.last().unwrap().choices[0].finish_reason.as_deref(), Some("tool_calls"));
+
+        let error = translator.translate("error", r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#);
+        assert!(error.is_err());
+    }
+}
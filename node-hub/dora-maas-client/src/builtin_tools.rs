@@ -0,0 +1,548 @@
+//! Built-in tools that run inside the node, without an MCP server.
+//!
+//! Registered from `[[builtin_tools]]` entries in the config and added to the
+//! same [`ToolSet`] as MCP tools, so the model calls them the same way.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::{FixedOffset, Local, Utc};
+use eyre::{Result, eyre};
+use figment::{
+    Figment,
+    providers::{Format, Json, Toml, Yaml},
+};
+use rmcp::model::{CallToolResult, Content};
+use serde_json::{Value, json};
+
+use crate::config::{BuiltinToolConfig, load_anchor_context};
+use crate::tool::{Tool, ToolSet};
+
+/// Add the tool described by `config` to `tool_set`.
+///
+/// `anchor_context` is the config's anchor context path, used by the
+/// `anchor_context` tool when it has no `path` of its own.
+pub fn register(
+    config: &BuiltinToolConfig,
+    anchor_context: Option<&str>,
+    tool_set: &mut ToolSet,
+) -> Result<()> {
+    match config {
+        BuiltinToolConfig::CurrentTime { utc_offset } => {
+            let offset = utc_offset
+                .as_deref()
+                .map(|offset| {
+                    offset
+                        .parse::<FixedOffset>()
+                        .map_err(|_| eyre!("Invalid utc_offset '{}', expected e.g. \"+08:00\"", offset))
+                })
+                .transpose()?;
+            tool_set.add_tool(CurrentTimeTool { offset });
+        }
+        BuiltinToolConfig::Calculator => tool_set.add_tool(CalculatorTool),
+        BuiltinToolConfig::AnchorContext { path, max_chars } => {
+            let path = path
+                .as_deref()
+                .or(anchor_context)
+                .ok_or_else(|| eyre!("anchor_context tool needs a path or a top-level anchor_context"))?;
+            tool_set.add_tool(AnchorContextTool {
+                path: path.to_string(),
+                max_chars: *max_chars,
+            });
+        }
+        BuiltinToolConfig::Dictionary { path } => {
+            tool_set.add_tool(DictionaryTool {
+                entries: load_dictionary(Path::new(path))?,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn text_result(text: impl Into<String>) -> CallToolResult {
+    CallToolResult::success(vec![Content::text(text.into())])
+}
+
+fn string_arg<'a>(args: &'a Value, key: &str) -> Option<&'a str> {
+    args.get(key).and_then(Value::as_str).map(str::trim).filter(|s| !s.is_empty())
+}
+
+/// Current date and time
+struct CurrentTimeTool {
+    /// Fixed timezone; the machine's local time when unset
+    offset: Option<FixedOffset>,
+}
+
+#[async_trait::async_trait]
+impl Tool for CurrentTimeTool {
+    fn name(&self) -> String {
+        "current_time".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Get the current date, time and weekday.".to_string()
+    }
+
+    fn parameters(&self) -> Value {
+        json!({ "type": "object", "properties": {} })
+    }
+
+    async fn call(&self, _args: Value) -> Result<CallToolResult> {
+        const FORMAT: &str = "%Y-%m-%d %H:%M:%S %:z (%A)";
+        let now = match self.offset {
+            Some(offset) => Utc::now().with_timezone(&offset).format(FORMAT).to_string(),
+            None => Local::now().format(FORMAT).to_string(),
+        };
+        Ok(text_result(now))
+    }
+}
+
+/// Arithmetic on an expression string
+struct CalculatorTool;
+
+#[async_trait::async_trait]
+impl Tool for CalculatorTool {
+    fn name(&self) -> String {
+        "calculator".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Evaluate an arithmetic expression. Supports + - * / % ^, parentheses, \
+         sqrt, abs, ln, log, log2, exp, sin, cos, tan, asin, acos, atan, floor, \
+         ceil, round, min, max, pow and the constants pi and e."
+            .to_string()
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "expression": { "type": "string", "description": "Expression such as \"(3 + 4) * 2 ^ 3\"" }
+            },
+            "required": ["expression"]
+        })
+    }
+
+    async fn call(&self, args: Value) -> Result<CallToolResult> {
+        let expression =
+            string_arg(&args, "expression").ok_or_else(|| eyre!("Missing 'expression'"))?;
+        let value = evaluate(expression)?;
+        Ok(text_result(format_number(value)))
+    }
+}
+
+/// Sections of the anchor context markdown file
+struct AnchorContextTool {
+    path: String,
+    max_chars: usize,
+}
+
+#[async_trait::async_trait]
+impl Tool for AnchorContextTool {
+    fn name(&self) -> String {
+        "anchor_context".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Read a section of the reference material. Call without `section` to list \
+         the section headings, then pass a heading or anchor (e.g. \"A3\") to read it."
+            .to_string()
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "section": { "type": "string", "description": "Heading text or anchor such as \"A3\"" }
+            }
+        })
+    }
+
+    async fn call(&self, args: Value) -> Result<CallToolResult> {
+        let document = load_anchor_context(&self.path)?;
+        let text = match string_arg(&args, "section") {
+            Some(query) => find_section(&document, query)
+                .ok_or_else(|| eyre!("No section matching '{}'", query))?,
+            None => list_sections(&document).join("\n"),
+        };
+        Ok(text_result(truncate(&text, self.max_chars)))
+    }
+}
+
+/// Term lookup in a local glossary file
+struct DictionaryTool {
+    entries: HashMap<String, String>,
+}
+
+#[async_trait::async_trait]
+impl Tool for DictionaryTool {
+    fn name(&self) -> String {
+        "dictionary".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Look up the definition of a term in the local glossary.".to_string()
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "term": { "type": "string", "description": "Term to look up" }
+            },
+            "required": ["term"]
+        })
+    }
+
+    async fn call(&self, args: Value) -> Result<CallToolResult> {
+        let term = string_arg(&args, "term").ok_or_else(|| eyre!("Missing 'term'"))?;
+        Ok(text_result(lookup(&self.entries, term)))
+    }
+}
+
+/// Glossary of `term = "definition"` pairs (TOML, YAML or JSON by extension)
+fn load_dictionary(path: &Path) -> Result<HashMap<String, String>> {
+    if !path.exists() {
+        return Err(eyre!("Dictionary file not found: {}", path.display()));
+    }
+    let figment = match path.extension().and_then(|s| s.to_str()) {
+        Some("yaml") | Some("yml") => Figment::new().merge(Yaml::file(path)),
+        Some("json") => Figment::new().merge(Json::file(path)),
+        _ => Figment::new().merge(Toml::file(path)),
+    };
+    Ok(figment.extract()?)
+}
+
+/// Exact (case-insensitive) match first, then up to five partial matches
+fn lookup(entries: &HashMap<String, String>, term: &str) -> String {
+    let needle = term.to_lowercase();
+    if let Some((key, definition)) = entries.iter().find(|(key, _)| key.to_lowercase() == needle) {
+        return format!("{}: {}", key, definition);
+    }
+
+    let mut partial: Vec<_> = entries
+        .iter()
+        .filter(|(key, _)| {
+            let key = key.to_lowercase();
+            key.contains(&needle) || needle.contains(&key)
+        })
+        .collect();
+    if partial.is_empty() {
+        return format!("No entry for '{}'", term);
+    }
+    partial.sort();
+    partial
+        .into_iter()
+        .take(5)
+        .map(|(key, definition)| format!("{}: {}", key, definition))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Heading level of a line: anchor lines (`[A3] ...`) are top level,
+/// markdown headings count their `#`s.
+fn heading_level(line: &str) -> Option<usize> {
+    let line = line.trim_start();
+    if line.starts_with("[A") && line.contains(']') {
+        return Some(0);
+    }
+    let hashes = line.chars().take_while(|&c| c == '#').count();
+    (hashes > 0 && line[hashes..].starts_with(' ')).then_some(hashes)
+}
+
+/// Label of a heading without `#`s or brackets, e.g. "A3" or "A1.1"
+fn heading_label(line: &str) -> &str {
+    let line = line.trim_start().trim_start_matches('#').trim_start();
+    let label = line.split_whitespace().next().unwrap_or_default();
+    label.trim_start_matches('[').trim_end_matches(']')
+}
+
+fn list_sections(document: &str) -> Vec<String> {
+    document
+        .lines()
+        .filter_map(|line| {
+            let level = heading_level(line)?;
+            let indent = "  ".repeat(level.saturating_sub(1));
+            Some(format!("{}{}", indent, line.trim().trim_start_matches('#').trim()))
+        })
+        .collect()
+}
+
+/// Text of the section whose label equals `query` (or, failing that, whose
+/// heading contains it), up to the next heading of the same or higher level
+fn find_section(document: &str, query: &str) -> Option<String> {
+    let lines: Vec<&str> = document.lines().collect();
+    let query = query.trim().trim_start_matches('[').trim_end_matches(']').to_lowercase();
+    let headings: Vec<(usize, usize)> = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| heading_level(line).map(|level| (i, level)))
+        .collect();
+
+    let (start, level) = headings
+        .iter()
+        .find(|(i, _)| heading_label(lines[*i]).to_lowercase() == query)
+        .or_else(|| headings.iter().find(|(i, _)| lines[*i].to_lowercase().contains(&query)))
+        .copied()?;
+
+    let end = headings
+        .iter()
+        .find(|(i, l)| *i > start && *l <= level)
+        .map(|(i, _)| *i)
+        .unwrap_or(lines.len());
+    Some(lines[start..end].join("\n").trim().to_string())
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_chars).collect();
+    truncated.push_str("\n[...truncated]");
+    truncated
+}
+
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        format!("{}", value)
+    }
+}
+
+/// Evaluate an arithmetic expression (recursive descent, `^` binds tighter
+/// than unary minus: `-2^2 = -4`)
+fn evaluate(expression: &str) -> Result<f64> {
+    let mut parser = Parser {
+        chars: expression.chars().filter(|c| !c.is_whitespace()).collect(),
+        pos: 0,
+    };
+    let value = parser.expr()?;
+    if parser.pos < parser.chars.len() {
+        return Err(eyre!("Unexpected '{}' in expression", parser.chars[parser.pos]));
+    }
+    if !value.is_finite() {
+        return Err(eyre!("Result is not a finite number"));
+    }
+    Ok(value)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expr(&mut self) -> Result<f64> {
+        let mut value = self.term()?;
+        loop {
+            if self.eat('+') {
+                value += self.term()?;
+            } else if self.eat('-') {
+                value -= self.term()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<f64> {
+        let mut value = self.unary()?;
+        loop {
+            if self.eat('*') {
+                value *= self.unary()?;
+            } else if self.eat('/') {
+                let divisor = self.unary()?;
+                if divisor == 0.0 {
+                    return Err(eyre!("Division by zero"));
+                }
+                value /= divisor;
+            } else if self.eat('%') {
+                let divisor = self.unary()?;
+                if divisor == 0.0 {
+                    return Err(eyre!("Division by zero"));
+                }
+                value %= divisor;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<f64> {
+        if self.eat('-') {
+            return Ok(-self.unary()?);
+        }
+        if self.eat('+') {
+            return self.unary();
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<f64> {
+        let base = self.primary()?;
+        if self.eat('^') {
+            return Ok(base.powf(self.unary()?));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<f64> {
+        if self.eat('(') {
+            let value = self.expr()?;
+            if !self.eat(')') {
+                return Err(eyre!("Missing ')'"));
+            }
+            return Ok(value);
+        }
+        match self.peek() {
+            Some(c) if c.is_ascii_digit() || c == '.' => self.number(),
+            Some(c) if c.is_ascii_alphabetic() => self.identifier(),
+            Some(c) => Err(eyre!("Unexpected '{}' in expression", c)),
+            None => Err(eyre!("Unexpected end of expression")),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+            self.pos += 1;
+        }
+        // Scientific notation, e.g. 1.5e-3
+        let exponent = self.chars.get(self.pos + 1);
+        if self.peek() == Some('e') && exponent.is_some_and(|c| c.is_ascii_digit() || "+-".contains(*c)) {
+            self.pos += 2;
+            while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1;
+            }
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        text.parse().map_err(|_| eyre!("Invalid number '{}'", text))
+    }
+
+    fn identifier(&mut self) -> Result<f64> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_alphanumeric()) {
+            self.pos += 1;
+        }
+        let name: String = self.chars[start..self.pos].iter().collect::<String>().to_lowercase();
+
+        if !self.eat('(') {
+            return match name.as_str() {
+                "pi" => Ok(std::f64::consts::PI),
+                "e" => Ok(std::f64::consts::E),
+                _ => Err(eyre!("Unknown constant '{}'", name)),
+            };
+        }
+        let mut args = vec![self.expr()?];
+        while self.eat(',') {
+            args.push(self.expr()?);
+        }
+        if !self.eat(')') {
+            return Err(eyre!("Missing ')' after arguments of {}", name));
+        }
+
+        let unary = |f: fn(f64) -> f64| match args.as_slice() {
+            [x] => Ok(f(*x)),
+            _ => Err(eyre!("{} takes one argument", name)),
+        };
+        match name.as_str() {
+            "sqrt" => unary(f64::sqrt),
+            "abs" => unary(f64::abs),
+            "ln" => unary(f64::ln),
+            "log" => unary(f64::log10),
+            "log2" => unary(f64::log2),
+            "exp" => unary(f64::exp),
+            "sin" => unary(f64::sin),
+            "cos" => unary(f64::cos),
+            "tan" => unary(f64::tan),
+            "asin" => unary(f64::asin),
+            "acos" => unary(f64::acos),
+            "atan" => unary(f64::atan),
+            "floor" => unary(f64::floor),
+            "ceil" => unary(f64::ceil),
+            "round" => unary(f64::round),
+            "min" | "max" | "pow" => match args.as_slice() {
+                [a, b] if name == "min" => Ok(a.min(*b)),
+                [a, b] if name == "max" => Ok(a.max(*b)),
+                [a, b] => Ok(a.powf(*b)),
+                _ => Err(eyre!("{} takes two arguments", name)),
+            },
+            _ => Err(eyre!("Unknown function '{}'", name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate() {
+        assert_eq!(evaluate("1 + 2 * 3").unwrap(), 7.0);
+        assert_eq!(evaluate("(1 + 2) * 3").unwrap(), 9.0);
+        assert_eq!(evaluate("2 ^ 3 ^ 2").unwrap(), 512.0);
+        assert_eq!(evaluate("-2^2").unwrap(), -4.0);
+        assert_eq!(evaluate("10 % 4 - 1.5e1").unwrap(), -13.0);
+        assert_eq!(evaluate("max(2, sqrt(16)) + round(pi)").unwrap(), 7.0);
+        assert!((evaluate("ln(e)").unwrap() - 1.0).abs() < 1e-12);
+        assert!(evaluate("1 / 0").is_err());
+        assert!(evaluate("2 +").is_err());
+        assert!(evaluate("foo(1)").is_err());
+        assert_eq!(format_number(evaluate("7 / 2").unwrap()), "3.5");
+        assert_eq!(format_number(evaluate("6 / 2").unwrap()), "3");
+    }
+
+    #[test]
+    fn test_sections() {
+        let document = "[A0] 导言\n\nintro text\n\n---\n\n[A1] 统计律\n\n### A1.1 原子为什么这么小\n\nsmall\n\n### A1.2 大数\n\nlarge\n\n[A10] 结语\n\nend";
+        assert_eq!(
+            list_sections(document),
+            vec!["[A0] 导言", "[A1] 统计律", "    A1.1 原子为什么这么小", "    A1.2 大数", "[A10] 结语"]
+        );
+
+        // Exact label wins over a prefix match ("A1" is not "A10" or "A1.1")
+        let section = find_section(document, "A1").unwrap();
+        assert!(section.starts_with("[A1] 统计律"));
+        assert!(section.ends_with("large"));
+
+        assert_eq!(find_section(document, "a1.1").unwrap(), "### A1.1 原子为什么这么小\n\nsmall");
+        assert_eq!(find_section(document, "大数").unwrap(), "### A1.2 大数\n\nlarge");
+        assert_eq!(find_section(document, "[A10]").unwrap(), "[A10] 结语\n\nend");
+        assert_eq!(find_section(document, "A7"), None);
+
+        assert_eq!(truncate("生命是什么", 2), "生命\n[...truncated]");
+    }
+
+    #[test]
+    fn test_lookup() {
+        let entries: HashMap<String, String> = [
+            ("Negentropy", "Order a living system draws from its surroundings."),
+            ("Entropy", "Measure of disorder."),
+            ("Aperiodic crystal", "Schrödinger's picture of the gene."),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        assert_eq!(lookup(&entries, "entropy"), "Entropy: Measure of disorder.");
+        assert_eq!(
+            lookup(&entries, "crystal"),
+            "Aperiodic crystal: Schrödinger's picture of the gene."
+        );
+        assert_eq!(lookup(&entries, "gene"), "No entry for 'gene'");
+    }
+}
@@ -1,13 +1,17 @@
 use eyre::{Result, eyre};
-use outfox_openai::spec::{
-    ChatCompletionMessageToolCall, CreateChatCompletionRequest, CreateChatCompletionResponse,
-};
-use reqwest::Client as HttpClient;
+use outfox_openai::spec::{CreateChatCompletionRequest, CreateChatCompletionResponse};
+use reqwest::{Client as HttpClient, StatusCode};
 use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 use std::time::Duration;
 
-use crate::config::{GeminiConfig, OpenaiConfig, get_env_or_value};
+use crate::anthropic::{ANTHROPIC_VERSION, from_anthropic_response, to_anthropic_request};
+use crate::config::{
+    AnthropicConfig, GeminiConfig, LocalApi, LocalConfig, OpenaiConfig, get_env_or_value,
+};
+use crate::gemini::{endpoint_url, from_gemini_response, to_gemini_request};
+use crate::local::{OllamaStreamState, ollama_base_url, parse_model_list, to_ollama_request};
+use crate::streaming::StreamResult;
 
 /// Trait for chat completion clients supporting multiple providers.
 ///
@@ -41,12 +45,13 @@ pub trait ChatClient: Send + Sync {
     /// * `chunk_sender` - Channel to send text chunks as they arrive
     ///
     /// # Returns
-    /// * `Result<(String, Option<Vec<ChatCompletionMessageToolCall>>)>` - The complete accumulated response text and any tool calls
+    /// * `StreamResult` - The complete accumulated response text, any tool calls
+    ///   and the token usage if the provider reported it
     async fn complete_streaming(
         &self,
         request: CreateChatCompletionRequest,
         chunk_sender: mpsc::UnboundedSender<String>,
-    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>)>;
+    ) -> StreamResult;
 
     /// Send a streaming chat completion request with cancellation support.
     async fn complete_streaming_with_cancellation(
@@ -55,7 +60,40 @@ pub trait ChatClient: Send + Sync {
         chunk_sender: mpsc::UnboundedSender<String>,
         cancellation_token: CancellationToken,
         timeout_duration: Duration,
-    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>)>;
+    ) -> StreamResult;
+}
+
+/// Provider failures that failover classifies by kind (see
+/// [`ErrorClass`](crate::failover::ErrorClass)) rather than by message
+#[derive(Debug, thiserror::Error)]
+pub enum ProviderError {
+    /// The provider answered with a non-success HTTP status
+    #[error("API Error ({status}): {body}")]
+    Status { status: StatusCode, body: String },
+    /// Sending the request or reading the response failed
+    #[error("{0}: {1}")]
+    Transport(&'static str, reqwest::Error),
+    #[error("Stream cancelled by user")]
+    Cancelled,
+    #[error("Stream timed out after {0:?}")]
+    TimedOut(Duration),
+    /// The stream closed before its end marker; the reply is incomplete
+    #[error("Stream ended before {0}")]
+    Truncated(&'static str),
+}
+
+impl ProviderError {
+    /// Error for a non-success response, with its body as the message
+    pub async fn from_response(response: reqwest::Response) -> Self {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        ProviderError::Status { status, body }
+    }
+
+    /// The first provider error in `error`'s chain
+    pub fn find(error: &eyre::Report) -> Option<&ProviderError> {
+        error.chain().find_map(|cause| cause.downcast_ref::<ProviderError>())
+    }
 }
 
 #[derive(Debug)]
@@ -98,18 +136,68 @@ impl GeminiClient {
     }
 }
 
+impl GeminiClient {
+    /// Translate an OpenAI-shaped request into a Gemini request body and URL
+    fn prepare(
+        &self,
+        request: &CreateChatCompletionRequest,
+        streaming: bool,
+    ) -> Result<(String, String, serde_json::Value)> {
+        let request_json = serde_json::to_value(request)?;
+        let (model, body) = to_gemini_request(&request_json)?;
+        let url = endpoint_url(&self.api_url, &model, streaming);
+        Ok((model, url, body))
+    }
+
+    async fn stream(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+        cancellation_token: Option<CancellationToken>,
+        timeout_duration: Option<Duration>,
+    ) -> StreamResult {
+        let (_, url, body) = self.prepare(&request, true)?;
+        let response_id = uuid::Uuid::new_v4().simple().to_string();
+
+        use crate::streaming::stream_gemini_completion;
+
+        stream_gemini_completion(
+            &self.client,
+            url,
+            self.api_key.clone(),
+            body,
+            &response_id,
+            cancellation_token,
+            timeout_duration,
+            |chunk| {
+                chunk_sender
+                    .send(chunk)
+                    .map_err(|e| eyre!("Failed to send chunk: {}", e))
+            },
+        )
+        .await
+    }
+}
+
+/// Google Gemini client using the native `generateContent` API.
+///
+/// Requests and responses are translated to and from the OpenAI format
+/// (see [`crate::gemini`]), including system instructions and function calling.
 #[async_trait::async_trait]
 impl ChatClient for GeminiClient {
     async fn complete(
         &self,
         request: CreateChatCompletionRequest,
     ) -> Result<CreateChatCompletionResponse> {
+        let (model, url, body) = self.prepare(&request, false)?;
+        eprintln!("[{}] Sending request to: {}", self.id, url);
+
         let response = self
             .client
-            .post(&self.api_url)
+            .post(&url)
             .header("X-goog-api-key", self.api_key.clone())
             .header("Content-Type", "application/json")
-            .json(&request)
+            .json(&body)
             .send()
             .await?;
 
@@ -119,7 +207,7 @@ impl ChatClient for GeminiClient {
         if !status.is_success() {
             let error_text = response.text().await?;
             eprintln!("[{}] Error response: {}", self.id, error_text);
-            return Err(eyre!("API Error: {}", error_text));
+            return Err(ProviderError::Status { status, body: error_text }.into());
         }
         let text_data = response.text().await?;
         // Debug log the response for troubleshooting
@@ -131,10 +219,10 @@ impl ChatClient for GeminiClient {
             eprintln!(
                 "[{}] Response (first 1000 chars): {}",
                 self.id,
-                &text_data[..1000]
+                text_data.chars().take(1000).collect::<String>()
             );
         }
-        let completion: CreateChatCompletionResponse =
+        let gemini_response: serde_json::Value =
             serde_json::from_str(&text_data).map_err(|e| {
                 eyre!(
                     "Failed to parse API response: {}. Response: {}",
@@ -142,31 +230,35 @@ impl ChatClient for GeminiClient {
                     text_data
                 )
             })?;
+        let response_id = uuid::Uuid::new_v4().simple().to_string();
+        let completion_json = from_gemini_response(&gemini_response, &model, &response_id)?;
+        let completion: CreateChatCompletionResponse = serde_json::from_value(completion_json)
+            .map_err(|e| eyre!("Failed to convert Gemini response: {}", e))?;
         Ok(completion)
     }
 
     async fn complete_streaming(
         &self,
-        _request: CreateChatCompletionRequest,
-        _chunk_sender: mpsc::UnboundedSender<String>,
-    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>)> {
-        Err(eyre!(
-            "Streaming not implemented for provider '{}'",
-            self.id
-        ))
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+    ) -> StreamResult {
+        self.stream(request, chunk_sender, None, None).await
     }
 
     async fn complete_streaming_with_cancellation(
         &self,
-        _request: CreateChatCompletionRequest,
-        _chunk_sender: mpsc::UnboundedSender<String>,
-        _cancellation_token: CancellationToken,
-        _timeout_duration: Duration,
-    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>)> {
-        Err(eyre!(
-            "Streaming with cancellation not implemented for provider '{}'",
-            self.id
-        ))
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+        cancellation_token: CancellationToken,
+        timeout_duration: Duration,
+    ) -> StreamResult {
+        self.stream(
+            request,
+            chunk_sender,
+            Some(cancellation_token),
+            Some(timeout_duration),
+        )
+        .await
     }
 }
 
@@ -179,6 +271,7 @@ pub struct OpenaiClient {
     id: String,
     api_key: String,
     api_url: String,
+    include_usage: bool,
     client: HttpClient,
 }
 
@@ -209,6 +302,7 @@ impl OpenaiClient {
             id: config.id.clone(),
             api_key: get_env_or_value(&config.api_key),
             api_url: get_env_or_value(&config.api_url),
+            include_usage: config.include_usage,
             client,
         }
     }
@@ -241,7 +335,7 @@ impl ChatClient for OpenaiClient {
         if !status.is_success() {
             let error_text = response.text().await?;
             eprintln!("[{}] Error: {}", self.id, error_text);
-            return Err(eyre!("API Error: {}", error_text));
+            return Err(ProviderError::Status { status, body: error_text }.into());
         }
 
         let text_data = response.text().await?;
@@ -263,20 +357,24 @@ impl ChatClient for OpenaiClient {
         &self,
         mut request: CreateChatCompletionRequest,
         chunk_sender: mpsc::UnboundedSender<String>,
-    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>)> {
+    ) -> StreamResult {
 
         // Force streaming mode
         request.stream = Some(true);
 
         // Convert request to JSON value to modify it
-        let request_json = serde_json::to_value(&request)?;
+        let mut request_json = serde_json::to_value(&request)?;
+        // Ask for token usage on the final chunk where the provider supports it
+        if self.include_usage {
+            request_json["stream_options"] = serde_json::json!({ "include_usage": true });
+        }
 
         let url = format!("{}/chat/completions", self.api_url);
 
         // Use the streaming module
         use crate::streaming::stream_completion;
 
-        let (accumulated, tool_calls) = stream_completion(
+        let (accumulated, tool_calls, usage) = stream_completion(
             &self.client,
             url,
             self.api_key.clone(),
@@ -289,8 +387,8 @@ impl ChatClient for OpenaiClient {
         )
         .await?;
 
-        // Return both text and tool calls
-        Ok((accumulated, tool_calls))
+        // Return text, tool calls and usage
+        Ok((accumulated, tool_calls, usage))
     }
 
     async fn complete_streaming_with_cancellation(
@@ -299,20 +397,24 @@ impl ChatClient for OpenaiClient {
         chunk_sender: mpsc::UnboundedSender<String>,
         cancellation_token: CancellationToken,
         timeout_duration: Duration,
-    ) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>)> {
+    ) -> StreamResult {
 
         // Force streaming mode
         request.stream = Some(true);
 
         // Convert request to JSON value to modify it
-        let request_json = serde_json::to_value(&request)?;
+        let mut request_json = serde_json::to_value(&request)?;
+        // Ask for token usage on the final chunk where the provider supports it
+        if self.include_usage {
+            request_json["stream_options"] = serde_json::json!({ "include_usage": true });
+        }
 
         let url = format!("{}/chat/completions", self.api_url);
 
         // Use the streaming module with cancellation support
         use crate::streaming::stream_completion_with_cancellation;
 
-        let (accumulated, tool_calls) = stream_completion_with_cancellation(
+        let (accumulated, tool_calls, usage) = stream_completion_with_cancellation(
             &self.client,
             url,
             self.api_key.clone(),
@@ -327,7 +429,528 @@ impl ChatClient for OpenaiClient {
         )
         .await?;
 
-        // Return both text and tool calls
-        Ok((accumulated, tool_calls))
+        // Return text, tool calls and usage
+        Ok((accumulated, tool_calls, usage))
+    }
+}
+
+/// Anthropic Messages API client.
+///
+/// Requests and responses are translated to and from the OpenAI format
+/// (see [`crate::anthropic`]): the system prompt is sent separately and tool
+/// use maps to `ChatCompletionMessageToolCall`.
+#[derive(Debug)]
+pub struct AnthropicClient {
+    id: String,
+    api_key: String,
+    api_url: String,
+    max_tokens: u32,
+    client: HttpClient,
+}
+
+impl AnthropicClient {
+    pub fn new(config: &AnthropicConfig) -> Self {
+        Self::new_with_timeout(config, Duration::from_secs(30))
+    }
+
+    pub fn new_with_timeout(config: &AnthropicConfig, timeout: Duration) -> Self {
+        let client = if config.proxy {
+            HttpClient::builder()
+                .timeout(timeout)
+                .connect_timeout(Duration::from_secs(10))
+                .pool_idle_timeout(Duration::from_secs(30))
+                .build()
+                .unwrap_or_else(|_| HttpClient::new())
+        } else {
+            HttpClient::builder()
+                .timeout(timeout)
+                .connect_timeout(Duration::from_secs(10))
+                .pool_idle_timeout(Duration::from_secs(30))
+                .no_proxy()
+                .build()
+                .unwrap_or_else(|_| HttpClient::new())
+        };
+
+        Self {
+            id: config.id.clone(),
+            api_key: get_env_or_value(&config.api_key),
+            api_url: get_env_or_value(&config.api_url),
+            max_tokens: config.max_tokens,
+            client,
+        }
+    }
+
+    /// Build a Messages API request for an OpenAI-shaped completion request
+    fn build_request(
+        &self,
+        request: &CreateChatCompletionRequest,
+        streaming: bool,
+    ) -> Result<reqwest::RequestBuilder> {
+        let mut request_json = serde_json::to_value(request)?;
+        request_json["stream"] = serde_json::Value::Bool(streaming);
+        let body = to_anthropic_request(&request_json, self.max_tokens)?;
+
+        Ok(self
+            .client
+            .post(format!("{}/messages", self.api_url.trim_end_matches('/')))
+            .header("x-api-key", self.api_key.clone())
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .header("Content-Type", "application/json")
+            .body(serde_json::to_vec(&body)?))
+    }
+
+    async fn stream(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+        cancellation_token: Option<CancellationToken>,
+        timeout_duration: Option<Duration>,
+    ) -> StreamResult {
+        let http_request = self.build_request(&request, true)?;
+
+        use crate::streaming::stream_anthropic_completion;
+
+        stream_anthropic_completion(
+            http_request,
+            cancellation_token,
+            timeout_duration,
+            |chunk| {
+                chunk_sender
+                    .send(chunk)
+                    .map_err(|e| eyre!("Failed to send chunk: {}", e))
+            },
+        )
+        .await
+    }
+}
+
+#[async_trait::async_trait]
+impl ChatClient for AnthropicClient {
+    async fn complete(
+        &self,
+        request: CreateChatCompletionRequest,
+    ) -> Result<CreateChatCompletionResponse> {
+        eprintln!("[{}] Sending request to: {}/messages", self.id, self.api_url);
+
+        let response = self.build_request(&request, false)?.send().await?;
+
+        let status = response.status();
+        eprintln!("[{}] Response status: {}", self.id, status);
+
+        if !status.is_success() {
+            let error_text = response.text().await?;
+            eprintln!("[{}] Error: {}", self.id, error_text);
+            return Err(ProviderError::Status { status, body: error_text }.into());
+        }
+
+        let text_data = response.text().await?;
+        eprintln!("[{}] Response length: {} chars", self.id, text_data.len());
+
+        let anthropic_response: serde_json::Value = serde_json::from_str(&text_data)
+            .map_err(|e| eyre!("Failed to parse API response: {}. Response: {}", e, text_data))?;
+        let completion_json = from_anthropic_response(&anthropic_response)?;
+        let completion: CreateChatCompletionResponse = serde_json::from_value(completion_json)
+            .map_err(|e| eyre!("Failed to convert Anthropic response: {}", e))?;
+        Ok(completion)
+    }
+
+    async fn complete_streaming(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+    ) -> StreamResult {
+        self.stream(request, chunk_sender, None, None).await
+    }
+
+    async fn complete_streaming_with_cancellation(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+        cancellation_token: CancellationToken,
+        timeout_duration: Duration,
+    ) -> StreamResult {
+        self.stream(
+            request,
+            chunk_sender,
+            Some(cancellation_token),
+            Some(timeout_duration),
+        )
+        .await
+    }
+}
+
+/// Local model server client (Ollama native API or OpenAI-compatible servers).
+///
+/// In `openai` mode requests go through [`OpenaiClient`] at `{api_url}/v1`.
+/// In `ollama` mode requests use `/api/chat` with `keep_alive`, so the model
+/// stays loaded between turns of a session.
+#[derive(Debug)]
+pub struct LocalClient {
+    id: String,
+    base_url: String,
+    api: LocalApi,
+    keep_alive: Option<String>,
+    client: HttpClient,
+    openai: OpenaiClient,
+}
+
+impl LocalClient {
+    pub fn new(config: &LocalConfig) -> Self {
+        // Local models can take minutes to load on first use
+        Self::new_with_timeout(config, Duration::from_secs(300))
+    }
+
+    pub fn new_with_timeout(config: &LocalConfig, timeout: Duration) -> Self {
+        let base_url = ollama_base_url(&get_env_or_value(&config.api_url));
+        let builder = HttpClient::builder()
+            .timeout(timeout)
+            .connect_timeout(Duration::from_secs(5))
+            .pool_idle_timeout(Duration::from_secs(30));
+        // Local servers never go through a proxy unless asked to
+        let builder = if config.proxy { builder } else { builder.no_proxy() };
+        let client = builder.build().unwrap_or_else(|_| HttpClient::new());
+
+        let openai = OpenaiClient::new_with_timeout(
+            &OpenaiConfig {
+                id: config.id.clone(),
+                api_key: config.api_key.clone(),
+                api_url: format!("{}/v1", base_url),
+                proxy: config.proxy,
+                include_usage: config.include_usage,
+            },
+            timeout,
+        );
+
+        Self {
+            id: config.id.clone(),
+            base_url,
+            api: config.api,
+            keep_alive: config.keep_alive.clone().filter(|k| !k.trim().is_empty()),
+            client,
+            openai,
+        }
+    }
+
+    /// List models served by the local server
+    pub async fn list_models(&self) -> Result<Vec<String>> {
+        let url = match self.api {
+            LocalApi::Ollama => format!("{}/api/tags", self.base_url),
+            LocalApi::Openai => format!("{}/v1/models", self.base_url),
+        };
+        let response = self.client.get(&url).send().await?;
+        if !response.status().is_success() {
+            return Err(ProviderError::from_response(response).await.into());
+        }
+        let body: serde_json::Value = response.json().await?;
+        Ok(parse_model_list(&body))
+    }
+
+    /// Load a model into memory and apply `keep_alive` (Ollama only)
+    pub async fn preload(&self, model: &str) -> Result<()> {
+        if self.api != LocalApi::Ollama {
+            return Ok(());
+        }
+        let mut body = serde_json::json!({ "model": model });
+        if let Some(keep_alive) = &self.keep_alive {
+            body["keep_alive"] = serde_json::json!(keep_alive);
+        }
+        let response = self
+            .client
+            .post(format!("{}/api/generate", self.base_url))
+            .json(&body)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(ProviderError::from_response(response).await.into());
+        }
+        Ok(())
+    }
+
+    fn chat_request(
+        &self,
+        request: &CreateChatCompletionRequest,
+        stream: bool,
+    ) -> Result<reqwest::RequestBuilder> {
+        let request_json = serde_json::to_value(request)?;
+        let body = to_ollama_request(&request_json, self.keep_alive.as_deref(), stream)?;
+        Ok(self
+            .client
+            .post(format!("{}/api/chat", self.base_url))
+            .header("Content-Type", "application/json")
+            .json(&body))
+    }
+
+    async fn stream(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+        cancellation_token: Option<CancellationToken>,
+        timeout_duration: Option<Duration>,
+    ) -> StreamResult {
+        let http_request = self.chat_request(&request, true)?;
+        let response_id = uuid::Uuid::new_v4().simple().to_string();
+
+        use crate::streaming::stream_ollama_completion;
+
+        stream_ollama_completion(
+            http_request,
+            &response_id,
+            cancellation_token,
+            timeout_duration,
+            |chunk| {
+                chunk_sender
+                    .send(chunk)
+                    .map_err(|e| eyre!("Failed to send chunk: {}", e))
+            },
+        )
+        .await
+    }
+}
+
+#[async_trait::async_trait]
+impl ChatClient for LocalClient {
+    async fn complete(
+        &self,
+        request: CreateChatCompletionRequest,
+    ) -> Result<CreateChatCompletionResponse> {
+        if self.api == LocalApi::Openai {
+            return self.openai.complete(request).await;
+        }
+
+        eprintln!("[{}] Sending request to: {}/api/chat", self.id, self.base_url);
+        let response = self.chat_request(&request, false)?.send().await?;
+
+        let status = response.status();
+        eprintln!("[{}] Response status: {}", self.id, status);
+        if !status.is_success() {
+            let error_text = response.text().await?;
+            eprintln!("[{}] Error: {}", self.id, error_text);
+            return Err(ProviderError::Status { status, body: error_text }.into());
+        }
+
+        let body: serde_json::Value = response.json().await?;
+        let model = body
+            .get("model")
+            .and_then(|m| m.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let mut state = OllamaStreamState::new(&uuid::Uuid::new_v4().simple().to_string());
+        state.push(&body)?;
+        let completion: CreateChatCompletionResponse =
+            serde_json::from_value(state.into_response(&model))
+                .map_err(|e| eyre!("Failed to convert Ollama response: {}", e))?;
+        Ok(completion)
+    }
+
+    async fn complete_streaming(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+    ) -> StreamResult {
+        if self.api == LocalApi::Openai {
+            return self.openai.complete_streaming(request, chunk_sender).await;
+        }
+        self.stream(request, chunk_sender, None, None).await
+    }
+
+    async fn complete_streaming_with_cancellation(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+        cancellation_token: CancellationToken,
+        timeout_duration: Duration,
+    ) -> StreamResult {
+        if self.api == LocalApi::Openai {
+            return self
+                .openai
+                .complete_streaming_with_cancellation(
+                    request,
+                    chunk_sender,
+                    cancellation_token,
+                    timeout_duration,
+                )
+                .await;
+        }
+        self.stream(
+            request,
+            chunk_sender,
+            Some(cancellation_token),
+            Some(timeout_duration),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve one canned SSE response on a local port, optionally stalling after it
+    async fn mock_sse_server(events: &'static str, stall: bool) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 16 * 1024];
+            let _ = socket.read(&mut buf).await;
+            let header = "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ncache-control: no-cache\r\n\r\n";
+            socket.write_all(header.as_bytes()).await.unwrap();
+            socket.write_all(events.as_bytes()).await.unwrap();
+            socket.flush().await.unwrap();
+            if stall {
+                tokio::time::sleep(Duration::from_secs(30)).await;
+            }
+        });
+        format!("http://{}/v1", addr)
+    }
+
+    fn test_client(api_url: String) -> AnthropicClient {
+        AnthropicClient::new(&AnthropicConfig {
+            id: "anthropic".to_string(),
+            api_key: "test-key".to_string(),
+            api_url,
+            proxy: false,
+            max_tokens: 256,
+        })
+    }
+
+    fn test_request() -> CreateChatCompletionRequest {
+        serde_json::from_value(serde_json::json!({
+            "model": "claude-sonnet-4-5",
+            "messages": [
+                { "role": "system", "content": "Be brief." },
+                { "role": "user", "content": "Weather in Paris?" },
+            ],
+        }))
+        .unwrap()
+    }
+
+    const TOOL_STREAM: &str = concat!(
+        "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_1\",\"model\":\"claude\"}}\n\n",
+        "event: content_block_start\ndata: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"text\",\"text\":\"\"}}\n\n",
+        "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Checking \"}}\n\n",
+        "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"now.\"}}\n\n",
+        "event: content_block_stop\ndata: {\"type\":\"content_block_stop\",\"index\":0}\n\n",
+        "event: content_block_start\ndata: {\"type\":\"content_block_start\",\"index\":1,\"content_block\":{\"type\":\"tool_use\",\"id\":\"toolu_1\",\"name\":\"get_weather\",\"input\":{}}}\n\n",
+        "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":1,\"delta\":{\"type\":\"input_json_delta\",\"partial_json\":\"{\\\"city\\\": \\\"Paris\\\"}\"}}\n\n",
+        "event: content_block_stop\ndata: {\"type\":\"content_block_stop\",\"index\":1}\n\n",
+        "event: message_delta\ndata: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"tool_use\"}}\n\n",
+        "event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n",
+    );
+
+    #[tokio::test]
+    async fn test_anthropic_streaming_against_mock_server() {
+        let client = test_client(mock_sse_server(TOOL_STREAM, false).await);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        let (text, tool_calls, _) = client.complete_streaming(test_request(), tx).await.unwrap();
+        assert_eq!(text, "Checking now.");
+
+        let mut chunks = Vec::new();
+        while let Ok(chunk) = rx.try_recv() {
+            chunks.push(chunk);
+        }
+        assert_eq!(chunks, vec!["Checking ", "now."]);
+
+        let tool_calls = tool_calls.unwrap();
+        assert_eq!(tool_calls.len(), 1);
+        assert_eq!(tool_calls[0].id, "toolu_1");
+        assert_eq!(tool_calls[0].function.name, "get_weather");
+        assert_eq!(tool_calls[0].function.arguments, "{\"city\": \"Paris\"}");
+    }
+
+    #[tokio::test]
+    async fn test_anthropic_stream_without_message_stop_fails() {
+        // Server sends one text delta and closes the connection
+        let events = concat!(
+            "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_3\",\"model\":\"claude\"}}\n\n",
+            "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Hello\"}}\n\n",
+        );
+        let client = test_client(mock_sse_server(events, false).await);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        let error = client.complete_streaming(test_request(), tx).await.unwrap_err();
+        assert_eq!(crate::failover::ErrorClass::classify(&error), crate::failover::ErrorClass::Timeout);
+        assert_eq!(rx.try_recv().unwrap(), "Hello");
+    }
+
+    #[tokio::test]
+    async fn test_anthropic_streaming_cancellation() {
+        // Server sends the first text delta and then stalls without message_stop
+        let events = concat!(
+            "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_2\",\"model\":\"claude\"}}\n\n",
+            "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Hello\"}}\n\n",
+        );
+        let client = test_client(mock_sse_server(events, true).await);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let token = CancellationToken::new();
+
+        let cancel = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            cancel.cancel();
+        });
+
+        let result = client
+            .complete_streaming_with_cancellation(test_request(), tx, token, Duration::from_secs(10))
+            .await;
+        assert!(result.unwrap_err().to_string().contains("cancelled"));
+        assert_eq!(rx.try_recv().unwrap(), "Hello");
+    }
+
+    /// Answer one OpenAI-style stream request with `[DONE]`, returning its body
+    async fn capture_openai_request() -> (String, tokio::task::JoinHandle<serde_json::Value>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = vec![0u8; 16 * 1024];
+            // Read until the headers and `content-length` bytes of body arrived
+            let body = loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                let Some((head, body)) = text.split_once("\r\n\r\n") else {
+                    assert!(n > 0, "connection closed before the request headers");
+                    continue;
+                };
+                let length = head
+                    .lines()
+                    .filter_map(|line| line.split_once(':'))
+                    .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                    .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+                    .unwrap_or(0);
+                if n == 0 || body.len() >= length {
+                    break body.to_string();
+                }
+            };
+            let response = "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\n\r\ndata: [DONE]\n\n";
+            socket.write_all(response.as_bytes()).await.unwrap();
+            socket.flush().await.unwrap();
+            serde_json::from_str(&body).unwrap()
+        });
+        (format!("http://{}/v1", addr), server)
+    }
+
+    #[tokio::test]
+    async fn test_openai_stream_options_follow_include_usage() {
+        for include_usage in [true, false] {
+            let (api_url, server) = capture_openai_request().await;
+            let client = OpenaiClient::new(&OpenaiConfig {
+                id: "openai".to_string(),
+                api_key: "test-key".to_string(),
+                api_url,
+                proxy: false,
+                include_usage,
+            });
+            let (tx, _rx) = mpsc::unbounded_channel();
+            client.complete_streaming(test_request(), tx).await.unwrap();
+
+            let body = server.await.unwrap();
+            assert_eq!(body.get("stream_options").is_some(), include_usage);
+        }
     }
 }
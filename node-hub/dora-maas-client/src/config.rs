@@ -1,5 +1,6 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 use std::process::Stdio;
 use std::sync::Arc;
 
@@ -10,7 +11,13 @@ use figment::{
 use rmcp::{RoleClient, ServiceExt, service::RunningService, transport::ConfigureCommandExt};
 use serde::Deserialize;
 
-use crate::client::{ChatClient, GeminiClient, OpenaiClient};
+use crate::builtin_tools;
+use crate::ratelimit::{RateLimitConfig, RateLimiter};
+use crate::segmenter::SegmenterConfig;
+use crate::session_store::SessionStoreConfig;
+use crate::structured::{StructuredMode, StructuredOutputConfig};
+use crate::client::{AnthropicClient, ChatClient, GeminiClient, LocalClient, OpenaiClient};
+use crate::mock::{MockClient, MockResponse, RecordingClient};
 use crate::tool::{Tool, ToolSet, get_mcp_tools};
 
 /// Main configuration structure for the MaaS client.
@@ -32,6 +39,11 @@ pub struct Config {
     #[serde(default)]
     pub enable_local_mcp: bool, // Enable local MCP host (false = pass through to client)
     pub mcp: Option<McpConfig>, // MCP server configurations
+    /// Tools implemented inside the node (see [`BuiltinToolConfig`])
+    #[serde(default)]
+    pub builtin_tools: Vec<BuiltinToolConfig>,
+    /// Names of the tools this participant may use (builtin and MCP); all when unset
+    pub allowed_tools: Option<Vec<String>>,
     // HTTP request cancellation settings
     #[serde(default = "default_request_timeout")]
     pub request_timeout_secs: u64,
@@ -41,6 +53,38 @@ pub struct Config {
     pub enable_cancellation: bool,
     // Anchor context settings
     pub anchor_context: Option<String>, // Path to anchor context markdown file
+    /// Default retry policy for model routes (see [`RetryPolicy`])
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    /// Token/cost limits per session and for this participant
+    #[serde(default)]
+    pub budget: BudgetConfig,
+    /// Trim history so the estimated prompt stays under this many tokens
+    pub max_context_tokens: Option<usize>,
+    /// Condense old turns into a running summary (see [`SummarizationConfig`])
+    pub summarization: Option<SummarizationConfig>,
+    /// Re-read this file when it changes on disk (checked on every input event)
+    #[serde(default)]
+    pub watch_config: bool,
+    /// How streamed text is cut into segments for TTS (see [`SegmenterConfig`])
+    #[serde(default)]
+    pub segmenter: SegmenterConfig,
+    /// Append every provider request and response to this JSON-lines fixture
+    /// file, for replay with a `mock` provider
+    pub record_fixtures: Option<String>,
+    /// Ask for JSON replies with spoken text and turn-taking fields
+    /// (see [`StructuredOutputConfig`])
+    #[serde(default)]
+    pub structured_output: StructuredOutputConfig,
+    /// Save sessions to disk and restore them (see [`SessionStoreConfig`])
+    pub session_store: Option<SessionStoreConfig>,
+    /// Limits per provider ID, shared with the other participants on this
+    /// machine (see [`RateLimitConfig`])
+    #[serde(default)]
+    pub rate_limits: HashMap<String, RateLimitConfig>,
+    /// Where the shared rate limit state lives (default: a `mofa-maas-rate-limits`
+    /// directory in the system temp directory)
+    pub rate_limit_dir: Option<String>,
 }
 
 fn default_log_level() -> String {
@@ -60,6 +104,10 @@ pub enum ProviderConfig {
     Gemini(GeminiConfig),
     Alicloud(AlicloudConfig),
     Deepseek(DeepseekConfig),
+    Anthropic(AnthropicConfig),
+    #[serde(alias = "ollama")]
+    Local(LocalConfig),
+    Mock(MockConfig),
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -69,6 +117,9 @@ pub struct OpenaiConfig {
     pub api_url: String,
     #[serde(default)]
     pub proxy: bool,
+    /// Ask for token usage on the last streamed chunk (`stream_options.include_usage`)
+    #[serde(default = "default_true")]
+    pub include_usage: bool,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -87,6 +138,9 @@ pub struct AlicloudConfig {
     pub api_url: String,
     #[serde(default)]
     pub proxy: bool,
+    /// Send `stream_options.include_usage`; off unless the endpoint supports it
+    #[serde(default)]
+    pub include_usage: bool,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -97,22 +151,228 @@ pub struct DeepseekConfig {
     pub api_url: String,
     #[serde(default)]
     pub proxy: bool,
+    /// Send `stream_options.include_usage`; off unless the endpoint supports it
+    #[serde(default)]
+    pub include_usage: bool,
 }
 
 fn default_deepseek_url() -> String {
     "https://api.deepseek.com/v1".to_string()
 }
 
+#[derive(Clone, Debug, Deserialize)]
+pub struct AnthropicConfig {
+    pub id: String,
+    pub api_key: String,
+    #[serde(default = "default_anthropic_url")]
+    pub api_url: String,
+    #[serde(default)]
+    pub proxy: bool,
+    /// Used when a request sets no token limit (the Messages API requires one)
+    #[serde(default = "default_anthropic_max_tokens")]
+    pub max_tokens: u32,
+}
+
+fn default_anthropic_url() -> String {
+    "https://api.anthropic.com/v1".to_string()
+}
+
+fn default_anthropic_max_tokens() -> u32 {
+    4096
+}
+
+/// Local model server (Ollama, llama.cpp server, LM Studio, ...) for offline sessions
+#[derive(Clone, Debug, Deserialize)]
+pub struct LocalConfig {
+    pub id: String,
+    /// Usually not needed for local servers
+    #[serde(default)]
+    pub api_key: String,
+    #[serde(default = "default_local_url")]
+    pub api_url: String,
+    /// `ollama` for the native `/api/chat` endpoint, `openai` for OpenAI-compatible servers
+    #[serde(default)]
+    pub api: LocalApi,
+    /// How long Ollama keeps the model loaded after a request (e.g. "30m", "-1" = forever)
+    #[serde(default = "default_keep_alive")]
+    pub keep_alive: Option<String>,
+    /// Load routed models at startup so the first turn does not pay the load time
+    #[serde(default = "default_true")]
+    pub preload: bool,
+    #[serde(default)]
+    pub proxy: bool,
+    /// With `api = "openai"`: send `stream_options.include_usage`; most local
+    /// servers ignore or reject it, so it is off by default
+    #[serde(default)]
+    pub include_usage: bool,
+}
+
+/// Scripted provider for tests and offline demos (see [`crate::mock`])
+#[derive(Clone, Debug, Deserialize)]
+pub struct MockConfig {
+    pub id: String,
+    /// JSON-lines fixture file, e.g. written with `record_fixtures`
+    pub fixture: Option<String>,
+    /// Responses given inline, used before the fixture's
+    #[serde(default)]
+    pub responses: Vec<MockResponse>,
+    /// Delay before each streamed chunk that has no recorded delay
+    #[serde(default)]
+    pub chunk_delay_ms: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LocalApi {
+    #[default]
+    Ollama,
+    Openai,
+}
+
+fn default_local_url() -> String {
+    "http://localhost:11434".to_string()
+}
+
+fn default_keep_alive() -> Option<String> {
+    Some("30m".to_string())
+}
+
+fn default_true() -> bool {
+    true
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct ModelConfig {
     pub id: String,
     pub route: ModelRoute,
+    /// Used to compute the cost reported on the `metrics` output
+    pub pricing: Option<ModelPricing>,
+    /// Whether the model accepts images; when false, attached images are
+    /// replaced by a text placeholder
+    #[serde(default = "default_true")]
+    pub vision: bool,
+}
+
+/// Price of a model in currency units per million tokens
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ModelPricing {
+    #[serde(default)]
+    pub input_per_million: f64,
+    #[serde(default)]
+    pub output_per_million: f64,
+}
+
+/// Usage limits. A participant over budget stops answering until reset.
+///
+/// Session limits apply to one `session_id` and are cleared by a session
+/// reset; total limits cover everything this node sent since start.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct BudgetConfig {
+    pub max_session_tokens: Option<u64>,
+    pub max_session_cost: Option<f64>,
+    pub max_total_tokens: Option<u64>,
+    pub max_total_cost: Option<f64>,
+    /// Sent as the participant's last answer when the budget runs out
+    pub exhausted_message: Option<String>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct ModelRoute {
     pub provider: String,
     pub model: Option<String>,
+    /// Providers tried in order when the primary one fails
+    #[serde(default)]
+    pub fallbacks: Vec<RouteTarget>,
+    /// Overrides the global `[retry]` policy for this model
+    pub retry: Option<RetryPolicy>,
+}
+
+/// Summarization of old turns.
+///
+/// When the estimated prompt grows past `trigger_tokens`, all but the most
+/// recent exchanges are condensed into one summary message kept right after
+/// the system prompt (and anchor context). Later summaries fold in the
+/// previous one.
+#[derive(Clone, Debug, Deserialize)]
+pub struct SummarizationConfig {
+    /// Model ID from `[[models]]` used to summarize (defaults to `default_model`)
+    pub model: Option<String>,
+    #[serde(default = "default_summary_trigger_tokens")]
+    pub trigger_tokens: usize,
+    /// Exchanges kept verbatim after summarizing
+    #[serde(default = "default_summary_keep_recent")]
+    pub keep_recent_exchanges: usize,
+    #[serde(default = "default_summary_max_tokens")]
+    pub max_summary_tokens: u32,
+    /// Instructions for the summarizer (replaces the built-in prompt)
+    pub prompt: Option<String>,
+}
+
+fn default_summary_trigger_tokens() -> usize {
+    12000
+}
+
+fn default_summary_keep_recent() -> usize {
+    4
+}
+
+fn default_summary_max_tokens() -> u32 {
+    512
+}
+
+/// A fallback provider for a model route
+#[derive(Clone, Debug, Deserialize)]
+pub struct RouteTarget {
+    pub provider: String,
+    /// Model name on this provider (defaults to the model ID)
+    pub model: Option<String>,
+}
+
+/// How failed requests are retried before moving to the next provider.
+///
+/// Each error class gets a number of retries on the same provider with
+/// exponential backoff; when they are used up the next fallback is tried.
+/// Cancellations are never retried.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Retries on the same provider after HTTP 429
+    pub rate_limit_retries: u32,
+    /// First backoff after a 429 (doubles on each retry)
+    pub rate_limit_backoff_ms: u64,
+    /// Retries on the same provider after HTTP 5xx
+    pub server_error_retries: u32,
+    pub server_error_backoff_ms: u64,
+    /// Retries on the same provider after a timeout or connection error
+    /// (default 0: go straight to the next provider)
+    pub timeout_retries: u32,
+    /// Whether other errors (4xx, parse errors) move on to the next provider
+    pub failover_on_other_errors: bool,
+    /// Upper bound for any single backoff
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            rate_limit_retries: 2,
+            rate_limit_backoff_ms: 1000,
+            server_error_retries: 1,
+            server_error_backoff_ms: 500,
+            timeout_retries: 0,
+            failover_on_other_errors: true,
+            max_backoff_ms: 8000,
+        }
+    }
+}
+
+/// Ordered providers for one model plus the retry policy that applies to them
+#[derive(Clone, Debug)]
+pub struct RouteChain {
+    /// `(provider_id, model_name)`, primary first
+    pub targets: Vec<(String, String)>,
+    pub retry: RetryPolicy,
 }
 
 impl Config {
@@ -121,15 +381,29 @@ impl Config {
     /// Supports TOML, YAML, and JSON formats based on file extension.
     /// Falls back to `maas_config.toml` if MAAS_CONFIG_PATH is not set.
     pub fn load() -> eyre::Result<Self> {
-        let config_file =
-            std::env::var("MAAS_CONFIG_PATH").unwrap_or_else(|_| "maas_config.toml".to_string());
-        let config_path = PathBuf::from(config_file);
+        let config_path = Self::file_path();
 
         if !config_path.exists() {
             eprintln!("Config file not found at: {}", config_path.display());
             std::process::exit(1);
         }
 
+        Self::load_from(&config_path)
+    }
+
+    /// Path of the configuration file (MAAS_CONFIG_PATH or `maas_config.toml`)
+    pub fn file_path() -> PathBuf {
+        let config_file =
+            std::env::var("MAAS_CONFIG_PATH").unwrap_or_else(|_| "maas_config.toml".to_string());
+        PathBuf::from(config_file)
+    }
+
+    /// Load configuration from `config_path`, used at startup and on reload.
+    pub fn load_from(config_path: &Path) -> eyre::Result<Self> {
+        if !config_path.exists() {
+            return Err(eyre::eyre!("Config file not found at: {}", config_path.display()));
+        }
+
         let figment = match config_path.extension().and_then(|s| s.to_str()) {
             Some("yaml") | Some("yml") => Figment::new().merge(Yaml::file(config_path)),
             Some("json") => Figment::new().merge(Json::file(config_path)),
@@ -162,6 +436,7 @@ impl Config {
                         api_key: config.api_key.clone(),
                         api_url: config.api_url.clone(),
                         proxy: config.proxy,
+                        include_usage: config.include_usage,
                     }))
                 }
                 ProviderConfig::Deepseek(config) => {
@@ -171,8 +446,25 @@ impl Config {
                         api_key: config.api_key.clone(),
                         api_url: config.api_url.clone(),
                         proxy: config.proxy,
+                        include_usage: config.include_usage,
                     }))
                 }
+                ProviderConfig::Anthropic(config) => Arc::new(AnthropicClient::new(config)),
+                ProviderConfig::Local(config) => Arc::new(LocalClient::new(config)),
+                ProviderConfig::Mock(config) => match MockClient::new(config) {
+                    Ok(client) => Arc::new(client),
+                    Err(e) => {
+                        eprintln!("⚠️ Warning: Skipping mock provider '{}': {:#}", config.id, e);
+                        continue;
+                    }
+                },
+            };
+            // Record real providers only; replaying a fixture must not grow it
+            let client: Arc<dyn ChatClient> = match &self.record_fixtures {
+                Some(path) if !matches!(provider, ProviderConfig::Mock(_)) => {
+                    Arc::new(RecordingClient::new(client, path))
+                }
+                _ => client,
             };
 
             let provider_id = match provider {
@@ -180,6 +472,9 @@ impl Config {
                 ProviderConfig::Gemini(c) => &c.id,
                 ProviderConfig::Alicloud(c) => &c.id,
                 ProviderConfig::Deepseek(c) => &c.id,
+                ProviderConfig::Anthropic(c) => &c.id,
+                ProviderConfig::Local(c) => &c.id,
+                ProviderConfig::Mock(c) => &c.id,
             };
 
             clients.insert(provider_id.clone(), client);
@@ -188,6 +483,83 @@ impl Config {
         clients
     }
 
+    /// Check local providers: list their models, warn about routed models they
+    /// do not serve, and preload routed models (Ollama keep-alive).
+    ///
+    /// Runs before the node starts; returns `(level, message)` log lines.
+    pub async fn probe_local_providers(&self) -> Vec<(&'static str, String)> {
+        let mut logs = Vec::new();
+
+        for provider in &self.providers {
+            let ProviderConfig::Local(local) = provider else {
+                continue;
+            };
+            let client = LocalClient::new(local);
+            let routed: Vec<String> = self
+                .models
+                .iter()
+                .filter(|m| m.route.provider == local.id)
+                .map(|m| m.route.model.clone().unwrap_or_else(|| m.id.clone()))
+                .collect();
+
+            let available = match client.list_models().await {
+                Ok(models) => models,
+                Err(e) => {
+                    logs.push((
+                        "WARNING",
+                        format!("Local provider '{}' unreachable at {}: {}", local.id, local.api_url, e),
+                    ));
+                    continue;
+                }
+            };
+            logs.push((
+                "INFO",
+                format!(
+                    "Local provider '{}': {} models available ({})",
+                    local.id,
+                    available.len(),
+                    available.join(", ")
+                ),
+            ));
+
+            for model in &routed {
+                if !crate::local::has_model(&available, model) {
+                    logs.push((
+                        "WARNING",
+                        format!("Model '{}' is not available on local provider '{}'", model, local.id),
+                    ));
+                } else if local.preload {
+                    match client.preload(model).await {
+                        Ok(()) => logs.push(("INFO", format!("Preloaded local model '{}'", model))),
+                        Err(e) => logs.push((
+                            "WARNING",
+                            format!("Failed to preload local model '{}': {}", model, e),
+                        )),
+                    }
+                }
+            }
+        }
+
+        logs
+    }
+
+    /// A limiter per provider with `[rate_limits.<provider id>]`
+    pub fn rate_limiters(&self) -> HashMap<String, Arc<RateLimiter>> {
+        let dir = match &self.rate_limit_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => std::env::temp_dir().join("mofa-maas-rate-limits"),
+        };
+        // Leases of a crashed process stop counting after its longest request
+        let lease_ttl = Duration::from_secs(self.request_timeout_secs.max(self.stream_timeout_secs) + 30);
+        self.rate_limits
+            .iter()
+            .map(|(provider_id, limits)| {
+                let limiter = RateLimiter::new(provider_id, limits.clone(), &dir, lease_ttl);
+                (provider_id.clone(), Arc::new(limiter))
+            })
+            .collect()
+    }
+
     /// Route a model ID to its provider and actual model name.
     ///
     /// # Arguments
@@ -203,6 +575,90 @@ impl Config {
             (provider, model)
         })
     }
+
+    /// Pricing configured for a model ID
+    pub fn model_pricing(&self, model_id: &str) -> Option<&ModelPricing> {
+        self.models
+            .iter()
+            .find(|m| m.id == model_id)
+            .and_then(|m| m.pricing.as_ref())
+    }
+
+    /// Whether requests to `model_id` may carry images (see [`ModelConfig::vision`])
+    pub fn model_vision(&self, model_id: &str) -> bool {
+        !self.models.iter().any(|m| m.id == model_id && !m.vision)
+    }
+
+    /// How to request structured replies from `provider_id`: the configured
+    /// mode, or for `auto` the strongest one the provider kind supports
+    pub fn structured_mode(&self, provider_id: &str) -> StructuredMode {
+        if self.structured_output.mode != StructuredMode::Auto {
+            return self.structured_output.mode;
+        }
+        let provider = self.providers.iter().find(|p| match p {
+            ProviderConfig::Openai(c) => c.id == provider_id,
+            ProviderConfig::Gemini(c) => c.id == provider_id,
+            ProviderConfig::Alicloud(c) => c.id == provider_id,
+            ProviderConfig::Deepseek(c) => c.id == provider_id,
+            ProviderConfig::Anthropic(c) => c.id == provider_id,
+            ProviderConfig::Local(c) => c.id == provider_id,
+            ProviderConfig::Mock(c) => c.id == provider_id,
+        });
+        match provider {
+            Some(ProviderConfig::Openai(_) | ProviderConfig::Gemini(_) | ProviderConfig::Local(_)) => {
+                StructuredMode::Schema
+            }
+            Some(ProviderConfig::Alicloud(_) | ProviderConfig::Deepseek(_)) => StructuredMode::JsonObject,
+            Some(ProviderConfig::Anthropic(_) | ProviderConfig::Mock(_)) | None => StructuredMode::Prompt,
+        }
+    }
+
+    /// Route a model ID to its primary provider followed by its fallbacks.
+    ///
+    /// # Returns
+    /// * `Some(RouteChain)` - Ordered `(provider_id, model_name)` targets and retry policy
+    /// * `None` - No routing found for the model ID
+    pub fn route_chain(&self, model_id: &str) -> Option<RouteChain> {
+        let model = self.models.iter().find(|m| m.id == model_id)?;
+        let (provider, model_name) = self.route_model(model_id)?;
+
+        let mut targets = vec![(provider, model_name)];
+        for fallback in &model.route.fallbacks {
+            let fallback_model = fallback.model.clone().unwrap_or_else(|| model.id.clone());
+            targets.push((fallback.provider.clone(), fallback_model));
+        }
+
+        Some(RouteChain {
+            targets,
+            retry: model.route.retry.clone().unwrap_or_else(|| self.retry.clone()),
+        })
+    }
+}
+
+/// A built-in tool, registered from a `[[builtin_tools]]` entry
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BuiltinToolConfig {
+    /// `current_time`: date, time and weekday
+    CurrentTime {
+        /// Fixed offset such as `"+08:00"`; the machine's local time when unset
+        utc_offset: Option<String>,
+    },
+    /// `calculator`: evaluates arithmetic expressions
+    Calculator,
+    /// `anchor_context`: lists and reads sections of a markdown file
+    AnchorContext {
+        /// Defaults to the top-level `anchor_context` file
+        path: Option<String>,
+        #[serde(default = "default_tool_max_chars")]
+        max_chars: usize,
+    },
+    /// `dictionary`: looks up terms in a `term = "definition"` file
+    Dictionary { path: String },
+}
+
+fn default_tool_max_chars() -> usize {
+    4000
 }
 
 /// MCP (Model Context Protocol) configuration
@@ -210,6 +666,17 @@ impl Config {
 pub struct McpConfig {
     #[serde(default)]
     pub servers: Vec<McpServerConfig>,
+    /// Resources read at startup and added to every session's system context
+    #[serde(default)]
+    pub resources: Vec<McpResourceConfig>,
+}
+
+/// An MCP resource to inject into the system context
+#[derive(Clone, Debug, Deserialize)]
+pub struct McpResourceConfig {
+    pub uri: String,
+    /// Server to read from; all running servers are tried when unset
+    pub server: Option<String>,
 }
 
 /// Configuration for an individual MCP server
@@ -291,14 +758,23 @@ impl McpServerTransportConfig {
 }
 
 impl Config {
-    /// Initialize the tool set with MCP tools
+    /// Initialize the tool set with built-in and MCP tools
     pub async fn init_tool_set(&self) -> eyre::Result<Option<ToolSet>> {
-        // Only initialize local MCP if both enable_tools and enable_local_mcp are true
-        if !self.enable_tools || !self.enable_local_mcp || self.mcp.is_none() {
+        // Tools run locally only if both enable_tools and enable_local_mcp are true
+        if !self.enable_tools || !self.enable_local_mcp {
             return Ok(None);
         }
 
         let mut tool_set = ToolSet::default();
+
+        for builtin in &self.builtin_tools {
+            if let Err(e) =
+                builtin_tools::register(builtin, self.anchor_context.as_deref(), &mut tool_set)
+            {
+                eprintln!("Warning: Failed to register built-in tool {:?}: {}", builtin, e);
+            }
+        }
+
         let mut mcp_clients = HashMap::new();
 
         // FIX: Made MCP server initialization graceful - if one server fails, others can still work
@@ -320,12 +796,10 @@ impl Config {
                     }
                 }
             }
-        }
 
-        // If no clients were created successfully, return None
-        if mcp_clients.is_empty() {
-            eprintln!("Warning: No MCP servers could be started");
-            return Ok(None);
+            if mcp_clients.is_empty() {
+                eprintln!("Warning: No MCP servers could be started");
+            }
         }
 
         // Load tools from successfully started servers
@@ -347,17 +821,57 @@ impl Config {
 
         tool_set.set_clients(mcp_clients);
 
+        if let Some(allowed) = &self.allowed_tools {
+            for name in tool_set.retain(allowed) {
+                eprintln!("  - Tool not in allowed_tools, skipped: {}", name);
+            }
+        }
+
         let tool_count = tool_set.tools().len();
         if tool_count > 0 {
-            eprintln!("Initialized {} MCP tools", tool_count);
+            eprintln!("Initialized {} tools", tool_count);
+            Ok(Some(tool_set))
+        } else if !tool_set.servers().is_empty() {
+            // Servers without tools can still offer resources and prompts
+            eprintln!("Initialized MCP servers without tools");
             Ok(Some(tool_set))
         } else {
-            eprintln!("Warning: No tools were loaded from any MCP server");
+            eprintln!("Warning: No tools were loaded");
             Ok(None)
         }
     }
 }
 
+/// Detects changes to the configuration file by its modification time.
+///
+/// Polled from the event loop, so a change is picked up at the next input
+/// event rather than the moment the file is written.
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: PathBuf) -> Self {
+        let modified = Self::modified(&path);
+        Self { path, modified }
+    }
+
+    /// True once per change of the file's modification time
+    pub fn changed(&mut self) -> bool {
+        let modified = Self::modified(&self.path);
+        if modified.is_none() || modified == self.modified {
+            return false;
+        }
+        self.modified = modified;
+        true
+    }
+
+    fn modified(path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+}
+
 /// Helper to resolve environment variable references in configuration.
 ///
 /// If value starts with "env:", looks up the environment variable.
@@ -377,22 +891,25 @@ pub fn get_env_or_value(value: &str) -> String {
             .trim()
             .to_string();
 
-        // Log masked API key for debugging
+        // Log whether the key is set, never any part of it
         if result.is_empty() {
             eprintln!("⚠️  {} resolved to EMPTY string", env_var);
-        } else if result.len() > 8 {
-            eprintln!("✓ {} = {}...{} (len={})", env_var, &result[..4], &result[result.len()-4..], result.len());
         } else {
-            eprintln!("⚠️  {} = {} (too short, len={})", env_var, result, result.len());
+            eprintln!("✓ {} = {}", env_var, mask_secret(&result));
         }
         result
     } else {
-        // Literal value - also log masked
-        let result = value.trim().to_string();
-        if result.len() > 8 {
-            eprintln!("✓ Literal API key: {}...{} (len={})", &result[..4], &result[result.len()-4..], result.len());
-        }
-        result
+        value.trim().to_string()
+    }
+}
+
+/// Mask a secret for log output, keeping only its length (same format as
+/// mofa-settings)
+pub fn mask_secret(secret: &str) -> String {
+    if secret.is_empty() {
+        "(empty)".to_string()
+    } else {
+        format!("***({} chars)", secret.chars().count())
     }
 }
 
@@ -0,0 +1,1389 @@
+//! Conversation engine shared by the Dora node and in-process participants.
+//!
+//! [`ConversationEngine`] owns the sessions, provider clients and tools of one
+//! LLM participant. A turn ([`ConversationEngine::run_turn`]) sends the session
+//! history to the routed provider, streams the reply through the
+//! [`StreamSegmenter`], runs local tool calls and reports everything to an
+//! [`OutputSink`]. The node binary implements the sink with Dora outputs; an
+//! app can implement it to run a participant without a dataflow.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use eyre::{Result, eyre};
+use outfox_openai::spec::{
+    ChatCompletionMessageToolCall, ChatCompletionTool, CreateChatCompletionRequest,
+};
+use rmcp::service::ServerSink;
+use tokio::sync::Mutex as AsyncMutex;
+use tokio_util::sync::CancellationToken;
+
+use crate::client::ChatClient;
+use crate::config::{Config, format_anchor_context, load_anchor_context};
+use crate::failover::{ErrorClass, FailoverClient, ServedBy};
+use crate::mcp_context;
+use crate::overrides::RequestOverrides;
+use crate::ratelimit::{RateLimitWait, RateLimiter};
+use crate::segmenter::{ProsodyHints, StreamSegmenter, to_ssml};
+use crate::session::{ChatSession, message_tokens};
+use crate::session_store::SessionStore;
+use crate::structured::{self, SpokenTextExtractor};
+use crate::summary;
+use crate::tool::ToolSet;
+use crate::usage::{RequestUsage, TokenUsage, UsageReport, UsageTotals, estimate_tokens};
+use crate::vision::{self, ImageInput};
+
+/// One piece of reply text
+#[derive(Clone, Debug, Default)]
+pub struct TextOutput<'a> {
+    pub text: &'a str,
+    /// `started`, `ongoing` or `ended`; `cancelled`, `timeout` or `error`
+    /// when the turn failed
+    pub session_status: &'a str,
+    /// Position of the segment in the reply
+    pub segment_index: Option<u32>,
+    /// Set when `segmenter.prosody_hints` is on
+    pub hints: Option<ProsodyHints>,
+    /// Set when `segmenter.ssml` is on
+    pub ssml: Option<String>,
+    /// [`ErrorClass::as_str`] of a failed turn, or `budget_exceeded`
+    pub error_type: Option<&'a str>,
+    pub error_message: Option<&'a str>,
+    /// Fields of a structured reply besides the spoken text, on the `ended`
+    /// marker (see [`crate::structured`])
+    pub fields: Option<&'a serde_json::Map<String, serde_json::Value>>,
+}
+
+/// Receives everything a turn produces
+pub trait OutputSink {
+    /// Log line; `level` is DEBUG, INFO, WARNING or ERROR
+    fn log(&mut self, level: &str, message: &str) -> Result<()>;
+
+    /// Participant status (`processing`, `complete`, `reloaded`, ...).
+    /// `served_by` is set on `complete`.
+    fn status(&mut self, status: &str, served_by: Option<&ServedBy>) -> Result<()>;
+
+    /// Reply text, one call per segment
+    fn text(&mut self, output: TextOutput<'_>) -> Result<()>;
+
+    /// Tool calls left to the client (`enable_local_mcp = false`)
+    fn tool_calls(&mut self, tool_calls: &[ChatCompletionMessageToolCall]) -> Result<()>;
+
+    /// Usage of a completed request
+    fn metrics(&mut self, report: &UsageReport<'_>) -> Result<()>;
+
+    /// A request waits for a shared rate limit (see [`crate::ratelimit`])
+    fn queued(&mut self, wait: &RateLimitWait) -> Result<()> {
+        self.log(
+            "INFO",
+            &format!(
+                "Queued for '{}' ({}), retry in {} ms",
+                wait.provider_id,
+                wait.reason,
+                wait.retry_after.as_millis()
+            ),
+        )?;
+        self.status("queued", None)
+    }
+}
+
+/// What starts a turn
+#[derive(Clone, Debug)]
+pub enum TurnInput {
+    /// A user message
+    User(String),
+    /// Results of tool calls run by the client, as `(tool_call_id, result)`
+    ToolResults(Vec<(String, String)>),
+}
+
+/// One request to the engine
+#[derive(Clone, Debug)]
+pub struct Turn {
+    pub input: TurnInput,
+    /// Per-request settings; `overrides.model` must be a configured model ID
+    pub overrides: RequestOverrides,
+    /// Tool definitions from the client, used when `enable_local_mcp` is off
+    pub client_tools: Option<Vec<ChatCompletionTool>>,
+}
+
+impl Turn {
+    /// Turn for a user message with the configured defaults
+    pub fn user(text: impl Into<String>) -> Self {
+        Self {
+            input: TurnInput::User(text.into()),
+            overrides: RequestOverrides::default(),
+            client_tools: None,
+        }
+    }
+}
+
+/// Session status of a turn that failed with `class`
+pub fn failure_status(class: ErrorClass) -> &'static str {
+    match class {
+        ErrorClass::Cancelled => "cancelled",
+        ErrorClass::Timeout => "timeout",
+        _ => "error",
+    }
+}
+
+/// A model reply, after its text went to the sink
+struct Reply {
+    text: String,
+    tool_calls: Option<Vec<ChatCompletionMessageToolCall>>,
+    usage: Option<TokenUsage>,
+    /// Text outputs sent for the reply
+    segments: u32,
+    /// Structured reply fields besides the spoken text
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+/// LLM participant: sessions, provider clients, tools and usage
+pub struct ConversationEngine {
+    config: Config,
+    clients: HashMap<String, Arc<dyn ChatClient>>,
+    tool_set: Option<Arc<Mutex<ToolSet>>>,
+    anchor_context: Option<String>,
+    /// MCP resources added to the system context of every session
+    mcp_resources: Vec<String>,
+    sessions: HashMap<String, ChatSession>,
+    /// Name reported on metrics
+    participant: String,
+    /// Usage of everything this participant sent, for metrics and budgets
+    usage: UsageTotals,
+    cancellation: Arc<RequestCancellationManager>,
+    /// Saved sessions of this participant, with `[session_store]`
+    session_store: Option<SessionStore>,
+    /// Shared limits by provider ID, with `[rate_limits]`
+    rate_limits: HashMap<String, Arc<RateLimiter>>,
+}
+
+impl ConversationEngine {
+    /// Engine for `config`: starts the MCP servers and built-in tools, reads
+    /// the MCP resources and anchor context and creates the provider clients.
+    pub async fn new(config: Config, participant: impl Into<String>) -> Self {
+        let tool_set = if config.enable_tools {
+            match config.init_tool_set().await {
+                Ok(ts) => ts.map(|ts| Arc::new(Mutex::new(ts))),
+                Err(e) => {
+                    eprintln!("Warning: Failed to initialize MCP tools: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let mcp_resources = load_mcp_resources(&config, &tool_set).await;
+        let clients = config.create_clients();
+
+        let mut engine = Self::with_clients(config, clients, participant);
+        engine.tool_set = tool_set;
+        engine.mcp_resources = mcp_resources;
+        engine
+    }
+
+    /// Engine using the given provider clients (by provider ID), without tools
+    pub fn with_clients(
+        config: Config,
+        clients: HashMap<String, Arc<dyn ChatClient>>,
+        participant: impl Into<String>,
+    ) -> Self {
+        let participant = participant.into();
+        Self {
+            anchor_context: load_anchor_context_for_session(&config),
+            session_store: config.session_store.as_ref().map(|c| SessionStore::new(c, &participant)),
+            rate_limits: config.rate_limiters(),
+            config,
+            clients,
+            tool_set: None,
+            mcp_resources: Vec::new(),
+            sessions: HashMap::new(),
+            participant,
+            usage: UsageTotals::default(),
+            cancellation: Arc::new(RequestCancellationManager::new()),
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn mcp_resources(&self) -> &[String] {
+        &self.mcp_resources
+    }
+
+    /// Usage of everything this participant sent
+    pub fn usage(&self) -> &UsageTotals {
+        &self.usage
+    }
+
+    /// Running MCP servers, for resources and prompts
+    pub fn mcp_servers(&self) -> Vec<(String, ServerSink)> {
+        self.tool_set
+            .as_ref()
+            .and_then(|ts| ts.lock().ok())
+            .map(|ts| ts.servers())
+            .unwrap_or_default()
+    }
+
+    /// New session with the configured system prompt, anchor context, MCP
+    /// resources and tools
+    pub fn new_session(&self) -> ChatSession {
+        let mut session =
+            ChatSession::new(self.config.system_prompt.clone(), self.anchor_context.clone());
+        for resource in &self.mcp_resources {
+            session.add_resource(resource.clone());
+        }
+        if let Some(ts) = &self.tool_set {
+            session.set_tool_set(ts.clone());
+        }
+        session
+    }
+
+    pub fn has_session(&self, session_id: &str) -> bool {
+        self.sessions.contains_key(session_id)
+    }
+
+    /// Session `session_id`, created (or restored, with `autoload`) on first use
+    pub fn session(&mut self, session_id: &str) -> &mut ChatSession {
+        if !self.sessions.contains_key(session_id) {
+            let session = match self.autoload_session(session_id) {
+                Ok(session) => session.unwrap_or_else(|| self.new_session()),
+                Err(e) => {
+                    eprintln!("Warning: Failed to restore session {}: {:#}", session_id, e);
+                    self.new_session()
+                }
+            };
+            self.sessions.insert(session_id.to_string(), session);
+        }
+        self.sessions.get_mut(session_id).unwrap()
+    }
+
+    /// The saved `session_id` when `autoload` is on and it was saved before
+    fn autoload_session(&self, session_id: &str) -> Result<Option<ChatSession>> {
+        let Some(store) = self.session_store.as_ref().filter(|store| store.autoload) else {
+            return Ok(None);
+        };
+        Ok(store.load(session_id)?.map(|snapshot| {
+            let mut session = self.new_session();
+            session.restore(snapshot);
+            session
+        }))
+    }
+
+    fn store(&self) -> Result<&SessionStore> {
+        self.session_store
+            .as_ref()
+            .ok_or_else(|| eyre!("No [session_store] configured"))
+    }
+
+    /// Save session `session_id` as `name` (default: the session ID)
+    pub fn save_session(&self, session_id: &str, name: Option<&str>) -> Result<PathBuf> {
+        let store = self.store()?;
+        let session = self
+            .sessions
+            .get(session_id)
+            .ok_or_else(|| eyre!("No session {}", session_id))?;
+        store.save(name.unwrap_or(session_id), &session.snapshot(session_id, &self.participant))
+    }
+
+    /// Replace session `session_id` with the saved `name` (default: the
+    /// session ID); false if nothing was saved under that name
+    pub fn load_session(&mut self, session_id: &str, name: Option<&str>) -> Result<bool> {
+        let Some(snapshot) = self.store()?.load(name.unwrap_or(session_id))? else {
+            return Ok(false);
+        };
+        self.session(session_id).restore(snapshot);
+        Ok(true)
+    }
+
+    /// Copy session `session_id` to a new session `new_session_id`, which is
+    /// saved too when a session store is configured
+    pub fn fork_session(&mut self, session_id: &str, new_session_id: &str) -> Result<Option<PathBuf>> {
+        let participant = self.participant.clone();
+        let snapshot = self.session(session_id).snapshot(new_session_id, &participant);
+        let mut fork = self.new_session();
+        fork.restore(snapshot.clone());
+        self.sessions.insert(new_session_id.to_string(), fork);
+        match &self.session_store {
+            Some(store) => store.save(new_session_id, &snapshot).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Save session `session_id` if `autosave` is on
+    pub fn autosave_session(&self, session_id: &str) -> Result<Option<PathBuf>> {
+        if !self.session_store.as_ref().is_some_and(|store| store.autosave) {
+            return Ok(None);
+        }
+        if !self.sessions.contains_key(session_id) {
+            return Ok(None);
+        }
+        self.save_session(session_id, None).map(Some)
+    }
+
+    /// Drop a session; false if there was none
+    pub fn remove_session(&mut self, session_id: &str) -> bool {
+        self.sessions.remove(session_id).is_some()
+    }
+
+    /// Clear a session's history and usage; false if there was none
+    pub fn reset_session(&mut self, session_id: &str) -> bool {
+        match self.sessions.get_mut(session_id) {
+            Some(session) => {
+                session.reset();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Cancel the streaming requests of a session, returning how many
+    pub async fn cancel_session(&self, session_id: &str) -> usize {
+        self.cancellation.cancel_session(session_id).await
+    }
+
+    /// Keep an image for the next user message of session `session_id`
+    pub fn attach_image(&mut self, session_id: &str, image: ImageInput) {
+        self.session(session_id).attach_image(image);
+    }
+
+    /// Add an assistant message produced elsewhere (another participant's
+    /// answer) to the history without calling the model
+    pub fn add_context(&mut self, session_id: &str, text: String) {
+        let (max_exchanges, max_tokens) =
+            (self.config.max_history_exchanges, self.config.max_context_tokens);
+        let session = self.session(session_id);
+        session.add_assistant_message(text);
+        session.manage_history(max_exchanges, max_tokens);
+    }
+
+    /// Switch to `new_config`: rebuild the provider clients and apply the new
+    /// system prompt to existing sessions (their history is kept). On error
+    /// the running config stays in place. MCP servers are not restarted.
+    pub async fn reload<S: OutputSink + ?Sized>(
+        &mut self,
+        new_config: Config,
+        sink: &mut S,
+    ) -> Result<()> {
+        if new_config.route_chain(&new_config.default_model).is_none() {
+            return Err(eyre!("No route found for model: {}", new_config.default_model));
+        }
+
+        for (level, message) in new_config.probe_local_providers().await {
+            sink.log(level, &message)?;
+        }
+        if new_config.enable_tools != self.config.enable_tools
+            || new_config.enable_local_mcp != self.config.enable_local_mcp
+        {
+            sink.log("WARNING", "Tool settings changed; restart the node to apply them")?;
+        }
+
+        self.anchor_context = load_anchor_context_for_session(&new_config);
+        for session in self.sessions.values_mut() {
+            session.set_system_prompt(new_config.system_prompt.clone(), self.anchor_context.clone());
+            session.manage_history(new_config.max_history_exchanges, new_config.max_context_tokens);
+        }
+
+        sink.log(
+            "INFO",
+            &format!(
+                "Config reloaded: model {} -> {}, {} providers, {} sessions updated",
+                self.config.default_model,
+                new_config.default_model,
+                new_config.providers.len(),
+                self.sessions.len()
+            ),
+        )?;
+        self.clients = new_config.create_clients();
+        self.rate_limits = new_config.rate_limiters();
+        self.session_store = new_config
+            .session_store
+            .as_ref()
+            .map(|c| SessionStore::new(c, &self.participant));
+        self.config = new_config;
+
+        sink.status("reloaded", None)
+    }
+
+    /// Run one turn of session `session_id`.
+    ///
+    /// The reply goes to `sink` as text segments followed by an `ended`
+    /// marker; local tool calls are executed and answered in the same turn.
+    /// Provider errors are reported to the sink, not returned; an `Err` means
+    /// the sink failed or the model has no route.
+    pub async fn run_turn<S: OutputSink + ?Sized>(
+        &mut self,
+        session_id: &str,
+        turn: Turn,
+        sink: &mut S,
+    ) -> Result<()> {
+        let mut session = match self.sessions.remove(session_id) {
+            Some(session) => session,
+            None => match self.autoload_session(session_id) {
+                Ok(Some(session)) => {
+                    sink.log("INFO", &format!("Restored saved session {}", session_id))?;
+                    session
+                }
+                Ok(None) => self.new_session(),
+                Err(e) => {
+                    sink.log("WARNING", &format!("Failed to restore session {}: {:#}", session_id, e))?;
+                    self.new_session()
+                }
+            },
+        };
+        let result = self.turn(&mut session, session_id, turn, sink).await;
+        self.sessions.insert(session_id.to_string(), session);
+        if let Err(e) = self.autosave_session(session_id) {
+            sink.log("WARNING", &format!("Failed to save session {}: {:#}", session_id, e))?;
+        }
+        result
+    }
+
+    async fn turn<S: OutputSink + ?Sized>(
+        &mut self,
+        session: &mut ChatSession,
+        session_id: &str,
+        turn: Turn,
+        sink: &mut S,
+    ) -> Result<()> {
+        if let Some(reason) = self.config.budget.exceeded(&session.usage, &self.usage) {
+            return self.send_budget_exhausted(sink, &reason);
+        }
+
+        match turn.input {
+            TurnInput::User(text) => {
+                let images = std::mem::take(&mut session.pending_images);
+                if images.is_empty() {
+                    session.add_user_message(text);
+                } else {
+                    sink.log("INFO", &format!("Attaching {} image(s) to the user message", images.len()))?;
+                    session.add_user_message_with_images(text, &images)?;
+                }
+                session.manage_history(self.config.max_history_exchanges, self.config.max_context_tokens);
+            }
+            TurnInput::ToolResults(results) => {
+                for (tool_call_id, result) in results {
+                    session.add_tool_message(tool_call_id, result);
+                }
+            }
+        }
+
+        let model_id = turn
+            .overrides
+            .model
+            .clone()
+            .unwrap_or_else(|| self.config.default_model.clone());
+
+        // Local tool calls are answered right away, without waiting for
+        // the next user message
+        loop {
+            let route = self
+                .config
+                .route_chain(&model_id)
+                .ok_or_else(|| eyre!("No route found for model: {}", model_id))?;
+            let (provider_id, model_name) = route.targets[0].clone();
+
+            let messages = if !self.config.model_vision(&model_id) && vision::has_images(&session.messages) {
+                sink.log("DEBUG", &format!("Model '{}' does not accept images, sending placeholders", model_id))?;
+                vision::strip_images(&session.messages)?
+            } else {
+                session.messages.clone()
+            };
+            let mut request = CreateChatCompletionRequest::new(model_name.clone(), messages);
+            request.tools = self.tool_definitions(session, turn.client_tools.as_ref(), sink)?;
+            request.stream = self.config.enable_streaming;
+            request.temperature = Some(0.7);
+            if let Err(e) = turn.overrides.apply(&mut request) {
+                sink.log("WARNING", &format!("Ignoring request overrides: {}", e))?;
+            }
+
+            // An explicit `response_format` override takes the request out of
+            // structured mode
+            let mut structured = self.config.structured_output.enabled
+                && turn.overrides.response_format.is_none();
+            if structured {
+                let mode = self.config.structured_mode(&provider_id);
+                if let Err(e) = self.config.structured_output.apply(mode, &mut request) {
+                    sink.log("WARNING", &format!("Requesting plain text, structured output failed: {}", e))?;
+                    structured = false;
+                } else {
+                    sink.log("DEBUG", &format!("Requesting a structured reply ({:?} mode)", mode))?;
+                }
+            }
+
+            let (queued_tx, mut queued) = tokio::sync::mpsc::unbounded_channel();
+            let client = Arc::new(
+                FailoverClient::new(&route, &self.clients)?
+                    .with_rate_limits(self.rate_limits.clone(), Some(queued_tx)),
+            );
+            sink.log(
+                "DEBUG",
+                &format!("Routing to provider '{}' with model '{}'", provider_id, model_name),
+            )?;
+            sink.status("processing", None)?;
+
+            let reply = if self.config.enable_streaming.unwrap_or(false) {
+                self.stream_reply(client.clone(), request, session_id, structured, &mut queued, sink)
+                    .await?
+            } else {
+                self.complete_reply(&client, request, structured, &mut queued, sink).await?
+            };
+            let Some(reply) = reply else {
+                return Ok(());
+            };
+
+            let request_usage = session.record_usage(
+                reply.usage,
+                &reply.text,
+                self.config.model_pricing(&model_id),
+            );
+            self.usage.record(&request_usage.usage, request_usage.cost);
+            self.send_metrics(sink, session_id, session, request_usage, &client)?;
+
+            let mut continue_turn = false;
+            let mut finished = false;
+            match reply.tool_calls {
+                Some(tool_calls) if !tool_calls.is_empty() => {
+                    sink.log("INFO", &format!("Received {} tool calls", tool_calls.len()))?;
+                    session.add_assistant_message_with_tools(reply.text, tool_calls.clone());
+
+                    if self.config.enable_local_mcp {
+                        sink.log("INFO", "Executing tool calls locally")?;
+                        for (tool_call_id, result) in execute_tool_calls(session, &tool_calls, sink).await? {
+                            session.add_tool_message(tool_call_id, result);
+                        }
+                        sink.log("DEBUG", "Sending tool results back to LLM for final response")?;
+                        continue_turn = true;
+                    } else {
+                        // Wait for the client's `tool_results`
+                        sink.log("INFO", "Passing tool calls to client")?;
+                        sink.tool_calls(&tool_calls)?;
+                    }
+                }
+                _ => {
+                    if !reply.text.is_empty() {
+                        session.add_assistant_message(reply.text);
+                    }
+                    finished = true;
+                }
+            }
+
+            if reply.segments > 0 || !reply.fields.is_empty() {
+                sink.text(TextOutput {
+                    session_status: "ended",
+                    segment_index: Some(reply.segments),
+                    fields: (!reply.fields.is_empty()).then_some(&reply.fields),
+                    ..Default::default()
+                })?;
+            }
+            sink.status("complete", client.served_by().as_ref())?;
+
+            if finished {
+                // The reply is out; condense old turns before trimming would drop them
+                if let Err(e) = self.summarize_history(session, session_id, sink).await {
+                    sink.log("WARNING", &format!("Summarization failed, keeping history: {}", e))?;
+                }
+                session.manage_history(self.config.max_history_exchanges, self.config.max_context_tokens);
+            }
+            if !continue_turn {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Tool definitions for a request: the local tools, or the ones the
+    /// client sent when tools are passed through
+    fn tool_definitions<S: OutputSink + ?Sized>(
+        &self,
+        session: &ChatSession,
+        client_tools: Option<&Vec<ChatCompletionTool>>,
+        sink: &mut S,
+    ) -> Result<Option<Vec<ChatCompletionTool>>> {
+        if !self.config.enable_tools {
+            return Ok(None);
+        }
+        if self.config.enable_local_mcp {
+            if !session.has_tools() {
+                return Ok(None);
+            }
+            let tools = session.get_tool_definitions();
+            sink.log(
+                "DEBUG",
+                &format!(
+                    "Added {} local MCP tool definitions",
+                    tools.as_ref().map(|t| t.len()).unwrap_or(0)
+                ),
+            )?;
+            return Ok(tools);
+        }
+        if let Some(tools) = client_tools {
+            sink.log("DEBUG", &format!("Added {} client-provided tool definitions", tools.len()))?;
+        }
+        Ok(client_tools.cloned())
+    }
+
+    /// Stream a reply, sending each segment as it is cut. For structured
+    /// replies only the spoken text field is segmented.
+    /// `None` when the request failed (already reported).
+    async fn stream_reply<S: OutputSink + ?Sized>(
+        &self,
+        client: Arc<FailoverClient>,
+        request: CreateChatCompletionRequest,
+        session_id: &str,
+        structured: bool,
+        queued: &mut tokio::sync::mpsc::UnboundedReceiver<RateLimitWait>,
+        sink: &mut S,
+    ) -> Result<Option<Reply>> {
+        sink.log("DEBUG", "Using streaming mode")?;
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+        let request_id = uuid::Uuid::new_v4().to_string();
+        let cancellation_token = if self.config.enable_cancellation {
+            Some(
+                self.cancellation
+                    .create_token(request_id.clone(), session_id.to_string())
+                    .await,
+            )
+        } else {
+            None
+        };
+        let timeout = Duration::from_secs(self.config.stream_timeout_secs);
+        let cancellation = self.cancellation.clone();
+        let session_id_clone = session_id.to_string();
+
+        let stream_handle = tokio::spawn(async move {
+            match cancellation_token {
+                Some(token) => {
+                    let result = client
+                        .complete_streaming_with_cancellation(request, tx, token, timeout)
+                        .await;
+                    cancellation.cleanup_request(&request_id, &session_id_clone).await;
+                    result
+                }
+                None => client.complete_streaming(request, tx).await,
+            }
+        });
+
+        // Buffer chunks into segments for TTS
+        let mut segmenter = StreamSegmenter::from_config(&self.config.segmenter);
+        let mut extractor =
+            structured.then(|| SpokenTextExtractor::new(&self.config.structured_output.text_field));
+        let mut chunk_count = 0;
+        let mut segments = 0;
+
+        loop {
+            let chunk = tokio::select! {
+                biased;
+                chunk = rx.recv() => chunk,
+                Some(wait) = queued.recv() => {
+                    sink.queued(&wait)?;
+                    continue;
+                }
+            };
+            let Some(chunk) = chunk else {
+                break;
+            };
+            chunk_count += 1;
+            let spoken = match extractor.as_mut() {
+                Some(extractor) => extractor.push(&chunk),
+                None => chunk,
+            };
+            if let Some(segment) = segmenter.add_chunk(&spoken) {
+                self.send_segment(sink, &segment, segmenter.hints(), segments)?;
+                segments += 1;
+            }
+        }
+
+        let mut fields = serde_json::Map::new();
+        if let Some(extractor) = &extractor {
+            let reply = structured::parse_reply(extractor.raw(), &self.config.structured_output.text_field);
+            if !extractor.emitted() {
+                // Not the expected JSON shape; speak whatever text it had
+                if !reply.text.is_empty() {
+                    sink.log("DEBUG", "Spoken text field not found while streaming, using the parsed reply")?;
+                }
+                for word in reply.text.split_inclusive(' ') {
+                    if let Some(segment) = segmenter.add_chunk(word) {
+                        self.send_segment(sink, &segment, segmenter.hints(), segments)?;
+                        segments += 1;
+                    }
+                }
+            }
+            fields = reply.fields;
+        }
+        if let Some(segment) = segmenter.flush() {
+            self.send_segment(sink, &segment, segmenter.hints(), segments)?;
+            segments += 1;
+            sink.log(
+                "DEBUG",
+                &format!("Sent final segment {} ({} chars)", segments, segment.len()),
+            )?;
+        }
+
+        match stream_handle.await {
+            Ok(Ok((text, tool_calls, usage))) => {
+                sink.log(
+                    "INFO",
+                    &format!(
+                        "Streaming complete: {} chars in {} segments (from {} chunks)",
+                        text.len(),
+                        segments,
+                        chunk_count
+                    ),
+                )?;
+                Ok(Some(Reply {
+                    text,
+                    tool_calls,
+                    usage,
+                    segments,
+                    fields,
+                }))
+            }
+            Ok(Err(e)) => {
+                report_error(sink, "Streaming error", &e)?;
+                Ok(None)
+            }
+            Err(e) => {
+                report_error(sink, "Stream task failed", &eyre!("{}", e))?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Request a whole reply and send it (or its spoken text field) as one
+    /// segment. `None` when the request failed (already reported).
+    async fn complete_reply<S: OutputSink + ?Sized>(
+        &self,
+        client: &FailoverClient,
+        request: CreateChatCompletionRequest,
+        structured: bool,
+        queued: &mut tokio::sync::mpsc::UnboundedReceiver<RateLimitWait>,
+        sink: &mut S,
+    ) -> Result<Option<Reply>> {
+        sink.log("DEBUG", "Using non-streaming mode")?;
+
+        let response = client.complete(request);
+        tokio::pin!(response);
+        let response = loop {
+            tokio::select! {
+                response = &mut response => break response,
+                Some(wait) = queued.recv() => sink.queued(&wait)?,
+            }
+        };
+        let response = match response {
+            Ok(response) => response,
+            Err(e) => {
+                report_error(sink, "API error", &e)?;
+                return Ok(None);
+            }
+        };
+        let usage = TokenUsage::from_response(&response);
+        let Some(choice) = response.choices.into_iter().next() else {
+            report_error(sink, "API error", &eyre!("response has no choices"))?;
+            return Ok(None);
+        };
+
+        let text = choice.message.content.unwrap_or_default();
+        sink.log("INFO", &format!("Generated response ({} chars)", text.len()))?;
+
+        // History keeps the raw JSON so the model sees its own format
+        let (spoken, fields) = if structured {
+            let reply = structured::parse_reply(&text, &self.config.structured_output.text_field);
+            (reply.text, reply.fields)
+        } else {
+            (text.clone(), serde_json::Map::new())
+        };
+
+        let mut segments = 0;
+        if !spoken.is_empty() {
+            sink.text(TextOutput {
+                text: &spoken,
+                session_status: "started",
+                segment_index: Some(0),
+                ..Default::default()
+            })?;
+            segments = 1;
+        }
+        Ok(Some(Reply {
+            text,
+            tool_calls: choice.message.tool_calls,
+            usage,
+            segments,
+            fields,
+        }))
+    }
+
+    fn send_segment<S: OutputSink + ?Sized>(
+        &self,
+        sink: &mut S,
+        segment: &str,
+        hints: &ProsodyHints,
+        index: u32,
+    ) -> Result<()> {
+        let config = &self.config.segmenter;
+        sink.text(TextOutput {
+            text: segment,
+            session_status: if index == 0 { "started" } else { "ongoing" },
+            segment_index: Some(index),
+            hints: config.prosody_hints.then(|| hints.clone()),
+            ssml: (config.prosody_hints && config.ssml).then(|| to_ssml(segment, hints)),
+            ..Default::default()
+        })
+    }
+
+    // Report a completed request's usage
+    fn send_metrics<S: OutputSink + ?Sized>(
+        &self,
+        sink: &mut S,
+        session_id: &str,
+        session: &ChatSession,
+        request: RequestUsage,
+        client: &FailoverClient,
+    ) -> Result<()> {
+        let served = client.served_by();
+        let report = UsageReport {
+            participant: &self.participant,
+            session_id,
+            provider: served.as_ref().map(|s| s.provider_id.clone()),
+            model: served.map(|s| s.model),
+            request,
+            session: &session.usage,
+            participant_total: &self.usage,
+            budget_exceeded: self.config.budget.exceeded(&session.usage, &self.usage),
+        };
+        sink.metrics(&report)
+    }
+
+    // End the turn without calling the model once the budget is used up
+    fn send_budget_exhausted<S: OutputSink + ?Sized>(&self, sink: &mut S, reason: &str) -> Result<()> {
+        sink.log("WARNING", &format!("Budget exhausted, skipping request: {}", reason))?;
+
+        if let Some(message) = self.config.budget.exhausted_message.as_deref().filter(|m| !m.is_empty()) {
+            sink.text(TextOutput {
+                text: message,
+                session_status: "started",
+                ..Default::default()
+            })?;
+        }
+        sink.text(TextOutput {
+            session_status: "ended",
+            error_type: Some("budget_exceeded"),
+            error_message: Some(reason),
+            ..Default::default()
+        })?;
+        sink.status("budget_exceeded", None)
+    }
+
+    // Condense old turns into the session's running summary once the prompt
+    // grows past the configured threshold. Cancelling the session skips it.
+    async fn summarize_history<S: OutputSink + ?Sized>(
+        &mut self,
+        session: &mut ChatSession,
+        session_id: &str,
+        sink: &mut S,
+    ) -> Result<()> {
+        let Some(summarization) = &self.config.summarization else {
+            return Ok(());
+        };
+        if session.estimated_prompt_tokens() <= summarization.trigger_tokens as u64 {
+            return Ok(());
+        }
+        let start = session.history_start();
+        let Some(end) = summary::split_point(
+            &session.messages,
+            start,
+            summarization.keep_recent_exchanges,
+        ) else {
+            return Ok(());
+        };
+
+        let model_id = summarization.model.as_deref().unwrap_or(&self.config.default_model);
+        let route = self
+            .config
+            .route_chain(model_id)
+            .ok_or_else(|| eyre!("No route found for summary model: {}", model_id))?;
+        let client =
+            FailoverClient::new(&route, &self.clients)?.with_rate_limits(self.rate_limits.clone(), None);
+        let request = summary::summary_request(
+            summarization,
+            &route.targets[0].1,
+            session.summary.as_deref(),
+            &session.messages[start..end],
+        )?;
+
+        let response = if self.config.enable_cancellation {
+            let request_id = uuid::Uuid::new_v4().to_string();
+            let token = self
+                .cancellation
+                .create_token(request_id.clone(), session_id.to_string())
+                .await;
+            let response = tokio::select! {
+                response = client.complete(request) => Some(response),
+                _ = token.cancelled() => None,
+            };
+            self.cancellation.cleanup_request(&request_id, session_id).await;
+            response
+        } else {
+            Some(client.complete(request).await)
+        };
+        let Some(response) = response else {
+            sink.log("INFO", "Summarization cancelled, keeping history")?;
+            return Ok(());
+        };
+        let response = response?;
+        let text = response
+            .choices
+            .first()
+            .and_then(|choice| choice.message.content.clone())
+            .unwrap_or_default();
+        if text.trim().is_empty() {
+            return Err(eyre!("summary model returned no text"));
+        }
+
+        // Summaries count against the participant's usage and budget
+        let usage = TokenUsage::from_response(&response).unwrap_or_else(|| {
+            let prompt: u64 = session.messages[start..end].iter().map(message_tokens).sum();
+            TokenUsage::new(prompt, estimate_tokens(&text))
+        });
+        let cost = self.config.model_pricing(model_id).map(|p| p.cost(&usage)).unwrap_or(0.0);
+        session.usage.record(&usage, cost);
+        self.usage.record(&usage, cost);
+
+        session.apply_summary(text, end);
+        sink.log(
+            "INFO",
+            &format!(
+                "Summarized {} old messages, prompt now ~{} tokens",
+                end - start,
+                session.estimated_prompt_tokens()
+            ),
+        )?;
+        Ok(())
+    }
+}
+
+// Send the error of a failed request as status and as the turn's last text
+fn report_error<S: OutputSink + ?Sized>(sink: &mut S, context: &str, error: &eyre::Report) -> Result<()> {
+    let message = error.to_string();
+    sink.log("ERROR", &format!("{}: {}", context, message))?;
+
+    let class = ErrorClass::classify(error);
+    let status = failure_status(class);
+    sink.status(status, None)?;
+
+    // A cancelled turn ends with empty text so no error reaches TTS
+    let text = if class == ErrorClass::Cancelled {
+        String::new()
+    } else {
+        format!("Error: {}", message)
+    };
+    sink.text(TextOutput {
+        text: &text,
+        session_status: status,
+        error_type: Some(class.as_str()),
+        error_message: Some(&message),
+        ..Default::default()
+    })
+}
+
+// Run tool calls against the session's tools, returning `(tool_call_id, result)`
+async fn execute_tool_calls<S: OutputSink + ?Sized>(
+    session: &ChatSession,
+    tool_calls: &[ChatCompletionMessageToolCall],
+    sink: &mut S,
+) -> Result<Vec<(String, String)>> {
+    let mut results = Vec::new();
+    let Some(tool_set) = &session.tool_set else {
+        return Ok(results);
+    };
+
+    for tool_call in tool_calls {
+        sink.log(
+            "DEBUG",
+            &format!(
+                "Calling tool: {} with args: {}",
+                tool_call.function.name, tool_call.function.arguments
+            ),
+        )?;
+
+        let tool = tool_set.lock().unwrap().get_tool(&tool_call.function.name);
+        let result = match tool {
+            Some(tool) => {
+                let args: serde_json::Value =
+                    serde_json::from_str(&tool_call.function.arguments).unwrap_or(serde_json::Value::Null);
+                match tool.call(args).await {
+                    Ok(result) => {
+                        let content = match result.content {
+                            Some(contents) => contents
+                                .iter()
+                                .filter_map(|c| c.as_text())
+                                .map(|t| t.text.clone())
+                                .collect::<Vec<_>>()
+                                .join("\n"),
+                            None => "Tool executed successfully".to_string(),
+                        };
+                        sink.log("DEBUG", &format!("Tool result: {}", content))?;
+                        content
+                    }
+                    Err(e) => {
+                        sink.log("ERROR", &format!("Tool execution failed: {}", e))?;
+                        format!("Error: {}", e)
+                    }
+                }
+            }
+            None => {
+                let msg = format!("Tool '{}' not found", tool_call.function.name);
+                sink.log("ERROR", &msg)?;
+                msg
+            }
+        };
+        results.push((tool_call.id.clone(), result));
+    }
+    Ok(results)
+}
+
+/// Read the `[[mcp.resources]]` of the config, formatted for the system context
+async fn load_mcp_resources(
+    config: &Config,
+    tool_set: &Option<Arc<Mutex<ToolSet>>>,
+) -> Vec<String> {
+    let Some(mcp) = &config.mcp else {
+        return Vec::new();
+    };
+    let servers = tool_set
+        .as_ref()
+        .and_then(|ts| ts.lock().ok())
+        .map(|ts| ts.servers())
+        .unwrap_or_default();
+    let mut resources = Vec::new();
+    for resource in &mcp.resources {
+        match mcp_context::read_resource(&servers, resource.server.as_deref(), &resource.uri).await {
+            Ok(text) => {
+                eprintln!("✅ Loaded MCP resource: {}", resource.uri);
+                resources.push(text);
+            }
+            Err(e) => {
+                eprintln!("⚠️ Warning: Failed to read MCP resource '{}': {}", resource.uri, e);
+            }
+        }
+    }
+    resources
+}
+
+/// Load and format anchor context for a given configuration
+fn load_anchor_context_for_session(config: &Config) -> Option<String> {
+    if let Some(ref context_path) = config.anchor_context {
+        match load_anchor_context(context_path) {
+            Ok(context_content) => {
+                let formatted = format_anchor_context(&context_content);
+                eprintln!("✅ Loaded anchor context from: {}", context_path);
+                Some(formatted)
+            }
+            Err(e) => {
+                eprintln!("⚠️ Warning: Failed to load anchor context from '{}': {}", context_path, e);
+                eprintln!("⚠️ Proceeding without anchor context");
+                None
+            }
+        }
+    } else {
+        None
+    }
+}
+
+/// Manages active request cancellation tokens
+struct RequestCancellationManager {
+    /// Active tokens by request_id
+    active_tokens: Arc<AsyncMutex<HashMap<String, CancellationToken>>>,
+    /// Session mapping for tokens (session_id -> Vec<request_id>)
+    session_requests: Arc<AsyncMutex<HashMap<String, Vec<String>>>>,
+}
+
+impl RequestCancellationManager {
+    fn new() -> Self {
+        Self {
+            active_tokens: Arc::new(AsyncMutex::new(HashMap::new())),
+            session_requests: Arc::new(AsyncMutex::new(HashMap::new())),
+        }
+    }
+
+    /// Create a new cancellation token for a request
+    async fn create_token(
+        &self,
+        request_id: String,
+        session_id: String,
+    ) -> CancellationToken {
+        let token = CancellationToken::new();
+
+        // Store token
+        let mut tokens = self.active_tokens.lock().await;
+        tokens.insert(request_id.clone(), token.clone());
+        drop(tokens);
+
+        // Track session -> request mapping
+        let mut sessions = self.session_requests.lock().await;
+        sessions
+            .entry(session_id)
+            .or_insert_with(Vec::new)
+            .push(request_id);
+
+        token
+    }
+
+    /// Cancel a specific request by ID
+    async fn cancel_request(&self, request_id: &str) -> bool {
+        let mut tokens = self.active_tokens.lock().await;
+        if let Some(token) = tokens.remove(request_id) {
+            token.cancel();
+            eprintln!("[CANCELLATION] Cancelled request: {}", request_id);
+            true
+        } else {
+            eprintln!("[CANCELLATION] Request not found: {}", request_id);
+            false
+        }
+    }
+
+    /// Cancel all requests for a session
+    async fn cancel_session(&self, session_id: &str) -> usize {
+        let mut sessions = self.session_requests.lock().await;
+        let request_ids = sessions.remove(session_id).unwrap_or_default();
+        drop(sessions);
+
+        let mut cancelled_count = 0;
+        for request_id in request_ids {
+            if self.cancel_request(&request_id).await {
+                cancelled_count += 1;
+            }
+        }
+
+        // Only show cancellation message if there were actual requests to cancel
+        if cancelled_count > 0 {
+            eprintln!("[CANCELLATION] Cancelled {} requests for session: {}", cancelled_count, session_id);
+        } else {
+            // During startup, show a more informative message
+            if session_id == "default" {
+                eprintln!("[INFO] node ready - starting dataflow");
+            } else {
+                eprintln!("[INFO] {} ready - no active requests to cancel", session_id);
+            }
+        }
+        cancelled_count
+    }
+
+    /// Clean up completed request
+    async fn cleanup_request(&self, request_id: &str, session_id: &str) {
+        let mut tokens = self.active_tokens.lock().await;
+        tokens.remove(request_id);
+        drop(tokens);
+
+        let mut sessions = self.session_requests.lock().await;
+        if let Some(request_ids) = sessions.get_mut(session_id) {
+            request_ids.retain(|id| id != request_id);
+            if request_ids.is_empty() {
+                sessions.remove(session_id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockClient, MockResponse};
+
+    /// Provider streaming `chunks` for every request
+    fn replying(chunks: &[&str]) -> MockClient {
+        let response = MockResponse {
+            chunks: chunks.iter().map(|c| c.to_string()).collect(),
+            ..Default::default()
+        };
+        MockClient::with_responses("mock", vec![response], 0)
+    }
+
+    /// Records sink calls as `kind:value` strings
+    #[derive(Default)]
+    struct RecordingSink {
+        events: Vec<String>,
+    }
+
+    impl OutputSink for RecordingSink {
+        fn log(&mut self, _level: &str, _message: &str) -> Result<()> {
+            Ok(())
+        }
+
+        fn status(&mut self, status: &str, _served_by: Option<&ServedBy>) -> Result<()> {
+            self.events.push(format!("status:{}", status));
+            Ok(())
+        }
+
+        fn text(&mut self, output: TextOutput<'_>) -> Result<()> {
+            self.events.push(format!(
+                "text:{}:{}:{}",
+                output.session_status,
+                output.error_type.unwrap_or(""),
+                output.text
+            ));
+            if let Some(fields) = output.fields {
+                self.events.push(format!("fields:{}:{}", fields.len(), fields["next_speaker"]));
+            }
+            Ok(())
+        }
+
+        fn tool_calls(&mut self, _tool_calls: &[ChatCompletionMessageToolCall]) -> Result<()> {
+            self.events.push("tool_calls".to_string());
+            Ok(())
+        }
+
+        fn metrics(&mut self, report: &UsageReport<'_>) -> Result<()> {
+            self.events.push(format!("metrics:{}", report.participant));
+            Ok(())
+        }
+    }
+
+    /// Engine with a minimal config plus the `extra` top-level settings
+    fn engine(client: MockClient, extra: serde_json::Value) -> ConversationEngine {
+        let mut config = serde_json::json!({
+            "default_model": "test-model",
+            "system_prompt": "You are a tutor.",
+            "max_history_exchanges": 10,
+            "enable_streaming": true,
+            "providers": [],
+            "models": [{ "id": "test-model", "route": { "provider": "mock" } }],
+        });
+        for (key, value) in extra.as_object().unwrap() {
+            config[key] = value.clone();
+        }
+        let config: Config = serde_json::from_value(config).unwrap();
+        let mut clients: HashMap<String, Arc<dyn ChatClient>> = HashMap::new();
+        clients.insert("mock".to_string(), Arc::new(client));
+        ConversationEngine::with_clients(config, clients, "tutor")
+    }
+
+    #[tokio::test]
+    async fn test_streaming_turn() {
+        let client = replying(&["Hello there. ", "How are you?"]);
+        let mut engine = engine(client, serde_json::json!({}));
+        let mut sink = RecordingSink::default();
+
+        engine.run_turn("s1", Turn::user("Hi"), &mut sink).await.unwrap();
+
+        assert_eq!(
+            sink.events,
+            vec![
+                "status:processing",
+                "text:started::Hello there.",
+                "text:ongoing::How are you?",
+                "metrics:tutor",
+                "text:ended::",
+                "status:complete",
+            ]
+        );
+        // System prompt, question and answer
+        assert_eq!(engine.session("s1").messages.len(), 3);
+        assert_eq!(engine.usage().requests, 1);
+    }
+
+    #[tokio::test]
+    async fn test_structured_turn_speaks_text_field() {
+        let client = replying(&[
+            "{\"text\": \"Hello there. ",
+            "How are you?\", \"next_speaker\": \"student1\", ",
+            "\"confidence\": 0.9, \"ends_round\": false}",
+        ]);
+        let mut engine = engine(client, serde_json::json!({ "structured_output": { "enabled": true } }));
+        let mut sink = RecordingSink::default();
+
+        engine.run_turn("s1", Turn::user("Hi"), &mut sink).await.unwrap();
+
+        assert_eq!(
+            sink.events,
+            vec![
+                "status:processing",
+                "text:started::Hello there.",
+                "text:ongoing::How are you?",
+                "metrics:tutor",
+                "text:ended::",
+                r#"fields:3:"student1""#,
+                "status:complete",
+            ]
+        );
+        // History keeps the JSON reply
+        let reply = serde_json::to_value(&engine.session("s1").messages[2]).unwrap();
+        assert!(reply["content"].as_str().unwrap().starts_with("{\"text\""));
+    }
+
+    #[tokio::test]
+    async fn test_failed_turn_is_classified() {
+        let stalled = MockResponse {
+            stall: true,
+            ..Default::default()
+        };
+        let client = MockClient::with_responses("mock", vec![stalled], 0);
+        let mut engine = engine(client, serde_json::json!({}));
+        let mut sink = RecordingSink::default();
+
+        // Cancel the session once its stalled request is registered
+        let cancellation = engine.cancellation.clone();
+        tokio::spawn(async move {
+            while cancellation.cancel_session("s1").await == 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        });
+        engine.run_turn("s1", Turn::user("Hi"), &mut sink).await.unwrap();
+
+        // Cancelled turns end without error text and without an `ended` marker
+        assert_eq!(
+            sink.events,
+            vec!["status:processing", "status:cancelled", "text:cancelled:cancelled:"]
+        );
+        assert_eq!(failure_status(ErrorClass::Timeout), "timeout");
+        assert_eq!(failure_status(ErrorClass::RateLimited), "error");
+    }
+
+    #[tokio::test]
+    async fn test_summary_runs_after_the_reply() {
+        let responses = ["First answer.", "Second answer.", "They greeted each other."]
+            .iter()
+            .map(|text| MockResponse {
+                chunks: vec![text.to_string()],
+                ..Default::default()
+            })
+            .collect();
+        let client = MockClient::with_responses("mock", responses, 0);
+        let summarization = serde_json::json!({ "trigger_tokens": 1, "keep_recent_exchanges": 1 });
+        let mut engine = engine(client, serde_json::json!({ "summarization": summarization }));
+
+        engine.run_turn("s1", Turn::user("Hi"), &mut RecordingSink::default()).await.unwrap();
+        let mut sink = RecordingSink::default();
+        engine.run_turn("s1", Turn::user("How are you?"), &mut sink).await.unwrap();
+
+        // The second request got the reply; the summary came after it
+        assert!(sink.events.contains(&"text:started::Second answer.".to_string()));
+        assert_eq!(sink.events.last().unwrap(), "status:complete");
+        let session = engine.session("s1");
+        assert_eq!(session.summary.as_deref(), Some("They greeted each other."));
+        // System prompt, summary and the last exchange
+        assert_eq!(session.messages.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_budget_exhausted_skips_the_model() {
+        let client = replying(&["Never sent."]);
+        let budget = serde_json::json!({ "max_total_tokens": 0, "exhausted_message": "Out of time." });
+        let mut engine = engine(client, serde_json::json!({ "budget": budget }));
+        let mut sink = RecordingSink::default();
+
+        engine.run_turn("s1", Turn::user("Hi"), &mut sink).await.unwrap();
+
+        assert_eq!(
+            sink.events,
+            vec![
+                "text:started::Out of time.",
+                "text:ended:budget_exceeded:",
+                "status:budget_exceeded",
+            ]
+        );
+        assert_eq!(engine.session("s1").messages.len(), 1);
+    }
+}
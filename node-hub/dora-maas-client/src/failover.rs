@@ -0,0 +1,540 @@
+//! Provider failover and retry for model routes.
+//!
+//! [`FailoverClient`] wraps the ordered providers of a [`RouteChain`] behind the
+//! regular [`ChatClient`] trait. Each failure is classified ([`ErrorClass`]) and
+//! the route's [`RetryPolicy`] decides whether to retry the same provider after
+//! a backoff or move on to the next one:
+//!
+//! - 429 rate limit: retry with exponential backoff, then next provider
+//! - 5xx server error: retry with backoff, then next provider
+//! - timeout / connection error: next provider (retries configurable)
+//! - cancellation: never retried
+//!
+//! A stream that already delivered text is never retried, so listeners do not
+//! hear the start of an answer twice.
+//!
+//! With [`FailoverClient::with_rate_limits`], every attempt first waits for a
+//! permit from its provider's shared [`RateLimiter`].
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use eyre::{Result, eyre};
+use futures::future::BoxFuture;
+use outfox_openai::spec::{CreateChatCompletionRequest, CreateChatCompletionResponse};
+use reqwest::StatusCode;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::client::{ChatClient, ProviderError};
+use crate::config::{RetryPolicy, RouteChain};
+use crate::ratelimit::{RateLimitWait, RateLimiter, request_tokens};
+use crate::streaming::StreamResult;
+
+/// Kind of failure, used to pick a retry strategy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// HTTP 429
+    RateLimited,
+    /// HTTP 5xx
+    ServerError,
+    /// Request or stream timeout, connection failure, truncated stream
+    Timeout,
+    /// Cancelled by the user or controller
+    Cancelled,
+    /// Anything else (4xx, unparseable responses, ...)
+    Other,
+}
+
+impl ErrorClass {
+    /// Classify an error from any provider client by the first provider or
+    /// HTTP error in its chain
+    pub fn classify(error: &eyre::Report) -> Self {
+        for cause in error.chain() {
+            if let Some(error) = cause.downcast_ref::<ProviderError>() {
+                return match error {
+                    ProviderError::Status { status, .. } => Self::from_status(*status),
+                    ProviderError::Transport(_, error) => Self::from_http(error),
+                    ProviderError::Cancelled => ErrorClass::Cancelled,
+                    ProviderError::TimedOut(_) | ProviderError::Truncated(_) => ErrorClass::Timeout,
+                };
+            }
+            if let Some(error) = cause.downcast_ref::<reqwest::Error>() {
+                return Self::from_http(error);
+            }
+        }
+        ErrorClass::Other
+    }
+
+    fn from_status(status: StatusCode) -> Self {
+        match status.as_u16() {
+            429 => ErrorClass::RateLimited,
+            408 => ErrorClass::Timeout,
+            500..=599 => ErrorClass::ServerError,
+            _ => ErrorClass::Other,
+        }
+    }
+
+    /// Timeouts and failed connections, or the status of an HTTP error
+    fn from_http(error: &reqwest::Error) -> Self {
+        if error.is_timeout() || error.is_connect() || error.is_request() || error.is_body() {
+            return ErrorClass::Timeout;
+        }
+        error.status().map_or(ErrorClass::Other, Self::from_status)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorClass::RateLimited => "rate_limited",
+            ErrorClass::ServerError => "server_error",
+            ErrorClass::Timeout => "timeout",
+            ErrorClass::Cancelled => "cancelled",
+            ErrorClass::Other => "error",
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Retries allowed on the same provider for an error class
+    pub fn retries_for(&self, class: ErrorClass) -> u32 {
+        match class {
+            ErrorClass::RateLimited => self.rate_limit_retries,
+            ErrorClass::ServerError => self.server_error_retries,
+            ErrorClass::Timeout => self.timeout_retries,
+            ErrorClass::Cancelled | ErrorClass::Other => 0,
+        }
+    }
+
+    /// Backoff before retry number `retry` (0-based) on the same provider
+    pub fn backoff(&self, class: ErrorClass, retry: u32) -> Duration {
+        let base = match class {
+            ErrorClass::RateLimited => self.rate_limit_backoff_ms,
+            ErrorClass::ServerError => self.server_error_backoff_ms,
+            _ => 0,
+        };
+        let ms = base.saturating_mul(1u64 << retry.min(16)).min(self.max_backoff_ms);
+        Duration::from_millis(ms)
+    }
+
+    /// Whether an error of this class may move on to the next provider
+    pub fn fails_over(&self, class: ErrorClass) -> bool {
+        match class {
+            ErrorClass::Cancelled => false,
+            ErrorClass::Other => self.failover_on_other_errors,
+            _ => true,
+        }
+    }
+}
+
+/// Which provider answered a request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServedBy {
+    pub provider_id: String,
+    pub model: String,
+    /// Total attempts, including failed ones
+    pub attempts: u32,
+}
+
+struct Target {
+    provider_id: String,
+    model: String,
+    client: Arc<dyn ChatClient>,
+}
+
+/// Chat client that tries the providers of a route in order.
+///
+/// Create one per request: [`FailoverClient::served_by`] reports the provider
+/// of the last successful call.
+pub struct FailoverClient {
+    targets: Vec<Target>,
+    retry: RetryPolicy,
+    served_by: Mutex<Option<ServedBy>>,
+    rate_limits: HashMap<String, Arc<RateLimiter>>,
+    /// Told when an attempt waits for a rate limit
+    queued: Option<mpsc::UnboundedSender<RateLimitWait>>,
+}
+
+impl FailoverClient {
+    pub fn new(chain: &RouteChain, clients: &HashMap<String, Arc<dyn ChatClient>>) -> Result<Self> {
+        let targets: Vec<Target> = chain
+            .targets
+            .iter()
+            .filter_map(|(provider_id, model)| match clients.get(provider_id) {
+                Some(client) => Some(Target {
+                    provider_id: provider_id.clone(),
+                    model: model.clone(),
+                    client: client.clone(),
+                }),
+                None => {
+                    eprintln!("Warning: No client found for provider: {}", provider_id);
+                    None
+                }
+            })
+            .collect();
+
+        if targets.is_empty() {
+            let providers: Vec<&str> = chain.targets.iter().map(|(p, _)| p.as_str()).collect();
+            return Err(eyre!("No client found for providers: {}", providers.join(", ")));
+        }
+
+        Ok(Self {
+            targets,
+            retry: chain.retry.clone(),
+            served_by: Mutex::new(None),
+            rate_limits: HashMap::new(),
+            queued: None,
+        })
+    }
+
+    /// Wait for the providers' rate limits (by provider ID) before each
+    /// attempt, sending a notice to `queued` when an attempt has to wait
+    pub fn with_rate_limits(
+        mut self,
+        rate_limits: HashMap<String, Arc<RateLimiter>>,
+        queued: Option<mpsc::UnboundedSender<RateLimitWait>>,
+    ) -> Self {
+        self.rate_limits = rate_limits;
+        self.queued = queued;
+        self
+    }
+
+    /// Provider that served the last successful request
+    pub fn served_by(&self) -> Option<ServedBy> {
+        self.served_by.lock().unwrap().clone()
+    }
+
+    /// Run `call` against each target until one succeeds.
+    ///
+    /// `may_retry` is checked after a failure; returning false stops at once
+    /// (used when a stream already delivered text).
+    async fn run<T, F>(
+        &self,
+        request: CreateChatCompletionRequest,
+        cancellation_token: Option<&CancellationToken>,
+        mut call: F,
+        may_retry: impl Fn() -> bool,
+    ) -> Result<T>
+    where
+        F: FnMut(Arc<dyn ChatClient>, CreateChatCompletionRequest) -> BoxFuture<'static, Result<T>>,
+    {
+        let mut attempts = 0;
+        let mut last_error = None;
+        let tokens = if self.rate_limits.is_empty() { 0 } else { request_tokens(&request) };
+
+        for (index, target) in self.targets.iter().enumerate() {
+            let mut request = request.clone();
+            request.model = target.model.clone();
+            let mut retries = 0;
+
+            loop {
+                let permit = match self.rate_limits.get(&target.provider_id) {
+                    Some(limiter) => Some(
+                        limiter
+                            .acquire(tokens, cancellation_token, |wait| {
+                                if let Some(queued) = &self.queued {
+                                    let _ = queued.send(wait.clone());
+                                }
+                            })
+                            .await?,
+                    ),
+                    None => None,
+                };
+                attempts += 1;
+                let result = call(target.client.clone(), request.clone()).await;
+                drop(permit);
+                let error = match result {
+                    Ok(result) => {
+                        *self.served_by.lock().unwrap() = Some(ServedBy {
+                            provider_id: target.provider_id.clone(),
+                            model: target.model.clone(),
+                            attempts,
+                        });
+                        return Ok(result);
+                    }
+                    Err(error) => error,
+                };
+
+                let class = ErrorClass::classify(&error);
+                if class == ErrorClass::Cancelled || !may_retry() {
+                    return Err(error);
+                }
+
+                if retries < self.retry.retries_for(class) {
+                    let backoff = self.retry.backoff(class, retries);
+                    retries += 1;
+                    eprintln!(
+                        "[{}] {} ({}), retry {} in {:?}",
+                        target.provider_id,
+                        class.as_str(),
+                        error,
+                        retries,
+                        backoff
+                    );
+                    match cancellation_token {
+                        Some(token) => tokio::select! {
+                            _ = tokio::time::sleep(backoff) => {}
+                            _ = token.cancelled() => return Err(ProviderError::Cancelled.into()),
+                        },
+                        None => tokio::time::sleep(backoff).await,
+                    }
+                    continue;
+                }
+
+                let has_next = index + 1 < self.targets.len();
+                if has_next && self.retry.fails_over(class) {
+                    eprintln!(
+                        "[{}] {} ({}), failing over to '{}'",
+                        target.provider_id,
+                        class.as_str(),
+                        error,
+                        self.targets[index + 1].provider_id
+                    );
+                    last_error = Some(error);
+                    break;
+                }
+                return Err(error);
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| eyre!("No provider available")))
+    }
+
+    async fn stream(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+        cancellation_token: Option<CancellationToken>,
+        timeout_duration: Duration,
+    ) -> StreamResult {
+        let emitted = Arc::new(AtomicBool::new(false));
+        let emitted_check = emitted.clone();
+
+        self.run(
+            request,
+            cancellation_token.as_ref(),
+            |client, request| {
+                let outer = chunk_sender.clone();
+                let emitted = emitted.clone();
+                let token = cancellation_token.clone();
+                Box::pin(async move {
+                    // Forward through a per-attempt channel to know whether text went out
+                    let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+                    let forward = tokio::spawn(async move {
+                        while let Some(chunk) = rx.recv().await {
+                            emitted.store(true, Ordering::Release);
+                            if outer.send(chunk).is_err() {
+                                break;
+                            }
+                        }
+                    });
+                    let result = match token {
+                        Some(token) => {
+                            client
+                                .complete_streaming_with_cancellation(request, tx, token, timeout_duration)
+                                .await
+                        }
+                        None => client.complete_streaming(request, tx).await,
+                    };
+                    let _ = forward.await;
+                    result
+                })
+            },
+            || !emitted_check.load(Ordering::Acquire),
+        )
+        .await
+    }
+}
+
+#[async_trait::async_trait]
+impl ChatClient for FailoverClient {
+    async fn complete(
+        &self,
+        request: CreateChatCompletionRequest,
+    ) -> Result<CreateChatCompletionResponse> {
+        self.run(
+            request,
+            None,
+            |client, request| Box::pin(async move { client.complete(request).await }),
+            || true,
+        )
+        .await
+    }
+
+    async fn complete_streaming(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+    ) -> StreamResult {
+        self.stream(request, chunk_sender, None, Duration::ZERO).await
+    }
+
+    async fn complete_streaming_with_cancellation(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+        cancellation_token: CancellationToken,
+        timeout_duration: Duration,
+    ) -> StreamResult {
+        self.stream(request, chunk_sender, Some(cancellation_token), timeout_duration)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockClient, MockResponse};
+
+    /// Provider answering with `responses` in order
+    fn mock(responses: Vec<MockResponse>) -> Arc<dyn ChatClient> {
+        Arc::new(MockClient::with_responses("mock", responses, 0))
+    }
+
+    fn reply(text: &str) -> MockResponse {
+        MockResponse {
+            chunks: vec![text.to_string()],
+            ..Default::default()
+        }
+    }
+
+    fn failure(status: u16) -> MockResponse {
+        MockResponse {
+            status: Some(status),
+            ..Default::default()
+        }
+    }
+
+    fn request() -> CreateChatCompletionRequest {
+        serde_json::from_value(serde_json::json!({
+            "model": "placeholder",
+            "messages": [{ "role": "user", "content": "Hi" }],
+        }))
+        .unwrap()
+    }
+
+    fn failover(clients: Vec<(&str, Arc<dyn ChatClient>)>, retry: RetryPolicy) -> FailoverClient {
+        let chain = RouteChain {
+            targets: clients
+                .iter()
+                .map(|(id, _)| (id.to_string(), format!("{}-model", id)))
+                .collect(),
+            retry,
+        };
+        let clients = clients
+            .into_iter()
+            .map(|(id, client)| (id.to_string(), client))
+            .collect();
+        FailoverClient::new(&chain, &clients).unwrap()
+    }
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy {
+            rate_limit_backoff_ms: 1,
+            server_error_backoff_ms: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[test]
+    fn test_classify_errors() {
+        let status = |code: u16| {
+            let status = StatusCode::from_u16(code).unwrap();
+            let body = "connection timed out".to_string();
+            ErrorClass::classify(&ProviderError::Status { status, body }.into())
+        };
+        assert_eq!(status(429), ErrorClass::RateLimited);
+        assert_eq!(status(503), ErrorClass::ServerError);
+        assert_eq!(status(408), ErrorClass::Timeout);
+        // The body text plays no part
+        assert_eq!(status(400), ErrorClass::Other);
+
+        let timed_out = ProviderError::TimedOut(Duration::from_secs(120));
+        assert_eq!(ErrorClass::classify(&timed_out.into()), ErrorClass::Timeout);
+        let queued = eyre::Report::new(ProviderError::Cancelled).wrap_err("Cancelled while queued");
+        assert_eq!(ErrorClass::classify(&queued), ErrorClass::Cancelled);
+        let message = eyre!("API Error (429 Too Many Requests): connection reset");
+        assert_eq!(ErrorClass::classify(&message), ErrorClass::Other);
+    }
+
+    #[tokio::test]
+    async fn test_classify_connection_failure() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let error = reqwest::get(url).await.unwrap_err();
+        assert_eq!(ErrorClass::classify(&error.into()), ErrorClass::Timeout);
+    }
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(ErrorClass::RateLimited, 0), Duration::from_millis(1000));
+        assert_eq!(policy.backoff(ErrorClass::RateLimited, 1), Duration::from_millis(2000));
+        assert_eq!(policy.backoff(ErrorClass::RateLimited, 10), Duration::from_millis(8000));
+        assert_eq!(policy.backoff(ErrorClass::Timeout, 0), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_retries_same_provider() {
+        let busy = failure(429);
+        let primary = mock(vec![busy.clone(), busy, reply("ok from primary")]);
+        let client = failover(vec![("primary", primary)], fast_policy());
+        let (tx, _rx) = mpsc::unbounded_channel();
+
+        let (text, _, _) = client.complete_streaming(request(), tx).await.unwrap();
+        assert_eq!(text, "ok from primary");
+        assert_eq!(client.served_by().unwrap().attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_fails_over_to_next_provider() {
+        let stalled = MockResponse {
+            stall: true,
+            ..Default::default()
+        };
+        let client = failover(
+            vec![("primary", mock(vec![stalled])), ("backup", mock(vec![reply("ok from backup")]))],
+            fast_policy(),
+        );
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        let (text, _, _) = client
+            .complete_streaming_with_cancellation(
+                request(),
+                tx,
+                CancellationToken::new(),
+                Duration::from_millis(20),
+            )
+            .await
+            .unwrap();
+        assert_eq!(text, "ok from backup");
+        assert_eq!(rx.recv().await.unwrap(), text);
+
+        let served = client.served_by().unwrap();
+        assert_eq!(served.provider_id, "backup");
+        assert_eq!(served.model, "backup-model");
+        assert_eq!(served.attempts, 2);
+    }
+
+    #[tokio::test]
+    async fn test_no_failover_after_text_was_streamed() {
+        let broken = MockResponse {
+            chunks: vec!["partial".to_string()],
+            status: Some(500),
+            ..Default::default()
+        };
+        let client = failover(
+            vec![("primary", mock(vec![broken])), ("backup", mock(vec![reply("ok from backup")]))],
+            fast_policy(),
+        );
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        assert!(client.complete_streaming(request(), tx).await.is_err());
+        assert_eq!(rx.recv().await.as_deref(), Some("partial"));
+        assert!(rx.recv().await.is_none());
+        assert!(client.served_by().is_none());
+    }
+}
@@ -0,0 +1,585 @@
+//! Translation between OpenAI chat completion JSON and Gemini `generateContent`.
+//!
+//! The rest of the client speaks OpenAI's wire format. `GeminiClient` serializes
+//! the request, converts it with [`to_gemini_request`], and converts responses
+//! back with [`from_gemini_response`] (or [`GeminiStreamState`] for SSE chunks),
+//! so Gemini behaves like any other provider to the main loop.
+//!
+//! Mapping:
+//! - `system` / `developer` messages -> `systemInstruction`
+//! - `user` -> `user`, `assistant` -> `model`
+//! - `image_url` parts with `data:` URLs -> `inlineData` parts
+//! - assistant `tool_calls` -> `functionCall` parts
+//! - `tool` messages -> `functionResponse` parts (name looked up by `tool_call_id`)
+//! - `tools` -> `functionDeclarations`, `tool_choice` -> `toolConfig`
+//! - `response_format` -> `responseMimeType` (and `responseSchema`)
+
+use std::collections::HashMap;
+
+use eyre::{Result, eyre};
+use serde_json::{Map, Value, json};
+
+use crate::openai_compat::{ReplyAccumulator, content_text};
+use crate::usage::TokenUsage;
+use crate::vision;
+
+/// Build the `generateContent` / `streamGenerateContent` URL for a model.
+///
+/// `api_url` is the API root (e.g. `https://generativelanguage.googleapis.com/v1beta`).
+/// A trailing OpenAI-compatibility suffix (`/openai`, `/openai/chat/completions`)
+/// from older configs is stripped.
+pub fn endpoint_url(api_url: &str, model: &str, streaming: bool) -> String {
+    let mut base = api_url.trim().trim_end_matches('/');
+    for suffix in ["/chat/completions", "/openai"] {
+        base = base.strip_suffix(suffix).unwrap_or(base);
+    }
+    let model = model.strip_prefix("models/").unwrap_or(model);
+    if streaming {
+        format!("{}/models/{}:streamGenerateContent?alt=sse", base, model)
+    } else {
+        format!("{}/models/{}:generateContent", base, model)
+    }
+}
+
+/// Convert an OpenAI chat completion request (as JSON) into a Gemini request.
+///
+/// Returns the model name and the request body.
+pub fn to_gemini_request(request: &Value) -> Result<(String, Value)> {
+    let model = request
+        .get("model")
+        .and_then(Value::as_str)
+        .ok_or_else(|| eyre!("Request has no model"))?
+        .to_string();
+
+    let messages = request
+        .get("messages")
+        .and_then(Value::as_array)
+        .ok_or_else(|| eyre!("Request has no messages"))?;
+
+    let mut system_parts: Vec<Value> = Vec::new();
+    let mut contents: Vec<Value> = Vec::new();
+    // Gemini matches function responses by name, OpenAI by call id
+    let mut call_names: HashMap<String, String> = HashMap::new();
+
+    for message in messages {
+        let role = message.get("role").and_then(Value::as_str).unwrap_or("user");
+        match role {
+            "system" | "developer" => {
+                let text = content_text(message.get("content"));
+                if !text.is_empty() {
+                    system_parts.push(json!({ "text": text }));
+                }
+            }
+            "assistant" => {
+                let mut parts = Vec::new();
+                let text = content_text(message.get("content"));
+                if !text.is_empty() {
+                    parts.push(json!({ "text": text }));
+                }
+                if let Some(calls) = message.get("tool_calls").and_then(Value::as_array) {
+                    for call in calls {
+                        let id = call.get("id").and_then(Value::as_str).unwrap_or_default();
+                        let function = call.get("function").cloned().unwrap_or(Value::Null);
+                        let name = function
+                            .get("name")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default()
+                            .to_string();
+                        let args = function
+                            .get("arguments")
+                            .and_then(Value::as_str)
+                            .and_then(|a| serde_json::from_str::<Value>(a).ok())
+                            .filter(Value::is_object)
+                            .unwrap_or_else(|| json!({}));
+                        call_names.insert(id.to_string(), name.clone());
+                        parts.push(json!({ "functionCall": { "name": name, "args": args } }));
+                    }
+                }
+                push_content(&mut contents, "model", parts);
+            }
+            "tool" | "function" => {
+                let name = message
+                    .get("tool_call_id")
+                    .and_then(Value::as_str)
+                    .and_then(|id| call_names.get(id).cloned())
+                    .or_else(|| message.get("name").and_then(Value::as_str).map(String::from))
+                    .unwrap_or_else(|| "tool".to_string());
+                let text = content_text(message.get("content"));
+                // functionResponse.response must be an object
+                let response = match serde_json::from_str::<Value>(&text) {
+                    Ok(Value::Object(obj)) => Value::Object(obj),
+                    _ => json!({ "content": text }),
+                };
+                push_content(
+                    &mut contents,
+                    "user",
+                    vec![json!({ "functionResponse": { "name": name, "response": response } })],
+                );
+            }
+            _ => {
+                let parts = user_parts(message.get("content"));
+                push_content(&mut contents, "user", parts);
+            }
+        }
+    }
+
+    let mut body = Map::new();
+    body.insert("contents".into(), Value::Array(contents));
+    if !system_parts.is_empty() {
+        body.insert("systemInstruction".into(), json!({ "parts": system_parts }));
+    }
+
+    if let Some(tools) = request.get("tools").and_then(Value::as_array) {
+        let declarations: Vec<Value> = tools
+            .iter()
+            .filter_map(|tool| tool.get("function"))
+            .map(|function| {
+                let mut decl = Map::new();
+                decl.insert("name".into(), function.get("name").cloned().unwrap_or(Value::Null));
+                if let Some(description) = function.get("description") {
+                    decl.insert("description".into(), description.clone());
+                }
+                if let Some(parameters) = function.get("parameters") {
+                    if has_properties(parameters) {
+                        decl.insert("parameters".into(), sanitize_schema(parameters));
+                    }
+                }
+                Value::Object(decl)
+            })
+            .collect();
+        if !declarations.is_empty() {
+            body.insert("tools".into(), json!([{ "functionDeclarations": declarations }]));
+        }
+    }
+
+    if let Some(choice) = request.get("tool_choice") {
+        let config = match choice {
+            Value::String(mode) => match mode.as_str() {
+                "none" => Some(json!({ "mode": "NONE" })),
+                "required" => Some(json!({ "mode": "ANY" })),
+                "auto" => Some(json!({ "mode": "AUTO" })),
+                _ => None,
+            },
+            Value::Object(_) => choice
+                .pointer("/function/name")
+                .and_then(Value::as_str)
+                .map(|name| json!({ "mode": "ANY", "allowedFunctionNames": [name] })),
+            _ => None,
+        };
+        if let Some(config) = config {
+            body.insert("toolConfig".into(), json!({ "functionCallingConfig": config }));
+        }
+    }
+
+    let mut generation = Map::new();
+    let copy = |generation: &mut Map<String, Value>, from: &str, to: &str| {
+        if let Some(value) = request.get(from).filter(|v| !v.is_null()) {
+            generation.insert(to.to_string(), value.clone());
+        }
+    };
+    copy(&mut generation, "temperature", "temperature");
+    copy(&mut generation, "top_p", "topP");
+    copy(&mut generation, "n", "candidateCount");
+    copy(&mut generation, "presence_penalty", "presencePenalty");
+    copy(&mut generation, "frequency_penalty", "frequencyPenalty");
+    copy(&mut generation, "seed", "seed");
+    copy(&mut generation, "max_tokens", "maxOutputTokens");
+    copy(&mut generation, "max_completion_tokens", "maxOutputTokens");
+    match request.get("stop") {
+        Some(Value::String(stop)) => {
+            generation.insert("stopSequences".into(), json!([stop]));
+        }
+        Some(Value::Array(stops)) if !stops.is_empty() => {
+            generation.insert("stopSequences".into(), Value::Array(stops.clone()));
+        }
+        _ => {}
+    }
+    let response_format = request.get("response_format");
+    match response_format.and_then(|f| f.get("type")).and_then(Value::as_str) {
+        Some("json_object") => {
+            generation.insert("responseMimeType".into(), json!("application/json"));
+        }
+        Some("json_schema") => {
+            generation.insert("responseMimeType".into(), json!("application/json"));
+            if let Some(schema) = response_format.and_then(|f| f.pointer("/json_schema/schema")) {
+                generation.insert("responseSchema".into(), sanitize_schema(schema));
+            }
+        }
+        _ => {}
+    }
+    if !generation.is_empty() {
+        body.insert("generationConfig".into(), Value::Object(generation));
+    }
+
+    Ok((model, Value::Object(body)))
+}
+
+/// Convert a Gemini `generateContent` response into an OpenAI chat completion response.
+///
+/// `id` is used as the completion id and as the prefix for synthesized tool call ids.
+pub fn from_gemini_response(response: &Value, model: &str, id: &str) -> Result<Value> {
+    let mut state = GeminiStreamState::new(id);
+    state.push(response)?;
+    Ok(state.into_response(model))
+}
+
+/// Accumulates a Gemini response, either whole or from SSE chunks.
+///
+/// Each streamed chunk is a complete `GenerateContentResponse` whose text parts
+/// are deltas and whose `functionCall` parts are whole calls.
+pub struct GeminiStreamState {
+    reply: ReplyAccumulator,
+    finish_reason: Option<String>,
+}
+
+impl GeminiStreamState {
+    pub fn new(id: &str) -> Self {
+        Self {
+            reply: ReplyAccumulator::new(id),
+            finish_reason: None,
+        }
+    }
+
+    /// Add one response chunk. Returns the new text delta (may be empty).
+    pub fn push(&mut self, chunk: &Value) -> Result<String> {
+        if let Some(error) = chunk.get("error") {
+            let message = error.get("message").and_then(Value::as_str).unwrap_or("unknown error");
+            return Err(eyre!("Gemini API error: {}", message));
+        }
+        if let Some(reason) = chunk.pointer("/promptFeedback/blockReason").and_then(Value::as_str) {
+            return Err(eyre!("Gemini blocked the prompt: {}", reason));
+        }
+
+        if let Some(usage) = chunk.get("usageMetadata") {
+            let count = |key: &str| usage.get(key).and_then(Value::as_u64).unwrap_or(0);
+            self.reply.set_usage(
+                count("promptTokenCount"),
+                count("candidatesTokenCount"),
+                count("totalTokenCount"),
+            );
+        }
+
+        let Some(candidate) = chunk.pointer("/candidates/0") else {
+            return Ok(String::new());
+        };
+
+        let mut delta = String::new();
+        if let Some(parts) = candidate.pointer("/content/parts").and_then(Value::as_array) {
+            for part in parts {
+                // Skip model "thoughts" - they are not part of the answer
+                if part.get("thought").and_then(Value::as_bool).unwrap_or(false) {
+                    continue;
+                }
+                if let Some(text) = part.get("text").and_then(Value::as_str) {
+                    delta.push_str(text);
+                }
+                if let Some(call) = part.get("functionCall") {
+                    let args = call.get("args").cloned().unwrap_or_else(|| json!({}));
+                    self.reply.push_tool_call(
+                        call.get("id").and_then(Value::as_str),
+                        call.get("name").and_then(Value::as_str).unwrap_or_default(),
+                        args.to_string(),
+                    );
+                }
+            }
+        }
+        self.reply.push_text(&delta);
+
+        if let Some(reason) = candidate.get("finishReason").and_then(Value::as_str) {
+            self.finish_reason = Some(reason.to_string());
+        }
+        Ok(delta)
+    }
+
+    /// Full text received so far
+    pub fn text(&self) -> &str {
+        self.reply.text()
+    }
+
+    /// Tool calls in OpenAI JSON format (`ChatCompletionMessageToolCall`)
+    pub fn tool_calls(&self) -> &[Value] {
+        self.reply.tool_calls()
+    }
+
+    /// Token usage, once the provider reported it
+    pub fn usage(&self) -> Option<TokenUsage> {
+        self.reply.usage()
+    }
+
+    /// OpenAI-style finish reason
+    pub fn finish_reason(&self) -> &'static str {
+        if !self.reply.tool_calls().is_empty() {
+            return "tool_calls";
+        }
+        match self.finish_reason.as_deref() {
+            Some("MAX_TOKENS") => "length",
+            Some("SAFETY") | Some("RECITATION") | Some("BLOCKLIST")
+            | Some("PROHIBITED_CONTENT") | Some("SPII") => "content_filter",
+            _ => "stop",
+        }
+    }
+
+    /// Build an OpenAI `CreateChatCompletionResponse` JSON value
+    pub fn into_response(self, model: &str) -> Value {
+        let finish_reason = self.finish_reason();
+        self.reply.into_response(model, finish_reason)
+    }
+}
+
+/// Append parts as a content turn, merging with the previous turn of the same role
+/// (Gemini expects alternating user/model turns)
+fn push_content(contents: &mut Vec<Value>, role: &str, parts: Vec<Value>) {
+    if parts.is_empty() {
+        return;
+    }
+    if let Some(last) = contents.last_mut() {
+        if last.get("role").and_then(Value::as_str) == Some(role) {
+            if let Some(existing) = last.get_mut("parts").and_then(Value::as_array_mut) {
+                existing.extend(parts);
+                return;
+            }
+        }
+    }
+    contents.push(json!({ "role": role, "parts": parts }));
+}
+
+/// Parts of a user message
+fn user_parts(content: Option<&Value>) -> Vec<Value> {
+    let Some(Value::Array(parts)) = content else {
+        let text = content_text(content);
+        return if text.is_empty() { Vec::new() } else { vec![json!({ "text": text })] };
+    };
+    parts
+        .iter()
+        .filter_map(|part| match vision::image_url(part) {
+            Some(url) => Some(match vision::parse_data_url(url) {
+                Some((mime_type, data)) => {
+                    json!({ "inlineData": { "mimeType": mime_type, "data": data } })
+                }
+                // fileData only takes Gemini File API URIs
+                None => json!({ "text": format!("[image: {}]", url) }),
+            }),
+            None => part
+                .get("text")
+                .and_then(Value::as_str)
+                .filter(|text| !text.is_empty())
+                .map(|text| json!({ "text": text })),
+        })
+        .collect()
+}
+
+/// Gemini rejects object schemas without properties
+fn has_properties(schema: &Value) -> bool {
+    schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .is_some_and(|props| !props.is_empty())
+}
+
+/// Remove JSON Schema keywords Gemini's OpenAPI subset rejects
+fn sanitize_schema(schema: &Value) -> Value {
+    match schema {
+        Value::Object(obj) => Value::Object(
+            obj.iter()
+                .filter(|(key, _)| {
+                    !matches!(key.as_str(), "$schema" | "additionalProperties" | "$id" | "default")
+                })
+                .map(|(key, value)| {
+                    // Property names are user data, not schema keywords
+                    if key == "properties" {
+                        let props = value
+                            .as_object()
+                            .map(|props| {
+                                props
+                                    .iter()
+                                    .map(|(name, prop)| (name.clone(), sanitize_schema(prop)))
+                                    .collect()
+                            })
+                            .unwrap_or_default();
+                        (key.clone(), Value::Object(props))
+                    } else {
+                        (key.clone(), sanitize_schema(value))
+                    }
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(sanitize_schema).collect()),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_url() {
+        let base = "https://generativelanguage.googleapis.com/v1beta";
+        assert_eq!(
+            endpoint_url(base, "gemini-2.0-flash", false),
+            "https://generativelanguage.googleapis.com/v1beta/models/gemini-2.0-flash:generateContent"
+        );
+        assert_eq!(
+            endpoint_url(&format!("{}/openai/chat/completions", base), "models/gemini-2.0-flash", true),
+            "https://generativelanguage.googleapis.com/v1beta/models/gemini-2.0-flash:streamGenerateContent?alt=sse"
+        );
+    }
+
+    #[test]
+    fn test_request_roles_and_system_instruction() {
+        let request = json!({
+            "model": "gemini-2.0-flash",
+            "messages": [
+                { "role": "system", "content": "Be brief." },
+                { "role": "developer", "content": "CONTEXT" },
+                { "role": "user", "content": "Hi" },
+                { "role": "assistant", "content": "Hello!" },
+                { "role": "user", "content": [{ "type": "text", "text": "How are you?" }] },
+            ],
+            "temperature": 0.5,
+            "max_completion_tokens": 200,
+            "stop": "END",
+        });
+
+        let (model, body) = to_gemini_request(&request).unwrap();
+        assert_eq!(model, "gemini-2.0-flash");
+        assert_eq!(body["systemInstruction"]["parts"][0]["text"], "Be brief.");
+        assert_eq!(body["systemInstruction"]["parts"][1]["text"], "CONTEXT");
+
+        let contents = body["contents"].as_array().unwrap();
+        assert_eq!(contents.len(), 3);
+        assert_eq!(contents[0]["role"], "user");
+        assert_eq!(contents[1]["role"], "model");
+        assert_eq!(contents[1]["parts"][0]["text"], "Hello!");
+        assert_eq!(contents[2]["parts"][0]["text"], "How are you?");
+
+        assert_eq!(body["generationConfig"]["temperature"], 0.5);
+        assert_eq!(body["generationConfig"]["maxOutputTokens"], 200);
+        assert_eq!(body["generationConfig"]["stopSequences"][0], "END");
+    }
+
+    #[test]
+    fn test_request_response_format() {
+        let request = json!({
+            "model": "gemini-2.0-flash",
+            "messages": [{ "role": "user", "content": "Hi" }],
+            "response_format": { "type": "json_schema", "json_schema": {
+                "name": "participant_turn",
+                "schema": {
+                    "type": "object",
+                    "properties": { "text": { "type": "string" } },
+                    "additionalProperties": false,
+                },
+            }},
+        });
+
+        let (_, body) = to_gemini_request(&request).unwrap();
+        let generation = &body["generationConfig"];
+        assert_eq!(generation["responseMimeType"], "application/json");
+        assert_eq!(generation["responseSchema"]["properties"]["text"]["type"], "string");
+        assert!(generation["responseSchema"].get("additionalProperties").is_none());
+    }
+
+    #[test]
+    fn test_request_image_parts() {
+        let request = json!({
+            "model": "gemini-2.0-flash",
+            "messages": [{ "role": "user", "content": [
+                { "type": "text", "text": "What is on this slide?" },
+                { "type": "image_url", "image_url": { "url": "data:image/png;base64,iVBORw0KGgo=" } },
+                { "type": "image_url", "image_url": { "url": "https://example.com/slide.png" } },
+            ]}],
+        });
+
+        let (_, body) = to_gemini_request(&request).unwrap();
+        let parts = &body["contents"][0]["parts"];
+        assert_eq!(parts[0]["text"], "What is on this slide?");
+        assert_eq!(parts[1]["inlineData"]["mimeType"], "image/png");
+        assert_eq!(parts[1]["inlineData"]["data"], "iVBORw0KGgo=");
+        assert_eq!(parts[2]["text"], "[image: https://example.com/slide.png]");
+    }
+
+    #[test]
+    fn test_request_tools_and_function_calls() {
+        let request = json!({
+            "model": "gemini-2.0-flash",
+            "messages": [
+                { "role": "user", "content": "Weather in Paris?" },
+                { "role": "assistant", "content": null, "tool_calls": [{
+                    "id": "call_1", "type": "function",
+                    "function": { "name": "get_weather", "arguments": "{\"city\":\"Paris\"}" }
+                }]},
+                { "role": "tool", "tool_call_id": "call_1", "content": "sunny" },
+            ],
+            "tools": [{ "type": "function", "function": {
+                "name": "get_weather",
+                "description": "Get weather",
+                "parameters": {
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "additionalProperties": false,
+                    "properties": { "city": { "type": "string" } },
+                    "required": ["city"],
+                }
+            }}],
+            "tool_choice": "required",
+        });
+
+        let (_, body) = to_gemini_request(&request).unwrap();
+        let contents = body["contents"].as_array().unwrap();
+        assert_eq!(contents[1]["parts"][0]["functionCall"]["name"], "get_weather");
+        assert_eq!(contents[1]["parts"][0]["functionCall"]["args"]["city"], "Paris");
+        assert_eq!(contents[2]["role"], "user");
+        assert_eq!(contents[2]["parts"][0]["functionResponse"]["name"], "get_weather");
+        assert_eq!(contents[2]["parts"][0]["functionResponse"]["response"]["content"], "sunny");
+
+        let decl = &body["tools"][0]["functionDeclarations"][0];
+        assert_eq!(decl["name"], "get_weather");
+        assert!(decl["parameters"].get("$schema").is_none());
+        assert!(decl["parameters"].get("additionalProperties").is_none());
+        assert_eq!(decl["parameters"]["required"][0], "city");
+        assert_eq!(body["toolConfig"]["functionCallingConfig"]["mode"], "ANY");
+    }
+
+    #[test]
+    fn test_response_translation() {
+        let response = json!({
+            "candidates": [{
+                "content": { "role": "model", "parts": [
+                    { "text": "thinking...", "thought": true },
+                    { "text": "Let me check." },
+                    { "functionCall": { "name": "get_weather", "args": { "city": "Paris" } } },
+                ]},
+                "finishReason": "STOP",
+            }],
+            "usageMetadata": { "promptTokenCount": 10, "candidatesTokenCount": 5, "totalTokenCount": 15 },
+        });
+
+        let openai = from_gemini_response(&response, "gemini-2.0-flash", "abc").unwrap();
+        let choice = &openai["choices"][0];
+        assert_eq!(choice["message"]["content"], "Let me check.");
+        assert_eq!(choice["finish_reason"], "tool_calls");
+        let call = &choice["message"]["tool_calls"][0];
+        assert_eq!(call["id"], "call_abc_0");
+        assert_eq!(call["function"]["name"], "get_weather");
+        assert_eq!(call["function"]["arguments"], "{\"city\":\"Paris\"}");
+        assert_eq!(openai["usage"]["total_tokens"], 15);
+    }
+
+    #[test]
+    fn test_stream_chunks_and_errors() {
+        let mut state = GeminiStreamState::new("s");
+        let delta = state
+            .push(&json!({ "candidates": [{ "content": { "parts": [{ "text": "Hel" }] } }] }))
+            .unwrap();
+        assert_eq!(delta, "Hel");
+        state
+            .push(&json!({ "candidates": [{ "content": { "parts": [{ "text": "lo" }] }, "finishReason": "MAX_TOKENS" }] }))
+            .unwrap();
+        assert_eq!(state.text(), "Hello");
+        assert_eq!(state.finish_reason(), "length");
+
+        assert!(state.push(&json!({ "promptFeedback": { "blockReason": "SAFETY" } })).is_err());
+        assert!(state.push(&json!({ "error": { "message": "quota" } })).is_err());
+    }
+}
@@ -0,0 +1,27 @@
+// Library exports for dora-maas-client
+// The node binary is a thin Dora adapter around `engine::ConversationEngine`;
+// apps can run an LLM participant in-process with the same engine, and the
+// Rust text segmenter node shares the stream segmenter
+
+pub mod anthropic;
+pub mod builtin_tools;
+pub mod client;
+pub mod config;
+pub mod engine;
+pub mod failover;
+pub mod gemini;
+pub mod local;
+pub mod mcp_context;
+pub mod mock;
+pub mod openai_compat;
+pub mod overrides;
+pub mod ratelimit;
+pub mod segmenter;
+pub mod session;
+pub mod session_store;
+pub mod streaming;
+pub mod structured;
+pub mod summary;
+pub mod tool;
+pub mod usage;
+pub mod vision;
@@ -0,0 +1,349 @@
+//! Translation for local model servers (Ollama native API).
+//!
+//! Local providers either speak the OpenAI API (llama.cpp server, Ollama's
+//! `/v1` endpoint, LM Studio, ...) and reuse the OpenAI code paths, or
+//! Ollama's native `/api/chat`, which adds `keep_alive` so a model stays loaded
+//! between turns. The native API streams newline-delimited JSON instead of SSE.
+//!
+//! Mapping to `/api/chat`:
+//! - messages keep their roles; content arrays are flattened to text
+//! - assistant `tool_calls` arguments become JSON objects
+//! - `max_tokens`, `temperature`, `top_p`, `stop`, `seed` -> `options`
+//! - `response_format` -> `format` (`"json"` or the JSON schema)
+
+use eyre::{Result, eyre};
+use serde_json::{Map, Value, json};
+
+use crate::openai_compat::{ReplyAccumulator, content_text};
+use crate::usage::TokenUsage;
+use crate::vision;
+
+/// Root URL of an Ollama server (`http://localhost:11434`), without a trailing
+/// `/v1` or `/api` so both native and OpenAI-style paths can be derived.
+pub fn ollama_base_url(api_url: &str) -> String {
+    let mut base = api_url.trim().trim_end_matches('/');
+    for suffix in ["/v1", "/api"] {
+        base = base.strip_suffix(suffix).unwrap_or(base);
+    }
+    base.to_string()
+}
+
+/// Model names from Ollama's `/api/tags` or an OpenAI-style `/v1/models` response
+pub fn parse_model_list(response: &Value) -> Vec<String> {
+    if let Some(models) = response.get("models").and_then(Value::as_array) {
+        return models
+            .iter()
+            .filter_map(|m| m.get("name").or_else(|| m.get("model")))
+            .filter_map(Value::as_str)
+            .map(String::from)
+            .collect();
+    }
+    response
+        .get("data")
+        .and_then(Value::as_array)
+        .map(|models| {
+            models
+                .iter()
+                .filter_map(|m| m.get("id").and_then(Value::as_str))
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether `model` is served, accepting Ollama's implicit `:latest` tag
+pub fn has_model(available: &[String], model: &str) -> bool {
+    available.iter().any(|name| {
+        name == model || name.strip_suffix(":latest") == Some(model)
+    })
+}
+
+/// Convert an OpenAI chat completion request (as JSON) into an Ollama `/api/chat` request.
+pub fn to_ollama_request(request: &Value, keep_alive: Option<&str>, stream: bool) -> Result<Value> {
+    let model = request
+        .get("model")
+        .and_then(Value::as_str)
+        .ok_or_else(|| eyre!("Request has no model"))?;
+    let messages = request
+        .get("messages")
+        .and_then(Value::as_array)
+        .ok_or_else(|| eyre!("Request has no messages"))?;
+
+    let messages: Vec<Value> = messages
+        .iter()
+        .map(|message| {
+            let role = message.get("role").and_then(Value::as_str).unwrap_or("user");
+            // Ollama has no developer role
+            let role = if role == "developer" { "system" } else { role };
+            let mut converted = json!({
+                "role": role,
+                "content": content_text(message.get("content")),
+            });
+            // Ollama takes images as bare base64 next to the text
+            let images: Vec<&str> = message
+                .get("content")
+                .and_then(Value::as_array)
+                .map(|parts| {
+                    parts
+                        .iter()
+                        .filter_map(vision::image_url)
+                        .filter_map(vision::parse_data_url)
+                        .map(|(_, data)| data)
+                        .collect()
+                })
+                .unwrap_or_default();
+            if !images.is_empty() {
+                converted["images"] = json!(images);
+            }
+            if let Some(calls) = message.get("tool_calls").and_then(Value::as_array) {
+                let calls: Vec<Value> = calls
+                    .iter()
+                    .map(|call| {
+                        let arguments = call
+                            .pointer("/function/arguments")
+                            .and_then(Value::as_str)
+                            .and_then(|a| serde_json::from_str::<Value>(a).ok())
+                            .filter(Value::is_object)
+                            .unwrap_or_else(|| json!({}));
+                        json!({
+                            "function": {
+                                "name": call.pointer("/function/name").cloned().unwrap_or(Value::Null),
+                                "arguments": arguments,
+                            }
+                        })
+                    })
+                    .collect();
+                converted["tool_calls"] = Value::Array(calls);
+            }
+            converted
+        })
+        .collect();
+
+    let mut body = Map::new();
+    body.insert("model".into(), json!(model));
+    body.insert("messages".into(), Value::Array(messages));
+    body.insert("stream".into(), json!(stream));
+    if let Some(keep_alive) = keep_alive {
+        body.insert("keep_alive".into(), json!(keep_alive));
+    }
+    // Ollama accepts OpenAI-style tool definitions as-is
+    if let Some(tools) = request.get("tools").filter(|t| t.as_array().is_some_and(|a| !a.is_empty())) {
+        body.insert("tools".into(), tools.clone());
+    }
+    let response_format = request.get("response_format");
+    match response_format.and_then(|f| f.get("type")).and_then(Value::as_str) {
+        Some("json_object") => {
+            body.insert("format".into(), json!("json"));
+        }
+        Some("json_schema") => {
+            let schema = response_format.and_then(|f| f.pointer("/json_schema/schema"));
+            body.insert("format".into(), schema.cloned().unwrap_or_else(|| json!("json")));
+        }
+        _ => {}
+    }
+
+    let mut options = Map::new();
+    for (from, to) in [
+        ("temperature", "temperature"),
+        ("top_p", "top_p"),
+        ("seed", "seed"),
+        ("presence_penalty", "presence_penalty"),
+        ("frequency_penalty", "frequency_penalty"),
+        ("max_tokens", "num_predict"),
+        ("max_completion_tokens", "num_predict"),
+    ] {
+        if let Some(value) = request.get(from).filter(|v| !v.is_null()) {
+            options.insert(to.to_string(), value.clone());
+        }
+    }
+    match request.get("stop") {
+        Some(Value::String(stop)) => {
+            options.insert("stop".into(), json!([stop]));
+        }
+        Some(Value::Array(stops)) if !stops.is_empty() => {
+            options.insert("stop".into(), Value::Array(stops.clone()));
+        }
+        _ => {}
+    }
+    if !options.is_empty() {
+        body.insert("options".into(), Value::Object(options));
+    }
+
+    Ok(Value::Object(body))
+}
+
+/// Accumulates an Ollama `/api/chat` response, whole or from NDJSON lines.
+pub struct OllamaStreamState {
+    reply: ReplyAccumulator,
+    done_reason: Option<String>,
+    done: bool,
+}
+
+impl OllamaStreamState {
+    pub fn new(id: &str) -> Self {
+        Self {
+            reply: ReplyAccumulator::new(id),
+            done_reason: None,
+            done: false,
+        }
+    }
+
+    /// Add one response object. Returns the new text delta (may be empty).
+    pub fn push(&mut self, chunk: &Value) -> Result<String> {
+        if let Some(error) = chunk.get("error") {
+            let message = error.as_str().map(String::from).unwrap_or_else(|| error.to_string());
+            return Err(eyre!("Ollama error: {}", message));
+        }
+
+        let delta = chunk
+            .pointer("/message/content")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        self.reply.push_text(&delta);
+
+        if let Some(calls) = chunk.pointer("/message/tool_calls").and_then(Value::as_array) {
+            for call in calls {
+                let arguments = match call.pointer("/function/arguments") {
+                    Some(Value::String(raw)) => raw.clone(),
+                    Some(value) => value.to_string(),
+                    None => "{}".to_string(),
+                };
+                self.reply.push_tool_call(
+                    call.get("id").and_then(Value::as_str),
+                    call.pointer("/function/name").and_then(Value::as_str).unwrap_or_default(),
+                    arguments,
+                );
+            }
+        }
+
+        if chunk.get("done").and_then(Value::as_bool).unwrap_or(false) {
+            self.done = true;
+            self.done_reason = chunk.get("done_reason").and_then(Value::as_str).map(String::from);
+            let count = |key: &str| chunk.get(key).and_then(Value::as_u64).unwrap_or(0);
+            self.reply.set_usage(
+                count("prompt_eval_count"),
+                count("eval_count"),
+                count("prompt_eval_count") + count("eval_count"),
+            );
+        }
+        Ok(delta)
+    }
+
+    /// Whether the final (`done: true`) object was received
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    pub fn text(&self) -> &str {
+        self.reply.text()
+    }
+
+    /// Tool calls in OpenAI JSON format (`ChatCompletionMessageToolCall`)
+    pub fn tool_calls(&self) -> &[Value] {
+        self.reply.tool_calls()
+    }
+
+    /// Token usage, once the provider reported it
+    pub fn usage(&self) -> Option<TokenUsage> {
+        self.reply.usage()
+    }
+
+    /// Build an OpenAI `CreateChatCompletionResponse` JSON value
+    pub fn into_response(self, model: &str) -> Value {
+        let finish_reason = match self.done_reason.as_deref() {
+            Some("length") => "length",
+            _ => "stop",
+        };
+        self.reply.into_response(model, finish_reason)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base_url_and_model_list() {
+        assert_eq!(ollama_base_url("http://localhost:11434/v1/"), "http://localhost:11434");
+        assert_eq!(ollama_base_url("http://localhost:11434"), "http://localhost:11434");
+
+        let tags = json!({ "models": [{ "name": "qwen2.5:7b" }, { "name": "llama3.2:latest" }] });
+        let models = parse_model_list(&tags);
+        assert_eq!(models, vec!["qwen2.5:7b", "llama3.2:latest"]);
+        assert!(has_model(&models, "llama3.2"));
+        assert!(!has_model(&models, "qwen2.5"));
+
+        let openai = json!({ "object": "list", "data": [{ "id": "local-model" }] });
+        assert_eq!(parse_model_list(&openai), vec!["local-model"]);
+    }
+
+    #[test]
+    fn test_request_translation() {
+        let request = json!({
+            "model": "qwen2.5:7b",
+            "messages": [
+                { "role": "developer", "content": "CONTEXT" },
+                { "role": "user", "content": [
+                    { "type": "text", "text": "Weather?" },
+                    { "type": "image_url", "image_url": { "url": "data:image/png;base64,iVBORw0KGgo=" } },
+                ]},
+                { "role": "assistant", "content": null, "tool_calls": [{
+                    "id": "call_1", "type": "function",
+                    "function": { "name": "get_weather", "arguments": "{\"city\":\"Paris\"}" }
+                }]},
+                { "role": "tool", "tool_call_id": "call_1", "content": "sunny" },
+            ],
+            "max_tokens": 128,
+            "stop": ["END"],
+        });
+
+        let body = to_ollama_request(&request, Some("30m"), true).unwrap();
+        assert_eq!(body["keep_alive"], "30m");
+        assert_eq!(body["stream"], true);
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(body["messages"][1]["content"], "Weather?");
+        assert_eq!(body["messages"][1]["images"][0], "iVBORw0KGgo=");
+        assert_eq!(body["messages"][2]["tool_calls"][0]["function"]["arguments"]["city"], "Paris");
+        assert_eq!(body["messages"][3]["role"], "tool");
+        assert_eq!(body["options"]["num_predict"], 128);
+        assert_eq!(body["options"]["stop"][0], "END");
+        assert!(body.get("format").is_none());
+
+        let json_mode = json!({
+            "model": "qwen2.5:7b",
+            "messages": [{ "role": "user", "content": "Hi" }],
+            "response_format": { "type": "json_object" },
+        });
+        assert_eq!(to_ollama_request(&json_mode, None, false).unwrap()["format"], "json");
+    }
+
+    #[test]
+    fn test_ndjson_accumulation() {
+        let mut state = OllamaStreamState::new("x");
+        let lines = [
+            r#"{"message":{"role":"assistant","content":"Hel"},"done":false}"#,
+            r#"{"message":{"role":"assistant","content":"lo"},"done":false}"#,
+            r#"{"message":{"role":"assistant","content":"","tool_calls":[{"function":{"name":"get_weather","arguments":{"city":"Paris"}}}]},"done":false}"#,
+            r#"{"message":{"role":"assistant","content":""},"done":true,"done_reason":"stop","prompt_eval_count":10,"eval_count":4}"#,
+        ];
+        let deltas: Vec<String> = lines
+            .iter()
+            .map(|line| state.push(&serde_json::from_str(line).unwrap()).unwrap())
+            .collect();
+        assert_eq!(deltas[..2], ["Hel", "lo"]);
+        assert!(state.is_done());
+        assert_eq!(state.usage(), Some(TokenUsage::new(10, 4)));
+
+        let response = state.into_response("qwen2.5:7b");
+        let choice = &response["choices"][0];
+        assert_eq!(choice["message"]["content"], "Hello");
+        assert_eq!(choice["finish_reason"], "tool_calls");
+        assert_eq!(choice["message"]["tool_calls"][0]["id"], "call_x_0");
+        assert_eq!(choice["message"]["tool_calls"][0]["function"]["arguments"], "{\"city\":\"Paris\"}");
+        assert_eq!(response["usage"]["total_tokens"], 14);
+
+        let mut failed = OllamaStreamState::new("y");
+        assert!(failed.push(&json!({ "error": "model 'x' not found" })).is_err());
+    }
+}
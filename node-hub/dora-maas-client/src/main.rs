@@ -13,43 +13,31 @@
 //!
 //! The client operates as a Dora node, processing events in a single async loop:
 //! 1. Receives text input events from ASR or other nodes
-//! 2. Routes requests to configured cloud providers
-//! 3. Streams responses through the segmenter
-//! 4. Emits segmented text for TTS processing
-
-use std::collections::{BTreeMap, HashMap};
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
-
+//! 2. Hands each turn to the library's [`ConversationEngine`], which routes it
+//!    to the configured cloud providers and streams the reply through the segmenter
+//! 3. Emits the engine's output (segmented text for TTS, status, metrics) through
+//!    [`DoraSink`]
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use dora_maas_client::config::{Config, ConfigWatcher};
+use dora_maas_client::engine::{ConversationEngine, OutputSink, TextOutput, Turn, TurnInput};
+use dora_maas_client::failover::ServedBy;
+use dora_maas_client::mcp_context;
+use dora_maas_client::overrides::RequestOverrides;
+use dora_maas_client::ratelimit::RateLimitWait;
+use dora_maas_client::usage::UsageReport;
+use dora_maas_client::vision::ImageInput;
 use dora_node_api::{
     DoraNode, Event, Parameter,
     arrow::array::{AsArray, StringArray, Array},
+    arrow::datatypes::UInt8Type,
     dora_core::config::DataId,
 };
-use eyre::{Context, Result};
-use outfox_openai::spec::{
-    ChatCompletionMessageToolCall, ChatCompletionRequestAssistantMessage,
-    ChatCompletionRequestAssistantMessageContent, ChatCompletionRequestMessage,
-    ChatCompletionRequestSystemMessage, ChatCompletionRequestToolMessage,
-    ChatCompletionRequestUserMessage, ChatCompletionRequestUserMessageContent, ChatCompletionTool,
-    ChatCompletionToolType, CreateChatCompletionRequest, FunctionObject, PartibleTextContent,
-};
+use eyre::{Context, Result, eyre};
+use outfox_openai::spec::{ChatCompletionMessageToolCall, ChatCompletionTool};
 use serde_json::json;
-use tokio::sync::Mutex as AsyncMutex;
-use tokio_util::sync::CancellationToken;
-
-mod client;
-mod config;
-mod segmenter;
-mod streaming;
-mod tool;
-
-use config::{Config, load_anchor_context, format_anchor_context};
-use segmenter::StreamSegmenter;
-use tool::ToolSet;
-
-// Import CancellationReason from streaming module
-use crate::streaming::CancellationReason;
 
 // Helper function to send log messages
 fn send_log(node: &mut DoraNode, level: &str, message: &str) -> Result<()> {
@@ -68,244 +56,252 @@ fn send_log(node: &mut DoraNode, level: &str, message: &str) -> Result<()> {
     Ok(())
 }
 
-/// Manages active request cancellation tokens
-struct RequestCancellationManager {
-    /// Active tokens by request_id
-    active_tokens: Arc<AsyncMutex<HashMap<String, CancellationToken>>>,
-    /// Session mapping for tokens (session_id -> Vec<request_id>)
-    session_requests: Arc<AsyncMutex<HashMap<String, Vec<String>>>>,
+// Status metadata naming the provider that served a request after failover
+fn served_by_metadata(served: &ServedBy) -> BTreeMap<String, Parameter> {
+    let mut metadata = BTreeMap::new();
+    metadata.insert("provider".to_string(), Parameter::String(served.provider_id.clone()));
+    metadata.insert("model".to_string(), Parameter::String(served.model.clone()));
+    metadata.insert("attempts".to_string(), Parameter::Integer(served.attempts as i64));
+    metadata
 }
 
-impl RequestCancellationManager {
-    fn new() -> Self {
-        Self {
-            active_tokens: Arc::new(AsyncMutex::new(HashMap::new())),
-            session_requests: Arc::new(AsyncMutex::new(HashMap::new())),
-        }
-    }
+/// Sends engine output on the node's outputs. Text outputs carry the
+/// metadata of the input that started the turn.
+struct DoraSink<'a> {
+    node: &'a mut DoraNode,
+    metadata: &'a BTreeMap<String, Parameter>,
+}
 
-    /// Create a new cancellation token for a request
-    async fn create_token(
-        &self,
-        request_id: String,
-        session_id: String,
-    ) -> CancellationToken {
-        let token = CancellationToken::new();
-
-        // Store token
-        let mut tokens = self.active_tokens.lock().await;
-        tokens.insert(request_id.clone(), token.clone());
-        drop(tokens);
-
-        // Track session -> request mapping
-        let mut sessions = self.session_requests.lock().await;
-        sessions
-            .entry(session_id)
-            .or_insert_with(Vec::new)
-            .push(request_id);
-
-        token
+impl<'a> DoraSink<'a> {
+    fn new(node: &'a mut DoraNode, metadata: &'a BTreeMap<String, Parameter>) -> Self {
+        Self { node, metadata }
     }
+}
 
-    /// Cancel a specific request by ID
-    async fn cancel_request(&self, request_id: &str) -> bool {
-        let mut tokens = self.active_tokens.lock().await;
-        if let Some(token) = tokens.remove(request_id) {
-            token.cancel();
-            eprintln!("[CANCELLATION] Cancelled request: {}", request_id);
-            true
-        } else {
-            eprintln!("[CANCELLATION] Request not found: {}", request_id);
-            false
-        }
+impl OutputSink for DoraSink<'_> {
+    fn log(&mut self, level: &str, message: &str) -> Result<()> {
+        send_log(self.node, level, message)
     }
 
-    /// Cancel all requests for a session
-    async fn cancel_session(&self, session_id: &str) -> usize {
-        let mut sessions = self.session_requests.lock().await;
-        let request_ids = sessions.remove(session_id).unwrap_or_default();
-        drop(sessions);
+    fn status(&mut self, status: &str, served_by: Option<&ServedBy>) -> Result<()> {
+        self.node
+            .send_output(
+                DataId::from("status".to_string()),
+                served_by.map(served_by_metadata).unwrap_or_default(),
+                StringArray::from(vec![status]),
+            )
+            .context("Failed to send status output")
+    }
 
-        let mut cancelled_count = 0;
-        for request_id in request_ids {
-            if self.cancel_request(&request_id).await {
-                cancelled_count += 1;
-            }
+    fn text(&mut self, output: TextOutput<'_>) -> Result<()> {
+        let mut metadata = self.metadata.clone();
+        metadata.insert(
+            "session_status".to_string(),
+            Parameter::String(output.session_status.to_string()),
+        );
+        if let Some(index) = output.segment_index {
+            metadata.insert("segment_index".to_string(), Parameter::String(index.to_string()));
         }
-
-        // Only show cancellation message if there were actual requests to cancel
-        if cancelled_count > 0 {
-            eprintln!("[CANCELLATION] Cancelled {} requests for session: {}", cancelled_count, session_id);
-        } else {
-            // During startup, show a more informative message
-            if session_id == "default" {
-                eprintln!("[INFO] node ready - starting dataflow");
-            } else {
-                eprintln!("[INFO] {} ready - no active requests to cancel", session_id);
-            }
+        if let Some(hints) = &output.hints {
+            metadata.insert("pause_ms".to_string(), Parameter::Integer(hints.pause_ms as i64));
+            metadata.insert("emphasis".to_string(), Parameter::Bool(hints.emphasis));
+            metadata.insert(
+                "language".to_string(),
+                Parameter::String(hints.language.as_str().to_string()),
+            );
         }
-        cancelled_count
-    }
-
-    /// Clean up completed request
-    async fn cleanup_request(&self, request_id: &str, session_id: &str) {
-        let mut tokens = self.active_tokens.lock().await;
-        tokens.remove(request_id);
-        drop(tokens);
-
-        let mut sessions = self.session_requests.lock().await;
-        if let Some(request_ids) = sessions.get_mut(session_id) {
-            request_ids.retain(|id| id != request_id);
-            if request_ids.is_empty() {
-                sessions.remove(session_id);
-            }
+        if let Some(ssml) = output.ssml {
+            metadata.insert("ssml".to_string(), Parameter::String(ssml));
         }
-    }
-}
-
-struct ChatSession {
-    messages: Vec<ChatCompletionRequestMessage>,
-    total_tokens: usize,
-    tool_set: Option<Arc<Mutex<ToolSet>>>,
-}
-
-impl ChatSession {
-    fn new(system_prompt: String, anchor_context: Option<String>) -> Self {
-        // Combine system prompt with anchor context if provided
-        let combined_prompt = if let Some(context) = anchor_context {
-            format!("{}\n\n{}", context, system_prompt)
-        } else {
-            system_prompt
-        };
-
-        let system_message =
-            ChatCompletionRequestMessage::System(ChatCompletionRequestSystemMessage {
-                content: PartibleTextContent::Text(combined_prompt),
-                name: None,
-            });
-
-        Self {
-            messages: vec![system_message],
-            total_tokens: 0,
-            tool_set: None, // Will be set separately
+        if let Some(error_type) = output.error_type {
+            metadata.insert("error_type".to_string(), Parameter::String(error_type.to_string()));
+        }
+        if let Some(error_message) = output.error_message {
+            metadata.insert("error_message".to_string(), Parameter::String(error_message.to_string()));
         }
+        // Structured reply fields; the node's own keys win
+        for (key, value) in output.fields.into_iter().flatten() {
+            if !metadata.contains_key(key) {
+                metadata.insert(key.clone(), field_parameter(value));
+            }
+        }
+        self.node
+            .send_output(
+                DataId::from("text".to_string()),
+                metadata,
+                StringArray::from(vec![output.text]),
+            )
+            .context("Failed to send text output")
     }
 
-    fn set_tool_set(&mut self, tool_set: Arc<Mutex<ToolSet>>) {
-        self.tool_set = Some(tool_set);
+    fn tool_calls(&mut self, tool_calls: &[ChatCompletionMessageToolCall]) -> Result<()> {
+        let tool_calls_json = serde_json::to_string(tool_calls)?;
+        self.node
+            .send_output(
+                DataId::from("tool_calls".to_string()),
+                Default::default(),
+                StringArray::from(vec![tool_calls_json.as_str()]),
+            )
+            .context("Failed to send tool calls")
     }
 
-    fn has_tools(&self) -> bool {
-        self.tool_set
-            .as_ref()
-            .and_then(|ts| ts.lock().ok())
-            .map(|ts| ts.has_tools())
-            .unwrap_or(false)
+    fn metrics(&mut self, report: &UsageReport<'_>) -> Result<()> {
+        let mut metadata = BTreeMap::new();
+        metadata.insert(
+            "session_id".to_string(),
+            Parameter::String(report.session_id.to_string()),
+        );
+        self.node
+            .send_output(
+                DataId::from("metrics".to_string()),
+                metadata,
+                StringArray::from(vec![serde_json::to_string(report)?.as_str()]),
+            )
+            .context("Failed to send metrics output")
     }
 
-    fn get_tool_definitions(&self) -> Option<Vec<ChatCompletionTool>> {
-        self.tool_set
-            .as_ref()
-            .and_then(|ts| ts.lock().ok())
-            .map(|ts| {
-                ts.tools()
-                    .iter()
-                    .map(|tool| ChatCompletionTool {
-                        kind: ChatCompletionToolType::Function,
-                        function: FunctionObject {
-                            name: tool.name(),
-                            description: Some(tool.description()),
-                            parameters: Some(tool.parameters()),
-                            strict: None,
-                        },
-                    })
-                    .collect()
-            })
+    fn queued(&mut self, wait: &RateLimitWait) -> Result<()> {
+        send_log(
+            self.node,
+            "INFO",
+            &format!(
+                "Queued for '{}' ({}), retry in {} ms",
+                wait.provider_id,
+                wait.reason,
+                wait.retry_after.as_millis()
+            ),
+        )?;
+        let mut metadata = BTreeMap::new();
+        metadata.insert("provider".to_string(), Parameter::String(wait.provider_id.clone()));
+        metadata.insert("reason".to_string(), Parameter::String(wait.reason.to_string()));
+        metadata.insert(
+            "retry_after_ms".to_string(),
+            Parameter::Integer(wait.retry_after.as_millis() as i64),
+        );
+        self.node
+            .send_output(
+                DataId::from("status".to_string()),
+                metadata,
+                StringArray::from(vec!["queued"]),
+            )
+            .context("Failed to send status output")
     }
+}
 
-    fn add_user_message(&mut self, content: String) {
-        let message = ChatCompletionRequestMessage::User(ChatCompletionRequestUserMessage {
-            content: ChatCompletionRequestUserMessageContent::Text(content),
-            name: None,
-        });
-        self.messages.push(message);
+// Metadata value of a structured reply field; arrays and objects stay JSON
+fn field_parameter(value: &serde_json::Value) -> Parameter {
+    match value {
+        serde_json::Value::String(s) => Parameter::String(s.clone()),
+        serde_json::Value::Bool(b) => Parameter::Bool(*b),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Parameter::Integer(i),
+            None => Parameter::Float(n.as_f64().unwrap_or_default()),
+        },
+        other => Parameter::String(other.to_string()),
     }
+}
 
-    fn add_assistant_message(&mut self, content: String) {
-        let message =
-            ChatCompletionRequestMessage::Assistant(ChatCompletionRequestAssistantMessage {
-                content: Some(ChatCompletionRequestAssistantMessageContent::Text(content)),
-                name: None,
-                tool_calls: None,
-                audio: None,
-                refusal: None,
-            });
-        self.messages.push(message);
-    }
+// Per-request overrides from input metadata (and the control command, whose
+// fields win). Invalid values are logged and the config defaults are used.
+fn request_overrides(
+    node: &mut DoraNode,
+    config: &Config,
+    metadata: &BTreeMap<String, Parameter>,
+    control: Option<&serde_json::Value>,
+) -> Result<RequestOverrides> {
+    let parsed = RequestOverrides::from_metadata(metadata).and_then(|overrides| match control {
+        Some(command) => Ok(overrides.merge(RequestOverrides::from_json(command)?)),
+        None => Ok(overrides),
+    });
+    let mut overrides = match parsed {
+        Ok(overrides) => overrides,
+        Err(e) => {
+            send_log(node, "WARNING", &format!("Ignoring request overrides: {}", e))?;
+            RequestOverrides::default()
+        }
+    };
 
-    fn add_assistant_message_with_tools(
-        &mut self,
-        content: String,
-        tool_calls: Vec<ChatCompletionMessageToolCall>,
-    ) {
-        let message =
-            ChatCompletionRequestMessage::Assistant(ChatCompletionRequestAssistantMessage {
-                content: if content.is_empty() {
-                    None
-                } else {
-                    Some(ChatCompletionRequestAssistantMessageContent::Text(content))
-                },
-                name: None,
-                tool_calls: Some(tool_calls),
-                audio: None,
-                refusal: None,
-            });
-        self.messages.push(message);
+    if let Some(model) = overrides.model.take() {
+        if config.route_chain(&model).is_some() {
+            overrides.model = Some(model);
+        } else {
+            send_log(
+                node,
+                "WARNING",
+                &format!("Unknown model override '{}', using {}", model, config.default_model),
+            )?;
+        }
     }
-
-    fn add_tool_message(&mut self, tool_call_id: String, content: String) {
-        let message = ChatCompletionRequestMessage::Tool(ChatCompletionRequestToolMessage {
-            content: PartibleTextContent::Text(content),
-            tool_call_id,
-        });
-        self.messages.push(message);
+    if !overrides.is_empty() {
+        send_log(node, "DEBUG", &format!("Request overrides: {}", overrides.describe()))?;
     }
+    Ok(overrides)
+}
 
-    fn manage_history(&mut self, max_exchanges: usize) {
-        // Keep system message + last N exchanges (N*2 messages)
-        let max_messages = 1 + (max_exchanges * 2);
-        if self.messages.len() > max_messages {
-            let excess = self.messages.len() - max_messages;
-            // Remove old messages but keep system prompt
-            self.messages.drain(1..=excess);
-        }
+// Tool definitions passed through from the client in the `tools` metadata
+// (a JSON array), used when `enable_local_mcp` is off
+fn client_tools(metadata: &BTreeMap<String, Parameter>) -> Option<Vec<ChatCompletionTool>> {
+    match metadata.get("tools") {
+        Some(Parameter::String(tools_json)) => serde_json::from_str(tools_json).ok(),
+        _ => None,
     }
+}
 
-    fn reset(&mut self) {
-        // Keep only system message
-        self.messages.truncate(1);
-        self.total_tokens = 0;
-    }
+// Re-read the config file and hand it to the engine. On error the running
+// config stays in place.
+async fn reload_config(node: &mut DoraNode, engine: &mut ConversationEngine) -> Result<()> {
+    let new_config = Config::load_from(&Config::file_path())?;
+    let metadata = BTreeMap::new();
+    engine.reload(new_config, &mut DoraSink::new(node, &metadata)).await
 }
 
-/// Load and format anchor context for a given configuration
-fn load_anchor_context_for_session(config: &Config) -> Option<String> {
-    if let Some(ref context_path) = config.anchor_context {
-        match load_anchor_context(context_path) {
-            Ok(context_content) => {
-                let formatted = format_anchor_context(&context_content);
-                eprintln!("✅ Loaded anchor context from: {}", context_path);
-                Some(formatted)
+// `save`, `load` and `fork` control commands; `name` is the snapshot name
+// (save/load, default: the session ID) or the new session ID (fork)
+fn session_store_command(
+    node: &mut DoraNode,
+    engine: &mut ConversationEngine,
+    session_id: &str,
+    command: &str,
+    name: Option<&str>,
+) -> Result<()> {
+    let status = match command {
+        "save" => match engine.save_session(session_id, name) {
+            Ok(path) => {
+                send_log(node, "INFO", &format!("💾 Saved session {} to {}", session_id, path.display()))?;
+                "saved"
             }
-            Err(e) => {
-                eprintln!("⚠️ Warning: Failed to load anchor context from '{}': {}", context_path, e);
-                eprintln!("⚠️ Proceeding without anchor context");
-                None
+            Err(e) => return send_log(node, "ERROR", &format!("Failed to save session {}: {:#}", session_id, e)),
+        },
+        "load" => match engine.load_session(session_id, name) {
+            Ok(true) => {
+                send_log(node, "INFO", &format!("📂 Loaded session {} from {}", session_id, name.unwrap_or(session_id)))?;
+                "loaded"
+            }
+            Ok(false) => {
+                return send_log(node, "WARNING", &format!("No saved session named {}", name.unwrap_or(session_id)));
+            }
+            Err(e) => return send_log(node, "ERROR", &format!("Failed to load session {}: {:#}", session_id, e)),
+        },
+        _ => {
+            let Some(new_session_id) = name else {
+                return send_log(node, "ERROR", "fork needs a \"name\" field (the new session ID)");
+            };
+            match engine.fork_session(session_id, new_session_id) {
+                Ok(path) => {
+                    let saved = path.map(|p| format!(", saved to {}", p.display())).unwrap_or_default();
+                    send_log(node, "INFO", &format!("🍴 Forked session {} as {}{}", session_id, new_session_id, saved))?;
+                    "forked"
+                }
+                Err(e) => return send_log(node, "ERROR", &format!("Failed to fork session {}: {:#}", session_id, e)),
             }
         }
-    } else {
-        None
-    }
+    };
+    node.send_output(
+        DataId::from("status".to_string()),
+        Default::default(),
+        StringArray::from(vec![status]),
+    )
+    .context("Failed to send status output")
 }
 
 #[tokio::main]
@@ -320,35 +316,7 @@ async fn main() -> Result<()> {
 
     // Load configuration
     let config = Config::load().context("Failed to load configuration")?;
-
-    // Log level is available for future use
-    let _log_level = &config.log_level;
-
-    // Load anchor context if configured
-    let anchor_context = load_anchor_context_for_session(&config);
-
-    // Initialize MCP tools if enabled
-    let tool_set = if config.enable_tools {
-        match config.init_tool_set().await {
-            Ok(Some(ts)) => {
-                let tool_count = ts.tools().len();
-                Some(Arc::new(Mutex::new(ts)))
-            }
-            Ok(None) => None,
-            Err(e) => {
-                eprintln!("Warning: Failed to initialize MCP tools: {}", e);
-                None
-            }
-        }
-    } else {
-        None
-    };
-
-    // Create provider clients
-    let clients = config.create_clients();
-
-    // Initialize cancellation manager if enabled
-    let cancellation_manager = Arc::new(RequestCancellationManager::new());
+    let mut config_watcher = ConfigWatcher::new(Config::file_path());
 
     // Initialize Dora node - use node_id if provided (dynamic node), otherwise from env
     let (mut node, events) = if let Some(id) = node_id {
@@ -371,21 +339,49 @@ async fn main() -> Result<()> {
         }
     };
 
+    // Tools, MCP resources, anchor context and provider clients
+    let mut engine = ConversationEngine::new(config, node.id().to_string()).await;
+
+    // Check local model servers (model list, preload) before the first request
+    let local_provider_logs = engine.config().probe_local_providers().await;
+
     // Send initialization logs
     send_log(&mut node, "INFO", "MaaS Client initialized")?;
     send_log(
         &mut node,
         "INFO",
-        &format!("Model: {}", config.default_model),
+        &format!("Model: {}", engine.config().default_model),
     )?;
     send_log(
         &mut node,
         "INFO",
-        &format!("Providers: {}", config.providers.len()),
+        &format!("Providers: {}", engine.config().providers.len()),
     )?;
+    for (level, message) in &local_provider_logs {
+        send_log(&mut node, level, message)?;
+    }
 
-    // Session storage
-    let mut sessions: HashMap<String, ChatSession> = HashMap::new();
+    if !engine.mcp_resources().is_empty() {
+        send_log(
+            &mut node,
+            "INFO",
+            &format!("MCP resources in system context: {}", engine.mcp_resources().len()),
+        )?;
+    }
+
+    if let Some(max_tokens) = engine.config().max_context_tokens {
+        let prompt_tokens = engine.new_session().estimated_prompt_tokens();
+        if prompt_tokens > max_tokens as u64 {
+            send_log(
+                &mut node,
+                "WARNING",
+                &format!(
+                    "System prompt and context (~{} tokens) exceed max_context_tokens ({})",
+                    prompt_tokens, max_tokens
+                ),
+            )?;
+        }
+    }
 
     // Process events
     let events = futures::executor::block_on_stream(events);
@@ -393,6 +389,14 @@ async fn main() -> Result<()> {
     for event in events {
         match event {
             Event::Input { id, data, metadata } => {
+                // Pick up edits to the config file before handling the input
+                if engine.config().watch_config && config_watcher.changed() {
+                    send_log(&mut node, "INFO", "Config file changed, reloading")?;
+                    if let Err(e) = reload_config(&mut node, &mut engine).await {
+                        send_log(&mut node, "ERROR", &format!("Config reload failed, keeping current config: {:#}", e))?;
+                    }
+                }
+
                 // Extract session ID from metadata
                 let session_id = metadata
                     .parameters
@@ -403,8 +407,6 @@ async fn main() -> Result<()> {
                     })
                     .unwrap_or_else(|| "default".to_string());
 
-                // debug!("Received input '{}' for session '{}'", id, session_id);
-
                 match id.as_str() {
                     "text" | "text_to_audio" => {
                         // Extract text from input
@@ -422,15 +424,6 @@ async fn main() -> Result<()> {
                             continue;
                         }
 
-                        // Get or create session
-                        let session = sessions.entry(session_id.clone()).or_insert_with(|| {
-                            let mut session = ChatSession::new(config.system_prompt.clone(), anchor_context.clone());
-                            if let Some(ref ts) = tool_set {
-                                session.set_tool_set(ts.clone());
-                            }
-                            session
-                        });
-
                         let role = metadata
                             .parameters
                             .get("role")
@@ -445,640 +438,60 @@ async fn main() -> Result<()> {
                                 "DEBUG",
                                 &format!("Caching assistant context: {}", user_text),
                             )?;
-                            session.add_assistant_message(user_text.clone());
-                            session.manage_history(config.max_history_exchanges);
+                            engine.add_context(&session_id, user_text);
                             continue;
                         }
 
-                        send_log(&mut node, "INFO", &format!("Processing: {}", user_text))?;
-
-                        // Add user message
-                        session.add_user_message(user_text.clone());
-
-                        // Manage history
-                        session.manage_history(config.max_history_exchanges);
-
-                        // Process the conversation with automatic tool handling
-                        // FIX: Added loop to handle tool calls without waiting for user input
-                        // When the LLM calls a tool, we execute it and immediately send the results
-                        // back to get the final response, instead of waiting for the next user message
-                        let mut continue_conversation = true;
-                        while continue_conversation {
-                            continue_conversation = false; // Default to not continuing unless we have tool calls
-
-                            // Route to appropriate provider
-                            let (provider_id, model_name) =
-                                config.route_model(&config.default_model).ok_or_else(|| {
-                                    eyre::eyre!(
-                                        "No route found for model: {}",
-                                        config.default_model
-                                    )
-                                })?;
+                        let overrides =
+                            request_overrides(&mut node, engine.config(), &metadata.parameters, None)?;
 
-                            // Create chat completion request with the mapped model name
-                            let mut request = CreateChatCompletionRequest::new(
-                                model_name.clone(), // Use the mapped model name, not the config model ID
-                                session.messages.clone(),
-                            );
+                        send_log(&mut node, "INFO", &format!("Processing: {}", user_text))?;
 
-                            // Add tool definitions if available
-                            if config.enable_tools {
-                                if config.enable_local_mcp && session.has_tools() {
-                                    // Use local MCP tools
-                                    request.tools = session.get_tool_definitions();
-                                    send_log(
-                                        &mut node,
-                                        "DEBUG",
-                                        &format!(
-                                            "Added {} local MCP tool definitions",
-                                            request.tools.as_ref().map(|t| t.len()).unwrap_or(0)
-                                        ),
-                                    )?;
-                                } else if !config.enable_local_mcp {
-                                    // Pass through tools from client metadata
-                                    if let Some(tools_param) = metadata.parameters.get("tools") {
-                                        // Parse tools from metadata (expecting JSON array)
-                                        if let Parameter::String(tools_json) = tools_param {
-                                            if let Ok(tools) =
-                                                serde_json::from_str::<Vec<ChatCompletionTool>>(
-                                                    tools_json,
-                                                )
-                                            {
-                                                request.tools = Some(tools.clone());
-                                                send_log(
-                                                    &mut node,
-                                                    "DEBUG",
-                                                    &format!(
-                                                        "Added {} client-provided tool definitions",
-                                                        tools.len()
-                                                    ),
-                                                )?;
-                                            }
-                                        }
-                                    }
-                                }
+                        let turn = Turn {
+                            input: TurnInput::User(user_text),
+                            overrides,
+                            client_tools: client_tools(&metadata.parameters),
+                        };
+                        engine
+                            .run_turn(&session_id, turn, &mut DoraSink::new(&mut node, &metadata.parameters))
+                            .await?;
+                    }
+                    "image" => {
+                        // Encoded image bytes, or a file path as text
+                        let text_param = |key: &str| match metadata.parameters.get(key) {
+                            Some(Parameter::String(s)) if !s.is_empty() => Some(s.clone()),
+                            _ => None,
+                        };
+                        let mime_type = text_param("mime_type");
+                        let image = if let Some(bytes) = data.as_primitive_opt::<UInt8Type>() {
+                            ImageInput::from_bytes(bytes.values().to_vec(), mime_type.as_deref())
+                        } else if let Some(paths) = data.as_string_opt::<i32>() {
+                            match paths.iter().flatten().map(str::trim).find(|p| !p.is_empty()) {
+                                Some(path) => ImageInput::from_path(Path::new(path), mime_type.as_deref()),
+                                None => Err(eyre!("Image input has no data")),
                             }
-                            request.stream = config.enable_streaming;
-                            request.temperature = Some(0.7);
-
-                            let client = clients.get(&provider_id).ok_or_else(|| {
-                                eyre::eyre!("No client found for provider: {}", provider_id)
-                            })?;
-
-                            send_log(
-                                &mut node,
-                                "DEBUG",
-                                &format!(
-                                    "Routing to provider '{}' with model '{}'",
-                                    provider_id, model_name
-                                ),
-                            )?;
-
-                            // Send "processing" status when starting API call
-                            node.send_output(
-                                DataId::from("status".to_string()),
-                                Default::default(),
-                                StringArray::from(vec!["processing"]),
-                            )
-                            .context("Failed to send status output")?;
-
-                            // Make API call - use streaming if enabled
-                            if config.enable_streaming.unwrap_or(false) {
-                                // Streaming mode
-                                send_log(&mut node, "DEBUG", "Using streaming mode")?;
-
-                                let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
-                                let mut has_sent_segment = false;
-                                let mut segment_index: u32 = 0;
-
-                                // Start streaming in background with cancellation support
-                                let client_clone = client.clone();
-                                let request_clone = request.clone();
-                                let request_id = uuid::Uuid::new_v4().to_string();
-                                let session_id_clone = session_id.clone();
-                                let metadata_clone = metadata.parameters.clone();
-                                let cancellation_manager_clone = cancellation_manager.clone();
-
-                                // Create cancellation token if enabled
-                                let cancellation_token = if config.enable_cancellation {
-                                    Some(cancellation_manager.create_token(
-                                        request_id.clone(),
-                                        session_id.clone(),
-                                    ).await)
-                                } else {
-                                    None
-                                };
-
-                                let stream_handle = tokio::spawn(async move {
-                                    let result = if let Some(token) = cancellation_token {
-                                        // Use cancellation-aware streaming
-                                        client_clone.complete_streaming_with_cancellation(
-                                            request_clone,
-                                            tx,
-                                            token,
-                                            Duration::from_secs(config.stream_timeout_secs),
-                                        ).await
-                                    } else {
-                                        // Use regular streaming
-                                        client_clone.complete_streaming(request_clone, tx).await
-                                    };
-
-                                    // Clean up token after completion
-                                    if config.enable_cancellation {
-                                        cancellation_manager_clone
-                                            .cleanup_request(&request_id, &session_id_clone)
-                                            .await;
-                                    }
-
-                                    result
-                                });
-
-                                // Use segmenter to buffer chunks into meaningful segments
-                                let mut segmenter = StreamSegmenter::new(10); // Max 10 words without punctuation
-                                let mut accumulated = String::new();
-                                let mut chunk_count = 0;
-                                let mut segment_count = 0;
-
-                                while let Some(chunk) = rx.recv().await {
-                                    chunk_count += 1;
-
-                                    // Add chunk to segmenter and check if we have a segment ready
-                                    if let Some(segment) = segmenter.add_chunk(&chunk) {
-                                        accumulated.push_str(&segment);
-                                        segment_count += 1;
-
-                                        // Send the meaningful segment with metadata passthrough
-                                        let mut segment_metadata = metadata.parameters.clone();
-                                        segment_metadata.insert(
-                                            "session_status".to_string(),
-                                            Parameter::String(
-                                                if !has_sent_segment {
-                                                    "started".to_string()
-                                                } else {
-                                                    "ongoing".to_string()
-                                                },
-                                            ),
-                                        );
-                                        segment_metadata.insert(
-                                            "segment_index".to_string(),
-                                            Parameter::String(segment_index.to_string()),
-                                        );
-
-                                        node.send_output(
-                                            DataId::from("text".to_string()),
-                                            segment_metadata,
-                                            StringArray::from(vec![segment.as_str()]),
-                                        )
-                                        .context("Failed to send text segment")?;
-                                        has_sent_segment = true;
-                                        segment_index += 1;
-                                    }
-                                }
-
-                                // Flush any remaining buffered content
-                                if let Some(final_segment) = segmenter.flush() {
-                                    accumulated.push_str(&final_segment);
-                                    segment_count += 1;
-
-                                    let mut segment_metadata = metadata.parameters.clone();
-                                    segment_metadata.insert(
-                                        "session_status".to_string(),
-                                        Parameter::String(
-                                            if !has_sent_segment {
-                                                "started".to_string()
-                                            } else {
-                                                "ongoing".to_string()
-                                            },
-                                        ),
-                                    );
-                                    segment_metadata.insert(
-                                        "segment_index".to_string(),
-                                        Parameter::String(segment_index.to_string()),
-                                    );
-
-                                    node.send_output(
-                                        DataId::from("text".to_string()),
-                                        segment_metadata,
-                                        StringArray::from(vec![final_segment.as_str()]),
-                                    )
-                                    .context("Failed to send final segment")?;
-                                    has_sent_segment = true;
-                                    segment_index += 1;
-
-                                    send_log(
-                                        &mut node,
-                                        "DEBUG",
-                                        &format!(
-                                            "Sent final segment {} ({} chars)",
-                                            segment_count,
-                                            final_segment.len()
-                                        ),
-                                    )?;
-                                }
-
-                                // Wait for streaming to complete
-                                match stream_handle.await {
-                                    Ok(Ok((final_text, tool_calls))) => {
-                                        send_log(
-                                            &mut node,
-                                            "INFO",
-                                            &format!(
-                                                "Streaming complete: {} chars in {} segments (from {} chunks)",
-                                                final_text.len(),
-                                                segment_count,
-                                                chunk_count
-                                            ),
-                                        )?;
-
-                                        // Send "complete" status
-                                        node.send_output(
-                                            DataId::from("status".to_string()),
-                                            Default::default(),
-                                            StringArray::from(vec!["complete"]),
-                                    )
-                                    .context("Failed to send status output")?;
-
-                                    // FIX: Handle tool calls from streaming response
-                                    // When the LLM returns tool calls, we either execute them locally (enable_local_mcp=true)
-                                    // or pass them back to the client (enable_local_mcp=false)
-                                    if let Some(tool_calls) = tool_calls {
-                                            send_log(
-                                                &mut node,
-                                                "INFO",
-                                                &format!(
-                                                    "Received {} tool calls",
-                                                    tool_calls.len()
-                                                ),
-                                            )?;
-
-                                            // Add assistant message with tool calls first
-                                            session.add_assistant_message_with_tools(
-                                                final_text.clone(),
-                                                tool_calls.clone(),
-                                            );
-
-                                            if config.enable_local_mcp {
-                                                // Execute tool calls locally
-                                                send_log(
-                                                    &mut node,
-                                                    "INFO",
-                                                    "Executing tool calls locally",
-                                                )?;
-
-                                                // Execute tool calls and collect results
-                                                let mut tool_results = Vec::new();
-                                                if let Some(ref tool_set) = session.tool_set {
-                                                    for tool_call in &tool_calls {
-                                                        send_log(
-                                                            &mut node,
-                                                            "DEBUG",
-                                                            &format!(
-                                                                "Calling tool: {} with args: {}",
-                                                                tool_call.function.name,
-                                                                tool_call.function.arguments
-                                                            ),
-                                                        )?;
-
-                                                        // Get the tool from the tool set
-                                                        let tool = {
-                                                            let tool_set_guard =
-                                                                tool_set.lock().unwrap();
-                                                            tool_set_guard
-                                                                .get_tool(&tool_call.function.name)
-                                                        };
-
-                                                        let result = if let Some(tool) = tool {
-                                                            // Parse arguments
-                                                            let args: serde_json::Value =
-                                                                serde_json::from_str(
-                                                                    &tool_call.function.arguments,
-                                                                )
-                                                                .unwrap_or(serde_json::Value::Null);
-
-                                                            // Execute the tool
-                                                            match tool.call(args).await {
-                                                                Ok(result) => {
-                                                                    let content = if let Some(
-                                                                        contents,
-                                                                    ) =
-                                                                        result.content
-                                                                    {
-                                                                        contents
-                                                                            .iter()
-                                                                            .filter_map(|c| {
-                                                                                c.as_text()
-                                                                            })
-                                                                            .map(|t| t.text.clone())
-                                                                            .collect::<Vec<_>>()
-                                                                            .join("\n")
-                                                                    } else {
-                                                                        "Tool executed successfully"
-                                                                            .to_string()
-                                                                    };
-                                                                    send_log(
-                                                                        &mut node,
-                                                                        "DEBUG",
-                                                                        &format!(
-                                                                            "Tool result: {}",
-                                                                            content
-                                                                        ),
-                                                                    )?;
-                                                                    content
-                                                                }
-                                                                Err(e) => {
-                                                                    send_log(
-                                                                        &mut node,
-                                                                        "ERROR",
-                                                                        &format!(
-                                                                            "Tool execution failed: {}",
-                                                                            e
-                                                                        ),
-                                                                    )?;
-                                                                    format!("Error: {}", e)
-                                                                }
-                                                            }
-                                                        } else {
-                                                            let msg = format!(
-                                                                "Tool '{}' not found",
-                                                                tool_call.function.name
-                                                            );
-                                                            send_log(&mut node, "ERROR", &msg)?;
-                                                            msg
-                                                        };
-
-                                                        // Collect the result to add later
-                                                        tool_results
-                                                            .push((tool_call.id.clone(), result));
-                                                    }
-                                                }
-
-                                                // Add all tool results to session
-                                                for (tool_call_id, result) in tool_results {
-                                                    session.add_tool_message(tool_call_id, result);
-                                                }
-
-                                                // After tool execution, immediately make another request to get the final response
-                                                // Don't wait for user input - we need to send the tool results back to the LLM
-                                                send_log(
-                                                    &mut node,
-                                                    "DEBUG",
-                                                    "Sending tool results back to LLM for final response",
-                                                )?;
-
-                                                // Set flag to continue the conversation with tool results
-                                                continue_conversation = true;
-                                            } else {
-                                                // Pass tool calls back to client
-                                                send_log(
-                                                    &mut node,
-                                                    "INFO",
-                                                    "Passing tool calls to client",
-                                                )?;
-
-                                                // Serialize tool calls and send to client
-                                                let tool_calls_json =
-                                                    serde_json::to_string(&tool_calls)?;
-                                                node.send_output(
-                                                    DataId::from("tool_calls".to_string()),
-                                                    Default::default(),
-                                                    StringArray::from(vec![
-                                                        tool_calls_json.as_str(),
-                                                    ]),
-                                                )
-                                                .context("Failed to send tool calls")?;
-
-                                                // Don't continue conversation - wait for tool results from client
-                                                continue_conversation = false;
-                                            }
-                                        } else {
-                                            // No tool calls, just add the text message
-                                            session.add_assistant_message(final_text.clone());
-                                        }
-                                    }
-                                    Ok(Err(e)) => {
-                                        let error_msg = format!("{}", e);
-                                        send_log(
-                                            &mut node,
-                                            "ERROR",
-                                            &format!("Streaming error: {}", error_msg),
-                                        )?;
-
-                                        // Classify error type and set appropriate session_status
-                                        let status = if error_msg.contains("cancelled") || error_msg.contains("cancelled by user") {
-                                            "cancelled"
-                                        } else if error_msg.contains("timed out") {
-                                            "timeout"
-                                        } else {
-                                            "error"
-                                        };
-
-                                        // Use same classification for session_status
-                                        let session_status = if error_msg.contains("cancelled") || error_msg.contains("cancelled by user") {
-                                            "cancelled"
-                                        } else if error_msg.contains("timed out") {
-                                            "timeout"
-                                        } else {
-                                            "error"
-                                        };
-
-                                        // Send status
-                                        node.send_output(
-                                            DataId::from("status".to_string()),
-                                            Default::default(),
-                                            StringArray::from(vec![status]),
-                                        )
-                                        .context("Failed to send status output")?;
-
-                                        let mut error_metadata = metadata.parameters.clone();
-                                        error_metadata.insert(
-                                            "session_status".to_string(),
-                                            Parameter::String(session_status.to_string()),
-                                        );
-                                        error_metadata.insert(
-                                            "error_type".to_string(),
-                                            Parameter::String(status.to_string()),
-                                        );
-                                        error_metadata.insert(
-                                            "error_message".to_string(),
-                                            Parameter::String(error_msg),
-                                        );
-
-                                        node.send_output(
-                                            DataId::from("text".to_string()),
-                                            error_metadata,
-                                            StringArray::from(vec![
-                                                format!("Error: {}", e).as_str(),
-                                            ]),
-                                        )
-                                        .context("Failed to send error")?;
-                                    }
-                                    Err(e) => {
-                                        let error_msg = format!("{}", e);
-                                        send_log(
-                                            &mut node,
-                                            "ERROR",
-                                            &format!("Task error: {}", error_msg),
-                                        )?;
-
-                                        // Classify error type for task errors
-                                        let error_type = if error_msg.contains("cancelled") || error_msg.contains("cancelled by user") {
-                                            "cancelled"
-                                        } else if error_msg.contains("timed out") {
-                                            "timeout"
-                                        } else {
-                                            "error"
-                                        };
-
-                                        // Send error status
-                                        node.send_output(
-                                            DataId::from("status".to_string()),
-                                            Default::default(),
-                                            StringArray::from(vec![format!("{}: {}", error_type, e)]),
-                                        )
-                                        .context("Failed to send status output")?;
-
-                                        let mut error_metadata = metadata.parameters.clone();
-                                        error_metadata.insert(
-                                            "session_status".to_string(),
-                                            Parameter::String(error_type.to_string()),
-                                        );
-                                        error_metadata.insert(
-                                            "error_type".to_string(),
-                                            Parameter::String(error_type.to_string()),
-                                        );
-                                        error_metadata.insert(
-                                            "error_message".to_string(),
-                                            Parameter::String(error_msg),
-                                        );
-
-                                        node.send_output(
-                                            DataId::from("text".to_string()),
-                                            error_metadata,
-                                            StringArray::from(vec![format!("Error: {}", e).as_str()]),
-                                        )
-                                        .context("Failed to send error output")?;
-                                    }
-                                }
-
-                                if has_sent_segment {
-                                    let mut end_metadata = metadata.parameters.clone();
-                                    end_metadata.insert(
-                                        "session_status".to_string(),
-                                        Parameter::String("ended".to_string()),
-                                    );
-                                    end_metadata.insert(
-                                        "segment_index".to_string(),
-                                        Parameter::String(segment_index.to_string()),
-                                    );
-                                    node.send_output(
-                                        DataId::from("text".to_string()),
-                                        end_metadata,
-                                        StringArray::from(vec![""]),
-                                    )
-                                    .context("Failed to send session end marker")?;
-                                }
-                            } else {
-                                // Non-streaming mode
-                                match client.complete(request).await {
-                                    Ok(response) => {
-                                        if let Some(choice) = response.choices.first() {
-                                            let content = match &choice.message {
-                                        outfox_openai::spec::ChatCompletionResponseMessage { content, .. } => {
-                                            content.clone().unwrap_or_default()
-                                        }
-                                    };
-
-                                            send_log(
-                                                &mut node,
-                                                "INFO",
-                                                &format!(
-                                                    "Generated response ({} chars)",
-                                                    content.len()
-                                                ),
-                                            )?;
-
-                                            // Add assistant message to session
-                                            session.add_assistant_message(content.clone());
-
-                                            // Send response with metadata passthrough
-                                            let mut reply_metadata = metadata.parameters.clone();
-                                            reply_metadata.insert(
-                                                "session_status".to_string(),
-                                                Parameter::String("started".to_string()),
-                                            );
-                                            node.send_output(
-                                                DataId::from("text".to_string()),
-                                                reply_metadata,
-                                                StringArray::from(vec![content.as_str()]),
-                                            )
-                                            .context("Failed to send text output")?;
-
-                                            // Send "complete" status
-                                            node.send_output(
-                                                DataId::from("status".to_string()),
-                                                Default::default(),
-                                                StringArray::from(vec!["complete"]),
-                                            )
-                                            .context("Failed to send status output")?;
-
-                                            let mut end_metadata = metadata.parameters.clone();
-                                            end_metadata.insert(
-                                                "session_status".to_string(),
-                                                Parameter::String("ended".to_string()),
-                                            );
-                                            node.send_output(
-                                                DataId::from("text".to_string()),
-                                                end_metadata,
-                                                StringArray::from(vec![""]),
-                                            )
-                                            .context("Failed to send session end marker")?;
-                                        }
-                                    }
-                                    Err(e) => {
-                                        let error_msg = format!("{}", e);
-                                                                send_log(&mut node, "ERROR", &error_msg)?;
-
-                                        // Classify error type for API call errors
-                                        let error_type = if error_msg.contains("cancelled") || error_msg.contains("cancelled by user") {
-                                            "cancelled"
-                                        } else if error_msg.contains("timed out") {
-                                            "timeout"
-                                        } else {
-                                            "error"
-                                        };
-
-                                        // Send error status
-                                        node.send_output(
-                                            DataId::from("status".to_string()),
-                                            Default::default(),
-                                            StringArray::from(vec![format!("{}: {}", error_type, e)]),
-                                        )
-                                        .context("Failed to send status output")?;
-
-                                        // Send error response with metadata passthrough
-                                        let mut error_metadata = metadata.parameters.clone();
-                                        error_metadata.insert(
-                                            "session_status".to_string(),
-                                            Parameter::String(error_type.to_string()),
-                                        );
-                                        error_metadata.insert(
-                                            "error_type".to_string(),
-                                            Parameter::String(error_type.to_string()),
-                                        );
-                                        error_metadata.insert(
-                                            "error_message".to_string(),
-                                            Parameter::String(error_msg.clone()),
-                                        );
-                                        node.send_output(
-                                            DataId::from("text".to_string()),
-                                            error_metadata,
-                                            StringArray::from(vec![error_msg.as_str()]),
-                                        )
-                                        .context("Failed to send error output")?;
-                                    }
-                                }
-                            } // Close the else block for non-streaming
-                        } // Close the while continue_conversation loop
+                        } else {
+                            Err(eyre!("Unsupported image data type: {:?}", data.data_type()))
+                        };
+
+                        match image {
+                            Ok(image) => {
+                                send_log(
+                                    &mut node,
+                                    "INFO",
+                                    &format!(
+                                        "Attached {} image ({} bytes) to the next message of session {}",
+                                        image.mime_type,
+                                        image.data.len(),
+                                        session_id
+                                    ),
+                                )?;
+                                engine.attach_image(&session_id, image.with_detail(text_param("detail")));
+                            }
+                            Err(e) => {
+                                send_log(&mut node, "ERROR", &format!("Ignoring image input: {:#}", e))?;
+                            }
+                        }
                     }
                     "tool_results" => {
                         // Handle tool results from client (when enable_local_mcp=false)
@@ -1090,119 +503,23 @@ async fn main() -> Result<()> {
                             if let Ok(tool_results) =
                                 serde_json::from_str::<Vec<(String, String)>>(results_json)
                             {
-                                // Get session
-                                if let Some(session) = sessions.get_mut(&session_id) {
-                                    // Add tool results to session
-                                    for (tool_call_id, result) in tool_results {
-                                        session.add_tool_message(tool_call_id.clone(), result);
-                                    }
-
+                                if engine.has_session(&session_id) {
                                     send_log(
                                         &mut node,
                                         "DEBUG",
-                                        "Added tool results to session, making API call for final response",
+                                        "Sending tool results to the LLM for the final response",
                                     )?;
 
-                                    // Route to appropriate provider
-                                    let (provider_id, model_name) = config
-                                        .route_model(&config.default_model)
-                                        .ok_or_else(|| {
-                                            eyre::eyre!(
-                                                "No route found for model: {}",
-                                                config.default_model
-                                            )
-                                        })?;
-
-                                    // Create chat completion request with tool results
-                                    let mut request = CreateChatCompletionRequest::new(
-                                        model_name.clone(),
-                                        session.messages.clone(),
-                                    );
-
-                                    // Tool definitions should still be included for context
-                                    if config.enable_tools && !config.enable_local_mcp {
-                                        if let Some(tools_param) = metadata.parameters.get("tools")
-                                        {
-                                            if let Parameter::String(tools_json) = tools_param {
-                                                if let Ok(tools) =
-                                                    serde_json::from_str::<Vec<ChatCompletionTool>>(
-                                                        tools_json,
-                                                    )
-                                                {
-                                                    request.tools = Some(tools);
-                                                }
-                                            }
-                                        }
-                                    }
-
-                                    request.stream = config.enable_streaming;
-                                    request.temperature = Some(0.7);
-
-                                    let client = clients.get(&provider_id).ok_or_else(|| {
-                                        eyre::eyre!("No client found for provider: {}", provider_id)
-                                    })?;
-
-                                    // Send "processing" status
-                                    node.send_output(
-                                        DataId::from("status".to_string()),
-                                        Default::default(),
-                                        StringArray::from(vec!["processing"]),
-                                    )
-                                    .context("Failed to send status output")?;
-
-                                    // Make API call to get final response after tool execution
-                                    match client.complete(request).await {
-                                        Ok(response) => {
-                                            if let Some(choice) = response.choices.first() {
-                                                let content = match &choice.message {
-                                                    outfox_openai::spec::ChatCompletionResponseMessage { content, .. } => {
-                                                        content.clone().unwrap_or_default()
-                                                    }
-                                                };
-
-                                                send_log(
-                                                    &mut node,
-                                                    "INFO",
-                                                    &format!(
-                                                        "Generated response after tool execution ({} chars)",
-                                                        content.len()
-                                                    ),
-                                                )?;
-
-                                                // Add assistant message to session
-                                                session.add_assistant_message(content.clone());
-
-                                                // Send response with metadata passthrough
-                                                node.send_output(
-                                                    DataId::from("text".to_string()),
-                                                    metadata.parameters.clone(),
-                                                    StringArray::from(vec![content.as_str()]),
-                                                )
-                                                .context("Failed to send text output")?;
-
-                                                // Send "complete" status
-                                                node.send_output(
-                                                    DataId::from("status".to_string()),
-                                                    Default::default(),
-                                                    StringArray::from(vec!["complete"]),
-                                                )
-                                                .context("Failed to send status output")?;
-                                            }
-                                        }
-                                        Err(e) => {
-                                            let error_msg =
-                                                format!("Error processing tool results: {}", e);
-                                            send_log(&mut node, "ERROR", &error_msg)?;
-
-                                            // Send "error" status
-                                            node.send_output(
-                                                DataId::from("status".to_string()),
-                                                Default::default(),
-                                                StringArray::from(vec![format!("error: {}", e)]),
-                                            )
-                                            .context("Failed to send status output")?;
-                                        }
-                                    }
+                                    let overrides =
+                                        request_overrides(&mut node, engine.config(), &metadata.parameters, None)?;
+                                    let turn = Turn {
+                                        input: TurnInput::ToolResults(tool_results),
+                                        overrides,
+                                        client_tools: client_tools(&metadata.parameters),
+                                    };
+                                    engine
+                                        .run_turn(&session_id, turn, &mut DoraSink::new(&mut node, &metadata.parameters))
+                                        .await?;
                                 }
                             } else {
                                 send_log(&mut node, "ERROR", "Failed to parse tool results")?;
@@ -1228,7 +545,11 @@ async fn main() -> Result<()> {
                         let is_known_command = control_text.eq_ignore_ascii_case("reset") ||
                                                control_text.eq_ignore_ascii_case("cancel") ||
                                                control_text.eq_ignore_ascii_case("ready") ||
-                                               control_text.eq_ignore_ascii_case("exit");
+                                               control_text.eq_ignore_ascii_case("exit") ||
+                                               control_text.eq_ignore_ascii_case("reload") ||
+                                               control_text.eq_ignore_ascii_case("save") ||
+                                               control_text.eq_ignore_ascii_case("load") ||
+                                               control_text.eq_ignore_ascii_case("fork");
 
                         let is_valid_json_prompt = serde_json::from_str::<serde_json::Value>(&control_text)
                             .ok()
@@ -1247,9 +568,13 @@ async fn main() -> Result<()> {
 
                         let mut should_reset = false;
                         let mut should_cancel = false;
+                        let mut should_reload = false;
+                        let mut resource_uri: Option<String> = None;
+                        let mut prompt_preset: Option<String> = None;
                         let mut prompt_text: Option<String> = None;
+                        let mut store_command: Option<(String, Option<String>)> = None;
 
-                        if let Some(json) = parsed {
+                        if let Some(json) = &parsed {
                             // Handle JSON control input
                             send_log(&mut node, "DEBUG", &format!("Parsed JSON control: {:?}", json))?;
 
@@ -1259,6 +584,24 @@ async fn main() -> Result<()> {
                                     should_reset = true;
                                 } else if command.eq_ignore_ascii_case("cancel") {
                                     should_cancel = true;
+                                } else if command.eq_ignore_ascii_case("reload") {
+                                    should_reload = true;
+                                } else if command.eq_ignore_ascii_case("list_resources") {
+                                    let resources = mcp_context::list_resources(&engine.mcp_servers()).await;
+                                    send_log(&mut node, "INFO", &format!("MCP resources: {}", serde_json::to_string(&resources)?))?;
+                                } else if command.eq_ignore_ascii_case("list_prompts") {
+                                    let prompts = mcp_context::list_prompts(&engine.mcp_servers()).await;
+                                    send_log(&mut node, "INFO", &format!("MCP prompts: {}", serde_json::to_string(&prompts)?))?;
+                                } else if command.eq_ignore_ascii_case("read_resource") {
+                                    resource_uri = json.get("uri").and_then(|v| v.as_str()).map(str::to_string);
+                                    if resource_uri.is_none() {
+                                        send_log(&mut node, "ERROR", "read_resource needs a \"uri\" field")?;
+                                    }
+                                } else if command.eq_ignore_ascii_case("use_prompt") {
+                                    prompt_preset = json.get("name").and_then(|v| v.as_str()).map(str::to_string);
+                                    if prompt_preset.is_none() {
+                                        send_log(&mut node, "ERROR", "use_prompt needs a \"name\" field")?;
+                                    }
                                 } else if command.eq_ignore_ascii_case("ready") {
                                     // Send ready status
                                     node.send_output(
@@ -1268,8 +611,11 @@ async fn main() -> Result<()> {
                                     )
                                     .context("Failed to send status output")?;
                                 } else if command.eq_ignore_ascii_case("exit") {
-                                    sessions.remove(&session_id);
+                                    engine.remove_session(&session_id);
                                     send_log(&mut node, "INFO", &format!("Removed session: {}", session_id))?;
+                                } else if ["save", "load", "fork"].iter().any(|c| command.eq_ignore_ascii_case(c)) {
+                                    let name = json.get("name").and_then(|v| v.as_str()).map(str::to_string);
+                                    store_command = Some((command.to_ascii_lowercase(), name));
                                 }
                             }
                             if let Some(prompt) = json.get("prompt").and_then(|v| v.as_str()) {
@@ -1285,6 +631,8 @@ async fn main() -> Result<()> {
                                 should_reset = true;
                             } else if control_text.eq_ignore_ascii_case("cancel") {
                                 should_cancel = true;
+                            } else if control_text.eq_ignore_ascii_case("reload") {
+                                should_reload = true;
                             } else if control_text.eq_ignore_ascii_case("ready") {
                                 node.send_output(
                                     DataId::from("status".to_string()),
@@ -1293,8 +641,10 @@ async fn main() -> Result<()> {
                                 )
                                 .context("Failed to send status output")?;
                             } else if control_text.eq_ignore_ascii_case("exit") {
-                                sessions.remove(&session_id);
+                                engine.remove_session(&session_id);
                                 send_log(&mut node, "INFO", &format!("Removed session: {}", session_id))?;
+                            } else if control_text.eq_ignore_ascii_case("save") || control_text.eq_ignore_ascii_case("load") {
+                                store_command = Some((control_text.to_ascii_lowercase(), None));
                             } else {
                                 // ENHANCED LOGGING: Unknown control command - show complete context
                                 send_log(&mut node, "WARNING", &format!("🚨 UNKNOWN CONTROL COMMAND DETECTED!"))?;
@@ -1305,7 +655,7 @@ async fn main() -> Result<()> {
                                 let context_msg = format!("🔍 DEBUG CONTEXT:\n  Node ID: {:?}\n  Session ID: {}\n  Input Port: control\n  Raw Control Text: '{}'\n  Control Text Length: {}\n  Metadata Parameters: {:?}",
                                     node_id, session_id, control_text, control_text.len(), metadata.parameters);
                                 send_log(&mut node, "WARNING", &context_msg)?;
-                                send_log(&mut node, "WARNING", &format!("  Expected Commands: reset, cancel, ready, exit"))?;
+                                send_log(&mut node, "WARNING", &format!("  Expected Commands: reset, cancel, ready, exit, reload, save, load"))?;
 
                                 // Log environment info for debugging
                                 if let Ok(node_name) = std::env::var("DORA_NODE_NAME") {
@@ -1328,13 +678,7 @@ async fn main() -> Result<()> {
 
                         // Handle cancel command - cancel streaming but keep history
                         if should_cancel {
-                            let cancellation_manager_for_cancel = cancellation_manager.clone();
-                            let session_id_for_cancel = session_id.clone();
-                            let cancelled_count = {
-                                futures::executor::block_on(
-                                    cancellation_manager_for_cancel.cancel_session(&session_id_for_cancel)
-                                )
-                            };
+                            let cancelled_count = engine.cancel_session(&session_id).await;
                             if cancelled_count > 0 {
                                 send_log(&mut node, "INFO", &format!("🛑 Cancelled {} active streaming request(s) for session: {} (history preserved)", cancelled_count, session_id))?;
 
@@ -1367,14 +711,7 @@ async fn main() -> Result<()> {
                         // Handle reset command - cancel streaming AND clear history
                         if should_reset {
                             // Cancel any active streaming requests for this session
-                            let cancellation_manager_for_reset = cancellation_manager.clone();
-                            let session_id_for_reset = session_id.clone();
-                            let cancelled_count = {
-                                // Use futures::executor for synchronous block
-                                futures::executor::block_on(
-                                    cancellation_manager_for_reset.cancel_session(&session_id_for_reset)
-                                )
-                            };
+                            let cancelled_count = engine.cancel_session(&session_id).await;
                             if cancelled_count > 0 {
                                 send_log(&mut node, "INFO", &format!("🔄 Cancelled {} active streaming request(s) for session: {}", cancelled_count, session_id))?;
                             }
@@ -1395,10 +732,12 @@ async fn main() -> Result<()> {
                                 StringArray::from(vec![""]),
                             ).context("Failed to send end signal on reset")?;
 
-                            // Clear conversation history
-                            if let Some(session) = sessions.get_mut(&session_id) {
-                                session.reset();
+                            // Clear conversation history (and its autosaved copy)
+                            if engine.reset_session(&session_id) {
                                 send_log(&mut node, "INFO", &format!("🔄 Reset session history: {}", session_id))?;
+                                if let Err(e) = engine.autosave_session(&session_id) {
+                                    send_log(&mut node, "WARNING", &format!("Failed to save reset session {}: {:#}", session_id, e))?;
+                                }
                             }
                             node.send_output(
                                 DataId::from("status".to_string()),
@@ -1408,380 +747,81 @@ async fn main() -> Result<()> {
                             .context("Failed to send status output")?;
                         }
 
-                        // Handle prompt field - process through LLM like text input
-                        if let Some(user_text) = prompt_text {
-                            send_log(&mut node, "INFO", &format!("Sending prompt from control to API: {}", user_text))?;
-
-                            // Get or create session
-                            let session = sessions.entry(session_id.clone()).or_insert_with(|| {
-                                let mut session = ChatSession::new(config.system_prompt.clone(), anchor_context.clone());
-                                if let Some(ref ts) = tool_set {
-                                    session.set_tool_set(ts.clone());
-                                }
-                                session
-                            });
-
-                            // Add user message
-                            session.add_user_message(user_text.clone());
-
-                            // Manage history
-                            session.manage_history(config.max_history_exchanges);
-
-                            // Route to appropriate provider
-                            let (provider_id, model_name) =
-                                config.route_model(&config.default_model).ok_or_else(|| {
-                                    eyre::eyre!(
-                                        "No route found for model: {}",
-                                        config.default_model
-                                    )
-                                })?;
-
-                            // Create chat completion request
-                            let mut request = CreateChatCompletionRequest::new(
-                                model_name.clone(),
-                                session.messages.clone(),
-                            );
-
-                            request.stream = config.enable_streaming;
-                            request.temperature = Some(0.7);
-
-                            let client = clients.get(&provider_id).ok_or_else(|| {
-                                eyre::eyre!("No client found for provider: {}", provider_id)
-                            })?;
+                        // Handle reload command - re-read the config before any prompt in the same command
+                        if should_reload {
+                            if let Err(e) = reload_config(&mut node, &mut engine).await {
+                                send_log(&mut node, "ERROR", &format!("Config reload failed, keeping current config: {:#}", e))?;
+                            }
+                        }
 
-                            // Send "processing" status
-                            node.send_output(
-                                DataId::from("status".to_string()),
-                                Default::default(),
-                                StringArray::from(vec!["processing"]),
-                            )
-                            .context("Failed to send status output")?;
+                        // Handle save/load/fork commands - before any prompt in the same command
+                        if let Some((command, name)) = store_command {
+                            session_store_command(&mut node, &mut engine, &session_id, &command, name.as_deref())?;
+                        }
 
-                            // Make API call - use streaming if enabled
-                            if config.enable_streaming.unwrap_or(false) {
-                                // Streaming mode
-                                send_log(&mut node, "DEBUG", "Using streaming mode for control prompt")?;
-
-                                let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
-                                let mut has_sent_segment = false;
-                                let mut segment_index: u32 = 0;
-
-                                // Start streaming in background with cancellation support
-                                let client_clone = client.clone();
-                                let request_clone = request.clone();
-                                let request_id = uuid::Uuid::new_v4().to_string();
-                                let session_id_clone = session_id.clone();
-                                let metadata_clone = metadata.parameters.clone();
-                                let cancellation_manager_clone = cancellation_manager.clone();
-
-                                // Create cancellation token if enabled
-                                let cancellation_token = if config.enable_cancellation {
-                                    Some(cancellation_manager.create_token(
-                                        request_id.clone(),
-                                        session_id.clone(),
-                                    ).await)
-                                } else {
-                                    None
-                                };
-
-                                let stream_handle = tokio::spawn(async move {
-                                    let result = if let Some(token) = cancellation_token {
-                                        // Use cancellation-aware streaming
-                                        client_clone.complete_streaming_with_cancellation(
-                                            request_clone,
-                                            tx,
-                                            token,
-                                            Duration::from_secs(config.stream_timeout_secs),
-                                        ).await
+                        // Handle read_resource command - add an MCP resource to this session's system context
+                        if let Some(uri) = resource_uri {
+                            let server = parsed.as_ref().and_then(|v| v.get("server")).and_then(|v| v.as_str());
+                            match mcp_context::read_resource(&engine.mcp_servers(), server, &uri).await {
+                                Ok(resource) => {
+                                    if engine.session(&session_id).add_resource(resource) {
+                                        send_log(&mut node, "INFO", &format!("Added MCP resource {} to session {}", uri, session_id))?;
                                     } else {
-                                        // Use regular streaming
-                                        client_clone.complete_streaming(request_clone, tx).await
-                                    };
-
-                                    // Clean up token after completion
-                                    if config.enable_cancellation {
-                                        cancellation_manager_clone
-                                            .cleanup_request(&request_id, &session_id_clone)
-                                            .await;
-                                    }
-
-                                    result
-                                });
-
-                                send_log(&mut node, "DEBUG", "Stream created successfully, starting event loop")?;
-
-                                // Use segmenter to buffer chunks
-                                let mut segmenter = StreamSegmenter::new(10);
-                                let mut accumulated = String::new();
-                                let mut chunk_count = 0;
-                                let mut segment_count = 0;
-
-                                while let Some(chunk) = rx.recv().await {
-                                    chunk_count += 1;
-
-                                    if let Some(segment) = segmenter.add_chunk(&chunk) {
-                                        accumulated.push_str(&segment);
-                                        segment_count += 1;
-
-                                        // Send segment with metadata
-                                        let mut segment_metadata = metadata.parameters.clone();
-                                        segment_metadata.insert(
-                                            "session_status".to_string(),
-                                            Parameter::String(
-                                                if !has_sent_segment {
-                                                    "started".to_string()
-                                                } else {
-                                                    "ongoing".to_string()
-                                                },
-                                            ),
-                                        );
-                                        segment_metadata.insert(
-                                            "segment_index".to_string(),
-                                            Parameter::String(segment_index.to_string()),
-                                        );
-
-                                        node.send_output(
-                                            DataId::from("text".to_string()),
-                                            segment_metadata,
-                                            StringArray::from(vec![segment.as_str()]),
-                                        )
-                                        .context("Failed to send text segment")?;
-                                        has_sent_segment = true;
-                                        segment_index += 1;
+                                        send_log(&mut node, "DEBUG", &format!("MCP resource {} already in session {}", uri, session_id))?;
                                     }
-                                }
-
-                                // Flush remaining content
-                                if let Some(final_segment) = segmenter.flush() {
-                                    accumulated.push_str(&final_segment);
-                                    segment_count += 1;
-
-                                    let mut segment_metadata = metadata.parameters.clone();
-                                    segment_metadata.insert(
-                                        "session_status".to_string(),
-                                        Parameter::String(
-                                            if !has_sent_segment {
-                                                "started".to_string()
-                                            } else {
-                                                "ongoing".to_string()
-                                            },
-                                        ),
-                                    );
-                                    segment_metadata.insert(
-                                        "segment_index".to_string(),
-                                        Parameter::String(segment_index.to_string()),
-                                    );
-
                                     node.send_output(
-                                        DataId::from("text".to_string()),
-                                        segment_metadata,
-                                        StringArray::from(vec![final_segment.as_str()]),
+                                        DataId::from("status".to_string()),
+                                        Default::default(),
+                                        StringArray::from(vec!["resource_loaded"]),
                                     )
-                                    .context("Failed to send final segment")?;
+                                    .context("Failed to send status output")?;
+                                }
+                                Err(e) => {
+                                    send_log(&mut node, "ERROR", &format!("Failed to read MCP resource {}: {:#}", uri, e))?;
                                 }
+                            }
+                        }
 
-                                // Wait for stream to complete
-                                match stream_handle.await {
-                                    Ok(Ok(_)) => {
-                                        // Send complete log matching openai-response-client
-                                        send_log(
-                                            &mut node,
-                                            "INFO",
-                                            &format!(
-                                                "Streaming complete: {} chars across {} segments ({} chunks)",
-                                                accumulated.len(),
-                                                segment_count,
-                                                chunk_count
-                                            ),
-                                        )?;
-                                    }
-                                    Ok(Err(e)) => {
-                                        let error_msg = format!("{}", e);
-                                        send_log(&mut node, "ERROR", &format!("Streaming error: {}", error_msg))?;
-
-                                        // Classify error type
-                                        let error_type = if error_msg.contains("cancelled") || error_msg.contains("cancelled by user") {
-                                            "cancelled"
-                                        } else if error_msg.contains("timed out") {
-                                            "timeout"
-                                        } else {
-                                            "error"
-                                        };
-
-                                        // Send error status
-                                        node.send_output(
-                                            DataId::from("status".to_string()),
-                                            Default::default(),
-                                            StringArray::from(vec![error_type]),
-                                        )
-                                        .context("Failed to send error status")?;
-
-                                        // Send error text output with metadata
-                                        let mut error_metadata = metadata.parameters.clone();
-                                        error_metadata.insert(
-                                            "session_status".to_string(),
-                                            Parameter::String(error_type.to_string()),
-                                        );
-                                        error_metadata.insert(
-                                            "error_type".to_string(),
-                                            Parameter::String(error_type.to_string()),
-                                        );
-                                        error_metadata.insert(
-                                            "error_message".to_string(),
-                                            Parameter::String(error_msg.clone()),
-                                        );
-
-                                        // For cancellation errors, send empty text (just metadata signal)
-                                        // For other errors, send the error message text
-                                        let text_content = if error_type == "cancelled" {
-                                            ""  // Empty - don't contaminate downstream with error text
-                                        } else {
-                                            &format!("Error: {}", error_msg)
-                                        };
-
-                                        node.send_output(
-                                            DataId::from("text".to_string()),
-                                            error_metadata,
-                                            StringArray::from(vec![text_content]),
-                                        )
-                                        .context("Failed to send error text")?;
-                                        continue;
+                        // Handle use_prompt command - expand an MCP prompt preset into the history;
+                        // a final user message is sent through the LLM like a `prompt`
+                        if let Some(name) = prompt_preset {
+                            let server = parsed.as_ref().and_then(|v| v.get("server")).and_then(|v| v.as_str());
+                            let arguments = parsed.as_ref().and_then(|v| v.get("arguments")).and_then(|v| v.as_object());
+                            match mcp_context::get_prompt(&engine.mcp_servers(), server, &name, arguments).await {
+                                Ok(mut messages) => {
+                                    if prompt_text.is_none() && messages.last().is_some_and(|m| m.role == "user") {
+                                        prompt_text = messages.pop().map(|m| m.text);
                                     }
-                                    Err(e) => {
-                                        let error_msg = format!("{}", e);
-                                        send_log(&mut node, "ERROR", &format!("Stream task failed: {}", error_msg))?;
-
-                                        // Classify error type for stream task failures
-                                        let error_type = if error_msg.contains("cancelled") || error_msg.contains("cancelled by user") {
-                                            "cancelled"
-                                        } else if error_msg.contains("timed out") {
-                                            "timeout"
+                                    let session = engine.session(&session_id);
+                                    for message in messages {
+                                        if message.role == "assistant" {
+                                            session.add_assistant_message(message.text);
                                         } else {
-                                            "error"
-                                        };
-
-                                        // Send error status
-                                        node.send_output(
-                                            DataId::from("status".to_string()),
-                                            Default::default(),
-                                            StringArray::from(vec![error_type]),
-                                        )
-                                        .context("Failed to send error status")?;
-
-                                        // Send error text output with metadata
-                                        let mut error_metadata = metadata.parameters.clone();
-                                        error_metadata.insert(
-                                            "session_status".to_string(),
-                                            Parameter::String(error_type.to_string()),
-                                        );
-                                        error_metadata.insert(
-                                            "error_type".to_string(),
-                                            Parameter::String(error_type.to_string()),
-                                        );
-                                        error_metadata.insert(
-                                            "error_message".to_string(),
-                                            Parameter::String(error_msg.clone()),
-                                        );
-
-                                        // For cancellation errors, send empty text (just metadata signal)
-                                        // For other errors, send the error message text
-                                        let text_content = if error_type == "cancelled" {
-                                            ""  // Empty - don't contaminate downstream with error text
-                                        } else {
-                                            &format!("Error: {}", error_msg)
-                                        };
-
-                                        node.send_output(
-                                            DataId::from("text".to_string()),
-                                            error_metadata,
-                                            StringArray::from(vec![text_content]),
-                                        )
-                                        .context("Failed to send error text")?;
-                                        continue;
+                                            session.add_user_message(message.text);
+                                        }
                                     }
+                                    send_log(&mut node, "INFO", &format!("Applied MCP prompt preset: {}", name))?;
                                 }
-
-                                // Add assistant message to session
-                                if !accumulated.is_empty() {
-                                    session.add_assistant_message(accumulated.clone());
-                                    session.manage_history(config.max_history_exchanges);
+                                Err(e) => {
+                                    send_log(&mut node, "ERROR", &format!("Failed to get MCP prompt {}: {:#}", name, e))?;
                                 }
+                            }
+                        }
 
-                                // Send final empty text message with session_status="ended" for bridge
-                                let mut final_metadata = metadata.parameters.clone();
-                                final_metadata.insert(
-                                    "session_status".to_string(),
-                                    Parameter::String("ended".to_string()),
-                                );
-                                node.send_output(
-                                    DataId::from("text".to_string()),
-                                    final_metadata.clone(),
-                                    StringArray::from(vec![""]),
-                                )
-                                .context("Failed to send final text message")?;
-
-                                // Send completion status
-                                node.send_output(
-                                    DataId::from("status".to_string()),
-                                    Default::default(),
-                                    StringArray::from(vec!["complete"]),
-                                )
-                                .context("Failed to send complete status")?;
+                        // Handle prompt field - process through LLM like text input
+                        if let Some(user_text) = prompt_text {
+                            send_log(&mut node, "INFO", &format!("Sending prompt from control to API: {}", user_text))?;
 
-                            } else {
-                                // Non-streaming mode
-                                send_log(&mut node, "DEBUG", "Using non-streaming mode for control prompt")?;
-
-                                match client.complete(request.clone()).await {
-                                    Ok(response) => {
-                                        let assistant_message = response.choices.first()
-                                            .and_then(|choice| choice.message.content.clone())
-                                            .unwrap_or_default();
-
-                                        // Send response
-                                        node.send_output(
-                                            DataId::from("text".to_string()),
-                                            Default::default(),
-                                            StringArray::from(vec![assistant_message.as_str()]),
-                                        )
-                                        .context("Failed to send text output")?;
-
-                                        // Add to session
-                                        session.add_assistant_message(assistant_message);
-                                        session.manage_history(config.max_history_exchanges);
-
-                                        // Send final empty text message with session_status="ended" for bridge
-                                        let mut final_metadata = metadata.parameters.clone();
-                                        final_metadata.insert(
-                                            "session_status".to_string(),
-                                            Parameter::String("ended".to_string()),
-                                        );
-                                        node.send_output(
-                                            DataId::from("text".to_string()),
-                                            final_metadata,
-                                            StringArray::from(vec![""]),
-                                        )
-                                        .context("Failed to send final text message")?;
-
-                                        // Send complete status
-                                        node.send_output(
-                                            DataId::from("status".to_string()),
-                                            Default::default(),
-                                            StringArray::from(vec!["complete"]),
-                                        )
-                                        .context("Failed to send status output")?;
-                                    }
-                                    Err(e) => {
-                                        send_log(&mut node, "ERROR", &format!("API error: {}", e))?;
-                                        node.send_output(
-                                            DataId::from("status".to_string()),
-                                            Default::default(),
-                                            StringArray::from(vec!["error"]),
-                                        )
-                                        .context("Failed to send error status")?;
-                                    }
-                                }
-                            }
+                            let overrides =
+                                request_overrides(&mut node, engine.config(), &metadata.parameters, parsed.as_ref())?;
+                            let turn = Turn {
+                                input: TurnInput::User(user_text),
+                                overrides,
+                                client_tools: None,
+                            };
+                            engine
+                                .run_turn(&session_id, turn, &mut DoraSink::new(&mut node, &metadata.parameters))
+                                .await?;
                         }
                     }
                     _ => {
@@ -0,0 +1,283 @@
+//! MCP resources and prompts.
+//!
+//! Besides tools, MCP servers can offer resources (documents the node reads
+//! into a session's system context) and prompts (message templates used as
+//! named presets from the `control` input). Results are read through
+//! `serde_json::Value` so only the wire format matters here.
+
+use eyre::{Result, eyre};
+use rmcp::model::{GetPromptRequestParam, ReadResourceRequestParam};
+use rmcp::service::ServerSink;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// A resource offered by an MCP server
+#[derive(Debug, Serialize)]
+pub struct ResourceInfo {
+    pub server: String,
+    pub uri: String,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// A prompt template offered by an MCP server
+#[derive(Debug, Serialize)]
+pub struct PromptInfo {
+    pub server: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub arguments: Vec<String>,
+}
+
+/// One message of an expanded prompt
+#[derive(Debug, Clone, PartialEq)]
+pub struct PresetMessage {
+    /// `"user"` or `"assistant"`
+    pub role: String,
+    pub text: String,
+}
+
+fn string_field(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+/// Resources of all servers; servers without resource support are skipped
+pub async fn list_resources(servers: &[(String, ServerSink)]) -> Vec<ResourceInfo> {
+    let mut resources = Vec::new();
+    for (server_name, server) in servers {
+        let listed = match server.list_all_resources().await {
+            Ok(listed) => listed,
+            Err(e) => {
+                eprintln!("Warning: Failed to list resources of '{}': {}", server_name, e);
+                continue;
+            }
+        };
+        for resource in listed {
+            let Ok(value) = serde_json::to_value(&resource) else {
+                continue;
+            };
+            resources.push(ResourceInfo {
+                server: server_name.clone(),
+                uri: string_field(&value, "uri").unwrap_or_default(),
+                name: string_field(&value, "name").unwrap_or_default(),
+                description: string_field(&value, "description"),
+            });
+        }
+    }
+    resources
+}
+
+/// Prompts of all servers; servers without prompt support are skipped
+pub async fn list_prompts(servers: &[(String, ServerSink)]) -> Vec<PromptInfo> {
+    let mut prompts = Vec::new();
+    for (server_name, server) in servers {
+        let listed = match server.list_all_prompts().await {
+            Ok(listed) => listed,
+            Err(e) => {
+                eprintln!("Warning: Failed to list prompts of '{}': {}", server_name, e);
+                continue;
+            }
+        };
+        for prompt in listed {
+            let Ok(value) = serde_json::to_value(&prompt) else {
+                continue;
+            };
+            let arguments = value
+                .get("arguments")
+                .and_then(Value::as_array)
+                .map(|arguments| {
+                    arguments
+                        .iter()
+                        .filter_map(|argument| string_field(argument, "name"))
+                        .collect()
+                })
+                .unwrap_or_default();
+            prompts.push(PromptInfo {
+                server: server_name.clone(),
+                name: string_field(&value, "name").unwrap_or_default(),
+                description: string_field(&value, "description"),
+                arguments,
+            });
+        }
+    }
+    prompts
+}
+
+/// Servers to ask: the named one, or all of them in order
+fn candidates<'a>(
+    servers: &'a [(String, ServerSink)],
+    server: Option<&str>,
+) -> Result<Vec<&'a (String, ServerSink)>> {
+    let selected: Vec<_> = servers
+        .iter()
+        .filter(|(name, _)| server.is_none() || server == Some(name.as_str()))
+        .collect();
+    if selected.is_empty() {
+        return Err(match server {
+            Some(name) => eyre!("MCP server '{}' is not running", name),
+            None => eyre!("No MCP servers are running"),
+        });
+    }
+    Ok(selected)
+}
+
+/// Read a resource and return it formatted for the system context.
+///
+/// Without `server`, the servers are tried in order until one has the URI.
+pub async fn read_resource(
+    servers: &[(String, ServerSink)],
+    server: Option<&str>,
+    uri: &str,
+) -> Result<String> {
+    let mut last_error = None;
+    for (server_name, sink) in candidates(servers, server)? {
+        let request = ReadResourceRequestParam {
+            uri: uri.to_string(),
+        };
+        match sink.read_resource(request).await {
+            Ok(result) => {
+                let text = resource_text(&serde_json::to_value(&result)?)?;
+                return Ok(format_resource(uri, &text));
+            }
+            Err(e) => last_error = Some(eyre!("{}: {}", server_name, e)),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| eyre!("Resource not found: {}", uri)))
+}
+
+/// Expand a prompt template into messages.
+///
+/// Without `server`, the first server listing a prompt called `name` is used.
+/// Argument values that are not strings are sent as their JSON text.
+pub async fn get_prompt(
+    servers: &[(String, ServerSink)],
+    server: Option<&str>,
+    name: &str,
+    arguments: Option<&Map<String, Value>>,
+) -> Result<Vec<PresetMessage>> {
+    let selected = candidates(servers, server)?;
+    let sink = if selected.len() == 1 {
+        &selected[0].1
+    } else {
+        let owner = list_prompts(servers)
+            .await
+            .into_iter()
+            .find(|prompt| prompt.name == name)
+            .ok_or_else(|| eyre!("No MCP server offers a prompt named '{}'", name))?;
+        &selected
+            .iter()
+            .find(|(server_name, _)| *server_name == owner.server)
+            .ok_or_else(|| eyre!("MCP server '{}' is not running", owner.server))?
+            .1
+    };
+
+    let arguments = arguments.map(|arguments| {
+        arguments
+            .iter()
+            .map(|(key, value)| {
+                let text = value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string());
+                (key.clone(), Value::String(text))
+            })
+            .collect()
+    });
+    let result = sink
+        .get_prompt(GetPromptRequestParam {
+            name: name.to_string(),
+            arguments,
+        })
+        .await?;
+    preset_messages(&serde_json::to_value(&result)?)
+}
+
+/// Text of a `resources/read` result; binary contents are skipped
+fn resource_text(result: &Value) -> Result<String> {
+    let contents = result
+        .get("contents")
+        .and_then(Value::as_array)
+        .ok_or_else(|| eyre!("Resource result has no contents"))?;
+    let texts: Vec<&str> = contents
+        .iter()
+        .filter_map(|content| content.get("text").and_then(Value::as_str))
+        .collect();
+    if texts.is_empty() {
+        return Err(eyre!("Resource has no text content"));
+    }
+    Ok(texts.join("\n\n"))
+}
+
+/// Messages of a `prompts/get` result; non-text content is skipped
+fn preset_messages(result: &Value) -> Result<Vec<PresetMessage>> {
+    let messages = result
+        .get("messages")
+        .and_then(Value::as_array)
+        .ok_or_else(|| eyre!("Prompt result has no messages"))?;
+    let messages: Vec<PresetMessage> = messages
+        .iter()
+        .filter_map(|message| {
+            let role = string_field(message, "role")?;
+            let text = message.pointer("/content/text").and_then(Value::as_str)?;
+            Some(PresetMessage {
+                role,
+                text: text.to_string(),
+            })
+        })
+        .collect();
+    if messages.is_empty() {
+        return Err(eyre!("Prompt has no text messages"));
+    }
+    Ok(messages)
+}
+
+/// Resource text wrapped for the system context, in the style of the anchor context
+pub fn format_resource(uri: &str, text: &str) -> String {
+    format!(
+        "RESOURCE {}:\n<<<BEGIN RESOURCE\n{}\nEND RESOURCE>>>",
+        uri,
+        text.trim()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_resource_text() {
+        let result = json!({
+            "contents": [
+                { "uri": "book://life/A0", "mimeType": "text/markdown", "text": "[A0] 导言" },
+                { "uri": "book://life/cover", "mimeType": "image/png", "blob": "iVBORw0KGgo=" },
+                { "uri": "book://life/A1", "text": "[A1] 统计律" },
+            ]
+        });
+        let text = resource_text(&result).unwrap();
+        assert_eq!(text, "[A0] 导言\n\n[A1] 统计律");
+        assert_eq!(
+            format_resource("book://life", &text),
+            "RESOURCE book://life:\n<<<BEGIN RESOURCE\n[A0] 导言\n\n[A1] 统计律\nEND RESOURCE>>>"
+        );
+
+        assert!(resource_text(&json!({ "contents": [{ "uri": "x", "blob": "AA==" }] })).is_err());
+    }
+
+    #[test]
+    fn test_preset_messages() {
+        let result = json!({
+            "description": "Open a discussion round",
+            "messages": [
+                { "role": "assistant", "content": { "type": "text", "text": "[孙老师] 今天读第一章。" } },
+                { "role": "user", "content": { "type": "image", "data": "AA==", "mimeType": "image/png" } },
+                { "role": "user", "content": { "type": "text", "text": "请提出第一个问题。" } },
+            ]
+        });
+        assert_eq!(
+            preset_messages(&result).unwrap(),
+            vec![
+                PresetMessage { role: "assistant".to_string(), text: "[孙老师] 今天读第一章。".to_string() },
+                PresetMessage { role: "user".to_string(), text: "请提出第一个问题。".to_string() },
+            ]
+        );
+        assert!(preset_messages(&json!({ "messages": [] })).is_err());
+    }
+}
@@ -0,0 +1,613 @@
+//! Scripted provider and fixture recording, for tests without API keys.
+//!
+//! A `mock` provider ([`MockClient`]) answers from [`MockResponse`]s given
+//! inline in the config or read from a JSON-lines fixture file. A response can
+//! stream chunks with recorded delays, return tool calls and usage, fail with
+//! an HTTP status (classified like a real provider's) or stall until the
+//! request times out.
+//!
+//! With `record_fixtures` set, real providers are wrapped in a
+//! [`RecordingClient`] that appends every request and its response to the
+//! fixture file in the same format, so a session can be replayed offline.
+//!
+//! Replay is deterministic: a response with `when` answers requests whose last
+//! user message contains that text (matching responses are used in order, the
+//! last one repeats). Requests that match no `when` get the responses without
+//! one, in order, wrapping around.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use eyre::{Context, Result, eyre};
+use outfox_openai::spec::{
+    ChatCompletionMessageToolCall, CreateChatCompletionRequest, CreateChatCompletionResponse,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::client::{ChatClient, ProviderError};
+use crate::config::MockConfig;
+use crate::failover::ErrorClass;
+use crate::streaming::StreamResult;
+use crate::usage::TokenUsage;
+
+/// How long a stalled response waits when the request has no timeout of its own
+const STALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// One scripted or recorded response (one line of a fixture file)
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MockResponse {
+    /// Answer requests whose last user message contains this text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub when: Option<String>,
+    /// Text chunks, streamed in order
+    pub chunks: Vec<String>,
+    /// Delay before each chunk in milliseconds; the provider's
+    /// `chunk_delay_ms` for chunks without one
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub delays_ms: Vec<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ChatCompletionMessageToolCall>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
+    /// Fail with this message after the chunks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Fail with this HTTP status after the chunks, `error` being the body
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Stall after the chunks until the request is cancelled or times out
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub stall: bool,
+    /// The recorded request (model and messages), for reference only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request: Option<Value>,
+}
+
+/// Read a JSON-lines fixture file; blank lines and `#` comments are skipped
+pub fn load_fixture(path: &Path) -> Result<Vec<MockResponse>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read fixture {}", path.display()))?;
+    parse_fixture(&content).with_context(|| format!("Invalid fixture {}", path.display()))
+}
+
+fn parse_fixture(content: &str) -> Result<Vec<MockResponse>> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(number, line)| {
+            serde_json::from_str(line).map_err(|e| eyre!("line {}: {}", number + 1, e))
+        })
+        .collect()
+}
+
+/// Text of the last user message of a request
+pub fn last_user_message(request: &CreateChatCompletionRequest) -> Option<String> {
+    let messages = serde_json::to_value(&request.messages).ok()?;
+    let message = messages
+        .as_array()?
+        .iter()
+        .rev()
+        .find(|m| m.get("role").and_then(Value::as_str) == Some("user"))?;
+    match message.get("content")? {
+        Value::String(text) => Some(text.clone()),
+        Value::Array(parts) => Some(
+            parts
+                .iter()
+                .filter_map(|part| part.get("text").and_then(Value::as_str))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        _ => None,
+    }
+}
+
+/// Which responses were used so far
+#[derive(Default)]
+struct ReplayState {
+    used: Vec<bool>,
+    next_default: usize,
+}
+
+/// Provider that replays scripted responses
+pub struct MockClient {
+    id: String,
+    responses: Vec<MockResponse>,
+    chunk_delay_ms: u64,
+    state: Mutex<ReplayState>,
+}
+
+impl MockClient {
+    /// Inline responses first, then the fixture file's
+    pub fn new(config: &MockConfig) -> Result<Self> {
+        let mut responses = config.responses.clone();
+        if let Some(fixture) = &config.fixture {
+            responses.extend(load_fixture(Path::new(fixture))?);
+        }
+        Ok(Self::with_responses(&config.id, responses, config.chunk_delay_ms))
+    }
+
+    pub fn with_responses(id: &str, responses: Vec<MockResponse>, chunk_delay_ms: u64) -> Self {
+        Self {
+            id: id.to_string(),
+            state: Mutex::new(ReplayState {
+                used: vec![false; responses.len()],
+                next_default: 0,
+            }),
+            responses,
+            chunk_delay_ms,
+        }
+    }
+
+    /// Pick the response for a request (see the module docs)
+    fn select(&self, last_user: Option<&str>) -> Option<MockResponse> {
+        let mut state = self.state.lock().unwrap();
+
+        let matching: Vec<usize> = self
+            .responses
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| {
+                r.when
+                    .as_deref()
+                    .is_some_and(|when| last_user.is_some_and(|text| text.contains(when)))
+            })
+            .map(|(i, _)| i)
+            .collect();
+        if let Some(&last) = matching.last() {
+            let index = matching.iter().copied().find(|&i| !state.used[i]).unwrap_or(last);
+            state.used[index] = true;
+            return Some(self.responses[index].clone());
+        }
+
+        let defaults: Vec<usize> = self
+            .responses
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| r.when.is_none())
+            .map(|(i, _)| i)
+            .collect();
+        if defaults.is_empty() {
+            return None;
+        }
+        let index = defaults[state.next_default % defaults.len()];
+        state.next_default += 1;
+        Some(self.responses[index].clone())
+    }
+
+    async fn play(
+        &self,
+        request: &CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+        cancellation_token: Option<CancellationToken>,
+        timeout_duration: Duration,
+    ) -> StreamResult {
+        let last_user = last_user_message(request);
+        let response = self.select(last_user.as_deref()).ok_or_else(|| {
+            eyre!(
+                "Mock provider '{}' has no response for: {}",
+                self.id,
+                last_user.as_deref().unwrap_or("(no user message)")
+            )
+        })?;
+
+        let replay = async {
+            let mut text = String::new();
+            for (i, chunk) in response.chunks.iter().enumerate() {
+                let delay = response.delays_ms.get(i).copied().unwrap_or(self.chunk_delay_ms);
+                if delay > 0 {
+                    tokio::time::sleep(Duration::from_millis(delay)).await;
+                }
+                text.push_str(chunk);
+                let _ = chunk_sender.send(chunk.clone());
+            }
+            if response.stall {
+                std::future::pending::<()>().await;
+            }
+            match (response.status, &response.error) {
+                (Some(code), error) => match reqwest::StatusCode::from_u16(code) {
+                    Ok(status) => Err(ProviderError::Status {
+                        status,
+                        body: error.clone().unwrap_or_default(),
+                    }
+                    .into()),
+                    Err(e) => Err(eyre!("Invalid mock status {}: {}", code, e)),
+                },
+                (None, Some(error)) => Err(eyre!("{}", error)),
+                (None, None) => Ok((text, response.tool_calls.clone(), response.usage)),
+            }
+        };
+
+        let cancelled = async {
+            match &cancellation_token {
+                Some(token) => token.cancelled().await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            result = replay => result,
+            _ = cancelled => Err(ProviderError::Cancelled.into()),
+            _ = tokio::time::sleep(timeout_duration) => {
+                Err(ProviderError::TimedOut(timeout_duration).into())
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ChatClient for MockClient {
+    async fn complete(
+        &self,
+        request: CreateChatCompletionRequest,
+    ) -> Result<CreateChatCompletionResponse> {
+        let (chunk_sender, _chunks) = mpsc::unbounded_channel();
+        let (text, tool_calls, usage) = self.play(&request, chunk_sender, None, STALL_TIMEOUT).await?;
+
+        let mut message = json!({ "role": "assistant", "content": text });
+        let finish_reason = match tool_calls {
+            Some(calls) if !calls.is_empty() => {
+                message["tool_calls"] = serde_json::to_value(calls)?;
+                "tool_calls"
+            }
+            _ => "stop",
+        };
+        let mut response = json!({
+            "id": uuid::Uuid::new_v4().simple().to_string(),
+            "object": "chat.completion",
+            "created": 0,
+            "model": request.model,
+            "choices": [{
+                "index": 0,
+                "message": message,
+                "finish_reason": finish_reason,
+                "logprobs": null,
+            }],
+        });
+        if let Some(usage) = usage {
+            response["usage"] = serde_json::to_value(usage)?;
+        }
+        serde_json::from_value(response).map_err(|e| eyre!("Failed to build mock response: {}", e))
+    }
+
+    async fn complete_streaming(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+    ) -> StreamResult {
+        self.play(&request, chunk_sender, None, STALL_TIMEOUT).await
+    }
+
+    async fn complete_streaming_with_cancellation(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+        cancellation_token: CancellationToken,
+        timeout_duration: Duration,
+    ) -> StreamResult {
+        self.play(&request, chunk_sender, Some(cancellation_token), timeout_duration)
+            .await
+    }
+}
+
+/// Wraps a real provider and appends its requests and responses to a fixture
+pub struct RecordingClient {
+    inner: Arc<dyn ChatClient>,
+    path: PathBuf,
+    /// Keeps lines from concurrent requests apart
+    write_lock: Mutex<()>,
+}
+
+impl RecordingClient {
+    pub fn new(inner: Arc<dyn ChatClient>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            path: path.into(),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    fn save(&self, request: &CreateChatCompletionRequest, mut response: MockResponse) {
+        response.when = last_user_message(request);
+        response.request = Some(json!({
+            "model": request.model,
+            "messages": request.messages,
+        }));
+
+        let _guard = self.write_lock.lock().unwrap();
+        let result = serde_json::to_string(&response)
+            .map_err(eyre::Report::from)
+            .and_then(|line| {
+                let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+                writeln!(file, "{}", line)?;
+                Ok(())
+            });
+        if let Err(e) = result {
+            eprintln!("⚠️ Warning: Failed to record fixture to {}: {}", self.path.display(), e);
+        }
+    }
+
+    fn save_stream(
+        &self,
+        request: &CreateChatCompletionRequest,
+        result: &StreamResult,
+        chunks: Vec<String>,
+        delays_ms: Vec<u64>,
+    ) {
+        let mut response = MockResponse {
+            chunks,
+            delays_ms,
+            ..Default::default()
+        };
+        match result {
+            Ok((_, tool_calls, usage)) => {
+                response.tool_calls = tool_calls.clone();
+                response.usage = *usage;
+            }
+            // A cancellation is the listener's doing, not the provider's
+            Err(e) if ErrorClass::classify(e) == ErrorClass::Cancelled => return,
+            Err(e) => record_error(&mut response, e),
+        }
+        self.save(request, response);
+    }
+}
+
+/// Record a failure so its replay is classified the same way
+fn record_error(response: &mut MockResponse, error: &eyre::Report) {
+    match ProviderError::find(error) {
+        Some(ProviderError::Status { status, body }) => {
+            response.status = Some(status.as_u16());
+            response.error = Some(body.clone());
+        }
+        Some(ProviderError::TimedOut(_)) => response.stall = true,
+        _ => response.error = Some(format!("{:#}", error)),
+    }
+}
+
+/// Forward chunks to `chunk_sender`, returning them with the delay before each
+async fn forward_chunks(
+    mut chunks: mpsc::UnboundedReceiver<String>,
+    chunk_sender: mpsc::UnboundedSender<String>,
+) -> (Vec<String>, Vec<u64>) {
+    let mut recorded = Vec::new();
+    let mut delays_ms = Vec::new();
+    let mut last = Instant::now();
+    while let Some(chunk) = chunks.recv().await {
+        delays_ms.push(last.elapsed().as_millis() as u64);
+        last = Instant::now();
+        let _ = chunk_sender.send(chunk.clone());
+        recorded.push(chunk);
+    }
+    (recorded, delays_ms)
+}
+
+#[async_trait::async_trait]
+impl ChatClient for RecordingClient {
+    async fn complete(
+        &self,
+        request: CreateChatCompletionRequest,
+    ) -> Result<CreateChatCompletionResponse> {
+        let result = self.inner.complete(request.clone()).await;
+        let response = match &result {
+            Ok(response) => {
+                let message = response.choices.first().map(|choice| &choice.message);
+                Some(MockResponse {
+                    chunks: message
+                        .and_then(|m| m.content.clone())
+                        .filter(|text| !text.is_empty())
+                        .into_iter()
+                        .collect(),
+                    tool_calls: message.and_then(|m| m.tool_calls.clone()),
+                    usage: TokenUsage::from_response(response),
+                    ..Default::default()
+                })
+            }
+            Err(e) if ErrorClass::classify(e) == ErrorClass::Cancelled => None,
+            Err(e) => {
+                let mut response = MockResponse::default();
+                record_error(&mut response, e);
+                Some(response)
+            }
+        };
+        if let Some(response) = response {
+            self.save(&request, response);
+        }
+        result
+    }
+
+    async fn complete_streaming(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+    ) -> StreamResult {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (result, (chunks, delays_ms)) = tokio::join!(
+            self.inner.complete_streaming(request.clone(), tx),
+            forward_chunks(rx, chunk_sender),
+        );
+        self.save_stream(&request, &result, chunks, delays_ms);
+        result
+    }
+
+    async fn complete_streaming_with_cancellation(
+        &self,
+        request: CreateChatCompletionRequest,
+        chunk_sender: mpsc::UnboundedSender<String>,
+        cancellation_token: CancellationToken,
+        timeout_duration: Duration,
+    ) -> StreamResult {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (result, (chunks, delays_ms)) = tokio::join!(
+            self.inner.complete_streaming_with_cancellation(
+                request.clone(),
+                tx,
+                cancellation_token,
+                timeout_duration,
+            ),
+            forward_chunks(rx, chunk_sender),
+        );
+        self.save_stream(&request, &result, chunks, delays_ms);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(text: &str) -> CreateChatCompletionRequest {
+        serde_json::from_value(json!({
+            "model": "mock-model",
+            "messages": [
+                { "role": "system", "content": "Be brief." },
+                { "role": "user", "content": text },
+            ],
+        }))
+        .unwrap()
+    }
+
+    fn response(when: Option<&str>, chunks: &[&str]) -> MockResponse {
+        MockResponse {
+            when: when.map(str::to_string),
+            chunks: chunks.iter().map(|c| c.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_fixture_skips_comments_and_blank_lines() {
+        let fixture = concat!(
+            "# recorded session\n",
+            "\n",
+            "{\"when\": \"hello\", \"chunks\": [\"Hi \", \"there.\"], \"delays_ms\": [120, 40]}\n",
+            "{\"status\": 429, \"error\": \"slow down\"}\n",
+        );
+        let responses = parse_fixture(fixture).unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0].when.as_deref(), Some("hello"));
+        assert_eq!(responses[0].delays_ms, vec![120, 40]);
+        assert_eq!(responses[1].status, Some(429));
+
+        let error = parse_fixture("{\"chunks\": [1]}").unwrap_err();
+        assert!(error.to_string().starts_with("line 1:"));
+    }
+
+    #[test]
+    fn test_select_prefers_matches_then_cycles_defaults() {
+        let client = MockClient::with_responses(
+            "mock",
+            vec![
+                response(Some("weather"), &["Sunny."]),
+                response(None, &["First."]),
+                response(Some("weather"), &["Rainy."]),
+                response(None, &["Second."]),
+            ],
+            0,
+        );
+        let text = |r: Option<MockResponse>| r.unwrap().chunks.concat();
+
+        assert_eq!(text(client.select(Some("What's the weather?"))), "Sunny.");
+        assert_eq!(text(client.select(Some("And the weather tomorrow?"))), "Rainy.");
+        assert_eq!(text(client.select(Some("weather again"))), "Rainy.");
+        assert_eq!(text(client.select(Some("hi"))), "First.");
+        assert_eq!(text(client.select(None)), "Second.");
+        assert_eq!(text(client.select(Some("hi"))), "First.");
+    }
+
+    #[tokio::test]
+    async fn test_replays_chunks_tool_calls_and_usage() {
+        let mut scripted = response(None, &["Checking ", "now."]);
+        scripted.tool_calls = Some(
+            serde_json::from_value(json!([{
+                "id": "call_1",
+                "type": "function",
+                "function": { "name": "get_weather", "arguments": "{\"city\":\"Paris\"}" },
+            }]))
+            .unwrap(),
+        );
+        scripted.usage = Some(TokenUsage::new(12, 5));
+        let client = MockClient::with_responses("mock", vec![scripted], 1);
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (text, tool_calls, usage) = client
+            .complete_streaming(request("Weather in Paris?"), tx)
+            .await
+            .unwrap();
+        assert_eq!(text, "Checking now.");
+        assert_eq!(tool_calls.unwrap()[0].function.name, "get_weather");
+        assert_eq!(usage, Some(TokenUsage::new(12, 5)));
+        assert_eq!(rx.recv().await.as_deref(), Some("Checking "));
+        assert_eq!(rx.recv().await.as_deref(), Some("now."));
+
+        let response = client.complete(request("Weather in Paris?")).await.unwrap();
+        let response = serde_json::to_value(response).unwrap();
+        assert_eq!(response["choices"][0]["finish_reason"], "tool_calls");
+        assert_eq!(response["usage"]["total_tokens"], 17);
+    }
+
+    #[tokio::test]
+    async fn test_scripted_errors_and_stalls_are_classified() {
+        let mut rate_limited = response(Some("busy"), &[]);
+        rate_limited.status = Some(429);
+        let mut stalled = response(Some("stall"), &["Partial "]);
+        stalled.stall = true;
+        let client = MockClient::with_responses("mock", vec![rate_limited, stalled], 0);
+
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let error = client.complete_streaming(request("busy?"), tx).await.unwrap_err();
+        assert_eq!(ErrorClass::classify(&error), ErrorClass::RateLimited);
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let error = client
+            .complete_streaming_with_cancellation(
+                request("stall"),
+                tx,
+                CancellationToken::new(),
+                Duration::from_millis(20),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(ErrorClass::classify(&error), ErrorClass::Timeout);
+        assert_eq!(rx.recv().await.as_deref(), Some("Partial "));
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let error = client
+            .complete_streaming_with_cancellation(request("stall"), tx, token, STALL_TIMEOUT)
+            .await
+            .unwrap_err();
+        assert_eq!(ErrorClass::classify(&error), ErrorClass::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_recorded_streams_replay_through_mock() {
+        let path = std::env::temp_dir().join(format!("maas-fixture-{}.jsonl", uuid::Uuid::new_v4()));
+        let inner = Arc::new(MockClient::with_responses(
+            "real",
+            vec![response(None, &["Hello ", "there."])],
+            0,
+        ));
+        let recorder = RecordingClient::new(inner, &path);
+        let (tx, _rx) = mpsc::unbounded_channel();
+        recorder.complete_streaming(request("Say hello"), tx).await.unwrap();
+
+        let recorded = load_fixture(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].when.as_deref(), Some("Say hello"));
+        assert_eq!(recorded[0].chunks, vec!["Hello ", "there."]);
+        assert_eq!(recorded[0].request.as_ref().unwrap()["model"], "mock-model");
+
+        let replay = MockClient::with_responses("mock", recorded, 0);
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let (text, _, _) = replay.complete_streaming(request("Say hello"), tx).await.unwrap();
+        assert_eq!(text, "Hello there.");
+    }
+}
@@ -0,0 +1,171 @@
+//! OpenAI chat completion shapes shared by the provider translations.
+//!
+//! [`gemini`](crate::gemini), [`anthropic`](crate::anthropic) and
+//! [`local`](crate::local) convert their providers' replies into the OpenAI
+//! JSON the rest of the client works with. The pieces that do not depend on
+//! the provider live here: reading message text, accumulating a reply and
+//! building the `chat.completion` object.
+
+use serde_json::{Value, json};
+
+use crate::usage::TokenUsage;
+
+/// Text of an OpenAI message content (plain string or array of text parts)
+pub(crate) fn content_text(content: Option<&Value>) -> String {
+    match content {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|part| part.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Seconds since the Unix epoch, for `created` fields
+pub(crate) fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// OpenAI `usage` JSON value
+pub(crate) fn usage_json(prompt_tokens: u64, completion_tokens: u64, total_tokens: u64) -> Value {
+    json!({
+        "prompt_tokens": prompt_tokens,
+        "completion_tokens": completion_tokens,
+        "total_tokens": total_tokens,
+    })
+}
+
+/// Build an OpenAI `CreateChatCompletionResponse` JSON value
+pub(crate) fn chat_completion(
+    id: &str,
+    model: &str,
+    text: String,
+    tool_calls: Vec<Value>,
+    finish_reason: &str,
+    usage: Option<Value>,
+) -> Value {
+    let mut message = json!({ "role": "assistant", "content": text });
+    if !tool_calls.is_empty() {
+        message["tool_calls"] = Value::Array(tool_calls);
+    }
+
+    let mut response = json!({
+        "id": id,
+        "object": "chat.completion",
+        "created": now_secs(),
+        "model": model,
+        "choices": [{
+            "index": 0,
+            "message": message,
+            "finish_reason": finish_reason,
+            "logprobs": null,
+        }],
+    });
+    if let Some(usage) = usage {
+        response["usage"] = usage;
+    }
+    response
+}
+
+/// Text, tool calls and usage of a reply, collected whole or from stream chunks
+pub(crate) struct ReplyAccumulator {
+    id: String,
+    text: String,
+    tool_calls: Vec<Value>,
+    usage: Option<Value>,
+}
+
+impl ReplyAccumulator {
+    pub(crate) fn new(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            text: String::new(),
+            tool_calls: Vec::new(),
+            usage: None,
+        }
+    }
+
+    pub(crate) fn push_text(&mut self, delta: &str) {
+        self.text.push_str(delta);
+    }
+
+    /// Add a whole tool call; calls without an `id` get `call_<reply id>_<index>`
+    pub(crate) fn push_tool_call(&mut self, id: Option<&str>, name: &str, arguments: String) {
+        let id = match id {
+            Some(id) => id.to_string(),
+            None => format!("call_{}_{}", self.id, self.tool_calls.len()),
+        };
+        self.tool_calls.push(json!({
+            "id": id,
+            "type": "function",
+            "function": { "name": name, "arguments": arguments },
+        }));
+    }
+
+    pub(crate) fn set_usage(&mut self, prompt_tokens: u64, completion_tokens: u64, total_tokens: u64) {
+        self.usage = Some(usage_json(prompt_tokens, completion_tokens, total_tokens));
+    }
+
+    /// Full text received so far
+    pub(crate) fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Tool calls in OpenAI JSON format (`ChatCompletionMessageToolCall`)
+    pub(crate) fn tool_calls(&self) -> &[Value] {
+        &self.tool_calls
+    }
+
+    /// Token usage, once the provider reported it
+    pub(crate) fn usage(&self) -> Option<TokenUsage> {
+        self.usage.as_ref().and_then(TokenUsage::from_value)
+    }
+
+    /// Build the completion. `finish_reason` is the provider's reason mapped to
+    /// OpenAI's; replies with tool calls always finish with `tool_calls`.
+    pub(crate) fn into_response(self, model: &str, finish_reason: &str) -> Value {
+        let finish_reason = if self.tool_calls.is_empty() { finish_reason } else { "tool_calls" };
+        chat_completion(&self.id, model, self.text, self.tool_calls, finish_reason, self.usage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_text() {
+        assert_eq!(content_text(Some(&json!("hi"))), "hi");
+        let parts = json!([{ "type": "text", "text": "a" }, { "type": "image_url" }, { "text": "b" }]);
+        assert_eq!(content_text(Some(&parts)), "a\nb");
+        assert_eq!(content_text(None), "");
+    }
+
+    #[test]
+    fn test_accumulator_response() {
+        let mut reply = ReplyAccumulator::new("r1");
+        reply.push_text("Hello");
+        let response = reply.into_response("m", "length");
+        assert_eq!(response["id"], "r1");
+        assert_eq!(response["choices"][0]["message"]["content"], "Hello");
+        assert_eq!(response["choices"][0]["finish_reason"], "length");
+        assert!(response.get("usage").is_none());
+
+        let mut reply = ReplyAccumulator::new("r2");
+        reply.push_tool_call(None, "get_weather", "{}".to_string());
+        reply.push_tool_call(Some("given"), "get_time", "{}".to_string());
+        reply.set_usage(3, 2, 5);
+        assert_eq!(reply.usage(), Some(TokenUsage::new(3, 2)));
+        let response = reply.into_response("m", "stop");
+        assert_eq!(response["choices"][0]["finish_reason"], "tool_calls");
+        let calls = &response["choices"][0]["message"]["tool_calls"];
+        assert_eq!(calls[0]["id"], "call_r2_0");
+        assert_eq!(calls[1]["id"], "given");
+        assert_eq!(response["usage"]["total_tokens"], 5);
+    }
+}
@@ -0,0 +1,288 @@
+//! Per-request parameter overrides.
+//!
+//! Upstream nodes can change generation settings for a single request through
+//! Dora metadata (or, for control prompts, fields of the JSON command) without
+//! touching the config file:
+//!
+//! | Key | Value |
+//! |-----|-------|
+//! | `model` | Model ID from `[[models]]` |
+//! | `temperature`, `top_p` | Number |
+//! | `max_tokens` | Integer |
+//! | `stop` | String or list of strings (a JSON array string also works) |
+//! | `response_format` | `"json_object"`, `"text"` or a JSON object |
+//! | `instructions` | Extra system instructions for this request only |
+
+use std::collections::BTreeMap;
+
+use dora_node_api::Parameter;
+use eyre::{Result, eyre};
+use outfox_openai::spec::CreateChatCompletionRequest;
+use serde_json::{Map, Value, json};
+
+/// Metadata / control JSON keys understood as overrides
+pub const OVERRIDE_KEYS: [&str; 7] = [
+    "model",
+    "temperature",
+    "top_p",
+    "max_tokens",
+    "stop",
+    "response_format",
+    "instructions",
+];
+
+/// Settings that replace the config defaults for one request
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RequestOverrides {
+    pub model: Option<String>,
+    pub temperature: Option<f64>,
+    pub top_p: Option<f64>,
+    pub max_tokens: Option<u64>,
+    pub stop: Option<Vec<String>>,
+    pub response_format: Option<Value>,
+    pub instructions: Option<String>,
+}
+
+impl RequestOverrides {
+    /// Read overrides from Dora input metadata
+    pub fn from_metadata(parameters: &BTreeMap<String, Parameter>) -> Result<Self> {
+        let mut fields = Map::new();
+        for key in OVERRIDE_KEYS {
+            let value = match parameters.get(key) {
+                Some(Parameter::String(s)) => json!(s),
+                Some(Parameter::Integer(i)) => json!(i),
+                Some(Parameter::Float(f)) => json!(f),
+                Some(Parameter::ListString(list)) => json!(list),
+                _ => continue,
+            };
+            fields.insert(key.to_string(), value);
+        }
+        Self::from_json(&Value::Object(fields))
+    }
+
+    /// Read overrides from a JSON object (unknown keys are ignored)
+    pub fn from_json(value: &Value) -> Result<Self> {
+        let get = |key: &str| value.get(key).filter(|v| !v.is_null());
+
+        Ok(Self {
+            model: get("model").map(|v| string(v, "model")).transpose()?,
+            temperature: get("temperature").map(|v| number(v, "temperature")).transpose()?,
+            top_p: get("top_p").map(|v| number(v, "top_p")).transpose()?,
+            max_tokens: get("max_tokens")
+                .map(|v| {
+                    let n = number(v, "max_tokens")?;
+                    if n < 1.0 || n.fract() != 0.0 {
+                        return Err(eyre!("max_tokens must be a positive integer, got {}", v));
+                    }
+                    Ok(n as u64)
+                })
+                .transpose()?,
+            stop: get("stop").map(stop_list).transpose()?,
+            response_format: get("response_format").map(response_format).transpose()?,
+            instructions: get("instructions").map(|v| string(v, "instructions")).transpose()?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Later overrides (e.g. control JSON fields) win over earlier ones (metadata)
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            model: other.model.or(self.model),
+            temperature: other.temperature.or(self.temperature),
+            top_p: other.top_p.or(self.top_p),
+            max_tokens: other.max_tokens.or(self.max_tokens),
+            stop: other.stop.or(self.stop),
+            response_format: other.response_format.or(self.response_format),
+            instructions: other.instructions.or(self.instructions),
+        }
+    }
+
+    /// Apply the generation settings to a request.
+    ///
+    /// `model` is not applied here: it selects the route, see `Config::route_chain`.
+    /// Instructions are appended to the system prompt of this request only.
+    /// On error the request is left unchanged.
+    pub fn apply(&self, request: &mut CreateChatCompletionRequest) -> Result<()> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        let mut value = serde_json::to_value(&*request)?;
+
+        if let Some(temperature) = self.temperature {
+            value["temperature"] = json!(temperature);
+        }
+        if let Some(top_p) = self.top_p {
+            value["top_p"] = json!(top_p);
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            value["max_tokens"] = json!(max_tokens);
+        }
+        if let Some(stop) = &self.stop {
+            value["stop"] = json!(stop);
+        }
+        if let Some(response_format) = &self.response_format {
+            value["response_format"] = response_format.clone();
+        }
+        if let Some(instructions) = &self.instructions {
+            append_instructions(&mut value, instructions);
+        }
+
+        *request = serde_json::from_value(value).map_err(|e| eyre!("Invalid request overrides: {}", e))?;
+        Ok(())
+    }
+
+    /// Short description for logs
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(model) = &self.model {
+            parts.push(format!("model={}", model));
+        }
+        if let Some(temperature) = self.temperature {
+            parts.push(format!("temperature={}", temperature));
+        }
+        if let Some(top_p) = self.top_p {
+            parts.push(format!("top_p={}", top_p));
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            parts.push(format!("max_tokens={}", max_tokens));
+        }
+        if let Some(stop) = &self.stop {
+            parts.push(format!("stop={:?}", stop));
+        }
+        if let Some(response_format) = &self.response_format {
+            parts.push(format!("response_format={}", response_format));
+        }
+        if let Some(instructions) = &self.instructions {
+            parts.push(format!("instructions={} chars", instructions.chars().count()));
+        }
+        parts.join(", ")
+    }
+}
+
+fn string(value: &Value, key: &str) -> Result<String> {
+    value
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| eyre!("{} must be a string, got {}", key, value))
+}
+
+/// Numbers may also arrive as strings (metadata values are often strings)
+fn number(value: &Value, key: &str) -> Result<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| eyre!("{} must be a number, got {}", key, value))
+}
+
+fn stop_list(value: &Value) -> Result<Vec<String>> {
+    let value = match value {
+        Value::String(s) if s.trim_start().starts_with('[') => serde_json::from_str(s)?,
+        Value::String(s) => return Ok(vec![s.clone()]),
+        other => other.clone(),
+    };
+    serde_json::from_value(value).map_err(|_| eyre!("stop must be a string or a list of strings"))
+}
+
+fn response_format(value: &Value) -> Result<Value> {
+    match value {
+        Value::String(s) if s.trim_start().starts_with('{') => Ok(serde_json::from_str(s)?),
+        Value::String(s) => Ok(json!({ "type": s })),
+        Value::Object(_) => Ok(value.clone()),
+        _ => Err(eyre!("response_format must be a string or an object")),
+    }
+}
+
+pub(crate) fn append_instructions(request: &mut Value, instructions: &str) {
+    let Some(messages) = request.get_mut("messages").and_then(Value::as_array_mut) else {
+        return;
+    };
+    let first_is_system = messages
+        .first()
+        .and_then(|m| m.get("role"))
+        .and_then(Value::as_str)
+        == Some("system");
+
+    match messages.first_mut() {
+        Some(system) if first_is_system => {
+            let prompt = system.get("content").and_then(Value::as_str).unwrap_or_default();
+            system["content"] = json!(format!("{}\n\n{}", prompt, instructions));
+        }
+        _ => messages.insert(0, json!({ "role": "system", "content": instructions })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> CreateChatCompletionRequest {
+        serde_json::from_value(json!({
+            "model": "qwen-plus",
+            "messages": [
+                { "role": "system", "content": "You are the tutor." },
+                { "role": "user", "content": "Wrap up." },
+            ],
+            "temperature": 0.7,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_from_metadata() {
+        let mut parameters = BTreeMap::new();
+        parameters.insert("session_id".to_string(), Parameter::String("s1".to_string()));
+        parameters.insert("temperature".to_string(), Parameter::String("0.2".to_string()));
+        parameters.insert("max_tokens".to_string(), Parameter::Integer(120));
+        parameters.insert("stop".to_string(), Parameter::String(r#"["。", "END"]"#.to_string()));
+        parameters.insert("response_format".to_string(), Parameter::String("json_object".to_string()));
+
+        let overrides = RequestOverrides::from_metadata(&parameters).unwrap();
+        assert_eq!(overrides.temperature, Some(0.2));
+        assert_eq!(overrides.max_tokens, Some(120));
+        assert_eq!(overrides.stop, Some(vec!["。".to_string(), "END".to_string()]));
+        assert_eq!(overrides.response_format, Some(json!({ "type": "json_object" })));
+        assert_eq!(overrides.model, None);
+
+        parameters.insert("max_tokens".to_string(), Parameter::String("lots".to_string()));
+        assert!(RequestOverrides::from_metadata(&parameters).is_err());
+    }
+
+    #[test]
+    fn test_merge_prefers_later() {
+        let metadata = RequestOverrides::from_json(&json!({ "temperature": 0.9, "top_p": 0.5 })).unwrap();
+        let control = RequestOverrides::from_json(&json!({ "temperature": 0.1, "prompt": "ignored" })).unwrap();
+        let merged = metadata.merge(control);
+        assert_eq!(merged.temperature, Some(0.1));
+        assert_eq!(merged.top_p, Some(0.5));
+    }
+
+    #[test]
+    fn test_apply() {
+        assert!(RequestOverrides::default().is_empty());
+
+        let overrides = RequestOverrides::from_json(&json!({
+            "temperature": 0.3,
+            "max_tokens": 80,
+            "stop": "END",
+            "instructions": "Answer in one sentence.",
+        }))
+        .unwrap();
+        let mut request = request();
+        overrides.apply(&mut request).unwrap();
+        let applied = serde_json::to_value(&request).unwrap();
+
+        assert_eq!(applied["max_tokens"], 80);
+        assert!((applied["temperature"].as_f64().unwrap() - 0.3).abs() < 1e-6);
+        assert_eq!(applied["stop"], json!(["END"]));
+        assert_eq!(
+            applied["messages"][0]["content"],
+            "You are the tutor.\n\nAnswer in one sentence."
+        );
+        assert_eq!(applied["messages"][1]["content"], "Wrap up.");
+    }
+}
@@ -0,0 +1,366 @@
+//! Provider rate limits shared by the participants on one machine.
+//!
+//! Every participant is its own process, so limits are kept in a small state
+//! file per provider (`<rate_limit_dir>/<key>.json`): the requests and
+//! estimated tokens of the last minute, and leases for the requests in flight.
+//! An advisory lock on a `.lock` file guards each read-modify-write; the OS
+//! releases it if the holder crashes. Processes that configure the same `key`
+//! share a budget; a crashed process's leases expire after the request timeout.
+//! File access runs on tokio's blocking pool when called from async code.
+//!
+//! [`FailoverClient`](crate::failover::FailoverClient) takes a [`Permit`]
+//! before every attempt. While it waits, [`RateLimitWait`] notices go to the
+//! engine, which reports them as a `queued` status.
+
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use eyre::{Context, Result};
+use fs2::FileExt;
+use outfox_openai::spec::CreateChatCompletionRequest;
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+use crate::client::ProviderError;
+use crate::usage::estimate_tokens;
+
+/// Length of the requests/tokens window
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Longest sleep between checks while queued; other processes may release
+/// their leases before the computed wait is over
+const MAX_POLL: Duration = Duration::from_secs(1);
+
+/// `[rate_limits.<provider id>]` settings; unset limits are not enforced
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct RateLimitConfig {
+    pub requests_per_minute: Option<u32>,
+    /// Estimated prompt tokens plus `max_tokens`, per minute
+    pub tokens_per_minute: Option<u64>,
+    /// Requests in flight (streaming or not) at once
+    pub max_concurrent_streams: Option<u32>,
+    /// Name of the shared state; providers with the same key share the
+    /// limits (e.g. two provider entries using one API key). Defaults to
+    /// the provider ID.
+    pub key: Option<String>,
+}
+
+/// Why a request waits, and for about how long
+#[derive(Clone, Debug, PartialEq)]
+pub struct RateLimitWait {
+    pub provider_id: String,
+    /// `requests_per_minute`, `tokens_per_minute` or `max_concurrent_streams`
+    pub reason: &'static str,
+    pub retry_after: Duration,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SharedState {
+    #[serde(default)]
+    requests: Vec<RequestRecord>,
+    #[serde(default)]
+    leases: Vec<Lease>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RequestRecord {
+    at_ms: u64,
+    tokens: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Lease {
+    id: String,
+    expires_ms: u64,
+}
+
+/// Limits of one provider
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    provider_id: String,
+    config: RateLimitConfig,
+    files: StateFiles,
+    /// How long an unreleased lease counts
+    lease_ttl: Duration,
+}
+
+impl RateLimiter {
+    pub fn new(provider_id: &str, config: RateLimitConfig, dir: &Path, lease_ttl: Duration) -> Self {
+        let key = config.key.as_deref().unwrap_or(provider_id);
+        let key: String = key
+            .chars()
+            .map(|c| if c.is_alphanumeric() || matches!(c, '-' | '_') { c } else { '_' })
+            .collect();
+        Self {
+            provider_id: provider_id.to_string(),
+            files: StateFiles {
+                state: dir.join(format!("{}.json", key)),
+                lock: dir.join(format!("{}.lock", key)),
+            },
+            config,
+            lease_ttl,
+        }
+    }
+
+    /// Take a permit for a request of about `tokens` tokens, or say how long
+    /// to wait
+    pub fn try_acquire(&self, tokens: u64) -> Result<std::result::Result<Permit, RateLimitWait>> {
+        let lease_id = uuid::Uuid::new_v4().to_string();
+        let granted = self.files.update(|state, now| {
+            if let Some(wait) = self.wait_time(state, tokens, now) {
+                return Err(wait);
+            }
+            state.requests.push(RequestRecord { at_ms: now, tokens });
+            if self.config.max_concurrent_streams.is_some() {
+                state.leases.push(Lease {
+                    id: lease_id.clone(),
+                    expires_ms: now + self.lease_ttl.as_millis() as u64,
+                });
+            }
+            Ok(())
+        })?;
+        Ok(granted.map(|()| Permit {
+            files: self.files.clone(),
+            lease_id: self.config.max_concurrent_streams.is_some().then_some(lease_id),
+        }))
+    }
+
+    /// Wait for a permit. `on_wait` is called when the request gets queued
+    /// and whenever the reason changes.
+    pub async fn acquire(
+        &self,
+        tokens: u64,
+        cancellation_token: Option<&CancellationToken>,
+        mut on_wait: impl FnMut(&RateLimitWait),
+    ) -> Result<Permit> {
+        let mut last_reason = None;
+        loop {
+            let limiter = self.clone();
+            let attempt = tokio::task::spawn_blocking(move || limiter.try_acquire(tokens))
+                .await
+                .wrap_err("Rate limit check panicked")?;
+            let wait = match attempt? {
+                Ok(permit) => return Ok(permit),
+                Err(wait) => wait,
+            };
+            if last_reason != Some(wait.reason) {
+                last_reason = Some(wait.reason);
+                on_wait(&wait);
+            }
+            let sleep = wait.retry_after.min(MAX_POLL);
+            match cancellation_token {
+                Some(token) => tokio::select! {
+                    _ = tokio::time::sleep(sleep) => {}
+                    _ = token.cancelled() => {
+                        return Err(ProviderError::Cancelled).wrap_err("Cancelled while queued");
+                    }
+                },
+                None => tokio::time::sleep(sleep).await,
+            }
+        }
+    }
+
+    fn wait_time(&self, state: &SharedState, tokens: u64, now: u64) -> Option<RateLimitWait> {
+        let window = WINDOW.as_millis() as u64;
+        let wait = |reason, until_ms: u64| RateLimitWait {
+            provider_id: self.provider_id.clone(),
+            reason,
+            retry_after: Duration::from_millis(until_ms.saturating_sub(now).max(1)),
+        };
+
+        if let Some(max) = self.config.max_concurrent_streams {
+            if state.leases.len() >= max as usize {
+                let first_expiry = state.leases.iter().map(|l| l.expires_ms).min().unwrap_or(now);
+                return Some(wait("max_concurrent_streams", first_expiry));
+            }
+        }
+        if let Some(max) = self.config.requests_per_minute {
+            if state.requests.len() >= max as usize {
+                // Requests are in time order
+                let index = state.requests.len() - max as usize;
+                return Some(wait("requests_per_minute", state.requests[index].at_ms + window));
+            }
+        }
+        if let Some(max) = self.config.tokens_per_minute {
+            let used: u64 = state.requests.iter().map(|r| r.tokens).sum();
+            // A request larger than the whole budget goes alone
+            if used + tokens > max && !state.requests.is_empty() {
+                let mut remaining = used;
+                for record in &state.requests {
+                    remaining -= record.tokens;
+                    if remaining + tokens <= max {
+                        return Some(wait("tokens_per_minute", record.at_ms + window));
+                    }
+                }
+                // Too large for any share of the budget; wait until it is all free
+                let last = state.requests.last().map(|r| r.at_ms).unwrap_or(now);
+                return Some(wait("tokens_per_minute", last + window));
+            }
+        }
+        None
+    }
+}
+
+/// Estimated tokens of a request: its messages plus `max_tokens`
+pub fn request_tokens(request: &CreateChatCompletionRequest) -> u64 {
+    let Ok(value) = serde_json::to_value(request) else {
+        return 0;
+    };
+    let prompt = value.get("messages").map(|m| estimate_tokens(&m.to_string())).unwrap_or(0);
+    let completion = ["max_tokens", "max_completion_tokens"]
+        .iter()
+        .find_map(|key| value.get(*key).and_then(|v| v.as_u64()))
+        .unwrap_or(0);
+    prompt + completion
+}
+
+/// Holds a request's lease until dropped
+#[derive(Debug)]
+pub struct Permit {
+    files: StateFiles,
+    lease_id: Option<String>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let Some(lease_id) = self.lease_id.take() else {
+            return;
+        };
+        let files = self.files.clone();
+        let release = move || {
+            let released = files.update(|state, _| state.leases.retain(|l| l.id != lease_id));
+            if let Err(e) = released {
+                eprintln!("Warning: Failed to release rate limit lease: {:#}", e);
+            }
+        };
+        // Don't block a runtime worker on file I/O
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => drop(handle.spawn_blocking(release)),
+            Err(_) => release(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct StateFiles {
+    state: PathBuf,
+    lock: PathBuf,
+}
+
+impl StateFiles {
+    /// Run `f` on the shared state (without expired entries) under the lock
+    fn update<T>(&self, f: impl FnOnce(&mut SharedState, u64) -> T) -> Result<T> {
+        let _lock = self.lock()?;
+        let now = now_ms();
+        let mut state: SharedState = std::fs::read(&self.state)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default();
+        let window_start = now.saturating_sub(WINDOW.as_millis() as u64);
+        state.requests.retain(|r| r.at_ms > window_start);
+        state.leases.retain(|l| l.expires_ms > now);
+
+        let result = f(&mut state, now);
+
+        let tmp = self.state.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec(&state)?)
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, &self.state)
+            .with_context(|| format!("Failed to write {}", self.state.display()))?;
+        Ok(result)
+    }
+
+    /// Blocks until no other process holds the lock (held for a file read
+    /// and write only). The lock file itself is never removed, so every
+    /// process always locks the same inode.
+    fn lock(&self) -> Result<LockFile> {
+        if let Some(dir) = self.lock.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.lock)
+            .with_context(|| format!("Failed to open {}", self.lock.display()))?;
+        file.lock_exclusive()
+            .with_context(|| format!("Failed to lock {}", self.lock.display()))?;
+        Ok(LockFile(file))
+    }
+}
+
+/// Releases the advisory lock when dropped
+struct LockFile(File);
+
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        let _ = self.0.unlock();
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(dir: &Path, config: RateLimitConfig) -> RateLimiter {
+        RateLimiter::new("dashscope", config, dir, Duration::from_secs(120))
+    }
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("maas-rate-limits-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_limits_are_shared_through_the_state_file() {
+        let dir = temp_dir();
+        let config = RateLimitConfig {
+            requests_per_minute: Some(2),
+            tokens_per_minute: Some(1000),
+            ..Default::default()
+        };
+        // Two participants (processes) with the same provider
+        let student = limiter(&dir, config.clone());
+        let tutor = limiter(&dir, config);
+
+        assert!(student.try_acquire(100).unwrap().is_ok());
+        let wait = tutor.try_acquire(950).unwrap().unwrap_err();
+        assert_eq!(wait.reason, "tokens_per_minute");
+        assert!(wait.retry_after <= WINDOW);
+        assert!(tutor.try_acquire(100).unwrap().is_ok());
+        let wait = student.try_acquire(1).unwrap().unwrap_err();
+        assert_eq!(wait.reason, "requests_per_minute");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_concurrent_leases_are_released_on_drop() {
+        let dir = temp_dir();
+        let config = RateLimitConfig {
+            max_concurrent_streams: Some(1),
+            ..Default::default()
+        };
+        let student = limiter(&dir, config.clone());
+        let tutor = limiter(&dir, config);
+
+        let permit = student.try_acquire(10).unwrap().unwrap();
+        let wait = tutor.try_acquire(10).unwrap().unwrap_err();
+        assert_eq!(wait.reason, "max_concurrent_streams");
+        drop(permit);
+        assert!(tutor.try_acquire(10).unwrap().is_ok());
+        // The advisory lock is free again
+        let lock = File::open(dir.join("dashscope.lock")).unwrap();
+        assert!(lock.try_lock_exclusive().is_ok());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,3 +1,99 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+/// Stream segmentation for TTS.
+///
+/// Each language has a built-in profile (limits and punctuation sets); the
+/// top-level fields override all profiles and `[segmenter.languages.<lang>]`
+/// overrides a single one. With `language = "auto"` the profile is picked
+/// from the buffered text.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct SegmenterConfig {
+    pub language: SegmenterLanguage,
+    /// Words (space separated) before a segment is cut without punctuation
+    pub max_words: Option<usize>,
+    /// CJK characters before a segment is cut without punctuation
+    pub max_chars: Option<usize>,
+    /// Punctuation sets, given as strings of characters (`"\n"` for newline)
+    pub sentence_endings: Option<String>,
+    pub clause_endings: Option<String>,
+    pub soft_breaks: Option<String>,
+    pub strip_markdown: bool,
+    /// Do not split inside numbers such as `3.14`, `1,000` or `10:30`
+    pub protect_numbers: bool,
+    /// Words ending in `.` that do not end a sentence
+    pub abbreviations: Vec<String>,
+    pub languages: HashMap<SegmenterLanguage, SegmenterProfileConfig>,
+    /// Add `pause_ms`, `emphasis` and `language` to the metadata of each segment
+    pub prosody_hints: bool,
+    /// Also add the segment as SSML (`ssml` metadata key); needs `prosody_hints`
+    pub ssml: bool,
+    /// Pause after a segment, by the punctuation it ends with
+    pub sentence_pause_ms: u32,
+    pub clause_pause_ms: u32,
+    pub soft_pause_ms: u32,
+}
+
+impl Default for SegmenterConfig {
+    fn default() -> Self {
+        Self {
+            language: SegmenterLanguage::Auto,
+            max_words: None,
+            max_chars: None,
+            sentence_endings: None,
+            clause_endings: None,
+            soft_breaks: None,
+            strip_markdown: true,
+            protect_numbers: true,
+            abbreviations: ["Mr.", "Mrs.", "Ms.", "Dr.", "Prof.", "Sr.", "Jr.", "St.", "vs.", "e.g.", "i.e.", "No.", "Fig."]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            languages: HashMap::new(),
+            prosody_hints: false,
+            ssml: false,
+            sentence_pause_ms: 400,
+            clause_pause_ms: 250,
+            soft_pause_ms: 150,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SegmenterLanguage {
+    #[default]
+    Auto,
+    Zh,
+    Ja,
+    Ko,
+    En,
+}
+
+impl SegmenterLanguage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Zh => "zh",
+            Self::Ja => "ja",
+            Self::Ko => "ko",
+            Self::En => "en",
+        }
+    }
+}
+
+/// Per-language overrides of the segmenter limits and punctuation sets
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct SegmenterProfileConfig {
+    pub max_words: Option<usize>,
+    pub max_chars: Option<usize>,
+    pub sentence_endings: Option<String>,
+    pub clause_endings: Option<String>,
+    pub soft_breaks: Option<String>,
+}
+
 /// Segmenter for streaming text that intelligently buffers chunks into meaningful segments.
 ///
 /// The segmenter is designed for real-time text-to-speech applications where sending
@@ -6,6 +102,7 @@
 ///
 /// # Example
 /// ```
+/// # use dora_maas_client::segmenter::StreamSegmenter;
 /// let mut segmenter = StreamSegmenter::new(10);
 /// assert_eq!(segmenter.add_chunk("Hello"), None);
 /// assert_eq!(segmenter.add_chunk(" world."), Some("Hello world.".to_string()));
@@ -13,17 +110,148 @@
 pub struct StreamSegmenter {
     buffer: String,
     word_count: usize,
+    /// Configured language; `Auto` picks a profile from the buffer
+    language: SegmenterLanguage,
+    profiles: HashMap<SegmenterLanguage, Profile>,
+    strip_markdown: bool,
+    protect_numbers: bool,
+    abbreviations: Vec<String>,
+    pauses_ms: [u32; 3], // Sentence, clause, soft break
+    /// Inside `**bold**` text at the end of the buffer
+    in_bold: bool,
+    /// The buffered text contains bold text
+    has_emphasis: bool,
+    hints: ProsodyHints,
+}
+
+/// Limits and punctuation sets for one language
+#[derive(Clone, Debug)]
+struct Profile {
     max_words_without_punctuation: usize,
-    max_chars_without_punctuation: usize, // For Chinese text
+    max_chars_without_punctuation: usize, // For CJK text
+    sentence_endings: Vec<char>,
+    clause_endings: Vec<char>,
+    soft_breaks: Vec<char>,
+}
+
+impl Profile {
+    fn builtin(language: SegmenterLanguage) -> Self {
+        let mut profile = Self {
+            max_words_without_punctuation: 10,
+            max_chars_without_punctuation: 10, // Strict 10 Chinese character limit for TTS
+            sentence_endings: vec!['。', '！', '？', '.', '!', '?'],
+            clause_endings: vec!['；', '：', ';', ':'],
+            soft_breaks: vec!['，', ',', '\n'],
+        };
+        match language {
+            // Kana make Japanese text longer per phrase; 、 is the usual pause
+            SegmenterLanguage::Ja => {
+                profile.max_chars_without_punctuation = 15;
+                profile.soft_breaks.insert(0, '、');
+            }
+            // Korean separates words with spaces, so the word limit applies
+            SegmenterLanguage::Ko => profile.max_words_without_punctuation = 8,
+            SegmenterLanguage::Auto | SegmenterLanguage::Zh | SegmenterLanguage::En => {}
+        }
+        profile
+    }
+
+    fn apply(
+        &mut self,
+        max_words: Option<usize>,
+        max_chars: Option<usize>,
+        sentence_endings: Option<&String>,
+        clause_endings: Option<&String>,
+        soft_breaks: Option<&String>,
+    ) {
+        if let Some(max_words) = max_words {
+            self.max_words_without_punctuation = max_words.max(1);
+        }
+        if let Some(max_chars) = max_chars {
+            self.max_chars_without_punctuation = max_chars.max(1);
+        }
+        if let Some(marks) = sentence_endings {
+            self.sentence_endings = marks.chars().collect();
+        }
+        if let Some(marks) = clause_endings {
+            self.clause_endings = marks.chars().collect();
+        }
+        if let Some(marks) = soft_breaks {
+            self.soft_breaks = marks.chars().collect();
+        }
+    }
+
+    fn is_mark(&self, c: char) -> bool {
+        self.sentence_endings.contains(&c) || self.clause_endings.contains(&c) || self.soft_breaks.contains(&c)
+    }
+}
+
+/// Prosody hints for a segment, for TTS nodes that support them
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProsodyHints {
+    /// Pause after the segment
+    pub pause_ms: u32,
+    /// The segment contains bold text or ends with an exclamation mark
+    pub emphasis: bool,
+    pub language: SegmenterLanguage,
 }
 
 impl StreamSegmenter {
     pub fn new(max_words: usize) -> Self {
+        Self::from_config(&SegmenterConfig {
+            max_words: Some(max_words),
+            ..SegmenterConfig::default()
+        })
+    }
+
+    pub fn from_config(config: &SegmenterConfig) -> Self {
+        let languages = [
+            SegmenterLanguage::Auto,
+            SegmenterLanguage::Zh,
+            SegmenterLanguage::Ja,
+            SegmenterLanguage::Ko,
+            SegmenterLanguage::En,
+        ];
+        let profiles = languages
+            .into_iter()
+            .map(|language| {
+                let mut profile = Profile::builtin(language);
+                profile.apply(
+                    config.max_words,
+                    config.max_chars,
+                    config.sentence_endings.as_ref(),
+                    config.clause_endings.as_ref(),
+                    config.soft_breaks.as_ref(),
+                );
+                if let Some(overrides) = config.languages.get(&language) {
+                    profile.apply(
+                        overrides.max_words,
+                        overrides.max_chars,
+                        overrides.sentence_endings.as_ref(),
+                        overrides.clause_endings.as_ref(),
+                        overrides.soft_breaks.as_ref(),
+                    );
+                }
+                (language, profile)
+            })
+            .collect();
+
         Self {
             buffer: String::new(),
             word_count: 0,
-            max_words_without_punctuation: max_words,
-            max_chars_without_punctuation: 10, // Strict 10 Chinese character limit for TTS
+            language: config.language,
+            profiles,
+            strip_markdown: config.strip_markdown,
+            protect_numbers: config.protect_numbers,
+            abbreviations: config.abbreviations.clone(),
+            pauses_ms: [config.sentence_pause_ms, config.clause_pause_ms, config.soft_pause_ms],
+            in_bold: false,
+            has_emphasis: false,
+            hints: ProsodyHints {
+                pause_ms: 0,
+                emphasis: false,
+                language: config.language,
+            },
         }
     }
 
@@ -39,9 +267,22 @@ impl StreamSegmenter {
     /// * `Some(String)` - A complete segment ready for TTS processing
     /// * `None` - No segment ready yet, more buffering needed
     pub fn add_chunk(&mut self, chunk: &str) -> Option<String> {
+        // Track bold text for emphasis hints before the markers are removed
+        let bold_markers = chunk.matches("**").count();
+        if bold_markers > 0 || self.in_bold {
+            self.has_emphasis = true;
+        }
+        if bold_markers % 2 == 1 {
+            self.in_bold = !self.in_bold;
+        }
+
         // Clean markdown from the chunk before adding to buffer
-        let cleaned_chunk = self.clean_markdown(chunk);
-        self.buffer.push_str(&cleaned_chunk);
+        if self.strip_markdown {
+            let cleaned_chunk = self.clean_markdown(chunk);
+            self.buffer.push_str(&cleaned_chunk);
+        } else {
+            self.buffer.push_str(chunk);
+        }
 
         // Count words in the current buffer
         self.word_count = self.buffer.split_whitespace().count();
@@ -54,55 +295,79 @@ impl StreamSegmenter {
         }
     }
 
+    /// Hints for the segment last returned by `add_chunk` or `flush`
+    pub fn hints(&self) -> &ProsodyHints {
+        &self.hints
+    }
+
+    /// Language of the buffered text: the configured one, or detected by script
+    fn current_language(&self) -> SegmenterLanguage {
+        if self.language != SegmenterLanguage::Auto {
+            return self.language;
+        }
+        detect_language(&self.buffer)
+    }
+
+    fn profile(&self) -> &Profile {
+        &self.profiles[&self.current_language()]
+    }
+
     /// Check if we should emit a segment
     fn should_emit_segment(&self) -> bool {
         if self.buffer.is_empty() {
             return false;
         }
+        let profile = self.profile();
 
-        // Check for meaningful punctuation (Chinese and English)
-        let punctuation_marks = [
-            '。', '！', '？', '；', '：', // Chinese
-            '.', '!', '?', ';', ':', // English
-            '，', ',',  // Comma (both Chinese and English)
-            '\n', // Newline
-        ];
-
-        // Check if buffer ends with punctuation
-        let has_punctuation = self.buffer.chars().any(|c| punctuation_marks.contains(&c));
-
-        // Count Chinese characters (for Chinese text segmentation)
-        let chinese_char_count = self
+        // Check for meaningful punctuation that is not inside a number or abbreviation
+        let has_punctuation = self
             .buffer
-            .chars()
-            .filter(|c| self.is_chinese_char(*c))
-            .count();
+            .char_indices()
+            .any(|(i, c)| profile.is_mark(c) && self.is_boundary(i, c));
 
         // Emit if:
         // 1. We have punctuation, OR
         // 2. We have reached max words without punctuation (for English), OR
-        // 3. We have reached max Chinese characters without punctuation
+        // 3. We have reached max CJK characters without punctuation
         has_punctuation
-            || self.word_count >= self.max_words_without_punctuation
-            || chinese_char_count >= self.max_chars_without_punctuation
+            || self.word_count >= profile.max_words_without_punctuation
+            || cjk_units(&self.buffer) >= profile.max_chars_without_punctuation
     }
 
-    /// Check if a character is Chinese
-    fn is_chinese_char(&self, c: char) -> bool {
-        // Unicode ranges for Chinese characters
-        match c {
-            // CJK Unified Ideographs
-            '\u{4e00}'..='\u{9fff}' => true,
-            // CJK Unified Ideographs Extension A
-            '\u{3400}'..='\u{4dbf}' => true,
-            // CJK Unified Ideographs Extension B
-            '\u{20000}'..='\u{2a6df}' => true,
-            // CJK Unified Ideographs Extension C
-            '\u{2a700}'..='\u{2b73f}' => true,
-            // CJK Unified Ideographs Extension D
-            '\u{2b740}'..='\u{2b81f}' => true,
-            _ => false,
+    /// Whether the punctuation mark at `byte_pos` really ends a phrase.
+    ///
+    /// `.`, `,` and `:` between digits belong to a number, and a `.` directly
+    /// followed by a letter or ending a known abbreviation does not end a
+    /// sentence. A mark right after a digit at the end of the buffer waits
+    /// for the next chunk.
+    fn is_boundary(&self, byte_pos: usize, c: char) -> bool {
+        if !matches!(c, '.' | ',' | ':') {
+            return true;
         }
+        let prev = self.buffer[..byte_pos].chars().next_back();
+        let next = self.buffer[byte_pos + c.len_utf8()..].chars().next();
+
+        if self.protect_numbers && prev.is_some_and(|p| p.is_ascii_digit()) {
+            match next {
+                None => return false,
+                Some(n) if n.is_ascii_digit() => return false,
+                _ => {}
+            }
+        }
+        if c == '.' {
+            if next.is_some_and(|n| n.is_ascii_alphanumeric()) {
+                return false;
+            }
+            let text = &self.buffer[..byte_pos + 1];
+            return !self.abbreviations.iter().any(|abbreviation| {
+                text.ends_with(abbreviation.as_str())
+                    && !text[..text.len() - abbreviation.len()]
+                        .chars()
+                        .next_back()
+                        .is_some_and(char::is_alphanumeric)
+            });
+        }
+        true
     }
 
     /// Emit the current buffer as a segment
@@ -110,11 +375,12 @@ impl StreamSegmenter {
         if self.buffer.is_empty() {
             return None;
         }
+        let profile = self.profile();
 
         // Find the best split point
         let split_point = self.find_split_point();
 
-        if split_point > 0 {
+        let segment = if split_point > 0 {
             // Take the segment up to the split point
             let segment = self.buffer[..split_point].to_string();
 
@@ -122,62 +388,71 @@ impl StreamSegmenter {
             self.buffer = self.buffer[split_point..].trim_start().to_string();
             self.word_count = self.buffer.split_whitespace().count();
 
-            Some(segment)
-        } else if self.word_count >= self.max_words_without_punctuation {
-            // No punctuation found but we've reached max words
+            segment
+        } else if self.word_count >= profile.max_words_without_punctuation
+            || cjk_units(&self.buffer) >= profile.max_chars_without_punctuation
+        {
+            // No punctuation found but we've reached the word or character limit
             // Emit the entire buffer
-            let segment = self.buffer.clone();
-            self.buffer.clear();
             self.word_count = 0;
-            Some(segment)
+            std::mem::take(&mut self.buffer)
         } else {
-            // Check if we need to emit due to Chinese character count
-            let chinese_char_count = self
-                .buffer
-                .chars()
-                .filter(|c| self.is_chinese_char(*c))
-                .count();
-
-            if chinese_char_count >= self.max_chars_without_punctuation {
-                // Too many Chinese characters without punctuation
-                let segment = self.buffer.clone();
-                self.buffer.clear();
-                self.word_count = 0;
-                Some(segment)
-            } else {
-                None
-            }
-        }
+            return None;
+        };
+
+        self.set_hints(&segment);
+        Some(segment)
+    }
+
+    /// Record the prosody hints of an emitted segment
+    fn set_hints(&mut self, segment: &str) {
+        let language = match self.language {
+            SegmenterLanguage::Auto => detect_language(segment),
+            language => language,
+        };
+        let profile = &self.profiles[&language];
+        let last = segment.trim_end_matches(|c: char| c.is_whitespace() && c != '\n').chars().next_back();
+        let pause_ms = match last {
+            Some(c) if profile.sentence_endings.contains(&c) => self.pauses_ms[0],
+            Some(c) if profile.clause_endings.contains(&c) => self.pauses_ms[1],
+            Some(c) if profile.soft_breaks.contains(&c) => self.pauses_ms[2],
+            _ => 0,
+        };
+        self.hints = ProsodyHints {
+            pause_ms,
+            emphasis: self.has_emphasis || matches!(last, Some('!' | '！')),
+            language,
+        };
+        self.has_emphasis = self.in_bold;
     }
 
     /// Find the best point to split the buffer
     fn find_split_point(&self) -> usize {
-        // Count Chinese characters to enforce strict limit
-        let chinese_char_count = self
-            .buffer
-            .chars()
-            .filter(|c| self.is_chinese_char(*c))
-            .count();
+        let profile = self.profile();
+        let char_limit = profile.max_chars_without_punctuation;
+
+        // Count CJK characters to enforce strict limit
+        let cjk_count = cjk_units(&self.buffer);
 
         // Priority punctuation for splitting (sentence endings first)
-        let sentence_endings = ['。', '！', '？', '.', '!', '?'];
-        let clause_endings = ['；', '：', ';', ':'];
-        let soft_breaks = ['，', ',', '\n'];
+        let sentence_endings = &profile.sentence_endings;
+        let clause_endings = &profile.clause_endings;
+        let soft_breaks = &profile.soft_breaks;
 
-        // For Chinese text, be more aggressive with segmentation
-        if chinese_char_count > 0 {
+        // For CJK text, be more aggressive with segmentation
+        if cjk_count > 0 {
             // If we're approaching the limit, look for ANY punctuation to split at
-            if chinese_char_count >= 8 {
+            if cjk_count >= char_limit.saturating_sub(2).max(1) {
                 // Try sentence endings first
-                if let Some(pos) = self.find_punctuation_before_limit(&sentence_endings, 10) {
+                if let Some(pos) = self.find_punctuation_before_limit(sentence_endings, char_limit) {
                     return pos + self.char_len_at(pos);
                 }
                 // Then clause endings
-                if let Some(pos) = self.find_punctuation_before_limit(&clause_endings, 10) {
+                if let Some(pos) = self.find_punctuation_before_limit(clause_endings, char_limit) {
                     return pos + self.char_len_at(pos);
                 }
                 // Then soft breaks (comma)
-                if let Some(pos) = self.find_punctuation_before_limit(&soft_breaks, 10) {
+                if let Some(pos) = self.find_punctuation_before_limit(soft_breaks, char_limit) {
                     return pos + self.char_len_at(pos);
                 }
             }
@@ -185,18 +460,20 @@ impl StreamSegmenter {
 
         // For English or mixed text, use the original logic
         // Try to find sentence ending first
-        if let Some(pos) = self.find_last_punctuation(&sentence_endings) {
+        if let Some(pos) = self.find_last_punctuation(sentence_endings) {
             return pos + self.char_len_at(pos);
         }
 
         // Then try clause endings
-        if let Some(pos) = self.find_last_punctuation(&clause_endings) {
+        if let Some(pos) = self.find_last_punctuation(clause_endings) {
             return pos + self.char_len_at(pos);
         }
 
         // Finally try soft breaks if we have enough words
-        if self.word_count >= 5 || chinese_char_count >= 5 {
-            if let Some(pos) = self.find_last_punctuation(&soft_breaks) {
+        if self.word_count >= (profile.max_words_without_punctuation / 2).max(1)
+            || cjk_count >= (char_limit / 2).max(1)
+        {
+            if let Some(pos) = self.find_last_punctuation(soft_breaks) {
                 return pos + self.char_len_at(pos);
             }
         }
@@ -209,25 +486,28 @@ impl StreamSegmenter {
         self.buffer
             .char_indices()
             .rev()
-            .find(|(_, c)| marks.contains(c))
+            .find(|&(i, c)| marks.contains(&c) && self.is_boundary(i, c))
             .map(|(i, _)| i)
     }
 
-    /// Find punctuation within the first N Chinese characters
+    /// Find punctuation within the first N CJK characters (Latin words count as one)
     fn find_punctuation_before_limit(&self, marks: &[char], char_limit: usize) -> Option<usize> {
-        let mut chinese_count = 0;
+        let mut cjk_count = 0;
         let mut last_punct_pos = None;
+        let mut prev_alnum = false;
 
         for (i, c) in self.buffer.char_indices() {
-            if self.is_chinese_char(c) {
-                chinese_count += 1;
-                if chinese_count > char_limit {
+            let starts_unit = is_cjk_char(c) || (c.is_ascii_alphanumeric() && !prev_alnum);
+            prev_alnum = c.is_ascii_alphanumeric();
+            if starts_unit {
+                cjk_count += 1;
+                if cjk_count > char_limit {
                     // We've exceeded the limit, return the last punctuation we found
                     return last_punct_pos;
                 }
             }
 
-            if marks.contains(&c) {
+            if marks.contains(&c) && self.is_boundary(i, c) {
                 last_punct_pos = Some(i);
             }
         }
@@ -308,7 +588,9 @@ impl StreamSegmenter {
         for line in lines {
             let trimmed = line.trim();
             if trimmed.starts_with('#')
-                && trimmed.chars().nth(1).map_or(false, |c| c.is_whitespace())
+                && trimmed
+                    .trim_start_matches('#')
+                    .starts_with(char::is_whitespace)
             {
                 // Remove the # symbols and leading whitespace
                 let cleaned = trimmed.trim_start_matches('#').trim();
@@ -365,14 +647,105 @@ impl StreamSegmenter {
         if self.buffer.is_empty() {
             None
         } else {
-            let segment = self.buffer.clone();
-            self.buffer.clear();
+            let segment = std::mem::take(&mut self.buffer);
             self.word_count = 0;
+            self.set_hints(&segment);
             Some(segment)
         }
     }
 }
 
+/// Check if a character is counted against the CJK character limit (Han or kana)
+fn is_cjk_char(c: char) -> bool {
+    // Unicode ranges for Chinese characters and Japanese kana
+    match c {
+        // CJK Unified Ideographs
+        '\u{4e00}'..='\u{9fff}' => true,
+        // CJK Unified Ideographs Extension A
+        '\u{3400}'..='\u{4dbf}' => true,
+        // CJK Unified Ideographs Extension B
+        '\u{20000}'..='\u{2a6df}' => true,
+        // CJK Unified Ideographs Extension C
+        '\u{2a700}'..='\u{2b73f}' => true,
+        // CJK Unified Ideographs Extension D
+        '\u{2b740}'..='\u{2b81f}' => true,
+        // Hiragana and Katakana
+        '\u{3040}'..='\u{30ff}' => true,
+        _ => false,
+    }
+}
+
+fn is_kana(c: char) -> bool {
+    matches!(c, '\u{3040}'..='\u{30ff}')
+}
+
+fn is_hangul(c: char) -> bool {
+    matches!(c, '\u{ac00}'..='\u{d7af}' | '\u{1100}'..='\u{11ff}' | '\u{3130}'..='\u{318f}')
+}
+
+/// Guess the language of a text by script: kana → ja, hangul → ko, Han → zh, else en
+fn detect_language(text: &str) -> SegmenterLanguage {
+    if text.chars().any(is_kana) {
+        SegmenterLanguage::Ja
+    } else if text.chars().any(is_hangul) {
+        SegmenterLanguage::Ko
+    } else if text.chars().any(is_cjk_char) {
+        SegmenterLanguage::Zh
+    } else {
+        SegmenterLanguage::En
+    }
+}
+
+/// Length of CJK text in characters, counting each embedded Latin word or
+/// number as one character so mixed zh/en text is measured evenly.
+/// Text without any CJK character counts as 0 (the word limit applies).
+fn cjk_units(text: &str) -> usize {
+    let mut cjk = 0;
+    let mut latin_words = 0;
+    let mut prev_alnum = false;
+    for c in text.chars() {
+        if is_cjk_char(c) {
+            cjk += 1;
+        } else if c.is_ascii_alphanumeric() && !prev_alnum {
+            latin_words += 1;
+        }
+        prev_alnum = c.is_ascii_alphanumeric();
+    }
+    if cjk == 0 {
+        0
+    } else {
+        cjk + latin_words
+    }
+}
+
+/// Wrap a segment in SSML carrying its prosody hints
+pub fn to_ssml(segment: &str, hints: &ProsodyHints) -> String {
+    let lang = match hints.language {
+        SegmenterLanguage::Zh | SegmenterLanguage::Auto => "zh-CN",
+        SegmenterLanguage::Ja => "ja-JP",
+        SegmenterLanguage::Ko => "ko-KR",
+        SegmenterLanguage::En => "en-US",
+    };
+    let text = segment
+        .trim()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;");
+    let mut ssml = format!("<speak xml:lang=\"{}\">", lang);
+    if hints.emphasis {
+        ssml.push_str(&format!("<emphasis level=\"strong\">{}</emphasis>", text));
+    } else {
+        ssml.push_str(&text);
+    }
+    if hints.pause_ms > 0 {
+        ssml.push_str(&format!("<break time=\"{}ms\"/>", hints.pause_ms));
+    }
+    ssml.push_str("</speak>");
+    ssml
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -443,7 +816,10 @@ mod tests {
 
     #[test]
     fn test_chinese_character_segmentation() {
-        let mut segmenter = StreamSegmenter::new(10); // 10 English words, 20 Chinese chars max
+        let mut segmenter = StreamSegmenter::from_config(&SegmenterConfig {
+            max_chars: Some(20),
+            ..SegmenterConfig::default()
+        }); // 10 English words, 20 Chinese chars max
 
         // Test long Chinese text without punctuation - should segment at 20 characters
         let long_chinese =
@@ -529,4 +905,104 @@ mod tests {
         // The rest should be in buffer
         assert_eq!(segmenter.flush(), Some("放入豆角煸炒".to_string()));
     }
+
+    #[test]
+    fn test_numbers_and_abbreviations() {
+        let mut segmenter = StreamSegmenter::new(10);
+
+        // A trailing "3." might be the start of "3.5"
+        assert_eq!(segmenter.add_chunk("It costs 3."), None);
+        assert_eq!(segmenter.add_chunk("5 dollars"), None);
+        assert_eq!(
+            segmenter.add_chunk(", or 1,000 yen."),
+            Some("It costs 3.5 dollars, or 1,000 yen.".to_string())
+        );
+
+        assert_eq!(segmenter.add_chunk("Ask Dr. Smith at 10:30"), None);
+        assert_eq!(
+            segmenter.add_chunk(" today."),
+            Some("Ask Dr. Smith at 10:30 today.".to_string())
+        );
+
+        let mut unprotected = StreamSegmenter::from_config(&SegmenterConfig {
+            protect_numbers: false,
+            ..SegmenterConfig::default()
+        });
+        assert_eq!(unprotected.add_chunk("Pi is 3."), Some("Pi is 3.".to_string()));
+    }
+
+    #[test]
+    fn test_japanese_and_mixed_segmentation() {
+        let mut segmenter = StreamSegmenter::new(10);
+
+        // 、 is a soft break for Japanese but not for Chinese
+        assert_eq!(segmenter.add_chunk("今日はとても"), None);
+        assert_eq!(
+            segmenter.add_chunk("いい天気なので、散歩"),
+            Some("今日はとてもいい天気なので、".to_string())
+        );
+        assert_eq!(segmenter.hints().language, SegmenterLanguage::Ja);
+        assert_eq!(segmenter.flush(), Some("散歩".to_string()));
+
+        // Latin words inside Chinese text count towards the character limit
+        let mut mixed = StreamSegmenter::new(10);
+        assert_eq!(mixed.add_chunk("我们用 Rust 和 Python"), None);
+        assert_eq!(
+            mixed.add_chunk(" 写服务端代码"),
+            Some("我们用 Rust 和 Python 写服务端代码".to_string())
+        );
+        assert_eq!(mixed.hints().language, SegmenterLanguage::Zh);
+    }
+
+    #[test]
+    fn test_config_overrides() {
+        let config: SegmenterConfig = serde_json::from_value(serde_json::json!({
+            "language": "en",
+            "soft_breaks": "",
+            "strip_markdown": false,
+            "languages": { "en": { "max_words": 3 } },
+        }))
+        .unwrap();
+        let mut segmenter = StreamSegmenter::from_config(&config);
+
+        assert_eq!(segmenter.add_chunk("**one**,"), None);
+        assert_eq!(
+            segmenter.add_chunk(" two three"),
+            Some("**one**, two three".to_string())
+        );
+    }
+
+    #[test]
+    fn test_prosody_hints() {
+        let mut segmenter = StreamSegmenter::new(10);
+
+        assert_eq!(segmenter.add_chunk("你好，"), None);
+        assert_eq!(segmenter.add_chunk("**注意安全**！"), Some("你好，注意安全！".to_string()));
+        let hints = segmenter.hints().clone();
+        assert_eq!(
+            hints,
+            ProsodyHints {
+                pause_ms: 400,
+                emphasis: true,
+                language: SegmenterLanguage::Zh,
+            }
+        );
+        assert_eq!(
+            to_ssml("你好，注意安全！", &hints),
+            "<speak xml:lang=\"zh-CN\"><emphasis level=\"strong\">你好，注意安全！</emphasis><break time=\"400ms\"/></speak>"
+        );
+
+        assert_eq!(segmenter.add_chunk("Then we wait;"), Some("Then we wait;".to_string()));
+        assert_eq!(segmenter.hints().pause_ms, 250);
+        assert!(!segmenter.hints().emphasis);
+        assert_eq!(segmenter.hints().language, SegmenterLanguage::En);
+
+        assert_eq!(segmenter.add_chunk("a <b>"), None);
+        assert_eq!(segmenter.flush(), Some("a <b>".to_string()));
+        assert_eq!(segmenter.hints().pause_ms, 0);
+        assert_eq!(
+            to_ssml("a <b>", segmenter.hints()),
+            "<speak xml:lang=\"en-US\">a &lt;b&gt;</speak>"
+        );
+    }
 }
@@ -0,0 +1,360 @@
+//! Conversation history of one session.
+//!
+//! A [`ChatSession`] holds the messages sent to the model: the system prompt
+//! (with anchor context and MCP resources) first, then an optional running
+//! summary, then the conversation. It also tracks the session's token usage.
+
+use std::sync::{Arc, Mutex};
+
+use eyre::Result;
+use outfox_openai::spec::{
+    ChatCompletionMessageToolCall, ChatCompletionRequestAssistantMessage,
+    ChatCompletionRequestAssistantMessageContent, ChatCompletionRequestMessage,
+    ChatCompletionRequestSystemMessage, ChatCompletionRequestToolMessage,
+    ChatCompletionRequestUserMessage, ChatCompletionRequestUserMessageContent, ChatCompletionTool,
+    ChatCompletionToolType, FunctionObject, PartibleTextContent,
+};
+use serde_json::json;
+
+use crate::config::ModelPricing;
+use crate::session_store::{SNAPSHOT_VERSION, SessionSnapshot};
+use crate::summary;
+use crate::tool::ToolSet;
+use crate::usage::{RequestUsage, TokenUsage, UsageTotals, estimate_tokens};
+use crate::vision::{self, ImageInput};
+
+/// Messages, usage and tools of one conversation
+pub struct ChatSession {
+    pub messages: Vec<ChatCompletionRequestMessage>,
+    /// Running summary of condensed turns, kept at `messages[1]`
+    pub summary: Option<String>,
+    pub usage: UsageTotals,
+    pub tool_set: Option<Arc<Mutex<ToolSet>>>,
+    system_prompt: String,
+    anchor_context: Option<String>,
+    /// MCP resources added to the system context
+    resources: Vec<String>,
+    /// Images for the next user message
+    pub pending_images: Vec<ImageInput>,
+}
+
+impl ChatSession {
+    pub fn new(system_prompt: String, anchor_context: Option<String>) -> Self {
+        let mut session = Self {
+            messages: Vec::new(),
+            summary: None,
+            usage: UsageTotals::default(),
+            tool_set: None, // Will be set separately
+            system_prompt,
+            anchor_context,
+            resources: Vec::new(),
+            pending_images: Vec::new(),
+        };
+        session.messages.push(session.system_message());
+        session
+    }
+
+    fn system_message(&self) -> ChatCompletionRequestMessage {
+        // Combine system prompt with anchor context and resources if provided
+        let mut parts: Vec<&str> = self.anchor_context.iter().map(String::as_str).collect();
+        parts.extend(self.resources.iter().map(String::as_str));
+        parts.push(&self.system_prompt);
+
+        ChatCompletionRequestMessage::System(ChatCompletionRequestSystemMessage {
+            content: PartibleTextContent::Text(parts.join("\n\n")),
+            name: None,
+        })
+    }
+
+    /// Replace the system prompt after a config reload, keeping the history
+    pub fn set_system_prompt(&mut self, system_prompt: String, anchor_context: Option<String>) {
+        self.system_prompt = system_prompt;
+        self.anchor_context = anchor_context;
+        self.messages[0] = self.system_message();
+    }
+
+    /// Add an MCP resource to the system context; false if it is already there
+    pub fn add_resource(&mut self, resource: String) -> bool {
+        if self.resources.contains(&resource) {
+            return false;
+        }
+        self.resources.push(resource);
+        self.messages[0] = self.system_message();
+        true
+    }
+
+    pub fn set_tool_set(&mut self, tool_set: Arc<Mutex<ToolSet>>) {
+        self.tool_set = Some(tool_set);
+    }
+
+    pub fn has_tools(&self) -> bool {
+        self.tool_set
+            .as_ref()
+            .and_then(|ts| ts.lock().ok())
+            .map(|ts| ts.has_tools())
+            .unwrap_or(false)
+    }
+
+    pub fn get_tool_definitions(&self) -> Option<Vec<ChatCompletionTool>> {
+        self.tool_set
+            .as_ref()
+            .and_then(|ts| ts.lock().ok())
+            .map(|ts| {
+                ts.tools()
+                    .iter()
+                    .map(|tool| ChatCompletionTool {
+                        kind: ChatCompletionToolType::Function,
+                        function: FunctionObject {
+                            name: tool.name(),
+                            description: Some(tool.description()),
+                            parameters: Some(tool.parameters()),
+                            strict: None,
+                        },
+                    })
+                    .collect()
+            })
+    }
+
+    pub fn add_user_message(&mut self, content: String) {
+        let message = ChatCompletionRequestMessage::User(ChatCompletionRequestUserMessage {
+            content: ChatCompletionRequestUserMessageContent::Text(content),
+            name: None,
+        });
+        self.messages.push(message);
+    }
+
+    /// Add a user message with image content parts
+    pub fn add_user_message_with_images(&mut self, content: String, images: &[ImageInput]) -> Result<()> {
+        let mut parts = vec![json!({ "type": "text", "text": content })];
+        parts.extend(images.iter().map(ImageInput::content_part));
+        let message = serde_json::from_value(json!({ "role": "user", "content": parts }))?;
+        self.messages.push(message);
+        Ok(())
+    }
+
+    /// Keep an image for the next user message
+    pub fn attach_image(&mut self, image: ImageInput) {
+        self.pending_images.push(image);
+    }
+
+    pub fn add_assistant_message(&mut self, content: String) {
+        let message =
+            ChatCompletionRequestMessage::Assistant(ChatCompletionRequestAssistantMessage {
+                content: Some(ChatCompletionRequestAssistantMessageContent::Text(content)),
+                name: None,
+                tool_calls: None,
+                audio: None,
+                refusal: None,
+            });
+        self.messages.push(message);
+    }
+
+    pub fn add_assistant_message_with_tools(
+        &mut self,
+        content: String,
+        tool_calls: Vec<ChatCompletionMessageToolCall>,
+    ) {
+        let message =
+            ChatCompletionRequestMessage::Assistant(ChatCompletionRequestAssistantMessage {
+                content: if content.is_empty() {
+                    None
+                } else {
+                    Some(ChatCompletionRequestAssistantMessageContent::Text(content))
+                },
+                name: None,
+                tool_calls: Some(tool_calls),
+                audio: None,
+                refusal: None,
+            });
+        self.messages.push(message);
+    }
+
+    pub fn add_tool_message(&mut self, tool_call_id: String, content: String) {
+        let message = ChatCompletionRequestMessage::Tool(ChatCompletionRequestToolMessage {
+            content: PartibleTextContent::Text(content),
+            tool_call_id,
+        });
+        self.messages.push(message);
+    }
+
+    /// Index of the first conversation message (after system prompt and summary)
+    pub fn history_start(&self) -> usize {
+        if self.summary.is_some() { 2 } else { 1 }
+    }
+
+    pub fn manage_history(&mut self, max_exchanges: usize, max_context_tokens: Option<usize>) {
+        let start = self.history_start();
+
+        // Keep system message (and summary) + last N exchanges (N*2 messages)
+        let max_messages = start + (max_exchanges * 2);
+        if self.messages.len() > max_messages {
+            let excess = self.messages.len() - max_messages;
+            // Remove old messages but keep system prompt
+            self.messages.drain(start..start + excess);
+        }
+
+        // Then drop the oldest messages until the prompt fits the token limit,
+        // always keeping the system prompt, the summary and the latest message
+        if let Some(max_tokens) = max_context_tokens {
+            let mut tokens: Vec<u64> = self.messages.iter().map(message_tokens).collect();
+            let mut total: u64 = tokens.iter().sum();
+            while self.messages.len() > start + 1
+                && (total > max_tokens as u64
+                    // A tool result must not outlive the call it answers
+                    || matches!(self.messages[start], ChatCompletionRequestMessage::Tool(_)))
+            {
+                total -= tokens.remove(start);
+                self.messages.remove(start);
+            }
+        }
+    }
+
+    /// Replace the messages before `end` with a new running summary
+    pub fn apply_summary(&mut self, summary: String, end: usize) {
+        let start = self.history_start();
+        self.messages.drain(start..end);
+        let message = summary::summary_message(&summary);
+        if self.summary.is_some() {
+            self.messages[1] = message;
+        } else {
+            self.messages.insert(1, message);
+        }
+        self.summary = Some(summary);
+    }
+
+    pub fn estimated_prompt_tokens(&self) -> u64 {
+        self.messages.iter().map(message_tokens).sum()
+    }
+
+    /// Add one request's usage to the session, estimating it when the
+    /// provider reported none. Call before the reply is added to the history.
+    pub fn record_usage(
+        &mut self,
+        reported: Option<TokenUsage>,
+        completion: &str,
+        pricing: Option<&ModelPricing>,
+    ) -> RequestUsage {
+        let (usage, estimated) = match reported {
+            Some(usage) => (usage, false),
+            None => (
+                TokenUsage::new(self.estimated_prompt_tokens(), estimate_tokens(completion)),
+                true,
+            ),
+        };
+        let cost = pricing.map(|p| p.cost(&usage)).unwrap_or(0.0);
+        self.usage.record(&usage, cost);
+        RequestUsage {
+            usage,
+            estimated,
+            cost,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        // Keep only system message
+        self.messages.truncate(1);
+        self.summary = None;
+        self.usage = UsageTotals::default();
+        self.pending_images.clear();
+    }
+
+    /// History, summary, resources and usage, for the session store
+    pub fn snapshot(&self, session_id: &str, participant: &str) -> SessionSnapshot {
+        SessionSnapshot {
+            version: SNAPSHOT_VERSION,
+            session_id: session_id.to_string(),
+            participant: participant.to_string(),
+            saved_at: chrono::Utc::now().to_rfc3339(),
+            summary: self.summary.clone(),
+            resources: self.resources.clone(),
+            messages: self.messages[self.history_start()..].to_vec(),
+            usage: self.usage.clone(),
+        }
+    }
+
+    /// Replace the conversation with a saved one, keeping the current system
+    /// prompt and anchor context
+    pub fn restore(&mut self, snapshot: SessionSnapshot) {
+        self.resources = snapshot.resources;
+        self.messages.truncate(1);
+        self.messages[0] = self.system_message();
+        self.summary = snapshot.summary;
+        if let Some(summary) = &self.summary {
+            self.messages.push(summary::summary_message(summary));
+        }
+        self.messages.extend(snapshot.messages);
+        self.usage = snapshot.usage;
+        self.pending_images.clear();
+    }
+}
+
+/// Estimated tokens of one message as sent to the provider
+pub fn message_tokens(message: &ChatCompletionRequestMessage) -> u64 {
+    let Ok(mut value) = serde_json::to_value(message) else {
+        return 0;
+    };
+    // Base64 image data would dwarf the text
+    let images = vision::take_image_parts(&mut value) as u64;
+    estimate_tokens(&value.to_string()) + images * vision::IMAGE_TOKENS
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manage_history_keeps_system_prompt() {
+        let mut session = ChatSession::new("You are a tutor.".to_string(), None);
+        for i in 0..5 {
+            session.add_user_message(format!("question {}", i));
+            session.add_assistant_message(format!("answer {}", i));
+        }
+
+        session.manage_history(2, None);
+
+        assert_eq!(session.messages.len(), 5);
+        assert!(matches!(session.messages[0], ChatCompletionRequestMessage::System(_)));
+        assert!(matches!(session.messages[1], ChatCompletionRequestMessage::User(_)));
+    }
+
+    #[test]
+    fn test_token_limit_drops_orphaned_tool_results() {
+        let mut session = ChatSession::new("You are a tutor.".to_string(), None);
+        session.add_user_message("What time is it?".to_string());
+        session.add_tool_message("call_1".to_string(), "12:00".to_string());
+        session.add_user_message("Thanks".to_string());
+
+        // Room for the system prompt and the latest message only
+        let limit = session.estimated_prompt_tokens() - message_tokens(&session.messages[1]);
+        session.manage_history(10, Some(limit as usize));
+
+        assert_eq!(session.messages.len(), 2);
+        assert!(matches!(session.messages[1], ChatCompletionRequestMessage::User(_)));
+    }
+
+    #[test]
+    fn test_reset_clears_history_and_usage() {
+        let mut session = ChatSession::new("You are a tutor.".to_string(), None);
+        session.add_user_message("Hi".to_string());
+        session.record_usage(Some(TokenUsage::new(10, 5)), "Hello", None);
+
+        session.reset();
+
+        assert_eq!(session.messages.len(), 1);
+        assert_eq!(session.usage, UsageTotals::default());
+    }
+
+    #[test]
+    fn test_images_attach_with_fixed_token_cost() {
+        let mut session = ChatSession::new("You are a tutor.".to_string(), None);
+        let image = ImageInput::from_bytes(vec![0xFF, 0xD8, 0xFF, 0xE0].repeat(4096), None).unwrap();
+        session
+            .add_user_message_with_images("Explain this diagram".to_string(), &[image])
+            .unwrap();
+
+        let tokens = message_tokens(&session.messages[1]);
+        assert!(tokens >= vision::IMAGE_TOKENS);
+        assert!(tokens < vision::IMAGE_TOKENS + 50);
+    }
+}
@@ -0,0 +1,175 @@
+//! Sessions saved to disk.
+//!
+//! With a `[session_store]` table, a session's history, running summary, MCP
+//! resources and token totals can be written to
+//! `<dir>/<participant>/<name>.json` and read back later, so a discussion
+//! survives a dataflow restart (e.g. to change a voice, or to continue the next
+//! day). The system prompt is not saved: a loaded session gets the one from
+//! the current config. Snapshots are written by the `save` and `fork` control
+//! commands and, with `autosave`, after every turn; `load` and `autoload` read
+//! them back.
+
+use std::path::{Path, PathBuf};
+
+use eyre::{Context, Result, bail};
+use outfox_openai::spec::ChatCompletionRequestMessage;
+use serde::{Deserialize, Serialize};
+
+use crate::usage::UsageTotals;
+
+/// Snapshot format version, bumped on incompatible changes
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// `[session_store]` settings
+#[derive(Clone, Debug, Deserialize)]
+pub struct SessionStoreConfig {
+    /// Directory holding one subdirectory of snapshots per participant
+    pub dir: String,
+    /// Save the session after every turn and reset
+    #[serde(default)]
+    pub autosave: bool,
+    /// Restore a saved session the first time its ID is used
+    #[serde(default)]
+    pub autoload: bool,
+}
+
+/// What is saved of a session
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub version: u32,
+    pub session_id: String,
+    pub participant: String,
+    /// RFC 3339 time of the save
+    pub saved_at: String,
+    pub summary: Option<String>,
+    /// MCP resources added with `read_resource`
+    #[serde(default)]
+    pub resources: Vec<String>,
+    /// The conversation, without the system prompt and summary messages
+    pub messages: Vec<ChatCompletionRequestMessage>,
+    #[serde(default)]
+    pub usage: UsageTotals,
+}
+
+/// Snapshot files of one participant
+#[derive(Clone, Debug)]
+pub struct SessionStore {
+    dir: PathBuf,
+    pub autosave: bool,
+    pub autoload: bool,
+}
+
+impl SessionStore {
+    pub fn new(config: &SessionStoreConfig, participant: &str) -> Self {
+        Self {
+            dir: Path::new(&config.dir).join(file_name(participant)),
+            autosave: config.autosave,
+            autoload: config.autoload,
+        }
+    }
+
+    /// File of snapshot `name`
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", file_name(name)))
+    }
+
+    /// Write `snapshot` as `name`, replacing an older one
+    pub fn save(&self, name: &str, snapshot: &SessionSnapshot) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let path = self.path(name);
+        // Write then rename, so a crash never leaves half a snapshot
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(snapshot)?)
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, &path)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Read snapshot `name`; `None` if it was never saved
+    pub fn load(&self, name: &str) -> Result<Option<SessionSnapshot>> {
+        let path = self.path(name);
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        let snapshot: SessionSnapshot = serde_json::from_slice(&data)
+            .with_context(|| format!("Invalid session snapshot {}", path.display()))?;
+        if snapshot.version > SNAPSHOT_VERSION {
+            bail!(
+                "Session snapshot {} has version {}, this node reads up to {}",
+                path.display(),
+                snapshot.version,
+                SNAPSHOT_VERSION
+            );
+        }
+        Ok(Some(snapshot))
+    }
+}
+
+/// `name` as a single path component
+fn file_name(name: &str) -> String {
+    let name: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+        .collect();
+    match name.trim_start_matches('.') {
+        "" => "_".to_string(),
+        name => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::ChatSession;
+    use crate::usage::TokenUsage;
+
+    #[test]
+    fn test_file_names_stay_in_the_store() {
+        let config = SessionStoreConfig {
+            dir: "sessions".to_string(),
+            autosave: false,
+            autoload: false,
+        };
+        let store = SessionStore::new(&config, "student1");
+        assert_eq!(store.path("default"), Path::new("sessions/student1/default.json"));
+        assert_eq!(store.path("../../etc/passwd"), Path::new("sessions/student1/_.._etc_passwd.json"));
+        assert_eq!(store.path(""), Path::new("sessions/student1/_.json"));
+    }
+
+    #[test]
+    fn test_save_and_restore_session() {
+        let dir = std::env::temp_dir().join(format!("maas-sessions-{}", uuid::Uuid::new_v4()));
+        let config = SessionStoreConfig {
+            dir: dir.to_string_lossy().into_owned(),
+            autosave: true,
+            autoload: true,
+        };
+        let store = SessionStore::new(&config, "tutor");
+        assert_eq!(store.load("s1").unwrap(), None);
+
+        let mut session = ChatSession::new("You are a tutor.".to_string(), None);
+        session.add_user_message("What is photosynthesis?".to_string());
+        session.add_assistant_message("Plants turning light into sugar.".to_string());
+        session.apply_summary("We covered the water cycle.".to_string(), 1);
+        session.record_usage(Some(TokenUsage::new(120, 30)), "", None);
+        store.save("s1", &session.snapshot("s1", "tutor")).unwrap();
+
+        // A new prompt (e.g. after a config change) with the old conversation
+        let mut restored = ChatSession::new("You are a patient tutor.".to_string(), None);
+        restored.restore(store.load("s1").unwrap().unwrap());
+        assert_eq!(restored.summary.as_deref(), Some("We covered the water cycle."));
+        assert_eq!(restored.messages.len(), session.messages.len());
+        assert_eq!(restored.usage.total_tokens, 150);
+        let system = serde_json::to_value(&restored.messages[0]).unwrap();
+        assert_eq!(system["content"], "You are a patient tutor.");
+        let last = serde_json::to_value(restored.messages.last().unwrap()).unwrap();
+        assert_eq!(last["content"], "Plants turning light into sugar.");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -6,6 +6,15 @@ use serde::Deserialize;
 use std::time::Duration;
 use tokio_util::sync::CancellationToken;
 
+use crate::anthropic::AnthropicStreamTranslator;
+use crate::client::ProviderError;
+use crate::gemini::GeminiStreamState;
+use crate::local::OllamaStreamState;
+use crate::usage::TokenUsage;
+
+/// Streamed text, tool calls and the usage reported by the provider
+pub type StreamResult = Result<(String, Option<Vec<ChatCompletionMessageToolCall>>, Option<TokenUsage>)>;
+
 /// Reasons why a request was cancelled
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub enum CancellationReason {
@@ -23,6 +32,9 @@ pub struct StreamChunk {
     pub created: i64,
     pub model: String,
     pub choices: Vec<StreamChoice>,
+    /// Sent on the last chunk when `stream_options.include_usage` is set
+    #[serde(default)]
+    pub usage: Option<TokenUsage>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -148,7 +160,7 @@ pub async fn stream_completion<F>(
     api_key: String,
     request_body: serde_json::Value,
     mut on_chunk: F,
-) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>)>
+) -> StreamResult
 where
     F: FnMut(String) -> Result<()>,
 {
@@ -163,6 +175,7 @@ where
     let mut event_source = EventSource::new(request)?;
     let mut accumulated_content = String::new();
     let mut tool_accumulator = ToolCallAccumulator::default();
+    let mut usage = None;
 
     // Track last few raw SSE events for debugging
     let mut last_raw_events: std::collections::VecDeque<String> = std::collections::VecDeque::with_capacity(5);
@@ -189,6 +202,9 @@ where
                 // Parse the JSON chunk
                 match serde_json::from_str::<StreamChunk>(&data) {
                     Ok(chunk) => {
+                        if chunk.usage.is_some() {
+                            usage = chunk.usage;
+                        }
                         if let Some(choice) = chunk.choices.first() {
                             // Handle content
                             if let Some(content) = &choice.delta.content {
@@ -214,7 +230,7 @@ where
             Err(e) => {
                 eprintln!("[SSE] Error after {} chunks: {}", chunk_count, e);
                 eprintln!("[SSE] Error details: {:?}", e);
-                return Err(eyre!("SSE error: {}", e));
+                return Err(sse_error(e).await);
             }
         }
     }
@@ -226,7 +242,7 @@ where
         None
     };
 
-    Ok((accumulated_content, tool_calls))
+    Ok((accumulated_content, tool_calls, usage))
 }
 
 /// Stream completion with cancellation support
@@ -238,7 +254,7 @@ pub async fn stream_completion_with_cancellation<F>(
     cancellation_token: CancellationToken,
     timeout_duration: Duration,
     mut on_chunk: F,
-) -> Result<(String, Option<Vec<ChatCompletionMessageToolCall>>)>
+) -> StreamResult
 where
     F: FnMut(String) -> Result<()>,
 {
@@ -253,6 +269,7 @@ where
     let mut event_source = EventSource::new(request)?;
     let mut accumulated_content = String::new();
     let mut tool_accumulator = ToolCallAccumulator::default();
+    let mut usage = None;
 
     // Track last few raw SSE events for debugging
     let mut last_raw_events: std::collections::VecDeque<String> = std::collections::VecDeque::with_capacity(5);
@@ -283,6 +300,9 @@ where
                         // Parse the JSON chunk
                         match serde_json::from_str::<StreamChunk>(&data) {
                             Ok(chunk) => {
+                                if chunk.usage.is_some() {
+                                    usage = chunk.usage;
+                                }
                                 if let Some(choice) = chunk.choices.first() {
                                     // Handle content
                                     if let Some(content) = &choice.delta.content {
@@ -307,10 +327,10 @@ where
                     }
                     Some(Err(e)) => {
                         if cancellation_token.is_cancelled() {
-                            return Err(eyre!("Stream cancelled by user"));
+                            return Err(ProviderError::Cancelled.into());
                         }
                         eprintln!("[SSE] Error after {} chunks: {}", chunk_count, e);
-                        return Err(eyre!("SSE error: {}", e));
+                        return Err(sse_error(e).await);
                     }
                     None => {
                         break;
@@ -319,11 +339,11 @@ where
             }
             _ = cancellation_token.cancelled() => {
                 event_source.close();
-                return Err(eyre!("Stream cancelled by user"));
+                return Err(ProviderError::Cancelled.into());
             }
             _ = timeout_future => {
                 event_source.close();
-                return Err(eyre!("Stream timed out after {:?}", timeout_duration));
+                return Err(ProviderError::TimedOut(timeout_duration).into());
             }
         }
     }
@@ -335,5 +355,317 @@ where
         None
     };
 
-    Ok((accumulated_content, tool_calls))
+    Ok((accumulated_content, tool_calls, usage))
+}
+
+/// Convert tool calls in OpenAI JSON form (from translated providers) into typed tool calls
+fn typed_tool_calls(calls: &[serde_json::Value]) -> Option<Vec<ChatCompletionMessageToolCall>> {
+    if calls.is_empty() {
+        return None;
+    }
+    let str_at = |call: &serde_json::Value, pointer: &str| {
+        call.pointer(pointer)
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    Some(
+        calls
+            .iter()
+            .map(|call| ChatCompletionMessageToolCall {
+                id: str_at(call, "/id"),
+                kind: ChatCompletionToolType::Function,
+                function: FunctionCall {
+                    name: str_at(call, "/function/name"),
+                    arguments: str_at(call, "/function/arguments"),
+                },
+            })
+            .collect(),
+    )
+}
+
+/// Keep the HTTP status or transport failure of an SSE error for failover
+async fn sse_error(error: reqwest_eventsource::Error) -> eyre::Report {
+    match error {
+        reqwest_eventsource::Error::InvalidStatusCode(_, response) => {
+            ProviderError::from_response(response).await.into()
+        }
+        reqwest_eventsource::Error::Transport(error) => ProviderError::Transport("SSE error", error).into(),
+        error => eyre!("SSE error: {}", error),
+    }
+}
+
+/// Stream a Gemini `streamGenerateContent?alt=sse` response.
+///
+/// Unlike OpenAI, Gemini sends no `[DONE]` marker: every SSE event is a full
+/// `GenerateContentResponse` and the stream simply ends. Text parts are deltas
+/// and are passed to `on_chunk`; function calls arrive whole.
+///
+/// Cancellation and the per-event timeout are optional so the same function
+/// serves both `complete_streaming` variants.
+#[allow(clippy::too_many_arguments)]
+pub async fn stream_gemini_completion<F>(
+    client: &reqwest::Client,
+    url: String,
+    api_key: String,
+    request_body: serde_json::Value,
+    response_id: &str,
+    cancellation_token: Option<CancellationToken>,
+    timeout_duration: Option<Duration>,
+    mut on_chunk: F,
+) -> StreamResult
+where
+    F: FnMut(String) -> Result<()>,
+{
+    let body_bytes: Bytes = serde_json::to_vec(&request_body)?.into();
+    let request = client
+        .post(&url)
+        .header("X-goog-api-key", api_key)
+        .header("Content-Type", "application/json")
+        .body(body_bytes);
+
+    let mut event_source = EventSource::new(request)?;
+    let mut state = GeminiStreamState::new(response_id);
+    let mut chunk_count: usize = 0;
+    let token = cancellation_token.unwrap_or_default();
+
+    loop {
+        let timeout_future = async {
+            match timeout_duration {
+                Some(duration) => tokio::time::sleep(duration).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            event = event_source.next() => {
+                match event {
+                    Some(Ok(Event::Open)) => {}
+                    Some(Ok(Event::Message(msg))) => {
+                        chunk_count += 1;
+                        let chunk = match serde_json::from_str::<serde_json::Value>(&msg.data) {
+                            Ok(chunk) => chunk,
+                            // Silently skip unparseable chunks
+                            Err(_) => continue,
+                        };
+                        let delta = match state.push(&chunk) {
+                            Ok(delta) => delta,
+                            Err(e) => {
+                                event_source.close();
+                                return Err(e);
+                            }
+                        };
+                        if !delta.is_empty() {
+                            on_chunk(delta)?;
+                        }
+                    }
+                    Some(Err(reqwest_eventsource::Error::StreamEnded)) | None => {
+                        // Normal end of a Gemini stream - stop EventSource from reconnecting
+                        event_source.close();
+                        break;
+                    }
+                    Some(Err(e)) => {
+                        event_source.close();
+                        if token.is_cancelled() {
+                            return Err(ProviderError::Cancelled.into());
+                        }
+                        eprintln!("[SSE] Error after {} chunks: {}", chunk_count, e);
+                        return Err(sse_error(e).await);
+                    }
+                }
+            }
+            _ = token.cancelled() => {
+                event_source.close();
+                return Err(ProviderError::Cancelled.into());
+            }
+            _ = timeout_future => {
+                event_source.close();
+                return Err(ProviderError::TimedOut(timeout_duration.unwrap_or_default()).into());
+            }
+        }
+    }
+
+    let tool_calls = typed_tool_calls(state.tool_calls());
+    Ok((state.text().to_string(), tool_calls, state.usage()))
+}
+
+/// Stream an Anthropic Messages API response.
+///
+/// Anthropic events are translated into OpenAI-style [`StreamChunk`]s by
+/// [`AnthropicStreamTranslator`], so text and tool call deltas go through the
+/// same handling (and [`ToolCallAccumulator`]) as OpenAI streams. The stream
+/// ends at `message_stop`; if the connection closes first, the partial reply
+/// is an error so failover can retry it.
+pub async fn stream_anthropic_completion<F>(
+    request: reqwest::RequestBuilder,
+    cancellation_token: Option<CancellationToken>,
+    timeout_duration: Option<Duration>,
+    mut on_chunk: F,
+) -> StreamResult
+where
+    F: FnMut(String) -> Result<()>,
+{
+    let mut event_source = EventSource::new(request)?;
+    let mut translator = AnthropicStreamTranslator::new();
+    let mut accumulated_content = String::new();
+    let mut tool_accumulator = ToolCallAccumulator::default();
+    let mut chunk_count: usize = 0;
+    let token = cancellation_token.unwrap_or_default();
+
+    while !translator.is_done() {
+        let timeout_future = async {
+            match timeout_duration {
+                Some(duration) => tokio::time::sleep(duration).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            event = event_source.next() => {
+                match event {
+                    Some(Ok(Event::Open)) => {}
+                    Some(Ok(Event::Message(msg))) => {
+                        chunk_count += 1;
+                        let chunk = match translator.translate(&msg.event, &msg.data) {
+                            Ok(Some(chunk)) => chunk,
+                            Ok(None) => continue,
+                            Err(e) => {
+                                event_source.close();
+                                return Err(e);
+                            }
+                        };
+                        if let Some(choice) = chunk.choices.first() {
+                            if let Some(content) = &choice.delta.content {
+                                if !content.is_empty() {
+                                    accumulated_content.push_str(content);
+                                    on_chunk(content.clone())?;
+                                }
+                            }
+                            if let Some(tool_calls) = &choice.delta.tool_calls {
+                                for delta_call in tool_calls {
+                                    tool_accumulator.add_delta(delta_call);
+                                }
+                            }
+                        }
+                    }
+                    Some(Err(reqwest_eventsource::Error::StreamEnded)) | None => {
+                        break;
+                    }
+                    Some(Err(e)) => {
+                        event_source.close();
+                        if token.is_cancelled() {
+                            return Err(ProviderError::Cancelled.into());
+                        }
+                        eprintln!("[SSE] Error after {} chunks: {}", chunk_count, e);
+                        return Err(sse_error(e).await);
+                    }
+                }
+            }
+            _ = token.cancelled() => {
+                event_source.close();
+                return Err(ProviderError::Cancelled.into());
+            }
+            _ = timeout_future => {
+                event_source.close();
+                return Err(ProviderError::TimedOut(timeout_duration.unwrap_or_default()).into());
+            }
+        }
+    }
+    event_source.close();
+
+    if !translator.is_done() {
+        eprintln!("[SSE] Stream closed after {} chunks without message_stop", chunk_count);
+        return Err(ProviderError::Truncated("message_stop").into());
+    }
+
+    let tool_calls = if tool_accumulator.has_tool_calls() {
+        Some(tool_accumulator.build_tool_calls())
+    } else {
+        None
+    };
+
+    Ok((accumulated_content, tool_calls, translator.usage()))
+}
+
+/// Stream an Ollama `/api/chat` response.
+///
+/// The native Ollama API streams newline-delimited JSON objects rather than
+/// SSE; the last object has `done: true`. Cancellation also covers the wait
+/// for response headers, which can be long while a local model loads.
+pub async fn stream_ollama_completion<F>(
+    request: reqwest::RequestBuilder,
+    response_id: &str,
+    cancellation_token: Option<CancellationToken>,
+    timeout_duration: Option<Duration>,
+    mut on_chunk: F,
+) -> StreamResult
+where
+    F: FnMut(String) -> Result<()>,
+{
+    let token = cancellation_token.unwrap_or_default();
+    let response = tokio::select! {
+        response = request.send() => response?,
+        _ = token.cancelled() => return Err(ProviderError::Cancelled.into()),
+    };
+
+    if !response.status().is_success() {
+        return Err(ProviderError::from_response(response).await.into());
+    }
+
+    let mut stream = response.bytes_stream();
+    let mut state = OllamaStreamState::new(response_id);
+    let mut buffer: Vec<u8> = Vec::new();
+
+    while !state.is_done() {
+        let timeout_future = async {
+            match timeout_duration {
+                Some(duration) => tokio::time::sleep(duration).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            bytes = stream.next() => {
+                let bytes = match bytes {
+                    Some(Ok(bytes)) => bytes,
+                    Some(Err(e)) => {
+                        if token.is_cancelled() {
+                            return Err(ProviderError::Cancelled.into());
+                        }
+                        return Err(ProviderError::Transport("Stream error", e).into());
+                    }
+                    None => break,
+                };
+                buffer.extend_from_slice(&bytes);
+
+                // Process every complete line; keep a partial line for the next read
+                while let Some(pos) = buffer.iter().position(|&b| b == b'\n') {
+                    let line: Vec<u8> = buffer.drain(..=pos).collect();
+                    let line = String::from_utf8_lossy(&line);
+                    let line = line.trim();
+                    if line.is_empty() {
+                        continue;
+                    }
+                    let chunk = match serde_json::from_str::<serde_json::Value>(line) {
+                        Ok(chunk) => chunk,
+                        // Silently skip unparseable lines
+                        Err(_) => continue,
+                    };
+                    let delta = state.push(&chunk)?;
+                    if !delta.is_empty() {
+                        on_chunk(delta)?;
+                    }
+                }
+            }
+            _ = token.cancelled() => {
+                return Err(ProviderError::Cancelled.into());
+            }
+            _ = timeout_future => {
+                return Err(ProviderError::TimedOut(timeout_duration.unwrap_or_default()).into());
+            }
+        }
+    }
+
+    let tool_calls = typed_tool_calls(state.tool_calls());
+    Ok((state.text().to_string(), tool_calls, state.usage()))
 }
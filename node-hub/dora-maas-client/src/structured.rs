@@ -0,0 +1,403 @@
+//! Structured participant replies.
+//!
+//! With `[structured_output]` enabled, the model answers with one JSON object
+//! instead of plain text: the words to speak in `text_field`, plus fields the
+//! conference controller can act on (by default `next_speaker`, `confidence`
+//! and `ends_round`). The spoken text is pulled out of the stream as it
+//! arrives ([`SpokenTextExtractor`]) so TTS starts as early as with plain
+//! text; the other fields go out with the `ended` marker's metadata.
+//!
+//! How the format is requested depends on the provider ([`StructuredMode`]):
+//! a `json_schema` response format where the API enforces schemas, JSON mode
+//! plus the schema in the instructions where it only guarantees valid JSON,
+//! and instructions alone otherwise. Replies that are not JSON after all are
+//! spoken as they are, without fields.
+
+use eyre::Result;
+use outfox_openai::spec::CreateChatCompletionRequest;
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+
+use crate::overrides::append_instructions;
+
+/// Schema name sent with `json_schema` response formats
+const SCHEMA_NAME: &str = "participant_turn";
+
+/// How structured replies are requested from a provider
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StructuredMode {
+    /// Pick by provider kind (see `Config::structured_mode`)
+    #[default]
+    Auto,
+    /// `response_format` with the JSON schema
+    Schema,
+    /// `response_format: json_object`, schema in the instructions
+    JsonObject,
+    /// Schema in the instructions only
+    Prompt,
+}
+
+/// `[structured_output]` settings
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct StructuredOutputConfig {
+    pub enabled: bool,
+    pub mode: StructuredMode,
+    /// Field holding the text to speak
+    pub text_field: String,
+    /// JSON Schema of the reply object; defaults to [`default_schema`]
+    pub schema: Option<Value>,
+    /// Names the model may give as `next_speaker` in the default schema
+    pub participants: Vec<String>,
+}
+
+impl Default for StructuredOutputConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: StructuredMode::Auto,
+            text_field: "text".to_string(),
+            schema: None,
+            participants: Vec::new(),
+        }
+    }
+}
+
+impl StructuredOutputConfig {
+    pub fn schema(&self) -> Value {
+        self.schema
+            .clone()
+            .unwrap_or_else(|| default_schema(&self.text_field, &self.participants))
+    }
+
+    /// Ask for a structured reply in `request`, the way `mode` says.
+    /// `mode` must be resolved (not `Auto`).
+    pub fn apply(&self, mode: StructuredMode, request: &mut CreateChatCompletionRequest) -> Result<()> {
+        let schema = self.schema();
+        let mut value = serde_json::to_value(&*request)?;
+        match mode {
+            StructuredMode::Auto | StructuredMode::Schema => {
+                value["response_format"] = json!({
+                    "type": "json_schema",
+                    "json_schema": { "name": SCHEMA_NAME, "schema": schema },
+                });
+            }
+            StructuredMode::JsonObject => {
+                value["response_format"] = json!({ "type": "json_object" });
+                append_instructions(&mut value, &instructions(&schema, &self.text_field));
+            }
+            StructuredMode::Prompt => {
+                append_instructions(&mut value, &instructions(&schema, &self.text_field));
+            }
+        }
+        *request = serde_json::from_value(value)?;
+        Ok(())
+    }
+}
+
+/// Spoken text plus the turn-taking fields the controller uses
+pub fn default_schema(text_field: &str, participants: &[String]) -> Value {
+    let mut next_speaker = json!({
+        "type": "string",
+        "description": "Who should speak next, or an empty string to leave it to the moderator",
+    });
+    if !participants.is_empty() {
+        let mut names: Vec<&str> = participants.iter().map(String::as_str).collect();
+        names.push("");
+        next_speaker["enum"] = json!(names);
+    }
+    json!({
+        "type": "object",
+        "properties": {
+            text_field: { "type": "string", "description": "What you say aloud" },
+            "next_speaker": next_speaker,
+            "confidence": {
+                "type": "number",
+                "description": "How sure you are about next_speaker, from 0 to 1",
+            },
+            "ends_round": {
+                "type": "boolean",
+                "description": "True when this reply closes the current round of discussion",
+            },
+        },
+        "required": [text_field, "next_speaker", "confidence", "ends_round"],
+        "additionalProperties": false,
+    })
+}
+
+/// Instructions for providers that do not enforce the schema themselves
+pub fn instructions(schema: &Value, text_field: &str) -> String {
+    format!(
+        "Reply with a single JSON object and nothing else, matching this JSON Schema:\n{}\n\
+         Put everything you say aloud in \"{}\" as plain spoken text.",
+        schema, text_field
+    )
+}
+
+/// A parsed reply: the words to speak and the other fields
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StructuredReply {
+    pub text: String,
+    pub fields: Map<String, Value>,
+}
+
+/// Split a whole reply into spoken text and fields. Markdown fences and text
+/// around the object are tolerated; a reply without a JSON object is all text.
+pub fn parse_reply(raw: &str, text_field: &str) -> StructuredReply {
+    let object = match (raw.find('{'), raw.rfind('}')) {
+        (Some(start), Some(end)) if start < end => {
+            serde_json::from_str::<Map<String, Value>>(&raw[start..=end]).ok()
+        }
+        _ => None,
+    };
+    let Some(mut fields) = object else {
+        return StructuredReply {
+            text: raw.trim().to_string(),
+            fields: Map::new(),
+        };
+    };
+    let text = match fields.remove(text_field) {
+        Some(Value::String(text)) => text,
+        Some(other) => other.to_string(),
+        None => String::new(),
+    };
+    StructuredReply { text, fields }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ExtractState {
+    /// Outside the spoken text field
+    Scanning,
+    /// After `"<text_field>":`, before the value starts
+    AwaitingValue,
+    /// Inside the spoken text string
+    InText,
+    /// The spoken text string has ended
+    Done,
+}
+
+/// Pulls the spoken text field out of a streamed JSON reply, chunk by chunk
+pub struct SpokenTextExtractor {
+    text_field: String,
+    state: ExtractState,
+    raw: String,
+    emitted: bool,
+    /// Nesting depth of objects and arrays
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    /// Current string at depth 1 (a candidate key)
+    key: String,
+    /// Last complete string at depth 1
+    last_string: Option<String>,
+    /// Unfinished escape sequence inside the spoken text
+    escape: String,
+    /// High half of a UTF-16 surrogate pair
+    high_surrogate: Option<u16>,
+}
+
+impl SpokenTextExtractor {
+    pub fn new(text_field: &str) -> Self {
+        Self {
+            text_field: text_field.to_string(),
+            state: ExtractState::Scanning,
+            raw: String::new(),
+            emitted: false,
+            depth: 0,
+            in_string: false,
+            escaped: false,
+            key: String::new(),
+            last_string: None,
+            escape: String::new(),
+            high_surrogate: None,
+        }
+    }
+
+    /// Feed a chunk; returns the spoken text it contains (possibly empty)
+    pub fn push(&mut self, chunk: &str) -> String {
+        self.raw.push_str(chunk);
+        let mut spoken = String::new();
+        for c in chunk.chars() {
+            match self.state {
+                ExtractState::Done => break,
+                ExtractState::InText => self.text_char(c, &mut spoken),
+                ExtractState::AwaitingValue => {
+                    if c == '"' {
+                        self.state = ExtractState::InText;
+                    } else if !c.is_whitespace() {
+                        // Not a string; keep scanning from here
+                        self.state = ExtractState::Scanning;
+                        self.scan_char(c);
+                    }
+                }
+                ExtractState::Scanning => self.scan_char(c),
+            }
+        }
+        if !spoken.is_empty() {
+            self.emitted = true;
+        }
+        spoken
+    }
+
+    /// Everything fed so far
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    /// Whether any spoken text was found
+    pub fn emitted(&self) -> bool {
+        self.emitted
+    }
+
+    fn scan_char(&mut self, c: char) {
+        if self.in_string {
+            if self.escaped {
+                self.escaped = false;
+            } else if c == '\\' {
+                self.escaped = true;
+            } else if c == '"' {
+                self.in_string = false;
+                if self.depth == 1 {
+                    self.last_string = Some(std::mem::take(&mut self.key));
+                }
+                return;
+            }
+            if self.depth == 1 {
+                self.key.push(c);
+            }
+            return;
+        }
+        match c {
+            '"' => {
+                self.in_string = true;
+                self.key.clear();
+            }
+            '{' | '[' => self.depth += 1,
+            '}' | ']' => self.depth = self.depth.saturating_sub(1),
+            ':' if self.depth == 1
+                && self.last_string.take().as_deref() == Some(self.text_field.as_str()) =>
+            {
+                self.state = ExtractState::AwaitingValue;
+            }
+            ',' => self.last_string = None,
+            _ => {}
+        }
+    }
+
+    fn text_char(&mut self, c: char, spoken: &mut String) {
+        if self.escape.is_empty() {
+            match c {
+                '\\' => self.escape.push(c),
+                '"' => self.state = ExtractState::Done,
+                _ => spoken.push(c),
+            }
+            return;
+        }
+
+        self.escape.push(c);
+        let decoded = match self.escape.as_str() {
+            "\\n" => Some('\n'),
+            "\\t" => Some('\t'),
+            "\\r" => Some('\r'),
+            "\\b" | "\\f" => Some(' '),
+            "\\\"" => Some('"'),
+            "\\\\" => Some('\\'),
+            "\\/" => Some('/'),
+            escape if escape.starts_with("\\u") => {
+                if escape.len() < 6 {
+                    return;
+                }
+                let code = u16::from_str_radix(&escape[2..], 16).unwrap_or(0xFFFD);
+                match (self.high_surrogate.take(), code) {
+                    (None, 0xD800..=0xDBFF) => {
+                        self.high_surrogate = Some(code);
+                        self.escape.clear();
+                        return;
+                    }
+                    (Some(high), 0xDC00..=0xDFFF) => {
+                        char::decode_utf16([high, code]).next().and_then(|c| c.ok())
+                    }
+                    (_, code) => char::from_u32(code as u32),
+                }
+            }
+            _ => None,
+        };
+        self.escape.clear();
+        spoken.push(decoded.unwrap_or(char::REPLACEMENT_CHARACTER));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extract(chunks: &[&str]) -> String {
+        let mut extractor = SpokenTextExtractor::new("text");
+        chunks.iter().map(|chunk| extractor.push(chunk)).collect()
+    }
+
+    #[test]
+    fn test_extracts_spoken_text_across_chunks() {
+        let chunks = [
+            "{\"next_speaker\": \"tutor\", \"te",
+            "xt\": \"Photosynthesis turns light ",
+            "into sugar. \\\"Chloro",
+            "phyll\\\" is green\\u00",
+            "e9.\", \"confidence\": 0.8, \"ends_round\": false}",
+        ];
+        assert_eq!(extract(&chunks), "Photosynthesis turns light into sugar. \"Chlorophyll\" is greené.");
+    }
+
+    #[test]
+    fn test_ignores_nested_and_value_matches() {
+        // "text" as a nested key or as a value is not the spoken field
+        let chunks = [
+            "{\"meta\": {\"text\": \"hidden\"}, \"note\": \"text\", ",
+            "\"text\": \"Spoken\\nline \\ud83d\\ude00\"}",
+        ];
+        assert_eq!(extract(&chunks), "Spoken\nline 😀");
+    }
+
+    #[test]
+    fn test_parse_reply_fields_and_fallbacks() {
+        let raw = "```json\n{\"text\": \"I agree.\", \"next_speaker\": \"student2\", \"confidence\": 0.9, \"ends_round\": true}\n```";
+        let reply = parse_reply(raw, "text");
+        assert_eq!(reply.text, "I agree.");
+        assert_eq!(reply.fields["next_speaker"], "student2");
+        assert_eq!(reply.fields["ends_round"], true);
+        assert!(!reply.fields.contains_key("text"));
+
+        let plain = parse_reply("  Just talking, no JSON.  ", "text");
+        assert_eq!(plain.text, "Just talking, no JSON.");
+        assert!(plain.fields.is_empty());
+    }
+
+    #[test]
+    fn test_apply_modes() {
+        let config = StructuredOutputConfig {
+            enabled: true,
+            participants: vec!["tutor".to_string(), "student1".to_string()],
+            ..Default::default()
+        };
+        let request: CreateChatCompletionRequest = serde_json::from_value(json!({
+            "model": "gpt-4o",
+            "messages": [{ "role": "system", "content": "You are a student." }],
+        }))
+        .unwrap();
+
+        let mut schema_request = request.clone();
+        config.apply(StructuredMode::Schema, &mut schema_request).unwrap();
+        let value = serde_json::to_value(&schema_request).unwrap();
+        assert_eq!(value["response_format"]["type"], "json_schema");
+        let properties = &value["response_format"]["json_schema"]["schema"]["properties"];
+        assert_eq!(properties["next_speaker"]["enum"], json!(["tutor", "student1", ""]));
+
+        let mut prompt_request = request;
+        config.apply(StructuredMode::Prompt, &mut prompt_request).unwrap();
+        let value = serde_json::to_value(&prompt_request).unwrap();
+        assert!(matches!(value.get("response_format"), None | Some(Value::Null)));
+        let system = value["messages"][0]["content"].as_str().unwrap();
+        assert!(system.starts_with("You are a student.\n\nReply with a single JSON object"));
+    }
+}
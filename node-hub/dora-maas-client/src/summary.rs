@@ -0,0 +1,172 @@
+//! Summarization of old conversation turns.
+//!
+//! Long sessions keep continuity by replacing old exchanges with a running
+//! summary instead of dropping them. The summary is a system message placed
+//! right after the system prompt; this module builds the summarizer request
+//! and decides which messages can be condensed.
+
+use eyre::Result;
+use outfox_openai::spec::{
+    ChatCompletionRequestMessage, ChatCompletionRequestSystemMessage, CreateChatCompletionRequest,
+    PartibleTextContent,
+};
+use serde_json::{Value, json};
+
+use crate::config::SummarizationConfig;
+
+/// Prefix of the summary message, also used to recognize it
+pub const SUMMARY_PREFIX: &str = "Summary of the earlier conversation:";
+
+const DEFAULT_PROMPT: &str = "You condense conversations. Write a concise summary of the \
+conversation below for a participant who will continue it: keep who said what, questions \
+still open, conclusions reached and any facts or commitments needed later. Merge the previous \
+summary, if given, into the new one. Write in the language of the conversation. Output only \
+the summary.";
+
+/// Index where condensing stops: everything from `start` up to the returned
+/// index is summarized, the last `keep_recent_exchanges` exchanges are kept.
+///
+/// The kept part starts at a user message so tool results are never
+/// separated from the call they answer. Returns `None` when there is
+/// nothing to condense.
+pub fn split_point(
+    messages: &[ChatCompletionRequestMessage],
+    start: usize,
+    keep_recent_exchanges: usize,
+) -> Option<usize> {
+    let mut end = messages.len().saturating_sub(keep_recent_exchanges * 2);
+    while end < messages.len() && !matches!(messages[end], ChatCompletionRequestMessage::User(_)) {
+        end += 1;
+    }
+    (end > start && end < messages.len()).then_some(end)
+}
+
+/// Plain-text transcript of messages (`role: text` per line)
+pub fn transcript(messages: &[ChatCompletionRequestMessage]) -> String {
+    messages
+        .iter()
+        .filter_map(|message| serde_json::to_value(message).ok())
+        .filter_map(|message| {
+            let role = message.get("role").and_then(Value::as_str)?.to_string();
+            let mut text = content_text(message.get("content").unwrap_or(&Value::Null));
+            if let Some(calls) = message.get("tool_calls").and_then(Value::as_array) {
+                for call in calls {
+                    let name = call.pointer("/function/name").and_then(Value::as_str);
+                    let arguments = call.pointer("/function/arguments").and_then(Value::as_str);
+                    text.push_str(&format!(
+                        " [called {}({})]",
+                        name.unwrap_or_default(),
+                        arguments.unwrap_or_default()
+                    ));
+                }
+            }
+            let text = text.trim();
+            (!text.is_empty()).then(|| format!("{}: {}", role, text))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Text of a message `content` (a string or an array of text parts)
+fn content_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| part.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => String::new(),
+    }
+}
+
+/// Request asking `model` to fold `messages` into `previous` summary
+pub fn summary_request(
+    config: &SummarizationConfig,
+    model: &str,
+    previous: Option<&str>,
+    messages: &[ChatCompletionRequestMessage],
+) -> Result<CreateChatCompletionRequest> {
+    let mut input = String::new();
+    if let Some(previous) = previous {
+        input.push_str(&format!("Previous summary:\n{}\n\n", previous));
+    }
+    input.push_str(&format!("Conversation:\n{}", transcript(messages)));
+
+    let request = serde_json::from_value(json!({
+        "model": model,
+        "messages": [
+            { "role": "system", "content": config.prompt.as_deref().unwrap_or(DEFAULT_PROMPT) },
+            { "role": "user", "content": input },
+        ],
+        "max_tokens": config.max_summary_tokens,
+        "temperature": 0.2,
+        "stream": false,
+    }))?;
+    Ok(request)
+}
+
+/// System message holding the running summary
+pub fn summary_message(summary: &str) -> ChatCompletionRequestMessage {
+    ChatCompletionRequestMessage::System(ChatCompletionRequestSystemMessage {
+        content: PartibleTextContent::Text(format!("{}\n{}", SUMMARY_PREFIX, summary.trim())),
+        name: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(value: Value) -> Vec<ChatCompletionRequestMessage> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_split_point_keeps_recent_exchanges() {
+        let history = messages(json!([
+            { "role": "system", "content": "sys" },
+            { "role": "user", "content": "q1" },
+            { "role": "assistant", "content": "a1" },
+            { "role": "user", "content": "q2" },
+            { "role": "assistant", "content": null, "tool_calls": [
+                { "id": "c1", "type": "function", "function": { "name": "lookup", "arguments": "{}" } }
+            ] },
+            { "role": "tool", "tool_call_id": "c1", "content": "result" },
+            { "role": "assistant", "content": "a2" },
+            { "role": "user", "content": "q3" },
+        ]));
+
+        // One exchange kept: the cut moves forward to the next user message
+        assert_eq!(split_point(&history, 1, 1), Some(7));
+        // Two exchanges kept: the cut would land on the tool result
+        assert_eq!(split_point(&history, 1, 2), Some(7));
+        assert_eq!(split_point(&history, 1, 3), Some(3));
+        // Nothing old enough to condense
+        assert_eq!(split_point(&history, 1, 4), None);
+        assert_eq!(split_point(&history, 3, 3), None);
+    }
+
+    #[test]
+    fn test_transcript_and_request() {
+        let old = messages(json!([
+            { "role": "user", "content": "[孙老师] 什么是负熵？" },
+            { "role": "assistant", "content": null, "tool_calls": [
+                { "id": "c1", "type": "function", "function": { "name": "lookup", "arguments": "{\"q\":\"负熵\"}" } }
+            ] },
+            { "role": "tool", "tool_call_id": "c1", "content": "order from order" },
+        ]));
+        let text = transcript(&old);
+        assert_eq!(
+            text,
+            "user: [孙老师] 什么是负熵？\nassistant: [called lookup({\"q\":\"负熵\"})]\ntool: order from order"
+        );
+
+        let config: SummarizationConfig = serde_json::from_value(json!({})).unwrap();
+        let request = summary_request(&config, "qwen-turbo", Some("Earlier."), &old).unwrap();
+        let request = serde_json::to_value(&request).unwrap();
+        assert_eq!(request["model"], "qwen-turbo");
+        let input = request["messages"][1]["content"].as_str().unwrap();
+        assert!(input.starts_with("Previous summary:\nEarlier.\n\nConversation:\nuser:"));
+    }
+}
@@ -1,4 +1,4 @@
-//! MCP tool management for function calling support
+//! Tool management for function calling support (MCP and built-in tools)
 //!
 //! This module provides the infrastructure for integrating MCP (Model Context Protocol)
 //! tools with the LLM streaming client, enabling function calling capabilities.
@@ -91,6 +91,17 @@ impl ToolSet {
         self.clients = clients;
     }
 
+    /// Running MCP servers by name, for resources and prompts
+    pub fn servers(&self) -> Vec<(String, ServerSink)> {
+        let mut servers: Vec<_> = self
+            .clients
+            .iter()
+            .map(|(name, client)| (name.clone(), client.peer().clone()))
+            .collect();
+        servers.sort_by(|a, b| a.0.cmp(&b.0));
+        servers
+    }
+
     /// Add a tool to the set
     pub fn add_tool<T: Tool + 'static>(&mut self, tool: T) {
         self.tools.insert(tool.name(), Arc::new(tool));
@@ -106,6 +117,21 @@ impl ToolSet {
         self.tools.values().cloned().collect()
     }
 
+    /// Keep only the tools named in `allowed`, returning the names removed
+    pub fn retain(&mut self, allowed: &[String]) -> Vec<String> {
+        let mut removed: Vec<String> = self
+            .tools
+            .keys()
+            .filter(|name| !allowed.contains(name))
+            .cloned()
+            .collect();
+        removed.sort();
+        for name in &removed {
+            self.tools.remove(name);
+        }
+        removed
+    }
+
     /// Check if any tools are available
     pub fn has_tools(&self) -> bool {
         !self.tools.is_empty()
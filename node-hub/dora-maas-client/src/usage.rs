@@ -0,0 +1,210 @@
+//! Token accounting, cost and budgets.
+//!
+//! Providers report usage in the OpenAI `usage` shape (translated providers
+//! convert theirs). When a provider reports nothing, usage is estimated from
+//! the text so budgets still apply.
+
+use outfox_openai::spec::CreateChatCompletionResponse;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::{BudgetConfig, ModelPricing};
+
+/// Token counts for one request
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
+    #[serde(default)]
+    pub total_tokens: u64,
+}
+
+impl TokenUsage {
+    pub fn new(prompt_tokens: u64, completion_tokens: u64) -> Self {
+        Self {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+        }
+    }
+
+    /// Parse an OpenAI-style `usage` object
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let usage: TokenUsage = serde_json::from_value(value.clone()).ok()?;
+        if usage.total_tokens == 0 && usage.prompt_tokens + usage.completion_tokens == 0 {
+            return None;
+        }
+        if usage.total_tokens == 0 {
+            return Some(Self::new(usage.prompt_tokens, usage.completion_tokens));
+        }
+        Some(usage)
+    }
+
+    /// Usage of a non-streaming response
+    pub fn from_response(response: &CreateChatCompletionResponse) -> Option<Self> {
+        let response = serde_json::to_value(response).ok()?;
+        response.get("usage").and_then(Self::from_value)
+    }
+}
+
+/// Rough token count: about 4 ASCII characters per token, one token per
+/// CJK (or other non-ASCII) character.
+pub fn estimate_tokens(text: &str) -> u64 {
+    let (ascii, other) = text.chars().fold((0u64, 0u64), |(ascii, other), c| {
+        if c.is_ascii() {
+            (ascii + 1, other)
+        } else {
+            (ascii, other + 1)
+        }
+    });
+    ascii.div_ceil(4) + other
+}
+
+impl ModelPricing {
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (usage.prompt_tokens as f64 * self.input_per_million
+            + usage.completion_tokens as f64 * self.output_per_million)
+            / 1_000_000.0
+    }
+}
+
+/// Accumulated usage for a session or a participant
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageTotals {
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    pub cost: f64,
+}
+
+impl UsageTotals {
+    pub fn record(&mut self, usage: &TokenUsage, cost: f64) {
+        self.requests += 1;
+        self.prompt_tokens += usage.prompt_tokens;
+        self.completion_tokens += usage.completion_tokens;
+        self.total_tokens += usage.total_tokens;
+        self.cost += cost;
+    }
+}
+
+/// Usage of a single request
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct RequestUsage {
+    #[serde(flatten)]
+    pub usage: TokenUsage,
+    /// True when the provider reported no usage and it was estimated
+    pub estimated: bool,
+    pub cost: f64,
+}
+
+/// Payload of the `metrics` output, sent after every completed request
+#[derive(Debug, Serialize)]
+pub struct UsageReport<'a> {
+    pub participant: &'a str,
+    pub session_id: &'a str,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub request: RequestUsage,
+    pub session: &'a UsageTotals,
+    pub participant_total: &'a UsageTotals,
+    /// Set once a budget limit is reached
+    pub budget_exceeded: Option<String>,
+}
+
+impl BudgetConfig {
+    /// Reason the budget is used up, if it is
+    pub fn exceeded(&self, session: &UsageTotals, total: &UsageTotals) -> Option<String> {
+        let over_tokens = |used: u64, limit: Option<u64>| limit.is_some_and(|l| used >= l);
+        let over_cost = |used: f64, limit: Option<f64>| limit.is_some_and(|l| used >= l);
+
+        if over_tokens(session.total_tokens, self.max_session_tokens) {
+            return Some(format!(
+                "session token budget reached ({} / {})",
+                session.total_tokens,
+                self.max_session_tokens.unwrap_or_default()
+            ));
+        }
+        if over_cost(session.cost, self.max_session_cost) {
+            return Some(format!(
+                "session cost budget reached ({:.4} / {:.4})",
+                session.cost,
+                self.max_session_cost.unwrap_or_default()
+            ));
+        }
+        if over_tokens(total.total_tokens, self.max_total_tokens) {
+            return Some(format!(
+                "total token budget reached ({} / {})",
+                total.total_tokens,
+                self.max_total_tokens.unwrap_or_default()
+            ));
+        }
+        if over_cost(total.cost, self.max_total_cost) {
+            return Some(format!(
+                "total cost budget reached ({:.4} / {:.4})",
+                total.cost,
+                self.max_total_cost.unwrap_or_default()
+            ));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_usage_from_value() {
+        let usage = TokenUsage::from_value(&json!({
+            "prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15
+        }));
+        assert_eq!(usage, Some(TokenUsage::new(10, 5)));
+
+        // Total is derived when missing; empty usage counts as none
+        let usage = TokenUsage::from_value(&json!({ "prompt_tokens": 3, "completion_tokens": 4 }));
+        assert_eq!(usage.unwrap().total_tokens, 7);
+        assert_eq!(TokenUsage::from_value(&json!({})), None);
+        assert_eq!(TokenUsage::from_value(&Value::Null), None);
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("Hello world!"), 3);
+        assert_eq!(estimate_tokens("生命是什么"), 5);
+    }
+
+    #[test]
+    fn test_cost_and_budget() {
+        let pricing = ModelPricing {
+            input_per_million: 2.0,
+            output_per_million: 8.0,
+        };
+        let usage = TokenUsage::new(1_000, 500);
+        assert!((pricing.cost(&usage) - 0.006).abs() < 1e-9);
+
+        let mut session = UsageTotals::default();
+        session.record(&usage, pricing.cost(&usage));
+        assert_eq!(session.requests, 1);
+        assert_eq!(session.total_tokens, 1_500);
+
+        let budget = BudgetConfig {
+            max_session_tokens: Some(2_000),
+            max_total_cost: Some(0.01),
+            ..BudgetConfig::default()
+        };
+        assert_eq!(budget.exceeded(&session, &session), None);
+
+        session.record(&usage, pricing.cost(&usage));
+        let reason = budget.exceeded(&session, &UsageTotals::default()).unwrap();
+        assert!(reason.starts_with("session token budget"));
+
+        let total = UsageTotals { cost: 0.02, ..UsageTotals::default() };
+        let reason = budget.exceeded(&UsageTotals::default(), &total).unwrap();
+        assert!(reason.starts_with("total cost budget"));
+    }
+}
@@ -0,0 +1,204 @@
+//! Image inputs for vision-capable models.
+//!
+//! Images arrive on the `image` input (encoded bytes or a file path) and wait
+//! on the session until its next user message, which then carries them as
+//! OpenAI `image_url` content parts with a base64 `data:` URL. The Gemini,
+//! Anthropic and Ollama translations turn those parts into their own image
+//! blocks (see [`parse_data_url`]). Models configured with `vision = false`
+//! get a text placeholder instead ([`strip_images`]).
+
+use std::path::Path;
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+use eyre::{Context, Result, bail, eyre};
+use outfox_openai::spec::ChatCompletionRequestMessage;
+use serde_json::{Value, json};
+
+/// Largest image accepted (OpenAI's per-image limit)
+pub const MAX_IMAGE_BYTES: usize = 20 * 1024 * 1024;
+
+/// Tokens counted per image when trimming history; providers bill images by
+/// resolution, so the encoded size says little
+pub const IMAGE_TOKENS: u64 = 1000;
+
+/// Sent instead of an image to models without vision support
+pub const IMAGE_PLACEHOLDER: &str = "[image omitted: this model does not accept images]";
+
+/// An encoded image waiting to be sent with a user message
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageInput {
+    pub mime_type: String,
+    pub data: Vec<u8>,
+    /// OpenAI `detail` hint: `low`, `high` or `auto`
+    pub detail: Option<String>,
+}
+
+impl ImageInput {
+    /// PNG, JPEG, GIF or WebP bytes; the type is detected when not given
+    pub fn from_bytes(data: Vec<u8>, mime_type: Option<&str>) -> Result<Self> {
+        if data.is_empty() {
+            bail!("Image is empty");
+        }
+        if data.len() > MAX_IMAGE_BYTES {
+            bail!("Image is {} bytes, the limit is {}", data.len(), MAX_IMAGE_BYTES);
+        }
+        let mime_type = match mime_type.map(str::trim).filter(|m| !m.is_empty()) {
+            Some(mime_type) => mime_type.to_string(),
+            None => sniff_mime_type(&data)
+                .ok_or_else(|| eyre!("Unsupported image format (expected PNG, JPEG, GIF or WebP)"))?
+                .to_string(),
+        };
+        if !mime_type.starts_with("image/") {
+            bail!("Not an image type: {}", mime_type);
+        }
+        Ok(Self {
+            mime_type,
+            data,
+            detail: None,
+        })
+    }
+
+    pub fn from_path(path: &Path, mime_type: Option<&str>) -> Result<Self> {
+        let data = std::fs::read(path)
+            .with_context(|| format!("Failed to read image {}", path.display()))?;
+        Self::from_bytes(data, mime_type)
+    }
+
+    pub fn with_detail(mut self, detail: Option<String>) -> Self {
+        self.detail = detail;
+        self
+    }
+
+    pub fn data_url(&self) -> String {
+        format!("data:{};base64,{}", self.mime_type, STANDARD.encode(&self.data))
+    }
+
+    /// OpenAI `image_url` content part
+    pub fn content_part(&self) -> Value {
+        let mut image_url = json!({ "url": self.data_url() });
+        if let Some(detail) = &self.detail {
+            image_url["detail"] = json!(detail);
+        }
+        json!({ "type": "image_url", "image_url": image_url })
+    }
+}
+
+fn sniff_mime_type(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// Split a base64 `data:` URL into its MIME type and data
+pub fn parse_data_url(url: &str) -> Option<(&str, &str)> {
+    let (header, data) = url.strip_prefix("data:")?.split_once(',')?;
+    let mime_type = header.strip_suffix(";base64")?;
+    Some((mime_type, data))
+}
+
+/// URL of an OpenAI `image_url` content part; `None` for other parts
+pub fn image_url(part: &Value) -> Option<&str> {
+    if part.get("type").and_then(Value::as_str) != Some("image_url") {
+        return None;
+    }
+    let image_url = part.get("image_url")?;
+    image_url
+        .get("url")
+        .and_then(Value::as_str)
+        .or_else(|| image_url.as_str())
+}
+
+/// Remove the image parts of a message (as JSON), returning how many there were
+pub fn take_image_parts(message: &mut Value) -> usize {
+    let Some(parts) = message.get_mut("content").and_then(Value::as_array_mut) else {
+        return 0;
+    };
+    let before = parts.len();
+    parts.retain(|part| image_url(part).is_none());
+    before - parts.len()
+}
+
+/// Whether any message carries an image
+pub fn has_images(messages: &[ChatCompletionRequestMessage]) -> bool {
+    messages.iter().any(|message| {
+        serde_json::to_value(message)
+            .ok()
+            .and_then(|value| value.get("content").and_then(Value::as_array).cloned())
+            .is_some_and(|parts| parts.iter().any(|part| image_url(part).is_some()))
+    })
+}
+
+/// The messages with image parts replaced by [`IMAGE_PLACEHOLDER`], for models
+/// without vision support
+pub fn strip_images(
+    messages: &[ChatCompletionRequestMessage],
+) -> Result<Vec<ChatCompletionRequestMessage>> {
+    messages
+        .iter()
+        .map(|message| {
+            let mut value = serde_json::to_value(message)?;
+            if let Some(parts) = value.get_mut("content").and_then(Value::as_array_mut) {
+                for part in parts.iter_mut().filter(|part| image_url(part).is_some()) {
+                    *part = json!({ "type": "text", "text": IMAGE_PLACEHOLDER });
+                }
+            }
+            Ok(serde_json::from_value(value)?)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    #[test]
+    fn test_detects_type_and_builds_data_url() {
+        let image = ImageInput::from_bytes(PNG.to_vec(), None).unwrap();
+        assert_eq!(image.mime_type, "image/png");
+
+        let url = image.data_url();
+        let (mime_type, data) = parse_data_url(&url).unwrap();
+        assert_eq!(mime_type, "image/png");
+        assert_eq!(STANDARD.decode(data).unwrap(), PNG);
+
+        let part = image.with_detail(Some("low".to_string())).content_part();
+        assert_eq!(image_url(&part), Some(url.as_str()));
+        assert_eq!(part["image_url"]["detail"], "low");
+
+        assert!(ImageInput::from_bytes(b"hello".to_vec(), None).is_err());
+        assert!(ImageInput::from_bytes(b"hello".to_vec(), Some("text/plain")).is_err());
+        assert!(ImageInput::from_bytes(Vec::new(), Some("image/png")).is_err());
+    }
+
+    #[test]
+    fn test_strip_and_count_images() {
+        let image = ImageInput::from_bytes(PNG.to_vec(), None).unwrap();
+        let message: ChatCompletionRequestMessage = serde_json::from_value(json!({
+            "role": "user",
+            "content": [{ "type": "text", "text": "What is this?" }, image.content_part()],
+        }))
+        .unwrap();
+        let messages = vec![message];
+        assert!(has_images(&messages));
+
+        let stripped = strip_images(&messages).unwrap();
+        assert!(!has_images(&stripped));
+        let value = serde_json::to_value(&stripped[0]).unwrap();
+        assert_eq!(value["content"][1]["text"], IMAGE_PLACEHOLDER);
+
+        let mut value = serde_json::to_value(&messages[0]).unwrap();
+        assert_eq!(take_image_parts(&mut value), 1);
+        assert_eq!(value["content"].as_array().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,97 @@
+use dora_maas_client::segmenter::{SegmenterConfig, SegmenterLanguage};
+
+/// Node settings, read from environment variables
+#[derive(Debug, Clone)]
+pub struct NodeConfig {
+    /// Passed to each participant's `StreamSegmenter`
+    pub segmenter: SegmenterConfig,
+    /// Strip a leading `[Speaker]` tag from every chunk
+    pub remove_speaker_id: bool,
+    /// Resume sending when the audio buffer drops below this fill percentage
+    pub low_water_mark: f64,
+    /// Pause sending when the audio buffer rises above this fill percentage
+    pub high_water_mark: f64,
+}
+
+impl Default for NodeConfig {
+    fn default() -> Self {
+        Self {
+            segmenter: SegmenterConfig::default(),
+            remove_speaker_id: true,
+            low_water_mark: 30.0,
+            high_water_mark: 60.0,
+        }
+    }
+}
+
+impl NodeConfig {
+    pub fn from_env() -> Self {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = Self::default();
+        let number = |key: &str| lookup(key).and_then(|v| v.trim().parse::<f64>().ok());
+        let count = |key: &str| lookup(key).and_then(|v| v.trim().parse::<usize>().ok());
+        let flag = |key: &str, default: bool| {
+            lookup(key)
+                .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+                .unwrap_or(default)
+        };
+
+        let mut segmenter = defaults.segmenter;
+        segmenter.language = match lookup("SEGMENTER_LANGUAGE")
+            .map(|v| v.trim().to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("zh") => SegmenterLanguage::Zh,
+            Some("ja") => SegmenterLanguage::Ja,
+            Some("ko") => SegmenterLanguage::Ko,
+            Some("en") => SegmenterLanguage::En,
+            _ => SegmenterLanguage::Auto,
+        };
+        // MAX_SEGMENT_LENGTH is the name used by the Python node
+        segmenter.max_chars = count("MAX_SEGMENT_CHARS").or_else(|| count("MAX_SEGMENT_LENGTH"));
+        segmenter.max_words = count("MAX_SEGMENT_WORDS");
+        segmenter.sentence_endings = lookup("SENTENCE_ENDINGS");
+        segmenter.clause_endings = lookup("CLAUSE_ENDINGS");
+        segmenter.soft_breaks = lookup("SOFT_BREAKS");
+        segmenter.strip_markdown = flag("STRIP_MARKDOWN", segmenter.strip_markdown);
+        segmenter.protect_numbers = flag("PROTECT_NUMBERS", segmenter.protect_numbers);
+        segmenter.prosody_hints = flag("PROSODY_HINTS", segmenter.prosody_hints);
+        segmenter.ssml = flag("SSML", segmenter.ssml);
+
+        Self {
+            segmenter,
+            remove_speaker_id: flag("REMOVE_SPEAKER_ID", defaults.remove_speaker_id),
+            low_water_mark: number("AUDIO_BUFFER_LOW_WATER_MARK").unwrap_or(defaults.low_water_mark),
+            high_water_mark: number("AUDIO_BUFFER_HIGH_WATER_MARK").unwrap_or(defaults.high_water_mark),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_from_lookup() {
+        let config = NodeConfig::from_lookup(|key| match key {
+            "MAX_SEGMENT_LENGTH" => Some("15".to_string()),
+            "SEGMENTER_LANGUAGE" => Some("JA".to_string()),
+            "REMOVE_SPEAKER_ID" => Some("false".to_string()),
+            "AUDIO_BUFFER_HIGH_WATER_MARK" => Some("75".to_string()),
+            "PROSODY_HINTS" => Some("yes".to_string()),
+            _ => None,
+        });
+
+        assert_eq!(config.segmenter.max_chars, Some(15));
+        assert_eq!(config.segmenter.max_words, None);
+        assert_eq!(config.segmenter.language, SegmenterLanguage::Ja);
+        assert!(config.segmenter.prosody_hints);
+        assert!(config.segmenter.strip_markdown);
+        assert!(!config.remove_speaker_id);
+        assert_eq!(config.low_water_mark, 30.0);
+        assert_eq!(config.high_water_mark, 75.0);
+    }
+}
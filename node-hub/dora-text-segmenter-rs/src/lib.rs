@@ -0,0 +1,5 @@
+// Library exports for dora-text-segmenter-rs
+// The session queue is kept free of Dora types so it can be unit tested
+
+pub mod config;
+pub mod queue;
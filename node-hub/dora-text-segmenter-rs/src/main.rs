@@ -0,0 +1,271 @@
+//! Multi-participant text segmenter node (Rust port of `dora-text-segmenter`
+//! in conference mode).
+//!
+//! Every input that is not a control or audio player input is a participant;
+//! its segments go out on `text_segment_<participant>`.
+
+use std::collections::BTreeMap;
+
+use dora_maas_client::segmenter::to_ssml;
+use dora_node_api::arrow::array::{Array, ArrayRef, AsArray, StringArray};
+use dora_node_api::arrow::compute::cast;
+use dora_node_api::arrow::datatypes::{DataType, Float64Type};
+use dora_node_api::dora_core::config::DataId;
+use dora_node_api::{DoraNode, Event, Parameter};
+use dora_text_segmenter::config::NodeConfig;
+use dora_text_segmenter::queue::{Segment, SessionQueue, TextError};
+use eyre::Result;
+
+/// Inputs that carry reset/cancel commands
+const CONTROL_INPUTS: [&str; 3] = ["control", "reset", "immediate_cancel"];
+/// Inputs that carry the audio player's buffer fill percentage
+const BUFFER_INPUTS: [&str; 2] = ["audio_buffer_control", "buffer_status"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LogLevel {
+    Error = 0,
+    Warn = 1,
+    Info = 2,
+    Debug = 3,
+}
+
+impl LogLevel {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "error" => Some(LogLevel::Error),
+            "warn" | "warning" => Some(LogLevel::Warn),
+            "info" => Some(LogLevel::Info),
+            "debug" => Some(LogLevel::Debug),
+            _ => None,
+        }
+    }
+
+    fn allows(self, other: LogLevel) -> bool {
+        other as i32 <= self as i32
+    }
+}
+
+fn send_log(node: &mut DoraNode, level: LogLevel, config_level: LogLevel, message: &str) {
+    if !config_level.allows(level) {
+        return;
+    }
+
+    let node_name = std::env::var("DORA_NODE_NAME")
+        .unwrap_or_else(|_| "multi-text-segmenter".to_string());
+
+    let log_data = serde_json::json!({
+        "level": format!("{:?}", level).to_uppercase(),
+        "message": message,
+        "node": node_name,
+        "timestamp": chrono::Utc::now().timestamp_millis(),
+    });
+
+    if node
+        .send_output(
+            DataId::from("log".to_string()),
+            Default::default(),
+            StringArray::from(vec![log_data.to_string().as_str()]),
+        )
+        .is_err()
+    {
+        eprintln!("[Segmenter] Failed to send log: {}", message);
+    }
+}
+
+fn is_participant_input(id: &str) -> bool {
+    !CONTROL_INPUTS.contains(&id)
+        && !BUFFER_INPUTS.contains(&id)
+        && id != "audio_complete"
+        && !id.starts_with("tts_complete_")
+}
+
+fn param_string(metadata: &BTreeMap<String, Parameter>, key: &str) -> Option<String> {
+    match metadata.get(key)? {
+        Parameter::String(s) => Some(s.clone()),
+        Parameter::Integer(i) => Some(i.to_string()),
+        Parameter::Float(f) => Some(f.to_string()),
+        _ => None,
+    }
+}
+
+fn input_text(data: &ArrayRef) -> String {
+    data.as_string_opt::<i32>()
+        .map(|array| array.iter().flatten().collect::<Vec<_>>().join(""))
+        .unwrap_or_default()
+}
+
+/// First value of a numeric (or numeric string) array
+fn first_number(data: &ArrayRef) -> Option<f64> {
+    let values = cast(data, &DataType::Float64).ok()?;
+    let values = values.as_primitive::<Float64Type>();
+    (!values.is_empty() && values.is_valid(0)).then(|| values.value(0))
+}
+
+fn send_segment(node: &mut DoraNode, config: &NodeConfig, log_level: LogLevel, segment: Segment) -> Result<()> {
+    send_log(
+        node,
+        LogLevel::Info,
+        log_level,
+        &format!(
+            "🎤 SENDING to {}: '{}' (session_id={}, question_id={:?})",
+            segment.participant, segment.text, segment.session_id, segment.question_id
+        ),
+    );
+
+    let mut metadata = BTreeMap::new();
+    metadata.insert("session_id".to_string(), Parameter::String(segment.session_id.clone()));
+    if let Some(question_id) = &segment.question_id {
+        metadata.insert("question_id".to_string(), Parameter::String(question_id.clone()));
+    }
+    metadata.insert(
+        "session_status".to_string(),
+        Parameter::String(segment.session_status.clone()),
+    );
+    if config.segmenter.prosody_hints {
+        let hints = &segment.hints;
+        metadata.insert("pause_ms".to_string(), Parameter::Integer(hints.pause_ms as i64));
+        metadata.insert("emphasis".to_string(), Parameter::Bool(hints.emphasis));
+        metadata.insert(
+            "language".to_string(),
+            Parameter::String(hints.language.as_str().to_string()),
+        );
+        if config.segmenter.ssml {
+            metadata.insert("ssml".to_string(), Parameter::String(to_ssml(&segment.text, hints)));
+        }
+    }
+
+    node.send_output(
+        DataId::from(format!("text_segment_{}", segment.participant)),
+        metadata,
+        StringArray::from(vec![segment.text.as_str()]),
+    )?;
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let (mut node, events) = DoraNode::init_from_env()?;
+
+    let log_level = std::env::var("LOG_LEVEL").ok()
+        .and_then(|s| LogLevel::parse(&s))
+        .unwrap_or(LogLevel::Info);
+    let config = NodeConfig::from_env();
+
+    send_log(
+        &mut node,
+        LogLevel::Info,
+        log_level,
+        &format!(
+            "Multi-Participant Text Segmenter started (language: {}, max_chars: {:?}, max_words: {:?}, water marks: {}/{}%)",
+            config.segmenter.language.as_str(),
+            config.segmenter.max_chars,
+            config.segmenter.max_words,
+            config.low_water_mark,
+            config.high_water_mark,
+        ),
+    );
+
+    let mut queue = SessionQueue::new(config.clone());
+    let events = futures::executor::block_on_stream(events);
+
+    for event in events {
+        match event {
+            Event::Input { id, metadata, data, .. } => {
+                let id = id.as_str();
+                let parameters = &metadata.parameters;
+
+                let next = if is_participant_input(id) {
+                    if queue.add_participant(id) {
+                        send_log(&mut node, LogLevel::Info, log_level, &format!("Discovered participant: {}", id));
+                    }
+                    let text = input_text(&data);
+                    let status = param_string(parameters, "session_status");
+                    send_log(
+                        &mut node,
+                        LogLevel::Debug,
+                        log_level,
+                        &format!("📥 {} ({}): '{}'", id, status.as_deref().unwrap_or("chunk"), text),
+                    );
+                    match queue.on_text(id, &text, status.as_deref(), param_string(parameters, "question_id")) {
+                        Ok(next) => next,
+                        Err(TextError::NoOpenSession) => {
+                            send_log(
+                                &mut node,
+                                LogLevel::Warn,
+                                log_level,
+                                &format!("Received text for {} but no current session", id),
+                            );
+                            None
+                        }
+                    }
+                } else if id == "audio_complete" {
+                    let Some(participant) = param_string(parameters, "participant") else {
+                        send_log(&mut node, LogLevel::Warn, log_level, "audio_complete without participant metadata");
+                        continue;
+                    };
+                    send_log(&mut node, LogLevel::Debug, log_level, &format!("✅ AUDIO_COMPLETE from {}", participant));
+                    queue.on_audio_complete(&participant, param_string(parameters, "question_id").as_deref())
+                } else if BUFFER_INPUTS.contains(&id) {
+                    let percentage = first_number(&data).or_else(|| {
+                        param_string(parameters, "buffer_percentage").and_then(|v| v.parse().ok())
+                    });
+                    let Some(percentage) = percentage else {
+                        send_log(&mut node, LogLevel::Warn, log_level, "Could not parse buffer percentage");
+                        continue;
+                    };
+                    let was_paused = queue.is_paused();
+                    let next = queue.on_buffer_status(percentage);
+                    if queue.is_paused() != was_paused {
+                        let message = if queue.is_paused() {
+                            format!("🎵 🛑 BUFFER BACKPRESSURE: Audio buffer at {:.1}%, pausing", percentage)
+                        } else {
+                            format!("🎵 ▶️ BUFFER RESUMED: Audio buffer at {:.1}%", percentage)
+                        };
+                        send_log(&mut node, LogLevel::Info, log_level, &message);
+                    }
+                    next
+                } else if CONTROL_INPUTS.contains(&id) {
+                    let text = input_text(&data);
+                    let command = param_string(parameters, "command")
+                        .or_else(|| {
+                            serde_json::from_str::<serde_json::Value>(&text)
+                                .ok()
+                                .and_then(|json| json.get("command")?.as_str().map(str::to_string))
+                        })
+                        .unwrap_or(text)
+                        .trim()
+                        .to_lowercase();
+                    if command != "reset" && command != "cancel" {
+                        send_log(&mut node, LogLevel::Debug, log_level, &format!("Ignoring {} command: {}", id, command));
+                        continue;
+                    }
+                    let question_id = param_string(parameters, "question_id");
+                    let outcome = queue.reset(question_id.as_deref());
+                    send_log(
+                        &mut node,
+                        LogLevel::Info,
+                        log_level,
+                        &format!(
+                            "🔄 {} from {} (question_id={:?}): cleared {} segments, kept {}",
+                            command.to_uppercase(),
+                            id,
+                            question_id,
+                            outcome.cleared,
+                            outcome.kept
+                        ),
+                    );
+                    outcome.next
+                } else {
+                    None
+                };
+
+                if let Some(segment) = next {
+                    send_segment(&mut node, &config, log_level, segment)?;
+                }
+            }
+            Event::Stop(_) => break,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,497 @@
+//! Per-participant session queue with audio backpressure.
+//!
+//! Streamed LLM text of every participant is cut into segments by a
+//! `StreamSegmenter` and queued per response session. Only one participant
+//! speaks at a time: the queue whose oldest open session started first is
+//! activated and drained one segment per `audio_complete`, until its session
+//! has ended and its last segment was played. Sending pauses while the audio
+//! player's buffer is above the high water mark and resumes below the low
+//! water mark.
+
+use std::collections::{HashMap, VecDeque};
+
+use dora_maas_client::segmenter::{ProsodyHints, StreamSegmenter};
+
+use crate::config::NodeConfig;
+
+/// A segment ready for a participant's TTS
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub participant: String,
+    pub text: String,
+    pub session_id: String,
+    pub question_id: Option<String>,
+    /// `session_status` of the message that started the session
+    pub session_status: String,
+    pub hints: ProsodyHints,
+}
+
+/// Why a text chunk was not queued
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextError {
+    /// A chunk arrived without a `started` message before it
+    NoOpenSession,
+}
+
+/// Result of a reset or cancel
+#[derive(Debug, Default, PartialEq)]
+pub struct ResetOutcome {
+    pub cleared: usize,
+    pub kept: usize,
+    /// Next segment to send from the sessions that were kept
+    pub next: Option<Segment>,
+}
+
+/// One LLM response of a participant
+struct Session {
+    id: String,
+    /// Arrival order across all participants
+    seq: u64,
+    question_id: Option<String>,
+    status: String,
+    segments: VecDeque<Segment>,
+    /// No more text will arrive for this session
+    ended: bool,
+}
+
+struct Participant {
+    segmenter: StreamSegmenter,
+    sessions: VecDeque<Session>,
+}
+
+impl Participant {
+    /// The session currently receiving text
+    fn open_session(&mut self) -> Option<&mut Session> {
+        self.sessions.back_mut().filter(|session| !session.ended)
+    }
+}
+
+pub struct SessionQueue {
+    config: NodeConfig,
+    /// Participants in discovery order
+    names: Vec<String>,
+    participants: HashMap<String, Participant>,
+    next_seq: u64,
+    /// Participant whose queue is being drained (only one at a time)
+    active: Option<String>,
+    /// Question ID of the segment sent and not yet acknowledged by `audio_complete`
+    in_flight: Option<Option<String>>,
+    paused: bool,
+    buffer_level: f64,
+}
+
+impl SessionQueue {
+    pub fn new(config: NodeConfig) -> Self {
+        Self {
+            config,
+            names: Vec::new(),
+            participants: HashMap::new(),
+            next_seq: 0,
+            active: None,
+            in_flight: None,
+            paused: false,
+            buffer_level: 0.0,
+        }
+    }
+
+    pub fn participants(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn active(&self) -> Option<&str> {
+        self.active.as_deref()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn buffer_level(&self) -> f64 {
+        self.buffer_level
+    }
+
+    /// Segments queued for a participant (all sessions)
+    pub fn queued(&self, participant: &str) -> usize {
+        self.participants
+            .get(participant)
+            .map(|p| p.sessions.iter().map(|s| s.segments.len()).sum())
+            .unwrap_or(0)
+    }
+
+    /// Register a participant; returns true when it is new
+    pub fn add_participant(&mut self, name: &str) -> bool {
+        if self.participants.contains_key(name) {
+            return false;
+        }
+        self.names.push(name.to_string());
+        self.participants.insert(
+            name.to_string(),
+            Participant {
+                segmenter: StreamSegmenter::from_config(&self.config.segmenter),
+                sessions: VecDeque::new(),
+            },
+        );
+        true
+    }
+
+    /// Handle a text chunk from a participant.
+    ///
+    /// `session_status` follows the MaaS client: `started` opens a session
+    /// (ending one that is still open), `ended` flushes and closes it,
+    /// `cancelled` drops what is left of it, anything else is a chunk of
+    /// the open session. Returns the segment to send now, if any.
+    pub fn on_text(
+        &mut self,
+        participant: &str,
+        text: &str,
+        session_status: Option<&str>,
+        question_id: Option<String>,
+    ) -> Result<Option<Segment>, TextError> {
+        self.add_participant(participant);
+        let text = if self.config.remove_speaker_id {
+            strip_speaker_id(text)
+        } else {
+            text
+        };
+
+        match session_status {
+            Some("started") => {
+                self.end_session(participant);
+                let seq = self.next_seq;
+                self.next_seq += 1;
+                let state = self.participants.get_mut(participant).expect("participant registered");
+                state.segmenter = StreamSegmenter::from_config(&self.config.segmenter);
+                state.sessions.push_back(Session {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    seq,
+                    question_id,
+                    status: "started".to_string(),
+                    segments: VecDeque::new(),
+                    ended: false,
+                });
+                self.push_text(participant, text);
+            }
+            Some("ended") => {
+                if !self.has_open_session(participant) {
+                    return Err(TextError::NoOpenSession);
+                }
+                self.push_text(participant, text);
+                self.end_session(participant);
+            }
+            Some("cancelled") => {
+                let state = self.participants.get_mut(participant).expect("participant registered");
+                let Some(session) = state.open_session() else {
+                    return Err(TextError::NoOpenSession);
+                };
+                session.segments.clear();
+                session.ended = true;
+                state.segmenter = StreamSegmenter::from_config(&self.config.segmenter);
+            }
+            _ => {
+                if !self.has_open_session(participant) {
+                    return Err(TextError::NoOpenSession);
+                }
+                self.push_text(participant, text);
+            }
+        }
+        Ok(self.pump())
+    }
+
+    /// The audio player received the audio of a participant's segment.
+    ///
+    /// Completions of other participants, or for a different question than
+    /// the segment in flight (left over from before a reset), are ignored.
+    pub fn on_audio_complete(&mut self, participant: &str, question_id: Option<&str>) -> Option<Segment> {
+        if self.active.as_deref() != Some(participant) {
+            return None;
+        }
+        if let (Some(Some(expected)), Some(question_id)) = (&self.in_flight, question_id) {
+            if expected != question_id {
+                return None;
+            }
+        }
+        self.in_flight = None;
+        self.pump()
+    }
+
+    /// Audio buffer fill percentage from the audio player
+    pub fn on_buffer_status(&mut self, percentage: f64) -> Option<Segment> {
+        self.buffer_level = percentage;
+        if percentage > self.config.high_water_mark && !self.paused {
+            self.paused = true;
+            None
+        } else if percentage < self.config.low_water_mark && self.paused {
+            self.paused = false;
+            self.pump()
+        } else {
+            None
+        }
+    }
+
+    /// Reset or cancel.
+    ///
+    /// Without a question ID everything is cleared. With one, only sessions
+    /// of other questions are dropped (sessions without a question ID are kept).
+    pub fn reset(&mut self, question_id: Option<&str>) -> ResetOutcome {
+        let mut outcome = ResetOutcome::default();
+        for state in self.participants.values_mut() {
+            let before = state.sessions.len();
+            let open_before = state.sessions.back().is_some_and(|s| !s.ended);
+            state.sessions.retain(|session| {
+                let keep = match (question_id, session.question_id.as_deref()) {
+                    (None, _) => false,
+                    (Some(_), None) => true,
+                    (Some(wanted), Some(current)) => wanted == current,
+                };
+                if keep {
+                    outcome.kept += session.segments.len();
+                } else {
+                    outcome.cleared += session.segments.len();
+                }
+                keep
+            });
+            let open_after = state.sessions.back().is_some_and(|s| !s.ended);
+            if state.sessions.len() != before && open_before && !open_after {
+                state.segmenter = StreamSegmenter::from_config(&self.config.segmenter);
+            }
+        }
+
+        self.active = None;
+        self.in_flight = None;
+        self.paused = false;
+        self.buffer_level = 0.0;
+        outcome.next = self.pump();
+        outcome
+    }
+
+    fn has_open_session(&self, participant: &str) -> bool {
+        self.participants
+            .get(participant)
+            .and_then(|p| p.sessions.back())
+            .is_some_and(|session| !session.ended)
+    }
+
+    fn push_text(&mut self, participant: &str, text: &str) {
+        let state = self.participants.get_mut(participant).expect("participant registered");
+        if let Some(piece) = state.segmenter.add_chunk(text) {
+            let hints = state.segmenter.hints().clone();
+            enqueue(participant, state, piece, hints);
+        }
+    }
+
+    /// Flush the open session of a participant and mark it ended
+    fn end_session(&mut self, participant: &str) {
+        let Some(state) = self.participants.get_mut(participant) else {
+            return;
+        };
+        if state.open_session().is_none() {
+            return;
+        }
+        if let Some(piece) = state.segmenter.flush() {
+            let hints = state.segmenter.hints().clone();
+            enqueue(participant, state, piece, hints);
+        }
+        if let Some(session) = state.open_session() {
+            session.ended = true;
+        }
+    }
+
+    /// Oldest session that has something to do: segments to send, or ended
+    fn select_next(&self) -> Option<String> {
+        self.names
+            .iter()
+            .filter_map(|name| {
+                let session = self.participants[name].sessions.front()?;
+                (!session.segments.is_empty() || session.ended).then_some((session.seq, name))
+            })
+            .min()
+            .map(|(_, name)| name.clone())
+    }
+
+    /// Send the next segment if nothing is in flight and sending is not paused
+    fn pump(&mut self) -> Option<Segment> {
+        if self.paused || self.in_flight.is_some() {
+            return None;
+        }
+        loop {
+            let name = match &self.active {
+                Some(name) => name.clone(),
+                None => {
+                    let next = self.select_next()?;
+                    self.active = Some(next.clone());
+                    next
+                }
+            };
+            let state = self.participants.get_mut(&name).expect("active participant registered");
+            let Some(session) = state.sessions.front_mut() else {
+                self.active = None;
+                continue;
+            };
+            if let Some(segment) = session.segments.pop_front() {
+                self.in_flight = Some(segment.question_id.clone());
+                return Some(segment);
+            }
+            if !session.ended {
+                // Wait for more text of the active session
+                return None;
+            }
+            // Session complete: its last segment was played, move on
+            state.sessions.pop_front();
+            self.active = None;
+        }
+    }
+}
+
+/// Add a segment to the open session unless it has nothing to say
+fn enqueue(participant: &str, state: &mut Participant, text: String, hints: ProsodyHints) {
+    let text = text.trim();
+    if !text.chars().any(char::is_alphanumeric) {
+        return;
+    }
+    let Some(session) = state.open_session() else {
+        return;
+    };
+    let segment = Segment {
+        participant: participant.to_string(),
+        text: text.to_string(),
+        session_id: session.id.clone(),
+        question_id: session.question_id.clone(),
+        session_status: session.status.clone(),
+        hints,
+    };
+    session.segments.push_back(segment);
+}
+
+/// Remove a leading `[Speaker Name]` tag
+pub fn strip_speaker_id(text: &str) -> &str {
+    if let Some(rest) = text.strip_prefix('[') {
+        if let Some(end) = rest.find(']') {
+            if end > 0 {
+                return rest[end + 1..].trim_start();
+            }
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue() -> SessionQueue {
+        SessionQueue::new(NodeConfig::default())
+    }
+
+    fn text(segment: Option<Segment>) -> Option<String> {
+        segment.map(|s| s.text)
+    }
+
+    #[test]
+    fn test_strip_speaker_id() {
+        assert_eq!(strip_speaker_id("[孙老师] 今天讲第一章。"), "今天讲第一章。");
+        assert_eq!(strip_speaker_id("[] text"), "[] text");
+        assert_eq!(strip_speaker_id("no tag"), "no tag");
+    }
+
+    #[test]
+    fn test_one_segment_per_audio_complete() {
+        let mut q = queue();
+        let first = q.on_text("tutor", "[孙老师] 你好。", Some("started"), Some("1".to_string())).unwrap();
+        let first = first.unwrap();
+        assert_eq!(first.text, "你好。");
+        assert_eq!(first.question_id.as_deref(), Some("1"));
+        assert_eq!(first.session_status, "started");
+
+        // Busy until the audio player confirms the first segment
+        assert_eq!(q.on_text("tutor", "今天讲统计律。", None, None).unwrap(), None);
+        assert_eq!(q.on_text("tutor", "。。", None, None).unwrap(), None);
+        assert_eq!(q.queued("tutor"), 1);
+        assert_eq!(text(q.on_audio_complete("tutor", Some("1"))), Some("今天讲统计律。".to_string()));
+
+        // Queue drained: the next chunk goes out as soon as it is complete
+        assert_eq!(q.on_audio_complete("tutor", Some("1")), None);
+        assert_eq!(text(q.on_text("tutor", "明白吗？", None, None).unwrap()), Some("明白吗？".to_string()));
+    }
+
+    #[test]
+    fn test_sessions_are_fifo_across_participants() {
+        let mut q = queue();
+        assert_eq!(
+            text(q.on_text("student1", "我先说。", Some("started"), None).unwrap()),
+            Some("我先说。".to_string())
+        );
+        // student2 starts later and waits, even though student1 is idle
+        assert_eq!(q.on_text("student2", "我也想说。", Some("started"), None).unwrap(), None);
+        assert_eq!(q.on_audio_complete("student1", None), None);
+        assert_eq!(q.active(), Some("student1"));
+        assert_eq!(q.on_text("student1", "还有一点", None, None).unwrap(), None);
+        assert_eq!(q.on_audio_complete("student2", None), None);
+
+        // Ending student1's session flushes its buffer; student2 follows its last segment
+        assert_eq!(text(q.on_text("student1", "", Some("ended"), None).unwrap()), Some("还有一点".to_string()));
+        assert_eq!(text(q.on_audio_complete("student1", None)), Some("我也想说。".to_string()));
+        assert_eq!(q.active(), Some("student2"));
+        assert_eq!(q.participants(), ["student1".to_string(), "student2".to_string()]);
+    }
+
+    #[test]
+    fn test_buffer_backpressure() {
+        let mut q = queue();
+        q.on_text("tutor", "第一句。", Some("started"), None).unwrap();
+        q.on_text("tutor", "第二句。", None, None).unwrap();
+        q.on_text("tutor", "第三句。", None, None).unwrap();
+
+        assert_eq!(q.on_buffer_status(70.0), None);
+        assert!(q.is_paused());
+        assert_eq!(q.on_audio_complete("tutor", None), None);
+        // Between the water marks nothing changes
+        assert_eq!(q.on_buffer_status(45.0), None);
+        assert!(q.is_paused());
+        assert_eq!(text(q.on_buffer_status(20.0)), Some("第二句。".to_string()));
+        assert!(!q.is_paused());
+        assert_eq!(q.buffer_level(), 20.0);
+    }
+
+    #[test]
+    fn test_chunks_without_session() {
+        let mut q = queue();
+        assert_eq!(q.on_text("tutor", "stray", None, None), Err(TextError::NoOpenSession));
+        assert_eq!(q.on_text("tutor", "", Some("ended"), None), Err(TextError::NoOpenSession));
+        assert_eq!(q.participants(), ["tutor".to_string()]);
+    }
+
+    #[test]
+    fn test_cancelled_session_is_dropped() {
+        let mut q = queue();
+        q.on_text("student1", "第一句。", Some("started"), None).unwrap();
+        q.on_text("student1", "第二句。", None, None).unwrap();
+        q.on_text("student2", "轮到我了。", Some("started"), None).unwrap();
+        assert_eq!(q.on_text("student1", "", Some("cancelled"), None).unwrap(), None);
+        assert_eq!(q.queued("student1"), 0);
+        assert_eq!(text(q.on_audio_complete("student1", None)), Some("轮到我了。".to_string()));
+    }
+
+    #[test]
+    fn test_reset_by_question_id() {
+        let mut q = queue();
+        q.on_text("student1", "旧问题。", Some("started"), Some("1".to_string())).unwrap();
+        q.on_text("student1", "旧答案。", None, None).unwrap();
+        q.on_text("student2", "新问题。", Some("started"), Some("2".to_string())).unwrap();
+        q.on_text("student2", "新答案", None, None).unwrap();
+
+        let outcome = q.reset(Some("2"));
+        assert_eq!(outcome.cleared, 1);
+        assert_eq!(outcome.kept, 1);
+        assert_eq!(text(outcome.next), Some("新问题。".to_string()));
+        // A late completion of the cleared question does not release the next segment
+        assert_eq!(q.on_audio_complete("student2", Some("1")), None);
+        assert_eq!(q.on_text("student2", "。", None, None).unwrap(), None);
+        assert_eq!(text(q.on_audio_complete("student2", Some("2"))), Some("新答案。".to_string()));
+
+        // Without a question ID everything goes, including the open session
+        let outcome = q.reset(None);
+        assert_eq!(outcome, ResetOutcome::default());
+        assert_eq!(q.on_text("student2", "更多", None, None), Err(TextError::NoOpenSession));
+        assert_eq!(q.active(), None);
+    }
+}